@@ -1,16 +1,24 @@
 use {
     super::*,
     crate::{
+        bookmark::Bookmarks,
         browser::BrowserState,
-        command::{Command, Sequence},
+        command::{Command, CommandParts, Sequence},
         conf::Conf,
-        display::{Areas, Screen, W},
+        display::{verb_bar, Areas, Screen, W},
         errors::ProgramError,
-        file_sum, git,
+        file_sum, git, journal,
         launchable::Launchable,
+        path,
+        pattern::InputPattern,
         skin::*,
+        stage::{MarkCommand, MarkSet, Stage},
         task_sync::{Dam, Either},
-        verb::Internal,
+        verb::{
+            internal_focus::locate_state, ExecutionStringBuilder, Internal, PrefixSearchResult,
+            VerbExecution, VerbStore,
+        },
+        yank::YankBuffer,
     },
     crossbeam::channel::{
         Receiver,
@@ -19,21 +27,27 @@ use {
     },
     crossterm::event::KeyModifiers,
     std::{
+        convert::TryInto,
         io::Write,
-        path::PathBuf,
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
     },
     strict::NonEmptyVec,
-    termimad::{Event, EventSource},
+    termimad::{Area, Event, EventSource},
 };
 
 const ESCAPE_TO_QUIT: bool = false;
 
+/// the interval between two checks of the watched directories
+const WATCH_PERIOD: Duration = Duration::from_secs(1);
+
 #[cfg(feature = "client-server")]
 use std::sync::{Arc, Mutex};
 
 /// The GUI
 pub struct App {
-    /// dimensions of the screen
+    /// dimensions of the screen given to the panels, which is the
+    /// whole terminal, except the verb bar when it's displayed
     screen: Screen,
 
     /// the panels of the application, at least one
@@ -58,11 +72,68 @@ pub struct App {
     #[cfg(feature = "client-server")]
     root: Arc<Mutex<PathBuf>>,
 
+    /// the selection of the active panel
+    #[cfg(feature = "client-server")]
+    selection: Arc<Mutex<PathBuf>>,
+
     /// sender to the sequence channel
     tx_seqs: Sender<Sequence>,
 
     /// receiver to listen to the sequence channel
     rx_seqs: Receiver<Sequence>,
+
+    /// the paths staged by the user, shared by all panels
+    stage: Stage,
+
+    /// the paths yanked or cut by the user, waiting for a paste
+    yank_buffer: YankBuffer,
+
+    /// the last command of a debounced verb, waiting for
+    /// the end of its debounce window
+    debouncer: Debouncer<Command>,
+
+    /// the processes launched by verbs in background
+    background_jobs: BackgroundJobs,
+
+    /// the last executed repeatable internal, as a command
+    /// which `:repeat` may apply again
+    last_repeatable: Option<Command>,
+
+    /// the split made with `:toggle_split`, to be closed on
+    /// the next `:toggle_split`
+    split: Option<Split>,
+
+    /// the files recently selected, for `:recent_prev`
+    /// and `:recent_next`
+    recent_files: RecentFiles,
+
+    /// whether the bar listing the verbs applicable to the
+    /// selection is displayed, below the panels
+    verb_bar: bool,
+
+    /// the watcher of the displayed directories, refreshing
+    /// the panels when they change
+    watcher: DirWatcher,
+}
+
+/// a split of a panel in two, made by `:toggle_split`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Split {
+    /// the panel which was split
+    original: PanelId,
+    /// the panel showing the copy of the original panel's state
+    copy: PanelId,
+}
+
+/// what `:toggle_split` must do
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SplitAction {
+    /// copy the single panel into a new one
+    Split,
+    /// close the copy and go back to the original panel
+    Unsplit(Split),
+    /// there are several panels which don't come from a split
+    Impossible,
 }
 
 impl App {
@@ -87,6 +158,17 @@ impl App {
             con,
         );
         let (tx_seqs, rx_seqs) = unbounded::<Sequence>();
+        // the main loop is woken up by an empty sequence when
+        // watched directories change
+        let tx_watch = tx_seqs.clone();
+        let watcher = DirWatcher::new(con.watch, WATCH_PERIOD, move || {
+            let _ = tx_watch.send(Sequence::new_single(String::new()));
+        });
+        // and at the end of the debounce window of a command
+        let tx_debounce = tx_seqs.clone();
+        let debouncer = Debouncer::with_waker(move || {
+            let _ = tx_debounce.send(Sequence::new_single(String::new()));
+        });
         Ok(App {
             screen,
             active_panel_idx: 0,
@@ -98,8 +180,19 @@ impl App {
 
             #[cfg(feature = "client-server")]
             root: Arc::new(Mutex::new(con.launch_args.root.clone())),
+            #[cfg(feature = "client-server")]
+            selection: Arc::new(Mutex::new(con.launch_args.root.clone())),
             tx_seqs,
             rx_seqs,
+            stage: Stage::default(),
+            yank_buffer: YankBuffer::default(),
+            debouncer,
+            background_jobs: BackgroundJobs::default(),
+            last_repeatable: None,
+            split: None,
+            recent_files: RecentFiles::default(),
+            verb_bar: false,
+            watcher,
         })
     }
 
@@ -157,6 +250,124 @@ impl App {
         }
     }
 
+    /// describe the panels showing a tree, for them to be restored later
+    fn layout(&self) -> Option<Layout> {
+        self.session().map(|session| Layout {
+            active: session.active,
+            panels: session.panels
+                .into_iter()
+                .map(|panel| PanelLayout {
+                    root: panel.root,
+                    selection: panel.selection,
+                })
+                .collect(),
+        })
+    }
+
+    /// return the description of the panels showing a tree, with
+    /// their patterns and options, or None if there's none
+    fn session(&self) -> Option<Session> {
+        let mut panels = Vec::new();
+        let mut active = 0;
+        for (idx, panel) in self.panels.iter().enumerate() {
+            if Some(panel.id) == self.preview {
+                continue;
+            }
+            if let Some(root) = panel.state().tree_root() {
+                if idx == self.active_panel_idx {
+                    active = panels.len();
+                }
+                let options = panel.state().tree_options();
+                panels.push(PanelSession {
+                    root: root.to_path_buf(),
+                    selection: panel.state().selected_path().to_path_buf(),
+                    pattern: options.pattern.raw.clone(),
+                    options: Some(PanelOptions::from(&options)),
+                });
+            }
+        }
+        if panels.is_empty() {
+            None
+        } else {
+            Some(Session { active, panels })
+        }
+    }
+
+    /// replace all panels with the ones described in the layout
+    fn apply_layout(
+        &mut self,
+        layout: &Layout,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        self.apply_session(&Session::from(layout), con)
+    }
+
+    /// replace all panels with the ones described in the session,
+    /// applying their patterns and options
+    pub fn apply_session(
+        &mut self,
+        session: &Session,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        let page_height = BrowserState::page_height(self.screen);
+        let mut panels: Vec<Panel> = Vec::new();
+        for (idx, panel_session) in session.panels.iter().enumerate() {
+            let mut options = con.launch_args.tree_options.clone();
+            if let Some(panel_options) = &panel_session.options {
+                panel_options.apply_to(&mut options);
+            }
+            if !panel_session.pattern.is_empty() {
+                let parts = CommandParts::from(panel_session.pattern.clone());
+                match InputPattern::new(parts.raw_pattern, &parts.pattern, con) {
+                    Ok(pattern) => options.pattern = pattern,
+                    Err(e) => warn!("pattern of the session not applied: {}", e),
+                }
+            }
+            let input = options.pattern.raw.clone();
+            let mut state = BrowserState::new(
+                panel_session.root.clone(),
+                options,
+                self.screen,
+                con,
+                &Dam::unlimited(),
+            )?
+            .ok_or_else(|| ProgramError::InternalError {
+                details: "tree building interrupted".to_string(),
+            })?;
+            if state.tree.try_select_path(&panel_session.selection) {
+                state.tree.make_selection_visible(page_height);
+            }
+            let areas = Areas::create(&mut panels, idx, self.screen, false)?;
+            let panel_id = self.created_panels_count.into();
+            self.created_panels_count += 1;
+            let mut panel = Panel::new(panel_id, Box::new(state), areas, con);
+            panel.set_input_content(&input);
+            panels.push(panel);
+        }
+        self.panels = panels.try_into().map_err(|_| ProgramError::InternalError {
+            details: "empty session".to_string(),
+        })?;
+        self.preview = None;
+        self.active_panel_idx = session.active.min(self.panels.len().get() - 1);
+        Ok(())
+    }
+
+    /// save the session which may be restored with `--resume`
+    fn save_last_session(&self) {
+        if let Some(session) = self.session() {
+            if let Err(e) = session.save(LAST_SESSION) {
+                warn!("last session not saved: {}", e);
+            }
+        }
+    }
+
+    /// return what must be launched after the application's end,
+    /// once the session has been saved
+    fn end(&mut self) -> Option<Launchable> {
+        self.save_last_session();
+        self.launch_at_end.take()
+    }
+
     /// remove the top state of the current panel
     ///
     /// Close the panel too if that was its only state.
@@ -175,28 +386,38 @@ impl App {
         skin: &AppSkin,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
-        // if some images are displayed by kitty, we'll erase it,
+        // if some images are displayed by kitty, we'll erase them,
         // but only after having displayed the new ones (if any)
         // to prevent some flickerings
         #[cfg(unix)]
-        let previous_images = crate::kitty::image_renderer()
+        let previous_images = crate::image::image_renderer()
             .as_ref()
             .and_then(|renderer| {
                 let mut renderer = renderer.lock().unwrap();
                 renderer.take_current_images()
             });
+        let progress = self.background_jobs.progress();
         for (idx, panel) in self.panels.as_mut_slice().iter_mut().enumerate() {
+            panel.set_progress(progress.clone());
             let focused = idx == self.active_panel_idx;
             let skin = if focused { &skin.focused } else { &skin.unfocused };
             time!(
                 Debug,
                 "display panel",
-                panel.display(w, focused, self.screen, skin, con)?,
+                panel.display(w, focused, self.screen, skin, &self.stage, con)?,
             );
         }
+        if self.verb_bar {
+            let sel = self.state().selection();
+            let verbs = con.verb_store.applicable_verbs(sel, self.other_panel_idx().is_some());
+            // the bar is on the last line of the terminal, below the
+            // screen of the panels, and doesn't touch the bottom right char
+            let area = Area::new(0, self.screen.height, self.screen.width - 1, 1);
+            verb_bar::write(w, &verbs, &area, &skin.focused, self.screen)?;
+        }
         #[cfg(unix)]
         if let Some(previous_images) = previous_images {
-            if let Some(renderer) = crate::kitty::image_renderer().as_ref() {
+            if let Some(renderer) = crate::image::image_renderer().as_ref() {
                 let mut renderer = renderer.lock().unwrap();
                 renderer.erase(w, previous_images)?;
             }
@@ -208,22 +429,15 @@ impl App {
     /// if there are exactly two non preview panels, return the selection
     /// in the non focused panel
     fn get_other_panel_path(&self) -> Option<PathBuf> {
-        let len = self.panels.len().get();
-        if len == 3 {
-            if let Some(preview_id) = self.preview {
-                for (idx, panel) in self.panels.iter().enumerate() {
-                    if self.active_panel_idx != idx && panel.id != preview_id {
-                        return Some(panel.state().selected_path().to_path_buf());
-                    }
-                }
-            }
-            None
-        } else if self.panels.len().get() == 2 && self.preview.is_none() {
-            let non_focused_panel_idx = if self.active_panel_idx == 0 { 1 } else { 0 };
-            Some(self.panels[non_focused_panel_idx].state().selected_path().to_path_buf())
-        } else {
-            None
-        }
+        self.other_panel_idx()
+            .map(|idx| self.panels[idx].state().selected_path().to_path_buf())
+    }
+
+    /// if there are exactly two non preview panels, return the index
+    /// of the non focused one
+    fn other_panel_idx(&self) -> Option<usize> {
+        let preview_idx = self.preview.and_then(|id| self.panel_idx(id));
+        other_panel_idx(self.panels.len().get(), self.active_panel_idx, preview_idx)
     }
 
     /// apply a command. Change the states but don't redraw on screen.
@@ -232,23 +446,64 @@ impl App {
         w: &mut W,
         cmd: Command,
         panel_skin: &PanelSkin,
-        con: &AppContext,
+        con: &mut AppContext,
     ) -> Result<(), ProgramError> {
         use AppStateCmdResult::*;
         let mut error: Option<String> = None;
+        let mut message: Option<String> = None;
         let is_input_invocation = cmd.is_verb_invocated_from_input();
         let other_path = self.get_other_panel_path();
         let preview = self.preview;
-        let screen = self.screen; // it can't change in this function
-        match self.mut_panel().apply_command(
+        // only `:toggle_verb_bar` changes the screen, and it doesn't
+        // use this copy after the change
+        let screen = self.screen;
+        let repeatable = repeatable_command(&cmd, &con.verb_store, self.state().selection().stype);
+        let result = self.panels[self.active_panel_idx].apply_command(
             w,
             &cmd,
             &other_path,
             screen,
             panel_skin,
             preview,
+            &self.stage,
             con,
-        )? {
+        )?;
+        if repeatable.is_some() && !matches!(result, DisplayError(_)) {
+            self.last_repeatable = repeatable;
+        }
+        match result {
+            AddToStage(paths) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let added = paths.into_iter().filter(|p| self.stage.add(p.clone())).count();
+                message = Some(format!(
+                    "{} files added to the stage, which now contains {} files",
+                    added,
+                    self.stage.len(),
+                ));
+            }
+            Bookmark { name, path } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let saved = Bookmarks::load().and_then(|mut bookmarks| {
+                    bookmarks.set(&name, path.clone())?;
+                    bookmarks.save()
+                });
+                match saved {
+                    Ok(()) => {
+                        message = Some(format!(
+                            "{} bookmarked as {:?}",
+                            path.to_string_lossy(),
+                            name,
+                        ));
+                    }
+                    Err(e) => {
+                        error = Some(format!("bookmark not saved: {}", e));
+                    }
+                }
+            }
             ApplyOnPanel { id } => {
                 if let Some(idx) = self.panel_idx(id) {
                     if let DisplayError(txt) = self.panels[idx].apply_command(
@@ -258,6 +513,7 @@ impl App {
                         screen,
                         panel_skin,
                         preview,
+                        &self.stage,
                         con,
                     )? {
                         // we should probably handle other results
@@ -299,13 +555,14 @@ impl App {
                         let new_input = self.panel().get_input_content();
                         let cmd = Command::from_raw(new_input, false);
                         let preview = self.preview;
-                        self.mut_panel().apply_command(
+                        self.panels[self.active_panel_idx].apply_command(
                             w,
                             &cmd,
                             &other_path,
                             screen,
                             panel_skin,
                             preview,
+                            &self.stage,
                             con,
                         )?;
                     }
@@ -316,9 +573,397 @@ impl App {
             DisplayError(txt) => {
                 error = Some(txt);
             }
+            ExecuteChain(steps) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                for step in steps {
+                    let cmd = Command::Internal {
+                        internal: step.internal,
+                        input_invocation: Some(step.to_invocation()),
+                    };
+                    self.apply_command(w, cmd, panel_skin, con)?;
+                    if self.quitting || self.panel().has_error() {
+                        break; // the next internals aren't executed
+                    }
+                }
+                return Ok(());
+            }
             ExecuteSequence { sequence } => {
                 self.tx_seqs.send(sequence).unwrap();
             }
+            ExecuteSteps { steps, stop_on_error } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                for step in steps {
+                    match step {
+                        VerbExecution::Internal(internal_exec) => {
+                            let cmd = Command::Internal {
+                                internal: internal_exec.internal,
+                                input_invocation: Some(internal_exec.to_invocation()),
+                            };
+                            self.apply_command(w, cmd, panel_skin, con)?;
+                        }
+                        VerbExecution::External(external_exec) => {
+                            let builder = ExecutionStringBuilder::from_selection(self.state().selection())
+                                .with_staged_paths(self.stage.paths());
+                            match external_exec.to_cmd_result(w, builder, con)? {
+                                DisplayError(txt) => {
+                                    self.mut_panel().set_error(txt);
+                                }
+                                _ => {
+                                    // the process didn't leave broot and may have
+                                    // changed the files
+                                    clear_caches();
+                                    for i in 0..self.panels.len().get() {
+                                        self.panels[i].mut_state().refresh(screen, con);
+                                    }
+                                }
+                            }
+                        }
+                        _ => {
+                            warn!("unexpected step: {}", step);
+                        }
+                    }
+                    if self.quitting || (stop_on_error && self.panel().has_error()) {
+                        break; // the next steps aren't executed
+                    }
+                }
+                return Ok(());
+            }
+            Repeat(count) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match self.last_repeatable.clone() {
+                    Some(cmd) => {
+                        for _ in 0..count {
+                            self.apply_command(w, cmd.clone(), panel_skin, con)?;
+                            if self.quitting || self.panel().has_error() {
+                                break;
+                            }
+                        }
+                        return Ok(());
+                    }
+                    None => {
+                        error = Some("no navigation internal to repeat".to_string());
+                    }
+                }
+            }
+            HandleInApp(Internal::reload_config) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match con.reload_config() {
+                    Ok(()) => {
+                        message = Some(format!(
+                            "configuration reloaded, {} verbs available",
+                            con.verb_store.verbs.len(),
+                        ));
+                    }
+                    Err(e) => {
+                        error = Some(format!("configuration not reloaded: {}", e));
+                    }
+                }
+                self.mut_panel().refresh_input_status(&other_path, con);
+            }
+            HandleInApp(internal @ Internal::yank) | HandleInApp(internal @ Internal::cut) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let paths = if self.stage.is_empty() {
+                    vec![self.state().selected_path().to_path_buf()]
+                } else {
+                    self.stage.paths().to_vec()
+                };
+                let action = if internal == Internal::yank {
+                    self.yank_buffer.yank(paths);
+                    "copied"
+                } else {
+                    self.yank_buffer.cut(paths);
+                    "moved"
+                };
+                message = Some(format!(
+                    "{} paths will be {} on :paste",
+                    self.yank_buffer.len(),
+                    action,
+                ));
+            }
+            HandleInApp(internal @ Internal::undo) | HandleInApp(internal @ Internal::redo) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let undo = internal == Internal::undo;
+                let result = if undo {
+                    journal::journal().undo()
+                } else {
+                    journal::journal().redo()
+                };
+                match result {
+                    Ok(Some(description)) => {
+                        message = Some(format!(
+                            "{}: {}",
+                            if undo { "undone" } else { "redone" },
+                            description,
+                        ));
+                        clear_caches();
+                        for i in 0..self.panels.len().get() {
+                            self.panels[i].mut_state().refresh(screen, con);
+                        }
+                    }
+                    Ok(None) => {
+                        error = Some(format!(
+                            "no file operation to {}",
+                            if undo { "undo" } else { "redo" },
+                        ));
+                    }
+                    Err(e) => {
+                        error = Some(format!(
+                            "the operation can't be {}: {}",
+                            if undo { "undone" } else { "redone" },
+                            e,
+                        ));
+                    }
+                }
+            }
+            Paste { on_conflict, answer } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                if self.yank_buffer.is_empty() {
+                    error = Some("nothing to paste: use :yank or :cut first".to_string());
+                } else {
+                    let dir = path::closest_dir(self.state().selected_path());
+                    let mode = self.yank_buffer.mode();
+                    match self.yank_buffer.paste(&dir, on_conflict, answer) {
+                        Ok(report) => {
+                            if let Some(operation) = report.operation(mode, &dir) {
+                                journal::record(operation);
+                            }
+                            let mut text = format!("{} paths pasted", report.created.len());
+                            if report.skipped > 0 {
+                                text.push_str(&format!(", {} skipped", report.skipped));
+                            }
+                            if let Some(conflict) = report.conflict {
+                                text.push_str(&format!(
+                                    ", {:?} already exists: `:paste overwrite`, `:paste skip` or `:paste rename` to go on",
+                                    conflict.file_name().unwrap_or_default(),
+                                ));
+                            }
+                            message = Some(text);
+                        }
+                        Err(e) => {
+                            // the paths pasted before the failure aren't known
+                            journal::record(journal::Operation::Irreversible {
+                                path: dir.clone(),
+                                description: format!("failed paste into {:?}", dir),
+                            });
+                            error = Some(format!("paste failed: {}", e));
+                        }
+                    }
+                    clear_caches();
+                    for i in 0..self.panels.len().get() {
+                        self.panels[i].mut_state().refresh(screen, con);
+                    }
+                }
+            }
+            HandleInApp(Internal::set_other_to_parent) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let selected_path = self.state().selected_path();
+                let target = selected_path.parent().unwrap_or(selected_path).to_path_buf();
+                if let Some(idx) = self.other_panel_idx() {
+                    let options = self.panels[idx].state().tree_options().without_pattern();
+                    match BrowserState::new(target, options, screen, con, &Dam::unlimited()) {
+                        Ok(Some(state)) => {
+                            self.panels[idx].push_state(Box::new(state));
+                        }
+                        Ok(None) => {} // interrupted
+                        Err(e) => {
+                            error = Some(e.to_string());
+                        }
+                    }
+                } else {
+                    // the state saw another panel, it may have been closed since
+                    error = Some("No other panel to display the parent in".to_string());
+                }
+            }
+            HandleInApp(internal @ Internal::recent_prev)
+            | HandleInApp(internal @ Internal::recent_next) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let older = internal == Internal::recent_prev;
+                let path = self.recent_files
+                    .cycle(older, Path::exists)
+                    .map(Path::to_path_buf);
+                if let Some(path) = path {
+                    if !self.mut_state().try_select_path(&path, screen) {
+                        // the file isn't in the tree, we open its parent
+                        let options = self.state().tree_options().without_pattern();
+                        match locate_state(&path, screen, options, con) {
+                            Ok(Some(state)) => {
+                                self.mut_panel().push_state(Box::new(state));
+                            }
+                            Ok(None) => {} // interrupted
+                            Err(e) => {
+                                error = Some(e.to_string());
+                            }
+                        }
+                    }
+                } else {
+                    error = Some("There's no other recent file".to_string());
+                }
+            }
+            HandleInApp(Internal::toggle_split) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let panel_ids: Vec<PanelId> = self.panels.iter().map(|panel| panel.id).collect();
+                match split_action(&panel_ids, self.preview, self.split) {
+                    SplitAction::Split => {
+                        if let Some(state) = self.state().clone_state() {
+                            let original = self.panel().id;
+                            let insertion_idx = self.active_panel_idx + 1;
+                            match Areas::create(
+                                self.panels.as_mut_slice(),
+                                insertion_idx,
+                                screen,
+                                self.preview.is_some(),
+                            ) {
+                                Ok(areas) => {
+                                    let copy = self.created_panels_count.into();
+                                    self.created_panels_count += 1;
+                                    self.panels.insert(insertion_idx, Panel::new(copy, state, areas, con));
+                                    self.active_panel_idx = insertion_idx;
+                                    self.split = Some(Split { original, copy });
+                                }
+                                Err(e) => {
+                                    error = Some(e.to_string());
+                                }
+                            }
+                        } else {
+                            error = Some("This panel can't be split".to_string());
+                        }
+                    }
+                    SplitAction::Unsplit(split) => {
+                        if let Some(idx) = self.panel_idx(split.copy) {
+                            self.close_panel(idx);
+                        }
+                        if let Some(idx) = self.panel_idx(split.original) {
+                            self.active_panel_idx = idx;
+                        }
+                        self.split = None;
+                    }
+                    SplitAction::Impossible => {
+                        error = Some(
+                            "toggle_split needs a single panel, or a split made with toggle_split"
+                                .to_string()
+                        );
+                    }
+                }
+            }
+            HandleInApp(Internal::toggle_verb_bar) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let mut screen = self.screen;
+                if self.verb_bar {
+                    screen.height += 1;
+                } else {
+                    screen.height = screen.height.saturating_sub(1);
+                }
+                match Areas::resize_all(self.panels.as_mut_slice(), screen, self.preview.is_some()) {
+                    Ok(()) => {
+                        self.screen = screen;
+                        self.verb_bar = !self.verb_bar;
+                        for panel in &mut self.panels {
+                            panel.mut_state().refresh(screen, con);
+                        }
+                    }
+                    Err(e) => {
+                        error = Some(e.to_string());
+                    }
+                }
+            }
+            HandleInApp(Internal::toggle_watch) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                message = Some(if self.watcher.toggle() {
+                    "the trees are refreshed when files change".to_string()
+                } else {
+                    "the trees aren't refreshed anymore when files change".to_string()
+                });
+            }
+            HandleInApp(Internal::toggle_exec) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let path = self.state().selected_path().to_path_buf();
+                #[cfg(not(any(target_family = "windows", target_os = "android")))]
+                match crate::permissions::toggle_owner_exec(&path) {
+                    Ok(executable) => {
+                        message = Some(if executable {
+                            format!("{} is now executable", path.to_string_lossy())
+                        } else {
+                            format!("{} is no longer executable", path.to_string_lossy())
+                        });
+                        journal::record(journal::Operation::ToggleExec { path: path.clone() });
+                    }
+                    Err(e) => {
+                        error = Some(format!("permissions not changed: {}", e));
+                    }
+                }
+                #[cfg(any(target_family = "windows", target_os = "android"))]
+                {
+                    message = Some(format!(
+                        "{} unchanged: executable bits aren't supported on this platform",
+                        path.to_string_lossy(),
+                    ));
+                }
+                self.mut_state().refresh(screen, con);
+            }
+            HandleInApp(internal @ Internal::stage)
+            | HandleInApp(internal @ Internal::unstage)
+            | HandleInApp(internal @ Internal::toggle_stage) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let path = self.state().selected_path().to_path_buf();
+                let add = match internal {
+                    Internal::stage => true,
+                    Internal::unstage => false,
+                    _ => !self.stage.contains(&path),
+                };
+                let changed = if add {
+                    self.stage.add(path.clone())
+                } else {
+                    self.stage.remove(&path)
+                };
+                let action = match (add, changed) {
+                    (true, true) => "added to the stage",
+                    (true, false) => "already staged",
+                    (false, true) => "removed from the stage",
+                    (false, false) => "not staged",
+                };
+                message = Some(format!(
+                    "{} {}, the stage now contains {} files",
+                    path.to_string_lossy(),
+                    action,
+                    self.stage.len(),
+                ));
+            }
+            HandleInApp(Internal::clear_stage) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let count = self.stage.len();
+                self.stage.clear();
+                message = Some(format!("{} files removed from the stage", count));
+            }
             HandleInApp(internal) => {
                 let new_active_panel_idx = match internal {
                     Internal::panel_left if self.active_panel_idx > 0 => {
@@ -341,11 +986,54 @@ impl App {
                     self.mut_panel().refresh_input_status(&other_path, con);
                 }
             }
+            FilterPaths(path_set) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match self.state().tree_root() {
+                    Some(root) => {
+                        let root = root.to_path_buf();
+                        let count = path_set.len();
+                        let mut options = self.state().tree_options().without_pattern();
+                        options.path_set = Some(path_set);
+                        match BrowserState::new(root, options, screen, con, &Dam::unlimited()) {
+                            Ok(Some(state)) => {
+                                self.mut_panel().clear_input();
+                                self.mut_panel().push_state(Box::new(state));
+                                message = Some(format!("tree filtered on the {} listed paths", count));
+                            }
+                            Ok(None) => {} // interrupted
+                            Err(e) => {
+                                error = Some(e.to_string());
+                            }
+                        }
+                    }
+                    None => {
+                        error = Some("no tree to filter".to_string());
+                    }
+                }
+            }
             Keep => {
                 if is_input_invocation {
                     self.mut_panel().clear_input_invocation();
                 }
             }
+            LaunchedInBackground(job) => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                message = Some(format!(
+                    "`{}` launched in background ({} running)",
+                    job.label,
+                    self.background_jobs.running_count() + 1,
+                ));
+                // the main loop is woken up by an empty sequence at the
+                // end of the job, and at each tick of its spinner
+                let tx_seqs = self.tx_seqs.clone();
+                self.background_jobs.watch(*job, move || {
+                    let _ = tx_seqs.send(Sequence::new_single(String::new()));
+                });
+            }
             Launch(launchable) => {
                 self.launch_at_end = Some(*launchable);
                 self.quitting = true;
@@ -390,6 +1078,131 @@ impl App {
                     }
                 }
             }
+            SaveLayout { name } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match self.layout() {
+                    Some(layout) => match layout.save(&name) {
+                        Ok(()) => {
+                            message = Some(format!("layout {:?} saved", name));
+                        }
+                        Err(e) => {
+                            error = Some(format!("layout not saved: {}", e));
+                        }
+                    },
+                    None => {
+                        error = Some("no panel to save".to_string());
+                    }
+                }
+            }
+            SaveSession { name } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match self.session() {
+                    Some(session) => match session.save(&name) {
+                        Ok(()) => {
+                            message = Some(format!("session {:?} saved", name));
+                        }
+                        Err(e) => {
+                            error = Some(format!("session not saved: {}", e));
+                        }
+                    },
+                    None => {
+                        error = Some("no panel to save".to_string());
+                    }
+                }
+            }
+            LoadSession { name } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match Session::load(&name).and_then(|session| self.apply_session(&session, con)) {
+                    Ok(()) => {
+                        message = Some(format!("session {:?} loaded", name));
+                    }
+                    Err(e) => {
+                        error = Some(format!("session not loaded: {}", e));
+                    }
+                }
+            }
+            SwitchMode { name } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                self.mut_panel().set_mode(name);
+            }
+            LoadLayout { name } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match Layout::load(&name).and_then(|layout| self.apply_layout(&layout, con)) {
+                    Ok(()) => {
+                        message = Some(format!("layout {:?} loaded", name));
+                    }
+                    Err(e) => {
+                        error = Some(format!("layout not loaded: {}", e));
+                    }
+                }
+            }
+            Mark { command: MarkCommand::Run { name, verb }, .. } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                match MarkSet::load(&name) {
+                    Ok(set) if set.is_empty() => {
+                        error = Some(format!("the mark set {:?} is empty", name));
+                    }
+                    Ok(set) => {
+                        // the verb is executed with the mark set as stage, so
+                        // that its paths are the `{selection}`
+                        let mut mark_stage = Stage::default();
+                        for path in set.paths() {
+                            mark_stage.add(path.clone());
+                        }
+                        let stage = std::mem::replace(&mut self.stage, mark_stage);
+                        let cmd = Command::from_raw(format!(":{}", verb), true);
+                        let result = self.apply_command(w, cmd, panel_skin, con);
+                        self.stage = stage;
+                        return result;
+                    }
+                    Err(e) => {
+                        error = Some(format!("mark set not read: {}", e));
+                    }
+                }
+            }
+            Mark { command, path } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                let name = command.name();
+                let add = matches!(command, MarkCommand::Add { .. });
+                let updated = MarkSet::load(name).and_then(|mut set| {
+                    let changed = if add { set.add(path) } else { set.remove(&path) };
+                    set.save(name)?;
+                    Ok((changed, set.len()))
+                });
+                match updated {
+                    Ok((changed, len)) => {
+                        let action = match (add, changed) {
+                            (true, true) => "added to",
+                            (true, false) => "already in",
+                            (false, true) => "removed from",
+                            (false, false) => "not in",
+                        };
+                        message = Some(format!(
+                            "selection {} the mark set {:?}, which now contains {} paths",
+                            action,
+                            name,
+                            len,
+                        ));
+                    }
+                    Err(e) => {
+                        error = Some(format!("mark set not updated: {}", e));
+                    }
+                }
+            }
             NewState(state) => {
                 self.mut_panel().clear_input();
                 self.mut_panel().push_state(state);
@@ -414,13 +1227,14 @@ impl App {
                 }
                 if self.remove_state() {
                     let preview = self.preview;
-                    self.mut_panel().apply_command(
+                    self.panels[self.active_panel_idx].apply_command(
                         w,
                         &cmd,
                         &other_path,
                         screen,
                         panel_skin,
                         preview,
+                        &self.stage,
                         con,
                     )?;
                 } else if ESCAPE_TO_QUIT {
@@ -441,9 +1255,41 @@ impl App {
                     self.panels[i].mut_state().refresh(screen, con);
                 }
             }
+            RefreshAndSelect { path } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                clear_caches();
+                for i in 0..self.panels.len().get() {
+                    self.panels[i].mut_state().refresh(screen, con);
+                }
+                if !self.mut_state().try_select_path(&path, screen) {
+                    message = Some(format!("{} isn't visible in the tree", path.to_string_lossy()));
+                }
+            }
+            Restage { paths } => {
+                if is_input_invocation {
+                    self.mut_panel().clear_input_invocation();
+                }
+                clear_caches();
+                self.stage.clear();
+                for path in paths {
+                    self.stage.add(path);
+                }
+                for i in 0..self.panels.len().get() {
+                    self.panels[i].mut_state().refresh(screen, con);
+                }
+                message = Some(format!("{} modified files staged", self.stage.len()));
+            }
         }
         if let Some(text) = error {
             self.mut_panel().set_error(text);
+        } else if let Some(text) = message {
+            self.mut_panel().set_message(text);
+        }
+        if self.state().tree_root().is_some() && self.state().selection().stype == SelectionType::File {
+            let path = self.state().selected_path().to_path_buf();
+            self.recent_files.push(&path);
         }
         self.update_preview(con);
 
@@ -451,10 +1297,71 @@ impl App {
         if let Ok(mut root) = self.root.lock() { // when does this not work ?
             *root = self.state().selected_path().to_path_buf();
         }
+        #[cfg(feature="client-server")]
+        if let Ok(mut selection) = self.selection.lock() {
+            *selection = self.state().selected_path().to_path_buf();
+        }
 
         Ok(())
     }
 
+    /// return the debounce window of the verb the command would
+    /// execute, if any
+    fn debounce_window(&self, cmd: &Command, con: &AppContext) -> Option<Duration> {
+        match cmd {
+            Command::VerbTrigger { index, .. } => con.verb_store.verbs[*index].debounce,
+            Command::VerbInvocate(invocation) => match con.verb_store.search(
+                &invocation.name,
+                Some(self.state().selection().stype),
+            ) {
+                PrefixSearchResult::Match(_, verb) => verb.debounce,
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// delay the command until its debounce window is elapsed
+    /// without other trigger
+    fn debounce(&mut self, cmd: Command, window: Duration) {
+        self.debouncer.push(cmd, Instant::now(), window);
+    }
+
+    /// tell the user about the background jobs which ended, and
+    /// refresh the panels as those jobs may have changed files
+    fn notify_ended_jobs(&mut self, con: &AppContext) {
+        let ends = self.background_jobs.take_ended();
+        if ends.is_empty() {
+            return;
+        }
+        let text = ends
+            .iter()
+            .map(JobEnd::description)
+            .collect::<Vec<String>>()
+            .join(", ");
+        clear_caches();
+        for i in 0..self.panels.len().get() {
+            self.panels[i].mut_state().refresh(self.screen, con);
+        }
+        if ends.iter().all(JobEnd::is_success) {
+            self.mut_panel().set_message(text);
+        } else {
+            self.mut_panel().set_error(text);
+        }
+    }
+
+    /// refresh the panels if the watcher found changes in
+    /// the displayed directories
+    fn refresh_watched(&mut self, con: &AppContext) {
+        if !self.watcher.take_changed() {
+            return;
+        }
+        clear_caches();
+        for i in 0..self.panels.len().get() {
+            self.panels[i].mut_state().refresh(self.screen, con);
+        }
+    }
+
     /// update the state of the preview, if there's some
     fn update_preview(&mut self, con: &AppContext) {
         let preview_idx = self.preview.and_then(|id| self.panel_idx(id));
@@ -529,7 +1436,7 @@ impl App {
     pub fn run(
         mut self,
         w: &mut W,
-        con: &AppContext,
+        con: &mut AppContext,
         conf: &Conf,
     ) -> Result<Option<Launchable>, ProgramError> {
         // we listen for events in a separate thread so that we can go on listening
@@ -554,6 +1461,7 @@ impl App {
                 &server_name,
                 self.tx_seqs.clone(),
                 Arc::clone(&self.root),
+                Arc::clone(&self.selection),
             ))
             .transpose()?;
 
@@ -565,6 +1473,14 @@ impl App {
                     "pending_tasks",
                     self.do_pending_tasks(w, &skin, &mut dam, con)?,
                 );
+                if self.watcher.is_enabled() {
+                    self.watcher.set_dirs(
+                        self.panels
+                            .iter()
+                            .flat_map(|panel| panel.state().watched_dirs())
+                            .collect()
+                    );
+                }
             }
             match dam.next(&self.rx_seqs) {
                 Either::First(Some(event)) => {
@@ -580,6 +1496,9 @@ impl App {
                         }
                         Event::Resize(w, h) => {
                             self.screen.set_terminal_size(w, h, con);
+                            if self.verb_bar {
+                                self.screen.height = self.screen.height.saturating_sub(1);
+                            }
                             Areas::resize_all(
                                 self.panels.as_mut_slice(),
                                 self.screen,
@@ -593,7 +1512,11 @@ impl App {
                             // event handled by the panel
                             let cmd = self.mut_panel().add_event(w, event, con)?;
                             debug!("command after add_event: {:?}", &cmd);
-                            self.apply_command(w, cmd, &skin.focused, con)?;
+                            if let Some(window) = self.debounce_window(&cmd, con) {
+                                self.debounce(cmd, window);
+                            } else {
+                                self.apply_command(w, cmd, &skin.focused, con)?;
+                            }
                         }
                     }
                     event_source.unblock(self.quitting);
@@ -604,13 +1527,22 @@ impl App {
                     break;
                 }
                 Either::Second(Some(raw_sequence)) => {
+                    self.notify_ended_jobs(con);
+                    self.refresh_watched(con);
+                    if let Some(cmd) = self.debouncer.take_due(Instant::now()) {
+                        debug!("executing debounced command: {:?}", &cmd);
+                        self.apply_command(w, cmd, &skin.focused, con)?;
+                        if self.quitting {
+                            return Ok(self.end());
+                        }
+                    }
                     debug!("got command sequence: {:?}", &raw_sequence);
                     for (input, arg_cmd) in raw_sequence.parse(con)? {
                         self.mut_panel().set_input_content(&input);
                         self.apply_command(w, arg_cmd, &skin.focused, con)?;
                         if self.quitting {
                             // is that a 100% safe way of quitting ?
-                            return Ok(self.launch_at_end.take());
+                            return Ok(self.end());
                         } else {
                             self.display_panels(w, &skin, con)?;
                             time!(
@@ -627,7 +1559,7 @@ impl App {
             }
         }
 
-        Ok(self.launch_at_end.take())
+        Ok(self.end())
     }
 }
 
@@ -637,6 +1569,198 @@ impl App {
 fn clear_caches() {
     file_sum::clear_cache();
     git::clear_status_computer_cache();
+    crate::provider::clear_annotation_cache();
     #[cfg(unix)]
     crate::filesystems::clear_cache();
 }
+
+/// if the command executes a repeatable internal, return the command
+/// executing it again, with the same argument
+fn repeatable_command(
+    cmd: &Command,
+    verb_store: &VerbStore,
+    stype: SelectionType,
+) -> Option<Command> {
+    let (verb, input_invocation) = match cmd {
+        Command::Internal { internal, .. } => {
+            return Some(cmd.clone()).filter(|_| internal.is_repeatable());
+        }
+        Command::VerbTrigger { index, input_invocation } => {
+            (verb_store.verbs.get(*index)?, input_invocation.as_ref())
+        }
+        Command::VerbInvocate(invocation) => match verb_store.search(&invocation.name, Some(stype)) {
+            PrefixSearchResult::Match(_, verb) => (verb, Some(invocation)),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match &verb.execution {
+        VerbExecution::Internal(internal_exec)
+            if internal_exec.internal.is_repeatable() && !internal_exec.is_chain() =>
+        {
+            // arguments typed by the user take precedence over the
+            // ones of the verb definition
+            let input_invocation = input_invocation
+                .filter(|inv| inv.args.is_some())
+                .cloned()
+                .unwrap_or_else(|| internal_exec.to_invocation());
+            Some(Command::Internal {
+                internal: internal_exec.internal,
+                input_invocation: Some(input_invocation),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// given the number of panels, the index of the focused one and the
+/// one of the preview panel, return the index of the non focused
+/// panel when there are exactly two non preview panels
+fn other_panel_idx(
+    panels_count: usize,
+    active_idx: usize,
+    preview_idx: Option<usize>,
+) -> Option<usize> {
+    match (panels_count, preview_idx) {
+        (2, None) => Some(if active_idx == 0 { 1 } else { 0 }),
+        (3, Some(preview_idx)) => (0..3).find(|&idx| idx != active_idx && idx != preview_idx),
+        _ => None,
+    }
+}
+
+/// tell what `:toggle_split` must do, given the ids of the panels
+/// and the last split, which may not be valid anymore (e.g. when
+/// one of its panels was closed)
+fn split_action(
+    panel_ids: &[PanelId],
+    preview: Option<PanelId>,
+    split: Option<Split>,
+) -> SplitAction {
+    let tree_panels: Vec<PanelId> = panel_ids
+        .iter()
+        .copied()
+        .filter(|&id| Some(id) != preview)
+        .collect();
+    match (tree_panels.as_slice(), split) {
+        ([_], _) => SplitAction::Split,
+        ([a, b], Some(split))
+            if (*a == split.original && *b == split.copy)
+                || (*a == split.copy && *b == split.original) =>
+        {
+            SplitAction::Unsplit(split)
+        }
+        _ => SplitAction::Impossible,
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use {
+        super::*,
+        crate::verb::{InternalExecution, Verb, VerbDescription, VerbInvocation},
+    };
+
+    fn internal_arg(cmd: Option<Command>) -> Option<(Internal, Option<String>)> {
+        match cmd {
+            Some(Command::Internal { internal, input_invocation }) => {
+                Some((internal, input_invocation.and_then(|inv| inv.args)))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_repeatable_command() {
+        let mut conf = Conf::default();
+        let mut verb_store = VerbStore::default();
+        verb_store.init(&mut conf).unwrap();
+        verb_store.verbs.push(Verb::new(
+            Some("down2"),
+            VerbExecution::Internal(InternalExecution::try_from("line_down 2").unwrap()),
+            VerbDescription::from_text(String::new()),
+        ).unwrap());
+        let repeatable = |cmd: Command| {
+            internal_arg(repeatable_command(&cmd, &verb_store, SelectionType::File))
+        };
+        let typed = |raw: &str| Command::from_raw(raw.to_string(), true);
+        // internals typed in the input, with or without argument
+        assert_eq!(repeatable(typed(":line_down")), Some((Internal::line_down, None)));
+        assert_eq!(
+            repeatable(typed(":line_up 5")),
+            Some((Internal::line_up, Some("5".to_string()))),
+        );
+        // the argument of the verb definition is kept
+        assert_eq!(
+            repeatable(typed(":down2")),
+            Some((Internal::line_down, Some("2".to_string()))),
+        );
+        // internals triggered by a key
+        let index = verb_store.verbs
+            .iter()
+            .position(|verb| verb.names.iter().any(|name| name == "page_down"))
+            .unwrap();
+        assert_eq!(
+            repeatable(Command::VerbTrigger { index, input_invocation: None }),
+            Some((Internal::page_down, None)),
+        );
+        assert_eq!(
+            repeatable(Command::Internal {
+                internal: Internal::next_match,
+                input_invocation: None,
+            }),
+            Some((Internal::next_match, None)),
+        );
+        // other commands aren't repeatable
+        assert_eq!(repeatable(typed(":focus")), None);
+        assert_eq!(repeatable(typed(":repeat 3")), None);
+        assert_eq!(repeatable(typed(":toggle_hidden")), None);
+        assert_eq!(repeatable(typed("some pattern")), None);
+        assert_eq!(
+            repeatable(Command::Internal {
+                internal: Internal::quit,
+                input_invocation: Some(VerbInvocation::from("quit")),
+            }),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_other_panel_idx() {
+        // a single panel, with or without preview: there's no other one
+        assert_eq!(other_panel_idx(1, 0, None), None);
+        assert_eq!(other_panel_idx(2, 0, Some(1)), None);
+        // two panels
+        assert_eq!(other_panel_idx(2, 0, None), Some(1));
+        assert_eq!(other_panel_idx(2, 1, None), Some(0));
+        // two panels and a preview
+        assert_eq!(other_panel_idx(3, 0, Some(2)), Some(1));
+        assert_eq!(other_panel_idx(3, 1, Some(2)), Some(0));
+        assert_eq!(other_panel_idx(3, 2, Some(0)), Some(1));
+        // too many panels
+        assert_eq!(other_panel_idx(3, 0, None), None);
+        assert_eq!(other_panel_idx(4, 0, Some(3)), None);
+    }
+
+    #[test]
+    fn test_split_action() {
+        let ids = |ids: &[usize]| ids.iter().map(|&id| PanelId::from(id)).collect::<Vec<_>>();
+        let split = Split { original: 0.into(), copy: 3.into() };
+        // a single panel, with or without preview, is split
+        assert_eq!(split_action(&ids(&[0]), None, None), SplitAction::Split);
+        assert_eq!(split_action(&ids(&[0, 1]), Some(1.into()), None), SplitAction::Split);
+        // a stale split doesn't prevent a new one
+        assert_eq!(split_action(&ids(&[0]), None, Some(split)), SplitAction::Split);
+        // the split is closed, whatever the order of the panels
+        assert_eq!(split_action(&ids(&[0, 3]), None, Some(split)), SplitAction::Unsplit(split));
+        assert_eq!(split_action(&ids(&[3, 0]), None, Some(split)), SplitAction::Unsplit(split));
+        assert_eq!(
+            split_action(&ids(&[0, 3, 4]), Some(4.into()), Some(split)),
+            SplitAction::Unsplit(split),
+        );
+        // panels which don't come from the split
+        assert_eq!(split_action(&ids(&[0, 1]), None, None), SplitAction::Impossible);
+        assert_eq!(split_action(&ids(&[0, 1]), None, Some(split)), SplitAction::Impossible);
+        assert_eq!(split_action(&ids(&[0, 3, 5]), None, Some(split)), SplitAction::Impossible);
+    }
+}
@@ -0,0 +1,279 @@
+use {
+    crossbeam::channel::{unbounded, Receiver, Sender},
+    std::{
+        io,
+        process::{Child, ExitStatus},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    },
+};
+
+/// the frames of the spinner shown while progress jobs run
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// the delay between two frames of the spinner
+const TICK: Duration = Duration::from_millis(120);
+
+/// a process launched by a verb in background: broot doesn't
+/// wait for its end but goes on answering the user
+#[derive(Debug)]
+pub struct BackgroundJob {
+    pub label: String,
+    pub child: Child,
+    /// whether a spinner must be displayed while the job runs
+    pub show_progress: bool,
+}
+
+/// the end of a background job
+#[derive(Debug)]
+pub struct JobEnd {
+    id: usize,
+    pub label: String,
+    pub status: io::Result<ExitStatus>,
+}
+
+impl JobEnd {
+    pub fn is_success(&self) -> bool {
+        matches!(&self.status, Ok(status) if status.success())
+    }
+    /// a short description, for the status line
+    pub fn description(&self) -> String {
+        match &self.status {
+            Ok(status) => match status.code() {
+                Some(code) => format!("`{}` ended with exit code {}", self.label, code),
+                None => format!("`{}` was terminated by a signal", self.label),
+            },
+            Err(e) => format!("`{}` couldn't be waited for: {}", self.label, e),
+        }
+    }
+}
+
+/// the background jobs launched by the application.
+///
+/// Each job is waited for in its own thread, so that it doesn't
+/// stay a zombie, and its end is queued until the application
+/// takes it. When broot quits, the processes still running
+/// aren't killed.
+#[derive(Debug)]
+pub struct BackgroundJobs {
+    tx_ends: Sender<JobEnd>,
+    rx_ends: Receiver<JobEnd>,
+    running: usize,
+    next_id: usize,
+    /// the jobs with a spinner whose end wasn't taken yet
+    progress_jobs: Vec<(usize, String)>,
+    ticker: Arc<Ticker>,
+}
+
+/// the state shared with the thread animating the spinner,
+/// which runs only while some progress jobs run
+#[derive(Debug, Default)]
+struct Ticker {
+    /// the number of progress jobs whose process is running
+    running: AtomicUsize,
+    /// whether the ticking thread is alive
+    ticking: AtomicBool,
+    /// the number of ticks since the start of the application
+    ticks: AtomicUsize,
+}
+
+impl Ticker {
+    /// start the ticking thread, unless it's already running
+    fn start<F>(ticker: &Arc<Self>, wake: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        if ticker.ticking.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let ticker = Arc::clone(ticker);
+        thread::spawn(move || loop {
+            while ticker.running.load(Ordering::SeqCst) > 0 {
+                thread::sleep(TICK);
+                ticker.ticks.fetch_add(1, Ordering::SeqCst);
+                wake();
+            }
+            ticker.ticking.store(false, Ordering::SeqCst);
+            // a job may have been launched just before the thread
+            // declared itself stopped, in which case it must go on
+            if ticker.running.load(Ordering::SeqCst) == 0
+                || ticker.ticking.swap(true, Ordering::SeqCst)
+            {
+                break;
+            }
+        });
+    }
+}
+
+impl Default for BackgroundJobs {
+    fn default() -> Self {
+        let (tx_ends, rx_ends) = unbounded();
+        Self {
+            tx_ends,
+            rx_ends,
+            running: 0,
+            next_id: 0,
+            progress_jobs: Vec::new(),
+            ticker: Arc::default(),
+        }
+    }
+}
+
+impl BackgroundJobs {
+    /// wait for the end of the job in a new thread, then queue
+    /// this end and call `wake`.
+    ///
+    /// When the job shows its progress, `wake` is also called at
+    /// each tick of the spinner, until the job ends.
+    pub fn watch<F>(&mut self, job: BackgroundJob, wake: F)
+    where
+        F: Fn() + Clone + Send + 'static,
+    {
+        self.running += 1;
+        let id = self.next_id;
+        self.next_id += 1;
+        let BackgroundJob { label, mut child, show_progress } = job;
+        if show_progress {
+            self.progress_jobs.push((id, label.clone()));
+            self.ticker.running.fetch_add(1, Ordering::SeqCst);
+            Ticker::start(&self.ticker, wake.clone());
+        }
+        let tx_ends = self.tx_ends.clone();
+        let ticker = Arc::clone(&self.ticker);
+        thread::spawn(move || {
+            let status = child.wait();
+            debug!("background job {:?} ended: {:?}", &label, &status);
+            if show_progress {
+                ticker.running.fetch_sub(1, Ordering::SeqCst);
+            }
+            let _ = tx_ends.send(JobEnd { id, label, status });
+            wake();
+        });
+    }
+    /// return the ends of the jobs which finished since the last call
+    pub fn take_ended(&mut self) -> Vec<JobEnd> {
+        let ends: Vec<JobEnd> = self.rx_ends.try_iter().collect();
+        self.running -= ends.len();
+        self.progress_jobs.retain(|(id, _)| !ends.iter().any(|end| end.id == *id));
+        ends
+    }
+    /// return the text of the spinner to display, if some jobs
+    /// showing their progress are running
+    pub fn progress(&self) -> Option<String> {
+        let (_, label) = self.progress_jobs.first()?;
+        let ticks = self.ticker.ticks.load(Ordering::SeqCst);
+        let frame = SPINNER[ticks % SPINNER.len()];
+        Some(match self.progress_jobs.len() {
+            1 => format!("{} {}", frame, label),
+            n => format!("{} {} (+{})", frame, label, n - 1),
+        })
+    }
+    /// return the number of jobs whose end wasn't taken yet
+    pub fn running_count(&self) -> usize {
+        self.running
+    }
+}
+
+#[cfg(all(test, unix))]
+mod background_jobs_tests {
+
+    use {
+        super::*,
+        std::{
+            process::{Command, Stdio},
+            time::Duration,
+        },
+    };
+
+    fn job(script: &str, show_progress: bool) -> BackgroundJob {
+        let child = Command::new("sh")
+            .args(["-c", script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        BackgroundJob {
+            label: script.to_string(),
+            child,
+            show_progress,
+        }
+    }
+
+    #[test]
+    fn test_several_jobs() {
+        let mut jobs = BackgroundJobs::default();
+        let (tx_wake, rx_wake) = unbounded();
+        for script in &["exit 0", "sleep 0.2; exit 3", "exit 1"] {
+            let tx_wake = tx_wake.clone();
+            jobs.watch(job(script, false), move || {
+                let _ = tx_wake.send(());
+            });
+        }
+        assert_eq!(jobs.running_count(), 3);
+        let mut ends = Vec::new();
+        while ends.len() < 3 {
+            rx_wake.recv_timeout(Duration::from_secs(10)).unwrap();
+            ends.extend(jobs.take_ended());
+        }
+        assert_eq!(jobs.running_count(), 0);
+        ends.sort_by(|a, b| a.label.cmp(&b.label));
+        let codes: Vec<Option<i32>> = ends
+            .iter()
+            .map(|end| end.status.as_ref().unwrap().code())
+            .collect();
+        assert_eq!(codes, vec![Some(0), Some(1), Some(3)]);
+        assert!(ends[0].is_success());
+        assert!(!ends[1].is_success());
+        assert_eq!(ends[2].description(), "`sleep 0.2; exit 3` ended with exit code 3");
+        assert!(jobs.take_ended().is_empty());
+        assert!(jobs.progress().is_none());
+    }
+
+    #[test]
+    fn test_progress_lifecycle() {
+        let mut jobs = BackgroundJobs::default();
+        let (tx_wake, rx_wake) = unbounded();
+        let wake = move || {
+            let _ = tx_wake.send(());
+        };
+        // a job without progress doesn't make the spinner visible
+        jobs.watch(job("sleep 0.3", false), wake.clone());
+        assert!(jobs.progress().is_none());
+        jobs.watch(job("sleep 0.8", true), wake.clone());
+        let first = jobs.progress().unwrap();
+        assert!(first.ends_with(" sleep 0.8"), "progress: {:?}", first);
+        jobs.watch(job("sleep 0.5", true), wake);
+        assert!(jobs.progress().unwrap().ends_with(" sleep 0.8 (+1)"));
+        // the spinner turns while the jobs run
+        let mut texts = vec![jobs.progress().unwrap()];
+        let mut ends = Vec::new();
+        while ends.len() < 3 {
+            rx_wake.recv_timeout(Duration::from_secs(10)).unwrap();
+            ends.extend(jobs.take_ended());
+            if let Some(text) = jobs.progress() {
+                texts.push(text);
+            }
+            // the spinner is visible exactly while progress jobs run
+            let progress_running = ends.iter().filter(|end| end.label != "sleep 0.3").count() < 2;
+            assert_eq!(jobs.progress().is_some(), progress_running);
+        }
+        texts.dedup();
+        assert!(texts.len() > 2, "texts: {:?}", texts);
+        assert!(jobs.progress().is_none());
+        // once all progress jobs ended, the ticking stops
+        thread::sleep(TICK * 3);
+        while rx_wake.try_recv().is_ok() {}
+        thread::sleep(TICK * 3);
+        assert!(rx_wake.try_recv().is_err());
+        assert!(!jobs.ticker.ticking.load(Ordering::SeqCst));
+        // and it starts again with a new progress job
+        jobs.watch(job("sleep 0.3", true), move || {});
+        assert!(jobs.ticker.ticking.load(Ordering::SeqCst));
+        assert!(jobs.progress().is_some());
+    }
+}
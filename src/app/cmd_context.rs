@@ -4,8 +4,9 @@ use {
         command::*,
         display::Areas,
         skin::PanelSkin,
+        stage::Stage,
     },
-    std::path::PathBuf,
+    std::path::{Path, PathBuf},
 };
 
 /// short lived wrapping of a few things which are needed for the handling
@@ -14,6 +15,8 @@ pub struct CmdContext<'c> {
     pub cmd: &'c Command,
     pub other_path: &'c Option<PathBuf>,
     pub panel_skin: &'c PanelSkin,
+    pub stage: &'c Stage, // the paths staged in the app
+    pub initial_selection: Option<&'c Path>, // selection when the verb was started
     pub con: &'c AppContext,
     pub areas: &'c Areas,
     pub preview: Option<PanelId>, // id of the app's preview panel
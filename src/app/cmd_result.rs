@@ -5,9 +5,15 @@ use {
         command::Sequence,
         errors::TreeBuildError,
         launchable::Launchable,
-        verb::Internal,
+        stage::MarkCommand,
+        tree::PathSet,
+        verb::{Internal, InternalExecution, VerbExecution},
+        yank::ConflictPolicy,
+    },
+    std::{
+        fmt,
+        path::PathBuf,
     },
-    std::fmt,
 };
 
 /// Either left or right
@@ -19,6 +25,11 @@ pub enum HDir {
 
 /// Result of applying a command to a state
 pub enum AppStateCmdResult {
+    AddToStage(Vec<PathBuf>), // the paths must be added to the app's stage
+    Bookmark {
+        name: String,
+        path: PathBuf, // the directory to bookmark
+    },
     ApplyOnPanel {
         id: PanelId,
     },
@@ -27,24 +38,61 @@ pub enum AppStateCmdResult {
         id: Option<PanelId>, // None if current panel
     },
     DisplayError(String),
+    ExecuteChain(Vec<InternalExecution>), // the internals must be executed in order
     ExecuteSequence {
         sequence: Sequence,
     },
+    ExecuteSteps {
+        steps: Vec<VerbExecution>, // internals and externals, their groups already replaced
+        stop_on_error: bool,
+    },
     HandleInApp(Internal), // command must be handled at the app level
+    FilterPaths(PathSet), // the tree must be filtered on these paths
     Keep,
     Launch(Box<Launchable>),
+    LaunchedInBackground(Box<BackgroundJob>), // the app must watch the job's end
+    LoadLayout {
+        name: String,
+    },
+    LoadSession {
+        name: String,
+    },
+    Mark {
+        command: MarkCommand,
+        path: PathBuf, // the selection, to add or remove
+    },
     NewPanel {
         state: Box<dyn AppState>,
         purpose: PanelPurpose,
         direction: HDir,
     },
     NewState(Box<dyn AppState>),
+    Paste {
+        on_conflict: ConflictPolicy, // the policy of the verb
+        answer: Option<ConflictPolicy>, // the policy typed by the user
+    },
     PopStateAndReapply, // the state asks the command be executed on a previous state
     PopState,
     Quit,
+    Repeat(usize), // the last repeatable internal must be executed this number of times
+    RefreshAndSelect {
+        path: PathBuf, // after the refresh of all states, the path to select
+    },
     RefreshState {
         clear_cache: bool,
     },
+    Restage {
+        paths: Vec<PathBuf>, // after the refresh of all states, the new content of the stage
+    },
+    SaveLayout {
+        name: String,
+    },
+    SaveSession {
+        name: String,
+    },
+    SwitchMode {
+        name: String, // the key mode of the panel's input
+    },
 }
 
 impl AppStateCmdResult {
@@ -85,6 +133,8 @@ impl fmt::Debug for AppStateCmdResult {
             f,
             "{}",
             match self {
+                AppStateCmdResult::AddToStage(_) => "AddToStage",
+                AppStateCmdResult::Bookmark { .. } => "Bookmark",
                 AppStateCmdResult::ApplyOnPanel { .. } => "ApplyOnPanel",
                 AppStateCmdResult::ClosePanel {
                     validate_purpose: false, ..
@@ -93,16 +143,30 @@ impl fmt::Debug for AppStateCmdResult {
                     validate_purpose: true, ..
                 } => "OkPanel",
                 AppStateCmdResult::DisplayError(_) => "DisplayError",
+                AppStateCmdResult::ExecuteChain(_) => "ExecuteChain",
                 AppStateCmdResult::ExecuteSequence{ .. } => "ExecuteSequence",
+                AppStateCmdResult::ExecuteSteps { .. } => "ExecuteSteps",
+                AppStateCmdResult::FilterPaths(_) => "FilterPaths",
                 AppStateCmdResult::Keep => "Keep",
                 AppStateCmdResult::Launch(_) => "Launch",
+                AppStateCmdResult::LaunchedInBackground(_) => "LaunchedInBackground",
+                AppStateCmdResult::LoadLayout { .. } => "LoadLayout",
+                AppStateCmdResult::LoadSession { .. } => "LoadSession",
+                AppStateCmdResult::Mark { .. } => "Mark",
                 AppStateCmdResult::NewState { .. } => "NewState",
                 AppStateCmdResult::NewPanel { .. } => "NewPanel",
+                AppStateCmdResult::Paste { .. } => "Paste",
                 AppStateCmdResult::PopStateAndReapply => "PopStateAndReapply",
                 AppStateCmdResult::PopState => "PopState",
                 AppStateCmdResult::HandleInApp(_) => "HandleInApp",
                 AppStateCmdResult::Quit => "Quit",
+                AppStateCmdResult::Repeat(_) => "Repeat",
+                AppStateCmdResult::RefreshAndSelect { .. } => "RefreshAndSelect",
                 AppStateCmdResult::RefreshState { .. } => "RefreshState",
+                AppStateCmdResult::Restage { .. } => "Restage",
+                AppStateCmdResult::SaveLayout { .. } => "SaveLayout",
+                AppStateCmdResult::SaveSession { .. } => "SaveSession",
+                AppStateCmdResult::SwitchMode { .. } => "SwitchMode",
             }
         )
     }
@@ -3,9 +3,11 @@ use {
     crate::{
         cli::AppLaunchArgs,
         conf::Conf,
-        errors::ConfError,
+        errors::{ConfError, ProgramError},
         icon::*,
+        path::{self, PathAnchor},
         pattern::SearchModeMap,
+        provider::ColumnProvider,
         skin::ExtColorMap,
         tree::SpecialPath,
         verb::VerbStore,
@@ -16,6 +18,9 @@ use {
     },
 };
 
+/// the key mode in which typed chars go to the input
+pub const INPUT_MODE: &str = "input";
+
 /// The immutable container that can be passed around
 /// to provide the configuration things for the whole
 /// life of the App
@@ -56,6 +61,25 @@ pub struct AppContext {
     /// map extensions to icons, icon set chosen based on config
     /// Send, Sync safely beause once created, everything is immutable
     pub icons: Option<Box<dyn IconPlugin + Send + Sync>>,
+
+    /// the file where verb executions are logged, if any
+    /// (verbs may specify their own log file)
+    pub verb_log: Option<PathBuf>,
+
+    /// whether the displayed directories are initially watched,
+    /// the trees being refreshed when their entries change
+    pub watch: bool,
+
+    /// the key mode in which panels start, `input` (where typed
+    /// chars go to the input) when not configured
+    pub initial_mode: String,
+
+    /// the command annotating the paths displayed in the custom column
+    pub column_provider: Option<ColumnProvider>,
+
+    /// whether to copy to the terminal's clipboard with OSC 52,
+    /// None to do it only when the local clipboard isn't usable
+    pub osc52_clipboard: Option<bool>,
 }
 
 impl AppContext {
@@ -84,6 +108,11 @@ impl AppContext {
             .transpose()?
             .unwrap_or_default();
         let ext_colors = ExtColorMap::try_from(&config.ext_colors)?;
+        let column_provider = config.custom_column.as_ref()
+            .map(ColumnProvider::new)
+            .transpose()?;
+        let verb_log = config.verb_log.as_ref()
+            .map(|s| path::path_from(crate::conf::dir(), PathAnchor::Unspecified, s));
         Ok(Self {
             config_paths,
             launch_args,
@@ -96,8 +125,76 @@ impl AppContext {
             standard_status,
             true_colors,
             icons,
+            verb_log,
+            watch: config.watch.unwrap_or(false),
+            initial_mode: config.initial_mode.clone()
+                .filter(|m| !m.is_empty())
+                .unwrap_or_else(|| INPUT_MODE.to_string()),
+            column_provider,
+            osc52_clipboard: config.osc52_clipboard,
         })
     }
+
+    /// tell whether a key mode is known: the input one, the
+    /// initial one, or one of the verbs
+    pub fn is_mode(&self, name: &str) -> bool {
+        name == INPUT_MODE
+            || name == self.initial_mode
+            || self.verb_store.verbs.iter().any(|v| v.mode.as_deref() == Some(name))
+    }
+
+    /// read again the configuration files and, if they're valid,
+    /// replace the verbs with the new ones.
+    /// On error, the current verbs are kept.
+    pub fn reload_config(&mut self) -> Result<(), ProgramError> {
+        let mut config = Conf::default();
+        for path in &self.config_paths {
+            config.read_file(path.clone())?;
+        }
+        self.reload_verbs(&mut config)?;
+        Ok(())
+    }
+
+    /// build a new verb store from the configuration and swap it
+    /// with the current one, at the condition all verbs are valid
+    fn reload_verbs(&mut self, config: &mut Conf) -> Result<(), ConfError> {
+        let mut verb_store = VerbStore::default();
+        verb_store.init(config)?;
+        self.standard_status = StandardStatus::new(&verb_store);
+        self.verb_store = verb_store;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl AppContext {
+    /// build a context for tests, with the default configuration
+    pub fn for_test(root: &std::path::Path) -> Self {
+        Self::for_test_with(root, Default::default(), &mut Conf::default())
+    }
+    /// build a context for tests, with the given tree options
+    /// and configuration
+    pub fn for_test_with(
+        root: &std::path::Path,
+        tree_options: crate::tree::TreeOptions,
+        config: &mut Conf,
+    ) -> Self {
+        let mut verb_store = VerbStore::default();
+        verb_store.init(config).unwrap();
+        let launch_args = AppLaunchArgs {
+            root: root.to_path_buf(),
+            file_export_path: None,
+            cmd_export_path: None,
+            tree_options,
+            commands: None,
+            height: None,
+            no_style: true,
+            output_format: Default::default(),
+            #[cfg(feature = "client-server")]
+            listen: None,
+        };
+        Self::from(launch_args, verb_store, config).unwrap()
+    }
 }
 
 /// try to determine whether the terminal supports true
@@ -121,3 +218,74 @@ fn are_true_colors_available() -> bool {
         true
     }
 }
+
+#[cfg(test)]
+mod context_test {
+
+    use {
+        super::*,
+        std::{
+            fs,
+            path::Path,
+        },
+    };
+
+    fn context(config: &mut Conf) -> AppContext {
+        AppContext::for_test_with(Path::new("."), Default::default(), config)
+    }
+
+    fn has_verb(con: &AppContext, name: &str) -> bool {
+        con.verb_store.verbs.iter().any(|v| v.names.iter().any(|n| n == name))
+    }
+
+    const VALID_VERBS: &str = r#"
+        [[verbs]]
+        invocation = "reloaded"
+        external = "echo {file}"
+    "#;
+
+    // tail_in_panel verbs can't leave broot
+    const INVALID_VERBS: &str = r#"
+        [[verbs]]
+        invocation = "broken"
+        external = "echo {file}"
+        leave_broot = true
+        tail_in_panel = true
+    "#;
+
+    #[test]
+    fn test_verbs_swap_and_rollback() {
+        let mut con = context(&mut Conf::default());
+        let verbs_count = con.verb_store.verbs.len();
+        let mut valid: Conf = toml::from_str(VALID_VERBS).unwrap();
+        con.reload_verbs(&mut valid).unwrap();
+        assert!(has_verb(&con, "reloaded"));
+        assert_eq!(con.verb_store.verbs.len(), verbs_count + 1);
+        let mut invalid: Conf = toml::from_str(INVALID_VERBS).unwrap();
+        assert!(con.reload_verbs(&mut invalid).is_err());
+        assert!(has_verb(&con, "reloaded"));
+        assert!(!has_verb(&con, "broken"));
+        assert_eq!(con.verb_store.verbs.len(), verbs_count + 1);
+    }
+
+    #[test]
+    fn test_reload_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("conf.toml");
+        fs::write(&path, "verbs = []").unwrap();
+        let mut config = Conf::default();
+        config.read_file(path.clone()).unwrap();
+        let mut con = context(&mut config);
+        assert!(!has_verb(&con, "reloaded"));
+        fs::write(&path, VALID_VERBS).unwrap();
+        con.reload_config().unwrap();
+        assert!(has_verb(&con, "reloaded"));
+        // a file which can't be parsed doesn't change the verbs
+        fs::write(&path, "[[verbs]\ninvocation").unwrap();
+        assert!(con.reload_config().is_err());
+        assert!(has_verb(&con, "reloaded"));
+        fs::write(&path, INVALID_VERBS).unwrap();
+        assert!(con.reload_config().is_err());
+        assert!(has_verb(&con, "reloaded"));
+    }
+}
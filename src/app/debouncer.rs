@@ -0,0 +1,120 @@
+use {
+    crossbeam::channel::{unbounded, RecvTimeoutError, Sender},
+    std::{
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// keeps the last of values pushed in rapid succession, so
+/// that they're handled only once, when no new value came
+/// during a time window.
+///
+/// The owner must call `take_due` after the deadline. A debouncer
+/// built with a waker has a timer thread calling it at the deadline.
+#[derive(Debug)]
+pub struct Debouncer<T> {
+    pending: Option<(Instant, T)>,
+    /// sender of the deadlines to the timer thread, if any
+    tx_deadlines: Option<Sender<Instant>>,
+}
+
+impl<T> Default for Debouncer<T> {
+    fn default() -> Self {
+        Self {
+            pending: None,
+            tx_deadlines: None,
+        }
+    }
+}
+
+impl<T> Debouncer<T> {
+    /// build a debouncer whose timer thread calls `wake` when
+    /// the deadline of the pending value is passed
+    pub fn with_waker<F>(wake: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (tx_deadlines, rx_deadlines) = unbounded::<Instant>();
+        thread::spawn(move || {
+            let mut deadline: Option<Instant> = None;
+            loop {
+                let received = match deadline {
+                    Some(d) => rx_deadlines.recv_timeout(d.saturating_duration_since(Instant::now())),
+                    None => rx_deadlines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    // a new value replaces the previous one, and its deadline
+                    Ok(d) => deadline = Some(d),
+                    Err(RecvTimeoutError::Timeout) => {
+                        deadline = None;
+                        wake();
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+        Self {
+            pending: None,
+            tx_deadlines: Some(tx_deadlines),
+        }
+    }
+    /// replace the pending value, if any, with this one, which
+    /// will be due when the window will have elapsed
+    pub fn push(&mut self, value: T, now: Instant, window: Duration) {
+        let deadline = now + window;
+        self.pending = Some((deadline, value));
+        if let Some(tx) = &self.tx_deadlines {
+            let _ = tx.send(deadline);
+        }
+    }
+    /// return the pending value if its deadline is passed
+    pub fn take_due(&mut self, now: Instant) -> Option<T> {
+        match &self.pending {
+            Some((deadline, _)) if *deadline <= now => self.pending.take().map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod debouncer_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_last_wins_within_window() {
+        let window = Duration::from_millis(200);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut debouncer = Debouncer::default();
+        assert_eq!(debouncer.take_due(at(0)), None);
+        debouncer.push("a", at(0), window);
+        debouncer.push("b", at(50), window);
+        // the first deadline doesn't apply anymore
+        assert_eq!(debouncer.take_due(at(210)), None);
+        debouncer.push("c", at(100), window);
+        assert_eq!(debouncer.take_due(at(299)), None);
+        // only the last value is executed, once
+        assert_eq!(debouncer.take_due(at(300)), Some("c"));
+        assert_eq!(debouncer.take_due(at(400)), None);
+        // after the window, a new value starts a new window
+        debouncer.push("d", at(500), window);
+        assert_eq!(debouncer.take_due(at(700)), Some("d"));
+    }
+
+    #[test]
+    fn test_waker() {
+        let (tx, rx) = unbounded();
+        let mut debouncer = Debouncer::with_waker(move || {
+            let _ = tx.send(Instant::now());
+        });
+        let window = Duration::from_millis(20);
+        debouncer.push("a", Instant::now(), window);
+        debouncer.push("b", Instant::now(), window);
+        // the waker is called once, after the last deadline
+        let woken = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(debouncer.take_due(woken), Some("b"));
+        assert!(rx.recv_timeout(window * 5).is_err());
+    }
+}
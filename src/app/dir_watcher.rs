@@ -0,0 +1,372 @@
+use {
+    fnv::FnvHashMap,
+    std::{
+        fs,
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+        time::{Duration, SystemTime},
+    },
+};
+
+/// the maximal number of directories watched
+const MAX_WATCHED_DIRS: usize = 500;
+
+/// watches directories for the creation, removal or renaming
+/// of their entries.
+///
+/// The changes are notified by the kernel on linux (with inotify). On
+/// other systems, or when inotify isn't available, the modification
+/// times of the directories are compared at each period.
+///
+/// The changes are waited for by a thread, which calls the `wake`
+/// function when it finds some. The owner must then call `take_changed`.
+pub struct DirWatcher {
+    checker: Arc<Checker>,
+    enabled: Arc<AtomicBool>,
+    changed: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl DirWatcher {
+    pub fn new<F>(enabled: bool, period: Duration, wake: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let checker = Arc::new(Checker::new());
+        let enabled = Arc::new(AtomicBool::new(enabled));
+        let changed = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let watcher = Self {
+            checker: Arc::clone(&checker),
+            enabled: Arc::clone(&enabled),
+            changed: Arc::clone(&changed),
+            stopped: Arc::clone(&stopped),
+        };
+        thread::spawn(move || {
+            loop {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                if !enabled.load(Ordering::Relaxed) {
+                    thread::sleep(period);
+                    continue;
+                }
+                if checker.wait_changes(period) {
+                    debug!("watched directories changed");
+                    changed.store(true, Ordering::Relaxed);
+                    wake();
+                }
+            }
+        });
+        watcher
+    }
+    /// set the directories to watch, only the first ones being
+    /// watched when there are too many.
+    ///
+    /// The changes done after this call are all notified.
+    pub fn set_dirs(&self, mut dirs: Vec<PathBuf>) {
+        dirs.truncate(MAX_WATCHED_DIRS);
+        self.checker.set_dirs(dirs);
+    }
+    /// tell whether changes were found since the last call
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+    /// enable or disable the watch, return whether it's now enabled
+    pub fn toggle(&self) -> bool {
+        let enabled = !self.enabled.fetch_xor(true, Ordering::Relaxed);
+        if !enabled {
+            self.checker.set_dirs(Vec::new());
+        }
+        enabled
+    }
+}
+
+impl Drop for DirWatcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// the way changes are found
+enum Checker {
+    #[cfg(target_os = "linux")]
+    Inotify(inotify::InotifyChecker),
+    Mtime(MtimeChecker),
+}
+
+impl Checker {
+    fn new() -> Self {
+        #[cfg(target_os = "linux")]
+        match inotify::InotifyChecker::new() {
+            Ok(checker) => {
+                return Self::Inotify(checker);
+            }
+            Err(e) => {
+                warn!("inotify not available, watching modification times: {}", e);
+            }
+        }
+        Self::Mtime(MtimeChecker::default())
+    }
+    fn set_dirs(&self, dirs: Vec<PathBuf>) {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Inotify(checker) => checker.set_dirs(&dirs),
+            Self::Mtime(checker) => *checker.dirs.lock().unwrap() = dirs,
+        }
+    }
+    /// wait at most `period` and tell whether some watched
+    /// directories changed
+    fn wait_changes(&self, period: Duration) -> bool {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Inotify(checker) => checker.wait_changes(period),
+            Self::Mtime(checker) => {
+                thread::sleep(period);
+                checker.check()
+            }
+        }
+    }
+}
+
+/// finds changes by comparing the modification times of the
+/// directories, which change when entries are created, removed
+/// or renamed in them
+#[derive(Default)]
+struct MtimeChecker {
+    dirs: Mutex<Vec<PathBuf>>,
+    mtimes: Mutex<FnvHashMap<PathBuf, Option<SystemTime>>>,
+}
+
+impl MtimeChecker {
+    fn check(&self) -> bool {
+        let dirs = self.dirs.lock().unwrap().clone();
+        self.compare(
+            dirs.into_iter()
+                .map(|dir| {
+                    let mtime = fs::metadata(&dir).and_then(|md| md.modified()).ok();
+                    (dir, mtime)
+                })
+                .collect()
+        )
+    }
+    /// replace the known modification times, and tell whether
+    /// some changed
+    fn compare(&self, new_mtimes: FnvHashMap<PathBuf, Option<SystemTime>>) -> bool {
+        let mut mtimes = self.mtimes.lock().unwrap();
+        // the directories which weren't watched before aren't changes
+        let has_changes = new_mtimes
+            .iter()
+            .any(|(dir, mtime)| mtimes.get(dir).is_some_and(|old| old != mtime));
+        *mtimes = new_mtimes;
+        has_changes
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+
+    use {
+        fnv::{FnvHashMap, FnvHashSet},
+        std::{
+            ffi::CString,
+            io,
+            os::unix::ffi::OsStrExt,
+            path::PathBuf,
+            sync::Mutex,
+            time::Duration,
+        },
+    };
+
+    /// the events which are changes of the list of entries
+    const CHANGE_MASK: u32 = libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVE_SELF;
+
+    /// the size of the fixed part of an event (wd, mask, cookie, len)
+    const EVENT_HEADER_SIZE: usize = 16;
+
+    /// finds changes with the notifications of the kernel
+    pub struct InotifyChecker {
+        fd: libc::c_int,
+        watches: Mutex<FnvHashMap<PathBuf, libc::c_int>>,
+    }
+
+    impl InotifyChecker {
+        pub fn new() -> io::Result<Self> {
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self {
+                fd,
+                watches: Mutex::default(),
+            })
+        }
+        /// watch the given directories, and only them
+        pub fn set_dirs(&self, dirs: &[PathBuf]) {
+            let wanted: FnvHashSet<&PathBuf> = dirs.iter().collect();
+            let mut watches = self.watches.lock().unwrap();
+            watches.retain(|dir, wd| {
+                let keep = wanted.contains(dir);
+                if !keep {
+                    unsafe {
+                        libc::inotify_rm_watch(self.fd, *wd);
+                    }
+                }
+                keep
+            });
+            for dir in dirs {
+                if watches.contains_key(dir) {
+                    continue;
+                }
+                let c_path = match CString::new(dir.as_os_str().as_bytes()) {
+                    Ok(c_path) => c_path,
+                    Err(_) => continue,
+                };
+                let wd = unsafe {
+                    libc::inotify_add_watch(self.fd, c_path.as_ptr(), CHANGE_MASK | libc::IN_ONLYDIR)
+                };
+                if wd < 0 {
+                    debug!("{:?} not watched: {}", dir, io::Error::last_os_error());
+                } else {
+                    watches.insert(dir.clone(), wd);
+                }
+            }
+        }
+        /// wait at most `timeout` for changes in the watched directories,
+        /// and read all the pending events
+        pub fn wait_changes(&self, timeout: Duration) -> bool {
+            let mut pfd = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = timeout.as_millis().min(i32::MAX as u128) as libc::c_int;
+            if unsafe { libc::poll(&mut pfd, 1, timeout) } <= 0 {
+                return false;
+            }
+            let mut has_changes = false;
+            let mut buffer = [0u8; 4096];
+            loop {
+                let read = unsafe {
+                    libc::read(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len())
+                };
+                if read <= 0 {
+                    break; // no more events
+                }
+                has_changes |= has_change_event(&buffer[..read as usize]);
+            }
+            has_changes
+        }
+    }
+
+    /// tell whether the events read in the buffer include changes (and
+    /// not just the acknowledgment of removed watches)
+    fn has_change_event(mut events: &[u8]) -> bool {
+        while events.len() >= EVENT_HEADER_SIZE {
+            let field = |offset: usize| {
+                u32::from_ne_bytes([
+                    events[offset],
+                    events[offset + 1],
+                    events[offset + 2],
+                    events[offset + 3],
+                ])
+            };
+            if field(4) & (CHANGE_MASK | libc::IN_Q_OVERFLOW) != 0 {
+                return true;
+            }
+            let len = field(12) as usize;
+            events = &events[(EVENT_HEADER_SIZE + len).min(events.len())..];
+        }
+        false
+    }
+
+    impl Drop for InotifyChecker {
+        fn drop(&mut self) {
+            unsafe {
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod dir_watcher_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_mtime_checker() {
+        let dir = PathBuf::from("/some/dir");
+        let other = PathBuf::from("/other/dir");
+        let at = |secs| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        let mtimes = |list: &[(&PathBuf, Option<SystemTime>)]| {
+            list.iter().map(|(dir, mtime)| ((*dir).clone(), *mtime)).collect()
+        };
+        let checker = MtimeChecker::default();
+        assert!(!checker.compare(mtimes(&[(&dir, at(1))])));
+        assert!(!checker.compare(mtimes(&[(&dir, at(1))])));
+        // a new directory isn't a change
+        assert!(!checker.compare(mtimes(&[(&dir, at(1)), (&other, at(5))])));
+        assert!(checker.compare(mtimes(&[(&dir, at(2)), (&other, at(5))])));
+        // neither is a directory which isn't watched anymore
+        assert!(!checker.compare(mtimes(&[(&dir, at(2))])));
+        // a removed directory is a change
+        assert!(checker.compare(mtimes(&[(&dir, None)])));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_inotify_checker() {
+        let dir = tempfile::tempdir().unwrap();
+        let no_wait = Duration::from_secs(0);
+        // the events are queued as soon as the changes are done,
+        // so there's no need to wait for them
+        let checker = inotify::InotifyChecker::new().unwrap();
+        checker.set_dirs(&[dir.path().to_path_buf()]);
+        assert!(!checker.wait_changes(no_wait));
+        fs::write(dir.path().join("a"), "").unwrap();
+        assert!(checker.wait_changes(no_wait));
+        assert!(!checker.wait_changes(no_wait));
+        // changing a file isn't changing its directory
+        fs::write(dir.path().join("a"), "content").unwrap();
+        assert!(!checker.wait_changes(no_wait));
+        fs::rename(dir.path().join("a"), dir.path().join("b")).unwrap();
+        assert!(checker.wait_changes(no_wait));
+        // removing the watch isn't a change
+        checker.set_dirs(&[]);
+        assert!(!checker.wait_changes(no_wait));
+        fs::remove_file(dir.path().join("b")).unwrap();
+        assert!(!checker.wait_changes(no_wait));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_dir_watcher() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = crossbeam::channel::unbounded();
+        let watcher = DirWatcher::new(true, Duration::from_millis(10), move || {
+            let _ = tx.send(());
+        });
+        watcher.set_dirs(vec![dir.path().to_path_buf()]);
+        assert!(!watcher.take_changed());
+        fs::write(dir.path().join("a"), "").unwrap();
+        rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(watcher.take_changed());
+        assert!(!watcher.take_changed());
+        assert!(!watcher.toggle());
+        assert!(!watcher.is_enabled());
+        assert!(watcher.toggle());
+    }
+}
@@ -0,0 +1,148 @@
+//! named panel layouts, saved in broot's data directory and
+//! restored with `:load_layout`
+
+use {
+    crate::errors::ProgramError,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// the persisted description of a browsing panel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanelLayout {
+    pub root: PathBuf,
+    pub selection: PathBuf,
+}
+
+/// the persisted set of panels of the application.
+///
+/// Only the panels showing a tree are saved (not the preview, nor
+/// the help or other states).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    /// index of the active panel in `panels`
+    pub active: usize,
+    pub panels: Vec<PanelLayout>,
+}
+
+/// return the directory where layouts are saved
+pub fn layouts_dir() -> PathBuf {
+    crate::conf::app_dirs().data_dir().join("layouts")
+}
+
+/// return the path of the file of a layout, checking the name
+/// is a simple one
+fn layout_path(dir: &Path, name: &str) -> Result<PathBuf, ProgramError> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !name.starts_with('.');
+    if valid {
+        Ok(dir.join(format!("{}.toml", name)))
+    } else {
+        Err(ProgramError::InvalidLayoutName { name: name.to_string() })
+    }
+}
+
+impl Layout {
+    /// write the layout in the given directory, replacing the
+    /// previous layout of the same name
+    pub fn save_in(&self, dir: &Path, name: &str) -> Result<(), ProgramError> {
+        let path = layout_path(dir, name)?;
+        let content = toml::to_string(self)
+            .map_err(|e| ProgramError::LayoutFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })?;
+        fs::create_dir_all(dir)?;
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    /// read the layout of the given name in the given directory
+    pub fn load_from(dir: &Path, name: &str) -> Result<Self, ProgramError> {
+        let path = layout_path(dir, name)?;
+        if !path.exists() {
+            return Err(ProgramError::UnknownLayout { name: name.to_string() });
+        }
+        let content = fs::read_to_string(&path)?;
+        let layout: Self = toml::from_str(&content)
+            .map_err(|e| ProgramError::LayoutFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })?;
+        if layout.panels.is_empty() {
+            return Err(ProgramError::LayoutFile {
+                path: path.to_string_lossy().to_string(),
+                details: "no panel".to_string(),
+            });
+        }
+        Ok(layout)
+    }
+
+    pub fn save(&self, name: &str) -> Result<(), ProgramError> {
+        self.save_in(&layouts_dir(), name)
+    }
+
+    pub fn load(name: &str) -> Result<Self, ProgramError> {
+        Self::load_from(&layouts_dir(), name)
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+
+    use super::*;
+
+    fn two_panels() -> Layout {
+        Layout {
+            active: 1,
+            panels: vec![
+                PanelLayout {
+                    root: PathBuf::from("/home/dys/dev"),
+                    selection: PathBuf::from("/home/dys/dev/broot"),
+                },
+                PanelLayout {
+                    root: PathBuf::from("/tmp"),
+                    selection: PathBuf::from("/tmp/some file.txt"),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_layout_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let layouts = dir.path().join("layouts");
+        let layout = two_panels();
+        layout.save_in(&layouts, "work").unwrap();
+        assert!(layouts.join("work.toml").exists());
+        assert_eq!(Layout::load_from(&layouts, "work").unwrap(), layout);
+        // saving again under the same name replaces the layout
+        let mut other = layout.clone();
+        other.active = 0;
+        other.panels.pop();
+        other.save_in(&layouts, "work").unwrap();
+        assert_eq!(Layout::load_from(&layouts, "work").unwrap(), other);
+    }
+
+    #[test]
+    fn test_layout_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = two_panels();
+        assert!(matches!(
+            Layout::load_from(dir.path(), "missing"),
+            Err(ProgramError::UnknownLayout { .. }),
+        ));
+        for name in &["", "../work", "a/b", ".hidden"] {
+            assert!(matches!(
+                layout.save_in(dir.path(), name),
+                Err(ProgramError::InvalidLayoutName { .. }),
+            ));
+        }
+        fs::write(dir.path().join("empty.toml"), "active = 0\npanels = []").unwrap();
+        assert!(Layout::load_from(dir.path(), "empty").is_err());
+    }
+}
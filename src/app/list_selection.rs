@@ -0,0 +1,158 @@
+use {
+    super::*,
+    crate::verb::{Internal, InternalExecution, VerbInvocation},
+};
+
+/// the selection, filtering and scrolling of the items of a
+/// state displaying a list, one item per line.
+///
+/// The displayed items are either all the items of the state
+/// or, when there's a filter, the ones matching it.
+#[derive(Debug, Default)]
+pub struct ListSelection {
+    len: usize, // number of items of the state
+    filtered: Option<Vec<usize>>, // indexes of the items matching the filter
+    selection_idx: usize, // index in the displayed items
+    scroll: usize,
+    page_height: usize, // height of the area for the items, set on display
+}
+
+impl ListSelection {
+    /// set the items of the state, either all of them or only the
+    /// ones at the given indexes, keeping the selection index valid
+    pub fn set_items(&mut self, len: usize, filtered: Option<Vec<usize>>) {
+        self.len = len;
+        self.filtered = filtered;
+        self.select(self.selection_idx);
+    }
+    pub fn is_filtered(&self) -> bool {
+        self.filtered.is_some()
+    }
+    /// the number of displayed items
+    pub fn count(&self) -> usize {
+        self.filtered
+            .as_ref()
+            .map_or(self.len, |indexes| indexes.len())
+    }
+    /// return the index in the items of the state of the
+    /// displayed item at the given index
+    pub fn item_idx(&self, idx: usize) -> Option<usize> {
+        match &self.filtered {
+            Some(indexes) => indexes.get(idx).copied(),
+            None if idx < self.len => Some(idx),
+            None => None,
+        }
+    }
+    /// return the index in the items of the state of the selected one
+    pub fn selected_item_idx(&self) -> Option<usize> {
+        self.item_idx(self.selection_idx)
+    }
+    /// return the index in the displayed items of the selected one
+    pub fn selection_idx(&self) -> usize {
+        self.selection_idx
+    }
+    pub fn page_height(&self) -> usize {
+        self.page_height
+    }
+    /// set the height of the displayed page, scrolling so that
+    /// the selection stays visible
+    pub fn set_page_height(&mut self, page_height: usize) {
+        self.page_height = page_height;
+        self.select(self.selection_idx);
+    }
+    /// return the index in the displayed items of the one
+    /// displayed at the line `y` of the page
+    pub fn idx_at(&self, y: usize) -> usize {
+        self.scroll + y
+    }
+    /// select the first displayed item and scroll to the top
+    pub fn reset(&mut self) {
+        self.scroll = 0;
+        self.selection_idx = 0;
+    }
+    pub fn select(&mut self, idx: usize) {
+        self.selection_idx = idx.min(self.count().saturating_sub(1));
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+    pub fn move_selection(&mut self, dy: i32) {
+        let idx = (self.selection_idx as i32 + dy).max(0) as usize;
+        self.select(idx);
+    }
+    /// apply the internal if it's a move of the selection, return
+    /// `None` if it's not
+    pub fn on_internal(
+        &mut self,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+    ) -> Option<AppStateCmdResult> {
+        match internal_exec.internal {
+            Internal::line_down => {
+                self.move_selection(get_arg(input_invocation, internal_exec, 1));
+            }
+            Internal::line_up => {
+                self.move_selection(-get_arg(input_invocation, internal_exec, 1));
+            }
+            Internal::page_down => {
+                self.move_selection(self.page_height as i32);
+            }
+            Internal::page_up => {
+                self.move_selection(-(self.page_height as i32));
+            }
+            Internal::select_first => {
+                self.select(0);
+            }
+            Internal::select_last => {
+                self.select(self.count().saturating_sub(1));
+            }
+            _ => {
+                return None;
+            }
+        }
+        Some(AppStateCmdResult::Keep)
+    }
+}
+
+#[cfg(test)]
+mod list_selection_tests {
+
+    use super::*;
+
+    fn apply(list: &mut ListSelection, internal: Internal) -> bool {
+        list.on_internal(&InternalExecution::from_internal(internal), None).is_some()
+    }
+
+    #[test]
+    fn test_list_selection() {
+        let mut list = ListSelection::default();
+        list.set_items(20, None);
+        list.set_page_height(5);
+        assert_eq!(list.selected_item_idx(), Some(0));
+        assert!(apply(&mut list, Internal::page_down));
+        assert_eq!(list.selection_idx(), 5);
+        // the page scrolls to show the selection
+        assert_eq!(list.idx_at(0), 1);
+        assert!(apply(&mut list, Internal::select_last));
+        assert_eq!(list.selected_item_idx(), Some(19));
+        assert_eq!(list.idx_at(0), 15);
+        assert!(apply(&mut list, Internal::line_down));
+        assert_eq!(list.selection_idx(), 19);
+        assert!(apply(&mut list, Internal::page_up));
+        assert_eq!(list.idx_at(0), 14);
+        assert!(!apply(&mut list, Internal::back));
+        // filtering keeps the selection in the displayed items
+        list.set_items(20, Some(vec![3, 8, 12]));
+        assert_eq!(list.count(), 3);
+        assert_eq!(list.selected_item_idx(), Some(12));
+        assert!(apply(&mut list, Internal::select_first));
+        assert_eq!(list.selected_item_idx(), Some(3));
+        assert_eq!(list.idx_at(0), 0);
+        list.set_items(0, None);
+        assert_eq!(list.selected_item_idx(), None);
+        assert!(apply(&mut list, Internal::line_down));
+        assert_eq!(list.selected_item_idx(), None);
+    }
+}
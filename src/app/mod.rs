@@ -1,11 +1,18 @@
 mod app;
+mod background_jobs;
 mod cmd_context;
 mod cmd_result;
 mod context;
+mod debouncer;
+mod dir_watcher;
+mod layout;
+mod list_selection;
 mod panel;
 mod panel_id;
 mod panel_purpose;
+mod recent;
 mod selection;
+mod session;
 mod standard_status;
 mod state;
 mod state_type;
@@ -13,13 +20,20 @@ mod status;
 
 pub use {
     app::App,
+    background_jobs::{BackgroundJob, BackgroundJobs, JobEnd},
     cmd_context::CmdContext,
     cmd_result::*,
-    context::AppContext,
+    context::{AppContext, INPUT_MODE},
+    debouncer::Debouncer,
+    dir_watcher::DirWatcher,
+    layout::*,
+    list_selection::ListSelection,
     panel::Panel,
     panel_id::PanelId,
     panel_purpose::PanelPurpose,
+    recent::RecentFiles,
     selection::{LineNumber, Selection, SelectionType},
+    session::*,
     standard_status::StandardStatus,
     state::*,
     state_type::AppStateType,
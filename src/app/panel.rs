@@ -13,6 +13,7 @@ use {
         errors::ProgramError,
         keys,
         skin::PanelSkin,
+        stage::Stage,
         task_sync::Dam,
         verb::*,
     },
@@ -28,8 +29,11 @@ pub struct Panel {
     states: Vec<Box<dyn AppState>>, // stack: the last one is current
     pub areas: Areas,
     status: Status,
+    /// the spinner of the background jobs showing their progress
+    progress: Option<String>,
     pub purpose: PanelPurpose,
     input: PanelInput,
+    verb_start_selection: Option<PathBuf>, // selection when the verb was started in input
 }
 
 impl Panel {
@@ -40,7 +44,7 @@ impl Panel {
         areas: Areas,
         con: &AppContext,
     ) -> Self {
-        let mut input = PanelInput::new(areas.input.clone());
+        let mut input = PanelInput::new(areas.input.clone(), &con.initial_mode);
         input.set_content(&state.get_starting_input());
         let status = state.no_verb_status(false, con);
         Self {
@@ -48,15 +52,29 @@ impl Panel {
             states: vec![state],
             areas,
             status,
+            progress: None,
             purpose: PanelPurpose::None,
             input,
+            verb_start_selection: None,
         }
     }
 
+    pub fn set_message(&mut self, text: String) {
+        self.status = Status::from_message(text);
+    }
+
     pub fn set_error(&mut self, text: String) {
         self.status = Status::from_error(text);
     }
 
+    pub fn set_progress(&mut self, progress: Option<String>) {
+        self.progress = progress;
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.status.error
+    }
+
     /// apply a command on the current state, with no
     /// effect on screen
     #[allow(clippy::too_many_arguments)] // a refactory could still be useful
@@ -68,13 +86,20 @@ impl Panel {
         screen: Screen,
         panel_skin: &PanelSkin,
         preview: Option<PanelId>,
+        stage: &Stage,
         con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
         let state_idx = self.states.len() - 1;
+        let initial_selection = match cmd {
+            Command::VerbInvocate(_) => self.verb_start_selection.take(),
+            _ => None,
+        };
         let cc = CmdContext {
             cmd,
             other_path,
             panel_skin,
+            stage,
+            initial_selection: initial_selection.as_deref(),
             con,
             areas: &self.areas,
             panel_purpose: self.purpose,
@@ -143,7 +168,20 @@ impl Panel {
         con: &AppContext,
     ) -> Result<Command, ProgramError> {
         let sel = self.states[self.states.len() - 1].selection();
-        self.input.on_event(w, event, con, sel)
+        let cmd = self.input.on_event(w, event, con, sel)?;
+        self.record_verb_start_selection();
+        Ok(cmd)
+    }
+
+    /// remember the selection at the time a verb starts being typed,
+    /// so that verbs with stable_selection can check it didn't change
+    fn record_verb_start_selection(&mut self) {
+        let parts = CommandParts::from(self.input.get_content());
+        if parts.verb_invocation.is_none() {
+            self.verb_start_selection = None;
+        } else if self.verb_start_selection.is_none() {
+            self.verb_start_selection = Some(self.state().selected_path().to_path_buf());
+        }
     }
 
     pub fn push_state(&mut self, new_state: Box<dyn AppState>) {
@@ -171,6 +209,11 @@ impl Panel {
         }
     }
 
+    /// change the key mode of the input
+    pub fn set_mode(&mut self, mode: String) {
+        self.input.mode = mode;
+    }
+
     pub fn set_input_content(&mut self, content: &str) {
         self.input.set_content(content);
     }
@@ -207,13 +250,14 @@ impl Panel {
         active: bool,
         screen: Screen,
         panel_skin: &PanelSkin,
+        stage: &Stage,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
         let state_area = self.areas.state.clone();
         self.mut_state()
-            .display(w, screen, state_area, panel_skin, con)?;
+            .display(w, screen, state_area, panel_skin, stage, con)?;
         if active || !WIDE_STATUS {
-            self.write_status(w, panel_skin, screen)?;
+            self.write_status(w, panel_skin, screen, con)?;
         }
         let mut input_area = self.areas.input.clone();
         if active {
@@ -236,10 +280,16 @@ impl Panel {
         w: &mut W,
         panel_skin: &PanelSkin,
         screen: Screen,
+        con: &AppContext,
     ) -> Result<(), ProgramError> {
-        let task = self.state().get_pending_task();
+        let task = self.state().get_pending_task()
+            .or(self.progress.as_deref());
+        // the mode is only shown when modes are in use
+        let mode = Some(self.input.mode.as_str())
+            .filter(|&mode| mode != INPUT_MODE || con.initial_mode != INPUT_MODE);
         status_line::write(
             w,
+            mode,
             task,
             &self.status,
             &self.areas.status,
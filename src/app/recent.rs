@@ -0,0 +1,160 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+/// the maximal number of files kept in the list of recent files
+const MAX_RECENT_FILES: usize = 50;
+
+/// the files recently selected, most recent first, through which
+/// `:recent_prev` and `:recent_next` cycle.
+///
+/// A file is only once in the list: selecting it again moves it to
+/// the front. While cycling, the selection of the file reached by
+/// cycling doesn't change the order, so that the cycle can go on.
+#[derive(Debug)]
+pub struct RecentFiles {
+    paths: VecDeque<PathBuf>,
+    cap: usize,
+    /// the index of the file reached by cycling, 0 when not cycling
+    cursor: usize,
+}
+
+impl Default for RecentFiles {
+    fn default() -> Self {
+        Self::with_cap(MAX_RECENT_FILES)
+    }
+}
+
+impl RecentFiles {
+    pub fn with_cap(cap: usize) -> Self {
+        Self {
+            paths: VecDeque::new(),
+            cap,
+            cursor: 0,
+        }
+    }
+    /// note the selection of a file
+    pub fn push(&mut self, path: &Path) {
+        if self.paths.get(self.cursor).is_some_and(|p| p == path) {
+            // the file is the current one, maybe reached by cycling
+            return;
+        }
+        self.paths.retain(|p| p != path);
+        self.paths.push_front(path.to_path_buf());
+        self.paths.truncate(self.cap);
+        self.cursor = 0;
+    }
+    /// go to an older file (`older` being true) or a more recent
+    /// one, wrapping at both ends, and return it.
+    ///
+    /// The files for which `exists` is false are removed from the
+    /// list. None is returned when there's no other file to go to.
+    pub fn cycle<F>(&mut self, older: bool, exists: F) -> Option<&Path>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let current = self.paths.get(self.cursor).cloned();
+        self.paths.retain(|p| exists(p) || Some(p) == current.as_ref());
+        self.cursor = current
+            .and_then(|current| self.paths.iter().position(|p| *p == current))
+            .unwrap_or(0);
+        let len = self.paths.len();
+        if len < 2 {
+            return None;
+        }
+        self.cursor = if older {
+            (self.cursor + 1) % len
+        } else {
+            (self.cursor + len - 1) % len
+        };
+        self.paths.get(self.cursor).map(|p| p.as_path())
+    }
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod recent_tests {
+
+    use super::*;
+
+    fn paths(recent: &RecentFiles) -> Vec<&str> {
+        recent.paths.iter().map(|p| p.to_str().unwrap()).collect()
+    }
+
+    fn cycle(recent: &mut RecentFiles, older: bool) -> Option<String> {
+        recent
+            .cycle(older, |_| true)
+            .map(|p| p.to_string_lossy().to_string())
+    }
+
+    #[test]
+    fn test_push() {
+        let mut recent = RecentFiles::with_cap(3);
+        recent.push(Path::new("a"));
+        recent.push(Path::new("b"));
+        recent.push(Path::new("b"));
+        assert_eq!(paths(&recent), vec!["b", "a"]);
+        // a file selected again moves to the front
+        recent.push(Path::new("c"));
+        recent.push(Path::new("a"));
+        assert_eq!(paths(&recent), vec!["a", "c", "b"]);
+        // the oldest file goes out when the cap is reached
+        recent.push(Path::new("d"));
+        assert_eq!(paths(&recent), vec!["d", "a", "c"]);
+        assert_eq!(recent.len(), 3);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut recent = RecentFiles::default();
+        assert_eq!(cycle(&mut recent, true), None);
+        recent.push(Path::new("a"));
+        assert_eq!(cycle(&mut recent, true), None);
+        recent.push(Path::new("b"));
+        recent.push(Path::new("c"));
+        // going back in time, then wrapping to the most recent
+        assert_eq!(cycle(&mut recent, true).as_deref(), Some("b"));
+        recent.push(Path::new("b")); // the selection of the reached file
+        assert_eq!(cycle(&mut recent, true).as_deref(), Some("a"));
+        recent.push(Path::new("a"));
+        assert_eq!(cycle(&mut recent, true).as_deref(), Some("c"));
+        // and forward, wrapping to the oldest
+        assert_eq!(cycle(&mut recent, false).as_deref(), Some("a"));
+        assert_eq!(cycle(&mut recent, false).as_deref(), Some("b"));
+        // the order didn't change while cycling
+        assert_eq!(paths(&recent), vec!["c", "b", "a"]);
+        // selecting another file ends the cycle
+        recent.push(Path::new("d"));
+        assert_eq!(paths(&recent), vec!["d", "c", "b", "a"]);
+        assert_eq!(cycle(&mut recent, true).as_deref(), Some("c"));
+        // selecting a file of the list while cycling moves it to the front
+        recent.push(Path::new("a"));
+        assert_eq!(paths(&recent), vec!["a", "d", "c", "b"]);
+        assert_eq!(cycle(&mut recent, true).as_deref(), Some("d"));
+    }
+
+    #[test]
+    fn test_cycle_skips_missing_files() {
+        let mut recent = RecentFiles::default();
+        for name in &["a", "b", "c", "d"] {
+            recent.push(Path::new(name));
+        }
+        let exists = |p: &Path| p != Path::new("c") && p != Path::new("a");
+        assert_eq!(recent.cycle(true, exists), Some(Path::new("b")));
+        assert_eq!(paths(&recent), vec!["d", "b"]);
+        assert_eq!(recent.cycle(true, exists), Some(Path::new("d")));
+        // the current file is kept even when it's missing
+        let mut recent = RecentFiles::default();
+        recent.push(Path::new("a"));
+        recent.push(Path::new("b"));
+        assert_eq!(recent.cycle(true, |p| p != Path::new("b")), Some(Path::new("a")));
+        assert_eq!(recent.cycle(true, |_| false), None);
+        assert_eq!(paths(&recent), vec!["a"]);
+    }
+}
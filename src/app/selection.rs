@@ -10,8 +10,9 @@ use {
     },
     std::{
         fs::OpenOptions,
-        io::Write,
+        io::{self, Write},
         path::Path,
+        process::ExitStatus,
     },
 };
 
@@ -69,4 +70,80 @@ impl Selection<'_> {
         })
     }
 
+    /// open the selection with the system's default application
+    /// while broot is still running, then quit broot. When the
+    /// opener fails, broot isn't quit and the error is displayed
+    pub fn open_and_quit(self) -> AppStateCmdResult {
+        open_then_quit(self.path, |path| open::that(path))
+    }
+}
+
+/// call the opener on the path and, if it succeeded, quit
+fn open_then_quit<F>(path: &Path, opener: F) -> AppStateCmdResult
+where
+    F: FnOnce(&Path) -> io::Result<ExitStatus>,
+{
+    match opener(path) {
+        Ok(exit_status) if exit_status.success() => {
+            info!("opener returned with exit_status {:?}", exit_status);
+            AppStateCmdResult::Quit
+        }
+        Ok(exit_status) => AppStateCmdResult::DisplayError(format!(
+            "{} not opened: the opener failed ({})",
+            path.to_string_lossy(),
+            exit_status,
+        )),
+        Err(e) => AppStateCmdResult::DisplayError(format!(
+            "{} not opened: {}",
+            path.to_string_lossy(),
+            e,
+        )),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod selection_tests {
+
+    use {
+        super::*,
+        std::{
+            cell::RefCell,
+            os::unix::process::ExitStatusExt,
+            path::PathBuf,
+        },
+    };
+
+    #[test]
+    fn test_open_then_quit() {
+        let path = Path::new("/home/dys/notes.md");
+        let opened: RefCell<Vec<PathBuf>> = RefCell::new(Vec::new());
+        let opener = |status: io::Result<ExitStatus>| {
+            let opened = &opened;
+            move |p: &Path| {
+                opened.borrow_mut().push(p.to_path_buf());
+                status
+            }
+        };
+        // broot is quit only after the opener succeeded
+        let result = open_then_quit(path, opener(Ok(ExitStatus::from_raw(0))));
+        assert!(matches!(result, AppStateCmdResult::Quit));
+        assert_eq!(*opened.borrow(), vec![path.to_path_buf()]);
+        // when the opener fails, broot displays an error
+        let result = open_then_quit(path, opener(Ok(ExitStatus::from_raw(1 << 8))));
+        match result {
+            AppStateCmdResult::DisplayError(e) => assert!(e.contains("opener failed")),
+            _ => panic!("broot shouldn't quit when the opener fails"),
+        }
+        let result = open_then_quit(
+            path,
+            opener(Err(io::Error::new(io::ErrorKind::NotFound, "no opener"))),
+        );
+        match result {
+            AppStateCmdResult::DisplayError(e) => {
+                assert_eq!(e, "/home/dys/notes.md not opened: no opener");
+            }
+            _ => panic!("broot shouldn't quit when there's no opener"),
+        }
+        assert_eq!(opened.borrow().len(), 3);
+    }
 }
@@ -0,0 +1,234 @@
+//! named sessions, saved with `:save_session` and restored with
+//! `:load_session`, the last one being saved on quit and restored
+//! with `--resume`
+
+use {
+    super::*,
+    crate::{
+        errors::ProgramError,
+        tree::{Sort, TreeOptions},
+    },
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// the name of the session saved when broot is quit
+pub const LAST_SESSION: &str = "last";
+
+/// the display options of a panel which are saved in sessions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanelOptions {
+    pub show_hidden: bool,
+    pub only_folders: bool,
+    pub show_counts: bool,
+    pub show_dates: bool,
+    pub show_sizes: bool,
+    pub show_permissions: bool,
+    pub show_git_file_info: bool,
+    pub respect_git_ignore: bool,
+    pub filter_by_git_status: bool,
+    pub flat_view: bool,
+    pub trim_root: bool,
+    pub max_depth: Option<u16>,
+    pub sort: Option<String>, // `count`, `date` or `size`
+}
+
+impl From<&TreeOptions> for PanelOptions {
+    fn from(options: &TreeOptions) -> Self {
+        Self {
+            show_hidden: options.show_hidden,
+            only_folders: options.only_folders,
+            show_counts: options.show_counts,
+            show_dates: options.show_dates,
+            show_sizes: options.show_sizes,
+            show_permissions: options.show_permissions,
+            show_git_file_info: options.show_git_file_info,
+            respect_git_ignore: options.respect_git_ignore,
+            filter_by_git_status: options.filter_by_git_status,
+            flat_view: options.flat_view,
+            trim_root: options.trim_root,
+            max_depth: options.max_depth,
+            sort: options.sort.name().map(|name| name.to_string()),
+        }
+    }
+}
+
+impl PanelOptions {
+    /// change the tree options, the other ones than the saved
+    /// ones being kept
+    pub fn apply_to(&self, options: &mut TreeOptions) {
+        options.show_hidden = self.show_hidden;
+        options.only_folders = self.only_folders;
+        options.show_counts = self.show_counts;
+        options.show_dates = self.show_dates;
+        options.show_sizes = self.show_sizes;
+        options.show_permissions = self.show_permissions;
+        options.show_git_file_info = self.show_git_file_info;
+        options.respect_git_ignore = self.respect_git_ignore;
+        options.filter_by_git_status = self.filter_by_git_status;
+        options.flat_view = self.flat_view;
+        options.trim_root = self.trim_root;
+        options.max_depth = self.max_depth;
+        options.sort = match self.sort.as_deref() {
+            Some("count") => Sort::Count,
+            Some("date") => Sort::Date,
+            Some("size") => Sort::Size,
+            _ => Sort::None,
+        };
+    }
+}
+
+/// the persisted description of a browsing panel, with its
+/// pattern and display options
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanelSession {
+    pub root: PathBuf,
+    pub selection: PathBuf,
+    #[serde(default)]
+    pub pattern: String, // the raw pattern, empty when there's none
+    #[serde(default)]
+    pub options: Option<PanelOptions>, // None when the launch options apply
+}
+
+/// the persisted set of panels of the application, which, unlike
+/// a layout, includes the patterns and display options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// index of the active panel in `panels`
+    pub active: usize,
+    pub panels: Vec<PanelSession>,
+}
+
+impl From<&Layout> for Session {
+    fn from(layout: &Layout) -> Self {
+        Self {
+            active: layout.active,
+            panels: layout.panels
+                .iter()
+                .map(|panel| PanelSession {
+                    root: panel.root.clone(),
+                    selection: panel.selection.clone(),
+                    pattern: String::new(),
+                    options: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// return the directory where sessions are saved
+pub fn sessions_dir() -> PathBuf {
+    crate::conf::app_dirs().data_dir().join("sessions")
+}
+
+/// return the path of the file of a session, checking the name
+/// is a simple one
+fn session_path(dir: &Path, name: &str) -> Result<PathBuf, ProgramError> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !name.starts_with('.');
+    if valid {
+        Ok(dir.join(format!("{}.toml", name)))
+    } else {
+        Err(ProgramError::InvalidSessionName { name: name.to_string() })
+    }
+}
+
+impl Session {
+    /// write the session in the given directory, replacing the
+    /// previous session of the same name
+    pub fn save_in(&self, dir: &Path, name: &str) -> Result<(), ProgramError> {
+        let path = session_path(dir, name)?;
+        let content = toml::to_string(self)
+            .map_err(|e| ProgramError::SessionFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })?;
+        fs::create_dir_all(dir)?;
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    /// read the session of the given name in the given directory
+    pub fn load_from(dir: &Path, name: &str) -> Result<Self, ProgramError> {
+        let path = session_path(dir, name)?;
+        if !path.exists() {
+            return Err(ProgramError::UnknownSession { name: name.to_string() });
+        }
+        let content = fs::read_to_string(&path)?;
+        let session: Self = toml::from_str(&content)
+            .map_err(|e| ProgramError::SessionFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })?;
+        if session.panels.is_empty() {
+            return Err(ProgramError::SessionFile {
+                path: path.to_string_lossy().to_string(),
+                details: "no panel".to_string(),
+            });
+        }
+        Ok(session)
+    }
+
+    pub fn save(&self, name: &str) -> Result<(), ProgramError> {
+        self.save_in(&sessions_dir(), name)
+    }
+
+    pub fn load(name: &str) -> Result<Self, ProgramError> {
+        Self::load_from(&sessions_dir(), name)
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = dir.path().join("sessions");
+        let options = TreeOptions {
+            show_hidden: true,
+            max_depth: Some(3),
+            sort: Sort::Date,
+            ..TreeOptions::default()
+        };
+        let session = Session {
+            active: 0,
+            panels: vec![
+                PanelSession {
+                    root: PathBuf::from("/home/dys/dev"),
+                    selection: PathBuf::from("/home/dys/dev/broot"),
+                    pattern: "rs&c/Session".to_string(),
+                    options: Some(PanelOptions::from(&options)),
+                },
+                PanelSession {
+                    root: PathBuf::from("/tmp"),
+                    selection: PathBuf::from("/tmp"),
+                    pattern: String::new(),
+                    options: None,
+                },
+            ],
+        };
+        session.save_in(&sessions, LAST_SESSION).unwrap();
+        let loaded = Session::load_from(&sessions, LAST_SESSION).unwrap();
+        assert_eq!(loaded, session);
+        let mut restored = TreeOptions::default();
+        loaded.panels[0].options.as_ref().unwrap().apply_to(&mut restored);
+        assert!(restored.show_hidden);
+        assert_eq!(restored.max_depth, Some(3));
+        assert_eq!(restored.sort, Sort::Date);
+        assert!(matches!(
+            Session::load_from(&sessions, "work"),
+            Err(ProgramError::UnknownSession { .. }),
+        ));
+        assert!(matches!(
+            Session::load_from(&sessions, "../work"),
+            Err(ProgramError::InvalidSessionName { .. }),
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 use {
     super::*,
     crate::{
+        tree::Sort,
         verb::{
             Internal,
             VerbStore,
@@ -117,6 +118,7 @@ pub struct StandardStatusBuilder<'s> {
     pub is_filtered: bool,
     pub has_removed_pattern: bool,
     pub on_tree_root: bool, // should this be part of the Selection struct ?
+    pub sort: Sort,
 }
 impl<'s> StandardStatusBuilder<'s> {
     fn new(
@@ -132,6 +134,7 @@ impl<'s> StandardStatusBuilder<'s> {
             is_filtered: false,
             has_removed_pattern: false,
             on_tree_root: false,
+            sort: Sort::None,
         }
     }
     pub fn status(self) -> Status {
@@ -186,6 +189,10 @@ impl<'s> StandardStatusBuilder<'s> {
                 }
             }
         }
-        parts.to_status()
+        let mut status = parts.to_status();
+        if let Some(sort_name) = self.sort.name() {
+            status.message = format!("*sorted by {}* - {}", sort_name, status.message);
+        }
+        status
     }
 }
@@ -1,18 +1,25 @@
 use {
     super::*,
     crate::{
+        bookmark::{Bookmarks, BookmarksState},
         command::*,
         display::{Screen, W},
         errors::ProgramError,
+        clipboard,
+        file_sum::{self, FileSum},
         flag::Flag,
-        help::HelpState,
+        help::{HelpState, PaletteState},
+        path,
         pattern::*,
         preview::{PreviewMode, PreviewState},
         print,
         skin::PanelSkin,
+        stage::{MarkCommand, Stage},
         task_sync::Dam,
+        template,
         tree::*,
         verb::*,
+        yank::ConflictPolicy,
     },
     std::{
         path::{Path, PathBuf},
@@ -75,7 +82,7 @@ pub trait AppState {
     /// behavior to execute
     fn on_internal_generic(
         &mut self,
-        _w: &mut W,
+        w: &mut W,
         internal_exec: &InternalExecution,
         input_invocation: Option<&VerbInvocation>,
         _trigger_type: TriggerType,
@@ -89,21 +96,263 @@ pub trait AppState {
         Ok(match internal_exec.internal {
             Internal::back => AppStateCmdResult::PopState,
             Internal::copy_path => {
-                #[cfg(not(feature = "clipboard"))]
+                let path = self.selected_path().to_string_lossy().to_string();
+                match clipboard::copy(&path, con.osc52_clipboard) {
+                    Ok(()) => AppStateCmdResult::Keep,
+                    Err(e) => AppStateCmdResult::DisplayError(
+                        format!("{} while copying path", e),
+                    ),
+                }
+            }
+            Internal::from_template => {
+                let args = get_string_arg(input_invocation, internal_exec)
+                    .and_then(|args| args.trim().split_once(char::is_whitespace));
+                if let Some((template, name)) = args {
+                    let dir = path::closest_dir(self.selected_path());
+                    match template::create_from_template(
+                        &template::templates_dir(),
+                        template,
+                        &dir,
+                        name.trim(),
+                    ) {
+                        Ok(path) => {
+                            crate::journal::record(crate::journal::Operation::Create {
+                                path,
+                                trashed: None,
+                            });
+                            AppStateCmdResult::RefreshState { clear_cache: false }
+                        }
+                        Err(e) => AppStateCmdResult::DisplayError(
+                            format!("file not created: {}", e),
+                        ),
+                    }
+                } else {
+                    AppStateCmdResult::DisplayError(
+                        "a template and a file name are needed".to_string(),
+                    )
+                }
+            }
+            Internal::swap_names => {
+                if let Some(other_path) = &cc.other_path {
+                    match crate::path::swap_names(self.selected_path(), other_path) {
+                        Ok((new_path, new_other_path)) => {
+                            let renames = vec![
+                                internal_batch_rename::Rename {
+                                    from: self.selected_path().to_path_buf(),
+                                    to: new_path,
+                                },
+                                internal_batch_rename::Rename {
+                                    from: other_path.to_path_buf(),
+                                    to: new_other_path,
+                                },
+                            ];
+                            crate::journal::record(crate::journal::Operation::Renames { renames });
+                            AppStateCmdResult::RefreshState { clear_cache: true }
+                        }
+                        Err(e) => AppStateCmdResult::DisplayError(
+                            format!("names not swapped: {}", e),
+                        ),
+                    }
+                } else {
+                    AppStateCmdResult::DisplayError(
+                        "This verb needs exactly two panels".to_string(),
+                    )
+                }
+            }
+            Internal::set_other_to_parent => {
+                let non_preview_count = cc.areas.nb_pos - if cc.preview.is_some() { 1 } else { 0 };
+                if cc.other_path.is_some() {
+                    // the other panel exists, it's changed by the app
+                    AppStateCmdResult::HandleInApp(Internal::set_other_to_parent)
+                } else if non_preview_count == 1 {
+                    let path = self.selected_path();
+                    let target = path.parent().unwrap_or(path).to_path_buf();
+                    crate::verb::internal_focus::new_panel_on_path(
+                        target,
+                        screen,
+                        self.tree_options().without_pattern(),
+                        PanelPurpose::None,
+                        con,
+                        HDir::Right,
+                    )
+                } else {
+                    AppStateCmdResult::DisplayError(
+                        "This verb needs at most two panels".to_string(),
+                    )
+                }
+            }
+            Internal::link_to_other => {
+                if let Some(other_path) = &cc.other_path {
+                    let dir = crate::path::closest_dir(other_path);
+                    let name = get_string_arg(input_invocation, internal_exec);
+                    match crate::path::link_in_dir(self.selected_path(), &dir, name) {
+                        Ok(link) => {
+                            crate::journal::record(crate::journal::Operation::Link {
+                                target: self.selected_path().to_path_buf(),
+                                link,
+                            });
+                            AppStateCmdResult::RefreshState { clear_cache: true }
+                        }
+                        Err(e) => AppStateCmdResult::DisplayError(
+                            format!("link not created: {}", e),
+                        ),
+                    }
+                } else {
+                    AppStateCmdResult::DisplayError(
+                        "This verb needs exactly two panels".to_string(),
+                    )
+                }
+            }
+            Internal::zip_to_other => {
+                #[cfg(not(feature = "archive"))]
+                {
+                    AppStateCmdResult::DisplayError(
+                        "Archive feature not enabled at compilation".to_string(),
+                    )
+                }
+                #[cfg(feature = "archive")]
+                {
+                    if let Some(other_path) = &cc.other_path {
+                        let dir = crate::path::closest_dir(other_path);
+                        let name = get_string_arg(input_invocation, internal_exec);
+                        match crate::path::zip_in_dir(self.selected_path(), &dir, name) {
+                            Ok(path) => {
+                                crate::journal::record(crate::journal::Operation::Create {
+                                    path,
+                                    trashed: None,
+                                });
+                                AppStateCmdResult::RefreshState { clear_cache: true }
+                            }
+                            Err(e) => AppStateCmdResult::DisplayError(
+                                format!("archive not created: {}", e),
+                            ),
+                        }
+                    } else {
+                        AppStateCmdResult::DisplayError(
+                            "This verb needs exactly two panels".to_string(),
+                        )
+                    }
+                }
+            }
+            Internal::browse_archive => {
+                #[cfg(not(feature = "archive"))]
+                {
+                    AppStateCmdResult::DisplayError(
+                        "Archive feature not enabled at compilation".to_string(),
+                    )
+                }
+                #[cfg(feature = "archive")]
+                {
+                    crate::archive::ArchiveState::open(
+                        self.selected_path(),
+                        self.tree_options(),
+                        bang && cc.preview.is_none(),
+                    )
+                }
+            }
+            Internal::extract => AppStateCmdResult::DisplayError(
+                "only entries of a browsed archive can be extracted".to_string(),
+            ),
+            Internal::trash => {
+                #[cfg(any(not(unix), target_os = "macos"))]
+                {
+                    AppStateCmdResult::DisplayError(
+                        "The trash is only supported on linux and BSD".to_string(),
+                    )
+                }
+                #[cfg(all(unix, not(target_os = "macos")))]
+                {
+                    match crate::trash::TrashDir::home() {
+                        Some(trash) => match trash.trash(self.selected_path()) {
+                            Ok(name) => {
+                                crate::journal::record(crate::journal::Operation::Trash {
+                                    path: self.selected_path().to_path_buf(),
+                                    name,
+                                });
+                                AppStateCmdResult::RefreshState { clear_cache: true }
+                            }
+                            Err(e) => AppStateCmdResult::DisplayError(
+                                format!("not trashed: {}", e),
+                            ),
+                        },
+                        None => AppStateCmdResult::DisplayError(
+                            "no trash directory found".to_string(),
+                        ),
+                    }
+                }
+            }
+            Internal::open_trash => {
+                #[cfg(any(not(unix), target_os = "macos"))]
                 {
                     AppStateCmdResult::DisplayError(
-                        "Clipboard feature not enabled at compilation".to_string(),
+                        "The trash is only supported on linux and BSD".to_string(),
                     )
                 }
-                #[cfg(feature = "clipboard")]
+                #[cfg(all(unix, not(target_os = "macos")))]
                 {
-                    let path = self.selected_path().to_string_lossy().to_string();
-                    match terminal_clipboard::set_string(path) {
+                    let trash_state = crate::trash::TrashDir::home()
+                        .ok_or_else(|| "no trash directory found".to_string())
+                        .and_then(|trash| {
+                            crate::trash::TrashState::new(trash, self.tree_options())
+                                .map_err(|e| format!("{}", e))
+                        });
+                    match trash_state {
+                        Ok(state) => {
+                            let bang = input_invocation
+                                .map(|inv| inv.bang)
+                                .unwrap_or(internal_exec.bang);
+                            if bang && cc.preview.is_none() {
+                                AppStateCmdResult::NewPanel {
+                                    state: Box::new(state),
+                                    purpose: PanelPurpose::None,
+                                    direction: HDir::Right,
+                                }
+                            } else {
+                                AppStateCmdResult::NewState(Box::new(state))
+                            }
+                        }
+                        Err(e) => AppStateCmdResult::DisplayError(e),
+                    }
+                }
+            }
+            Internal::restore | Internal::purge => AppStateCmdResult::DisplayError(
+                "only items of the trash can be restored or purged".to_string(),
+            ),
+            Internal::batch_rename => {
+                if cc.stage.is_empty() {
+                    AppStateCmdResult::DisplayError(
+                        "stage the paths to rename first".to_string(),
+                    )
+                } else {
+                    internal_batch_rename::batch_rename(
+                        w,
+                        cc.stage.paths(),
+                        &crate::path::closest_dir(self.selected_path()),
+                    )?
+                }
+            }
+            Internal::copy_pair => {
+                if let Some(other_path) = &cc.other_path {
+                    let path = self.selected_path();
+                    let (left, right) = if cc.areas.is_first() {
+                        (path, other_path.as_path())
+                    } else {
+                        (other_path.as_path(), path)
+                    };
+                    let assignments = crate::path::shell_assignments(&[
+                        ("LEFT", left),
+                        ("RIGHT", right),
+                    ]);
+                    match clipboard::copy(&assignments, con.osc52_clipboard) {
                         Ok(()) => AppStateCmdResult::Keep,
-                        Err(_) => AppStateCmdResult::DisplayError(
-                            "Clipboard error while copying path".to_string(),
+                        Err(e) => AppStateCmdResult::DisplayError(
+                            format!("{} while copying paths", e),
                         ),
                     }
+                } else {
+                    AppStateCmdResult::DisplayError(
+                        "This verb needs exactly two panels".to_string(),
+                    )
                 }
             }
             Internal::close_panel_ok => AppStateCmdResult::ClosePanel {
@@ -139,6 +388,134 @@ pub trait AppState {
                     Err(e) => AppStateCmdResult::DisplayError(format!("{}", e)),
                 }
             }
+            Internal::load_layout | Internal::save_layout => {
+                match get_string_arg(input_invocation, internal_exec) {
+                    Some(name) if internal_exec.internal == Internal::load_layout => {
+                        AppStateCmdResult::LoadLayout { name: name.to_string() }
+                    }
+                    Some(name) => AppStateCmdResult::SaveLayout { name: name.to_string() },
+                    None => AppStateCmdResult::DisplayError(
+                        "a layout name is needed".to_string(),
+                    ),
+                }
+            }
+            Internal::mode => match get_string_arg(input_invocation, internal_exec) {
+                Some(name) if con.is_mode(name) => {
+                    AppStateCmdResult::SwitchMode { name: name.to_string() }
+                }
+                Some(name) => AppStateCmdResult::DisplayError(
+                    format!("no key mode named {:?}", name),
+                ),
+                None => AppStateCmdResult::DisplayError(
+                    "a mode name is needed".to_string(),
+                ),
+            },
+            Internal::load_session | Internal::save_session => {
+                match get_string_arg(input_invocation, internal_exec) {
+                    Some(name) if internal_exec.internal == Internal::load_session => {
+                        AppStateCmdResult::LoadSession { name: name.to_string() }
+                    }
+                    Some(name) => AppStateCmdResult::SaveSession { name: name.to_string() },
+                    None => AppStateCmdResult::DisplayError(
+                        "a session name is needed".to_string(),
+                    ),
+                }
+            }
+            Internal::pipe_listing => match get_string_arg(input_invocation, internal_exec) {
+                Some(cmd) => {
+                    // with a bang, the complete paths are listed
+                    let kind = if bang {
+                        internal_pipe_listing::ListingKind::Paths
+                    } else {
+                        internal_pipe_listing::ListingKind::Names
+                    };
+                    let dir = path::closest_dir(self.selected_path());
+                    internal_pipe_listing::on_dir(&dir, cmd, kind, &self.tree_options())?
+                }
+                None => AppStateCmdResult::DisplayError(
+                    "a command is needed, e.g. `:pipe_listing wc -l`".to_string(),
+                ),
+            },
+            Internal::locate => crate::verb::internal_focus::locate(
+                self.selected_path(),
+                screen,
+                self.tree_options().without_pattern(),
+                con,
+            ),
+            Internal::run => match get_string_arg(input_invocation, internal_exec) {
+                Some(cmd) => {
+                    let builder = ExecutionStringBuilder::from_selection(self.selection())
+                        .with_staged_paths(cc.stage.paths());
+                    internal_run::execution(cmd, &builder).to_cmd_result(w, builder, con)?
+                }
+                None => AppStateCmdResult::DisplayError(
+                    "a command is needed, e.g. `:run ls -l`".to_string(),
+                ),
+            },
+            Internal::repeat => AppStateCmdResult::Repeat(
+                get_arg(input_invocation, internal_exec, 1),
+            ),
+            Internal::bookmark => match get_string_arg(input_invocation, internal_exec) {
+                Some(name) => AppStateCmdResult::Bookmark {
+                    name: name.to_string(),
+                    path: path::closest_dir(self.selected_path()),
+                },
+                None => AppStateCmdResult::DisplayError(
+                    "a bookmark name is needed, e.g. `:bookmark work`".to_string(),
+                ),
+            },
+            Internal::bookmarks => match BookmarksState::new(self.tree_options()) {
+                Ok(state) => {
+                    if bang && cc.preview.is_none() {
+                        AppStateCmdResult::NewPanel {
+                            state: Box::new(state),
+                            purpose: PanelPurpose::None,
+                            direction: HDir::Right,
+                        }
+                    } else {
+                        AppStateCmdResult::NewState(Box::new(state))
+                    }
+                }
+                Err(e) => AppStateCmdResult::DisplayError(format!("{}", e)),
+            },
+            Internal::jump => {
+                let bookmark = get_string_arg(input_invocation, internal_exec)
+                    .ok_or_else(|| "a bookmark name is needed, e.g. `:jump work`".to_string())
+                    .and_then(|name| {
+                        let bookmarks = Bookmarks::load().map_err(|e| format!("{}", e))?;
+                        bookmarks
+                            .get(name)
+                            .map(|path| path.to_path_buf())
+                            .ok_or_else(|| format!("no bookmark named {:?}", name))
+                    });
+                match bookmark {
+                    Ok(path) if path.is_dir() => internal_focus::on_path(
+                        path,
+                        screen,
+                        self.tree_options(),
+                        bang && cc.preview.is_none(),
+                        con,
+                    ),
+                    Ok(path) => AppStateCmdResult::DisplayError(format!(
+                        "{} isn't a directory anymore",
+                        path.to_string_lossy(),
+                    )),
+                    Err(e) => AppStateCmdResult::DisplayError(e),
+                }
+            }
+            Internal::mark => {
+                let args = get_string_arg(input_invocation, internal_exec).unwrap_or_default();
+                match MarkCommand::parse(args) {
+                    Ok(command) => AppStateCmdResult::Mark {
+                        command,
+                        path: self.selected_path().to_path_buf(),
+                    },
+                    Err(e) => AppStateCmdResult::DisplayError(e),
+                }
+            }
+            Internal::commands => AppStateCmdResult::NewState(Box::new(
+                PaletteState::new(self.selection(), self.tree_options(), con)
+            )),
             Internal::help => {
                 let bang = input_invocation
                     .map(|inv| inv.bang)
@@ -156,10 +533,31 @@ pub trait AppState {
                 }
             }
             Internal::open_leave => self.selection().to_opener(con)?,
+            Internal::open_and_quit => self.selection().open_and_quit(),
+            Internal::open_url => match path::url_of_file(self.selected_path()) {
+                Ok(Some(url)) => match open::that(&url) {
+                    Ok(exit_status) => {
+                        info!("open returned with exit_status {:?}", exit_status);
+                        AppStateCmdResult::Keep
+                    }
+                    Err(e) => AppStateCmdResult::DisplayError(format!("{} not opened: {}", url, e)),
+                },
+                Ok(None) => AppStateCmdResult::DisplayError(
+                    "no URL found in the selected file".to_string(),
+                ),
+                Err(e) => AppStateCmdResult::DisplayError(format!("{}", e)),
+            },
             Internal::open_preview => self.open_preview(None, false, cc),
             Internal::preview_image => self.open_preview(Some(PreviewMode::Image), false, cc),
             Internal::preview_text => self.open_preview(Some(PreviewMode::Text), false, cc),
             Internal::preview_binary => self.open_preview(Some(PreviewMode::Hex), false, cc),
+            Internal::render => self.open_preview(Some(PreviewMode::Rendered), false, cc),
+            Internal::preview_as => {
+                match get_string_arg(input_invocation, internal_exec) {
+                    Some(syntax) => self.open_preview_as(syntax, cc),
+                    None => AppStateCmdResult::DisplayError("a syntax is needed".to_string()),
+                }
+            }
             Internal::toggle_preview => self.open_preview(None, true, cc),
             Internal::sort_by_count => self.with_new_options(
                 screen,
@@ -226,10 +624,12 @@ pub trait AppState {
             Internal::toggle_git_file_info => {
                 self.with_new_options(screen, &|o| o.show_git_file_info ^= true, bang, con)
             }
-            Internal::toggle_git_status => {
+            Internal::toggle_git_status | Internal::git_status => {
+                // git_status only turns the filtering on
+                let toggle = internal_exec.internal == Internal::toggle_git_status;
                 self.with_new_options(
                     screen, &|o| {
-                        if o.filter_by_git_status {
+                        if toggle && o.filter_by_git_status {
                             o.filter_by_git_status = false;
                         } else {
                             o.filter_by_git_status = true;
@@ -238,23 +638,18 @@ pub trait AppState {
                     }, bang, con
                 )
             }
+            Internal::toggle_flat => {
+                self.with_new_options(screen, &|o| o.flat_view ^= true, bang, con)
+            }
             Internal::toggle_perm => {
-                self.with_new_options(screen, &|o| o.show_permissions ^= true, bang, con)
+                self.with_new_options(screen, &|o| o.toggle_permissions(), bang, con)
+            }
+            Internal::toggle_perm_style => {
+                self.with_new_options(screen, &|o| o.toggle_permission_style(), bang, con)
+            }
+            Internal::toggle_sizes => {
+                self.with_new_options(screen, &|o| o.toggle_sizes(), bang, con)
             }
-            Internal::toggle_sizes => self.with_new_options(
-                screen,
-                &|o| {
-                    if o.show_sizes {
-                        o.show_sizes = false;
-                        o.show_root_fs = false;
-                    } else {
-                        o.show_sizes = true;
-                        o.show_root_fs = true;
-                    }
-                },
-                bang,
-                con,
-            ),
             Internal::toggle_trim_root => {
                 self.with_new_options(screen, &|o| o.trim_root ^= true, bang, con)
             }
@@ -290,7 +685,65 @@ pub trait AppState {
             Internal::print_relative_path => {
                 print::print_relative_path(self.selected_path(), con)?
             }
-            Internal::refresh => AppStateCmdResult::RefreshState { clear_cache: true },
+            Internal::refresh => {
+                let root = self.tree_root().unwrap_or_else(|| self.selected_path());
+                file_sum::clear_dir_cache(root);
+                AppStateCmdResult::RefreshState { clear_cache: true }
+            }
+            Internal::reload_config => AppStateCmdResult::HandleInApp(Internal::reload_config),
+            Internal::yank => AppStateCmdResult::HandleInApp(Internal::yank),
+            Internal::undo => AppStateCmdResult::HandleInApp(Internal::undo),
+            Internal::redo => AppStateCmdResult::HandleInApp(Internal::redo),
+            Internal::journal => {
+                let state = crate::journal::JournalState::new(self.tree_options());
+                let bang = input_invocation
+                    .map(|inv| inv.bang)
+                    .unwrap_or(internal_exec.bang);
+                if bang && cc.preview.is_none() {
+                    AppStateCmdResult::NewPanel {
+                        state: Box::new(state),
+                        purpose: PanelPurpose::None,
+                        direction: HDir::Right,
+                    }
+                } else {
+                    AppStateCmdResult::NewState(Box::new(state))
+                }
+            }
+            Internal::cut => AppStateCmdResult::HandleInApp(Internal::cut),
+            Internal::paste => {
+                // the policy of the verb, from its configuration, and the one
+                // typed by the user, which answers a conflict
+                let on_conflict = internal_exec.arg.as_deref().filter(|s| !s.is_empty());
+                let answer = input_invocation
+                    .and_then(|vi| vi.args.as_deref())
+                    .filter(|s| !s.is_empty());
+                let invalid = on_conflict
+                    .into_iter()
+                    .chain(answer)
+                    .find(|name| ConflictPolicy::from_name(name).is_none());
+                match invalid {
+                    Some(name) => AppStateCmdResult::DisplayError(format!(
+                        "invalid conflict policy {:?} (expected ask, skip, overwrite or rename)",
+                        name,
+                    )),
+                    None => AppStateCmdResult::Paste {
+                        on_conflict: on_conflict
+                            .and_then(ConflictPolicy::from_name)
+                            .unwrap_or_default(),
+                        answer: answer.and_then(ConflictPolicy::from_name),
+                    },
+                }
+            }
+            Internal::toggle_exec => AppStateCmdResult::HandleInApp(Internal::toggle_exec),
+            Internal::stage => AppStateCmdResult::HandleInApp(Internal::stage),
+            Internal::unstage => AppStateCmdResult::HandleInApp(Internal::unstage),
+            Internal::toggle_stage => AppStateCmdResult::HandleInApp(Internal::toggle_stage),
+            Internal::clear_stage => AppStateCmdResult::HandleInApp(Internal::clear_stage),
+            Internal::toggle_split => AppStateCmdResult::HandleInApp(Internal::toggle_split),
+            Internal::toggle_verb_bar => AppStateCmdResult::HandleInApp(Internal::toggle_verb_bar),
+            Internal::toggle_watch => AppStateCmdResult::HandleInApp(Internal::toggle_watch),
+            Internal::recent_next => AppStateCmdResult::HandleInApp(Internal::recent_next),
+            Internal::recent_prev => AppStateCmdResult::HandleInApp(Internal::recent_prev),
             Internal::quit => AppStateCmdResult::Quit,
             _ => AppStateCmdResult::Keep,
         })
@@ -305,6 +758,49 @@ pub trait AppState {
         cc: &CmdContext,
         screen: Screen,
     ) -> Result<AppStateCmdResult, ProgramError> {
+        #[cfg(unix)]
+        {
+            if verb.skip_on_network_fs
+                && crate::filesystems::is_on_network_fs(self.selected_path())
+            {
+                return Ok(AppStateCmdResult::DisplayError(
+                    "verb skipped: the selection is on a network file system".to_string()
+                ));
+            }
+        }
+        if verb.require_foreground && !crate::display::is_foreground() {
+            return Ok(AppStateCmdResult::DisplayError(
+                "verb skipped: broot isn't in the foreground of its terminal".to_string()
+            ));
+        }
+        let args = invocation.and_then(|inv| inv.args.as_deref());
+        if let Some(msg) = verb.check_typed_confirmation(self.selection(), args) {
+            return Ok(AppStateCmdResult::DisplayError(msg));
+        }
+        if let Some(msg) = verb.check_stable_selection(cc.initial_selection, self.selected_path()) {
+            return Ok(AppStateCmdResult::DisplayError(msg));
+        }
+        if verb.has_size_condition() {
+            // the size of a directory isn't computed here, as it may
+            // take minutes: only the one of the displayed tree is used
+            let path = self.selected_path();
+            let sum = if path.is_dir() {
+                match FileSum::cached_dir(path) {
+                    Some(sum) => sum,
+                    None => {
+                        return Ok(AppStateCmdResult::DisplayError(
+                            "verb skipped: the size of the directory isn't computed yet (see :toggle_sizes)"
+                                .to_string()
+                        ));
+                    }
+                }
+            } else {
+                FileSum::from_file(path)
+            };
+            if let Some(msg) = verb.check_size(sum.to_size()) {
+                return Ok(AppStateCmdResult::DisplayError(msg));
+            }
+        }
         let exec_builder = || {
             ExecutionStringBuilder::from_invocation(
                 &verb.invocation_parser,
@@ -316,19 +812,45 @@ pub trait AppState {
                     &None
                 },
             )
+            .with_match_count(self.match_count())
+            .with_search_mode(self.search_mode())
+            .with_staged_paths(cc.stage.paths())
+            .with_root(self.tree_root())
         };
         match &verb.execution {
+            VerbExecution::Internal(internal_exec) if internal_exec.is_chain() => {
+                let mut steps: Vec<InternalExecution> = internal_exec
+                    .steps()
+                    .map(|step| InternalExecution { then: Vec::new(), ..step.clone() })
+                    .collect();
+                // the arguments typed in the input go to the first internal
+                if let Some(args) = invocation.and_then(|inv| inv.args.as_ref()) {
+                    steps[0].arg = Some(args.clone());
+                }
+                Ok(AppStateCmdResult::ExecuteChain(steps))
+            }
             VerbExecution::Internal(internal_exec) => {
                 self.on_internal(w, internal_exec, invocation, trigger_type, cc, screen)
             }
             VerbExecution::External(external) => external.to_cmd_result(w, exec_builder(), &cc.con),
             VerbExecution::Sequence(seq_ex) => {
+                let exec_builder = exec_builder();
+                if let Some(err) = exec_builder.check_values(&seq_ex.sequence.raw) {
+                    return Ok(AppStateCmdResult::DisplayError(err));
+                }
                 let sequence = Sequence {
-                    raw: exec_builder().shell_exec_string(&seq_ex.sequence.raw),
+                    raw: exec_builder.shell_exec_string(&seq_ex.sequence.raw),
                     separator: seq_ex.sequence.separator.clone(),
                 };
                 Ok(AppStateCmdResult::ExecuteSequence { sequence })
             }
+            VerbExecution::Steps(steps_ex) => Ok(match steps_ex.resolve(&exec_builder()) {
+                Ok(steps) => AppStateCmdResult::ExecuteSteps {
+                    steps,
+                    stop_on_error: steps_ex.stop_on_error,
+                },
+                Err(err) => AppStateCmdResult::DisplayError(err),
+            }),
         }
     }
 
@@ -399,6 +921,40 @@ pub trait AppState {
         }
     }
 
+    /// return a cmdresult asking for the opening of a preview
+    /// with a forced syntax, or for the change of the syntax
+    /// of the current preview
+    fn open_preview_as(
+        &mut self,
+        syntax: &str,
+        cc: &CmdContext,
+    ) -> AppStateCmdResult {
+        if let Some(id) = cc.preview {
+            return AppStateCmdResult::ApplyOnPanel { id };
+        }
+        let path = self.selected_path();
+        if !path.is_file() {
+            return AppStateCmdResult::DisplayError(
+                "only regular files can be previewed".to_string()
+            );
+        }
+        let mut state = PreviewState::new(
+            path.to_path_buf(),
+            InputPattern::none(),
+            Some(PreviewMode::Text),
+            self.tree_options(),
+            cc.con,
+        );
+        match state.set_syntax(syntax, cc.con) {
+            Ok(()) => AppStateCmdResult::NewPanel {
+                state: Box::new(state),
+                purpose: PanelPurpose::Preview,
+                direction: HDir::Right,
+            },
+            Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+        }
+    }
+
     /// return a cmdresult asking for the opening of a preview
     fn open_preview(
         &mut self,
@@ -481,9 +1037,16 @@ pub trait AppState {
         screen: Screen,
         state_area: Area,
         skin: &PanelSkin,
+        stage: &Stage,
         con: &AppContext,
     ) -> Result<(), ProgramError>;
 
+    /// return the directories whose entries are displayed, to
+    /// be watched for changes
+    fn watched_dirs(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
     /// return the flags to display
     fn get_flags(&self) -> Vec<Flag> {
         vec![]
@@ -493,6 +1056,33 @@ pub trait AppState {
         String::new()
     }
 
+    /// return the number of displayed entries matching the
+    /// current search, 0 when there's no search
+    fn match_count(&self) -> usize {
+        0
+    }
+
+    /// return the mode of the current search, if any
+    fn search_mode(&self) -> Option<SearchMode> {
+        None
+    }
+
+    /// return the root of the displayed tree, if the state displays one
+    fn tree_root(&self) -> Option<&Path> {
+        None
+    }
+
+    /// return a copy of the state, to be displayed in another
+    /// panel, if this kind of state can be copied
+    fn clone_state(&self) -> Option<Box<dyn AppState>> {
+        None
+    }
+
+    /// select the path if it's displayed, return whether it is
+    fn try_select_path(&mut self, _path: &Path, _screen: Screen) -> bool {
+        false
+    }
+
     fn set_selected_path(&mut self, _path: PathBuf, _con: &AppContext) {
         // this function is useful for preview states
     }
@@ -554,6 +1144,19 @@ pub trait AppState {
     }
 }
 
+/// return the non empty string argument given in the invocation
+/// or in the internal execution
+pub fn get_string_arg<'a>(
+    verb_invocation: Option<&'a VerbInvocation>,
+    internal_exec: &'a InternalExecution,
+) -> Option<&'a str> {
+    verb_invocation
+        .and_then(|vi| vi.args.as_ref())
+        .or(internal_exec.arg.as_ref())
+        .map(|s| s.as_str())
+        .filter(|s| !s.is_empty())
+}
+
 pub fn get_arg<T: Copy + FromStr>(
     verb_invocation: Option<&VerbInvocation>,
     internal_exec: &InternalExecution,
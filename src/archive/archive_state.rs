@@ -0,0 +1,329 @@
+use {
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, Screen, SPACE_FILLING, W},
+        errors::ProgramError,
+        path::{self, ArchiveEntry},
+        pattern::*,
+        skin::PanelSkin,
+        stage::Stage,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+    tempfile::TempDir,
+    termimad::Area,
+};
+
+/// entries bigger than this aren't extracted for the preview
+const MAX_PREVIEWED_SIZE: u64 = 10_000_000;
+
+/// an application state listing the entries of an archive (zip,
+/// tar or tar.gz), from which they may be extracted.
+///
+/// When there's a preview, the selected file entry is extracted in a
+/// temporary directory and its path is the selected one.
+pub struct ArchiveState {
+    archive: PathBuf,
+    entries: Vec<ArchiveEntry>,
+    list: ListSelection,
+    tree_options: TreeOptions,
+    message: Option<String>, // the result of the last extraction
+    preview_dir: Option<TempDir>,
+    preview_file: Option<(String, PathBuf)>, // the entry extracted for preview
+}
+
+impl ArchiveState {
+    pub fn new(
+        archive: &Path,
+        tree_options: TreeOptions,
+    ) -> Result<ArchiveState, ProgramError> {
+        let entries = path::archive_listing(archive)?;
+        let mut list = ListSelection::default();
+        list.set_items(entries.len(), None);
+        Ok(ArchiveState {
+            archive: archive.to_path_buf(),
+            entries,
+            list,
+            tree_options,
+            message: None,
+            preview_dir: None,
+            preview_file: None,
+        })
+    }
+    /// open the archive in a new panel, or replacing the current state
+    pub fn open(
+        archive: &Path,
+        tree_options: TreeOptions,
+        in_new_panel: bool,
+    ) -> AppStateCmdResult {
+        match ArchiveState::new(archive, tree_options) {
+            Ok(state) if in_new_panel => AppStateCmdResult::NewPanel {
+                state: Box::new(state),
+                purpose: PanelPurpose::None,
+                direction: HDir::Right,
+            },
+            Ok(state) => AppStateCmdResult::NewState(Box::new(state)),
+            Err(e) => AppStateCmdResult::DisplayError(
+                format!("not a readable archive: {}", e),
+            ),
+        }
+    }
+    /// return the displayed entry at the given index
+    fn entry(&self, idx: usize) -> Option<&ArchiveEntry> {
+        self.list.item_idx(idx).map(|i| &self.entries[i])
+    }
+    fn selected_entry(&self) -> Option<&ArchiveEntry> {
+        self.list.selected_item_idx().map(|i| &self.entries[i])
+    }
+    /// extract the selected entry next to the archive
+    fn extract(&mut self) -> AppStateCmdResult {
+        let name = match self.selected_entry() {
+            Some(entry) => entry.name.clone(),
+            None => {
+                return AppStateCmdResult::DisplayError("no entry to extract".to_string());
+            }
+        };
+        let dir = path::closest_dir(&self.archive);
+        match path::extract_archive_entry(&self.archive, &name, &dir) {
+            Ok(extracted) => {
+                self.message = Some(format!("extracted to {}", extracted.to_string_lossy()));
+                AppStateCmdResult::RefreshState { clear_cache: true }
+            }
+            Err(e) => AppStateCmdResult::DisplayError(format!("{} not extracted: {}", name, e)),
+        }
+    }
+    /// extract the selected file entry in the temporary directory,
+    /// so that it can be previewed
+    fn extract_for_preview(&mut self) {
+        let name = match self.selected_entry() {
+            Some(entry) if !entry.is_dir && entry.size <= MAX_PREVIEWED_SIZE => entry.name.clone(),
+            _ => {
+                return;
+            }
+        };
+        if self.preview_file.as_ref().is_some_and(|(previewed, _)| previewed == &name) {
+            return;
+        }
+        if let Some((_, path)) = self.preview_file.take() {
+            let _ = fs::remove_file(path);
+        }
+        if self.preview_dir.is_none() {
+            match tempfile::tempdir() {
+                Ok(dir) => self.preview_dir = Some(dir),
+                Err(e) => {
+                    warn!("no temporary directory for preview: {}", e);
+                    return;
+                }
+            }
+        }
+        if let Some(dir) = &self.preview_dir {
+            match path::extract_archive_entry(&self.archive, &name, dir.path()) {
+                Ok(path) => self.preview_file = Some((name, path)),
+                Err(e) => warn!("{} not extracted for preview: {}", name, e),
+            }
+        }
+    }
+}
+
+impl AppState for ArchiveState {
+
+    fn selected_path(&self) -> &Path {
+        match (&self.preview_file, self.selected_entry()) {
+            (Some((name, path)), Some(entry)) if name == &entry.name => path,
+            _ => &self.archive,
+        }
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.archive,
+            stype: SelectionType::File,
+            is_exe: false,
+            line: 0,
+        }
+    }
+
+    fn tree_options(&self) -> TreeOptions {
+        self.tree_options.clone()
+    }
+
+    fn with_new_options(
+        &mut self,
+        _screen: Screen,
+        change_options: &dyn Fn(&mut TreeOptions),
+        _in_new_panel: bool,
+        _con: &AppContext,
+    ) -> AppStateCmdResult {
+        change_options(&mut self.tree_options);
+        AppStateCmdResult::Keep
+    }
+
+    fn refresh(&mut self, _screen: Screen, _con: &AppContext) -> Command {
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pattern: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let selected = self.selected_entry().map(|e| e.name.clone());
+        let filtered = if pattern.is_none() {
+            None
+        } else {
+            let pattern = pattern.pattern;
+            Some(
+                self.entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| pattern.score_of_string(&entry.name).is_some())
+                    .map(|(idx, _)| idx)
+                    .collect()
+            )
+        };
+        self.list.set_items(self.entries.len(), filtered);
+        // we keep the selected entry when it's still displayed
+        let idx = (0..self.list.count())
+            .find(|&idx| self.entry(idx).map(|e| &e.name) == selected.as_ref())
+            .unwrap_or(0);
+        self.list.reset();
+        self.list.select(idx);
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn match_count(&self) -> usize {
+        self.list.count()
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _stage: &Stage,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height < 2 {
+            warn!("area too small for archive");
+            return Ok(());
+        }
+        let styles = &panel_skin.styles;
+        w.queue(cursor::MoveTo(state_area.left, state_area.top))?;
+        let mut cw = CropWriter::new(w, state_area.width as usize);
+        cw.queue_str(&styles.default, &self.archive.to_string_lossy())?;
+        cw.fill(&styles.default, &SPACE_FILLING)?;
+        self.list.set_page_height(state_area.height as usize - 1);
+        for y in 0..self.list.page_height() {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + 1 + y as u16))?;
+            let mut cw = CropWriter::new(w, state_area.width as usize);
+            let idx = self.list.idx_at(y);
+            let selected = idx == self.list.selection_idx();
+            let bg_style = if selected { &styles.selected_line } else { &styles.default };
+            if let Some(entry) = self.entry(idx) {
+                let mut size_style = styles.count.clone();
+                let mut name_style = if entry.is_dir {
+                    styles.directory.clone()
+                } else {
+                    styles.file.clone()
+                };
+                if selected {
+                    if let Some(c) = styles.selected_line.get_bg() {
+                        size_style.set_bg(c);
+                        name_style.set_bg(c);
+                    }
+                }
+                let size = if entry.is_dir {
+                    String::new()
+                } else {
+                    file_size::fit_4(entry.size)
+                };
+                cw.queue_g_string(&size_style, format!("{:>4}", size))?;
+                cw.queue_char(bg_style, ' ')?;
+                if self.list.is_filtered() {
+                    // the whole names are shown as entries of different
+                    // directories are mixed
+                    cw.queue_str(&name_style, &entry.name)?;
+                } else {
+                    let depth = entry.name.matches('/').count();
+                    let file_name = entry.name.rsplit('/').next().unwrap_or(&entry.name);
+                    cw.queue_g_string(bg_style, "  ".repeat(depth))?;
+                    cw.queue_str(&name_style, file_name)?;
+                }
+                if entry.is_dir {
+                    cw.queue_char(&name_style, '/')?;
+                }
+            }
+            cw.fill(bg_style, &SPACE_FILLING)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        match &self.message {
+            Some(message) => Status::from_message(message.clone()),
+            None => Status::from_message(
+                "Hit *esc* to go back, or type `:extract` to extract the selected entry",
+            ),
+        }
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let opens_preview = matches!(
+            internal_exec.internal,
+            Internal::open_preview | Internal::toggle_preview
+                | Internal::preview_image | Internal::preview_text
+                | Internal::preview_binary | Internal::preview_as
+        );
+        if opens_preview {
+            self.extract_for_preview();
+        }
+        let res = match self.list.on_internal(internal_exec, input_invocation) {
+            Some(res) => res,
+            None => match internal_exec.internal {
+                Internal::back => {
+                    if self.list.is_filtered() {
+                        self.list.set_items(self.entries.len(), None);
+                        AppStateCmdResult::Keep
+                    } else {
+                        AppStateCmdResult::PopState
+                    }
+                }
+                Internal::extract => self.extract(),
+                _ => self.on_internal_generic(
+                    w,
+                    internal_exec,
+                    input_invocation,
+                    trigger_type,
+                    cc,
+                    screen,
+                )?,
+            },
+        };
+        if cc.preview.is_some() {
+            self.extract_for_preview();
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,5 @@
+//! The whole module is only available with the "archive" feature
+
+mod archive_state;
+
+pub use archive_state::ArchiveState;
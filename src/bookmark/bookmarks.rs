@@ -0,0 +1,138 @@
+use {
+    crate::errors::ProgramError,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// the directories saved with `:bookmark`, by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    bookmarks: BTreeMap<String, PathBuf>,
+}
+
+/// return the path of the file where bookmarks are saved
+pub fn bookmarks_path() -> PathBuf {
+    crate::conf::app_dirs().data_dir().join("bookmarks.toml")
+}
+
+/// check the name of a bookmark is a simple one, easy to type
+fn check_name(name: &str) -> Result<(), ProgramError> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(ProgramError::InvalidBookmarkName { name: name.to_string() })
+    }
+}
+
+impl Bookmarks {
+    pub fn get(&self, name: &str) -> Option<&Path> {
+        self.bookmarks.get(name).map(|path| path.as_path())
+    }
+    /// add a bookmark, replacing the previous one of the same name
+    pub fn set(&mut self, name: &str, path: PathBuf) -> Result<(), ProgramError> {
+        check_name(name)?;
+        self.bookmarks.insert(name.to_string(), path);
+        Ok(())
+    }
+    /// remove a bookmark, return false if there was none of this name
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.bookmarks.remove(name).is_some()
+    }
+    /// the names of the bookmarks starting with the given string,
+    /// in alphabetical order
+    pub fn names_starting_with<'s>(&'s self, start: &'s str) -> impl Iterator<Item = &'s str> {
+        self.bookmarks
+            .keys()
+            .map(|name| name.as_str())
+            .filter(move |name| name.starts_with(start))
+    }
+    /// the bookmarks, in the alphabetical order of their names
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.bookmarks
+            .iter()
+            .map(|(name, path)| (name.as_str(), path.as_path()))
+    }
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    /// write the bookmarks in the given file, replacing its content
+    pub fn save_to(&self, path: &Path) -> Result<(), ProgramError> {
+        let content = toml::to_string(self)
+            .map_err(|e| ProgramError::BookmarksFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// read the bookmarks of the given file, a missing file
+    /// meaning there's no bookmark
+    pub fn load_from(path: &Path) -> Result<Self, ProgramError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content)
+            .map_err(|e| ProgramError::BookmarksFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })
+    }
+
+    pub fn save(&self) -> Result<(), ProgramError> {
+        self.save_to(&bookmarks_path())
+    }
+
+    pub fn load() -> Result<Self, ProgramError> {
+        Self::load_from(&bookmarks_path())
+    }
+}
+
+#[cfg(test)]
+mod bookmarks_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data/bookmarks.toml");
+        let mut bookmarks = Bookmarks::load_from(&file).unwrap();
+        assert!(bookmarks.is_empty());
+        bookmarks.set("broot", PathBuf::from("/home/dys/dev/broot")).unwrap();
+        bookmarks.set("backup", PathBuf::from("/mnt/backup")).unwrap();
+        bookmarks.set("dl", PathBuf::from("/home/dys/Downloads")).unwrap();
+        bookmarks.set("broot", PathBuf::from("/home/dys/dev/broot/src")).unwrap();
+        for name in &["", "a b", "a/b"] {
+            assert!(matches!(
+                bookmarks.set(name, PathBuf::from("/tmp")),
+                Err(ProgramError::InvalidBookmarkName { .. }),
+            ));
+        }
+        bookmarks.save_to(&file).unwrap();
+        let mut bookmarks = Bookmarks::load_from(&file).unwrap();
+        assert_eq!(bookmarks.len(), 3);
+        assert_eq!(bookmarks.get("broot"), Some(Path::new("/home/dys/dev/broot/src")));
+        assert_eq!(
+            bookmarks.names_starting_with("b").collect::<Vec<_>>(),
+            vec!["backup", "broot"],
+        );
+        assert!(bookmarks.remove("dl"));
+        assert!(!bookmarks.remove("dl"));
+        assert_eq!(bookmarks.get("dl"), None);
+    }
+}
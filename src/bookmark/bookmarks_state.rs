@@ -0,0 +1,234 @@
+use {
+    super::*,
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, Screen, SPACE_FILLING, W},
+        errors::ProgramError,
+        pattern::*,
+        skin::PanelSkin,
+        stage::Stage,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::path::{Path, PathBuf},
+    termimad::Area,
+};
+
+/// an application state listing the bookmarks, the selected
+/// one being focused on `:open_stay`
+pub struct BookmarksState {
+    bookmarks: Vec<(String, PathBuf)>,
+    pattern: Option<Pattern>,
+    list: ListSelection,
+    tree_options: TreeOptions,
+}
+
+impl BookmarksState {
+    pub fn new(tree_options: TreeOptions) -> Result<BookmarksState, ProgramError> {
+        let mut state = BookmarksState {
+            bookmarks: Vec::new(),
+            pattern: None,
+            list: ListSelection::default(),
+            tree_options,
+        };
+        state.reload()?;
+        Ok(state)
+    }
+    /// read again the bookmarks, keeping the current filter
+    fn reload(&mut self) -> Result<(), ProgramError> {
+        self.bookmarks = Bookmarks::load()?
+            .iter()
+            .map(|(name, path)| (name.to_string(), path.to_path_buf()))
+            .collect();
+        let filtered = self.pattern.as_ref().map(|pattern| {
+            self.bookmarks
+                .iter()
+                .enumerate()
+                .filter(|(_, (name, _))| pattern.score_of_string(name).is_some())
+                .map(|(idx, _)| idx)
+                .collect()
+        });
+        self.list.set_items(self.bookmarks.len(), filtered);
+        Ok(())
+    }
+}
+
+impl AppState for BookmarksState {
+
+    fn selected_path(&self) -> &Path {
+        match self.list.selected_item_idx() {
+            Some(idx) => &self.bookmarks[idx].1,
+            None => Path::new("/"),
+        }
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: self.selected_path(),
+            stype: SelectionType::Directory,
+            is_exe: false,
+            line: 0,
+        }
+    }
+
+    fn tree_options(&self) -> TreeOptions {
+        self.tree_options.clone()
+    }
+
+    fn with_new_options(
+        &mut self,
+        _screen: Screen,
+        change_options: &dyn Fn(&mut TreeOptions),
+        _in_new_panel: bool,
+        _con: &AppContext,
+    ) -> AppStateCmdResult {
+        change_options(&mut self.tree_options);
+        AppStateCmdResult::Keep
+    }
+
+    fn refresh(&mut self, _screen: Screen, _con: &AppContext) -> Command {
+        if let Err(e) = self.reload() {
+            warn!("error while reading the bookmarks: {}", e);
+        }
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pattern: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = if pattern.is_none() {
+            None
+        } else {
+            Some(pattern.pattern)
+        };
+        self.list.reset();
+        self.reload()?;
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn match_count(&self) -> usize {
+        self.list.count()
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _stage: &Stage,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height < 2 {
+            warn!("area too small for bookmarks");
+            return Ok(());
+        }
+        let styles = &panel_skin.styles;
+        w.queue(cursor::MoveTo(state_area.left, state_area.top))?;
+        let mut cw = CropWriter::new(w, state_area.width as usize);
+        cw.queue_g_string(
+            &styles.default,
+            format!("{} bookmarks", self.bookmarks.len()),
+        )?;
+        cw.fill(&styles.default, &SPACE_FILLING)?;
+        let name_width = self.bookmarks
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        self.list.set_page_height(state_area.height as usize - 1);
+        for y in 0..self.list.page_height() {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + 1 + y as u16))?;
+            let mut cw = CropWriter::new(w, state_area.width as usize);
+            let idx = self.list.idx_at(y);
+            let selected = idx == self.list.selection_idx();
+            let bg_style = if selected { &styles.selected_line } else { &styles.default };
+            if let Some(bookmark_idx) = self.list.item_idx(idx) {
+                let (name, path) = &self.bookmarks[bookmark_idx];
+                let mut name_style = styles.file.clone();
+                let mut path_style = styles.directory.clone();
+                if selected {
+                    if let Some(c) = styles.selected_line.get_bg() {
+                        name_style.set_bg(c);
+                        path_style.set_bg(c);
+                    }
+                }
+                cw.queue_g_string(&name_style, format!("{:w$}", name, w = name_width))?;
+                cw.queue_char(bg_style, ' ')?;
+                cw.queue_str(&path_style, &path.to_string_lossy())?;
+            }
+            cw.fill(bg_style, &SPACE_FILLING)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        if self.bookmarks.is_empty() {
+            Status::from_message(
+                "No bookmark yet, `:bookmark {name}` saves one. Hit *esc* to go back",
+            )
+        } else {
+            Status::from_message("Hit *enter* to focus the selected bookmark")
+        }
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        if let Some(res) = self.list.on_internal(internal_exec, input_invocation) {
+            return Ok(res);
+        }
+        let con = cc.con;
+        Ok(match internal_exec.internal {
+            Internal::back => {
+                if self.pattern.is_some() {
+                    self.pattern = None;
+                    self.reload()?;
+                    AppStateCmdResult::Keep
+                } else {
+                    AppStateCmdResult::PopState
+                }
+            }
+            Internal::open_stay => match self.list.selected_item_idx() {
+                Some(idx) => {
+                    let bang = input_invocation
+                        .map(|inv| inv.bang)
+                        .unwrap_or(internal_exec.bang);
+                    internal_focus::on_path(
+                        self.bookmarks[idx].1.clone(),
+                        screen,
+                        self.tree_options(),
+                        bang && cc.preview.is_none(),
+                        con,
+                    )
+                }
+                None => AppStateCmdResult::DisplayError("no bookmark selected".to_string()),
+            },
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+}
@@ -0,0 +1,10 @@
+//! named directories, saved with `:bookmark`, focused with
+//! `:jump` and listed with `:bookmarks`
+
+mod bookmarks;
+mod bookmarks_state;
+
+pub use {
+    bookmarks::{bookmarks_path, Bookmarks},
+    bookmarks_state::BookmarksState,
+};
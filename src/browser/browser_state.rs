@@ -2,26 +2,33 @@ use {
     crate::{
         app::*,
         command::{Command, TriggerType},
+        content_search::{search_subtree, Needle, SubtreeSearchLimits},
         display::{DisplayableTree, Screen, W},
         errors::{ProgramError, TreeBuildError},
         flag::Flag,
         git,
+        journal,
         pattern::*,
         path::{self, PathAnchor},
         print,
         skin::PanelSkin,
+        stage::Stage,
         task_sync::Dam,
         tree::*,
         tree_build::TreeBuilder,
         verb::*,
     },
     open,
-    std::path::{Path, PathBuf},
+    std::{
+        path::{Path, PathBuf},
+        time::SystemTime,
+    },
     termimad::Area,
 };
 
 /// An application state dedicated to displaying a tree.
 /// It's the first and main screen of broot.
+#[derive(Clone)]
 pub struct BrowserState {
     pub tree: Tree,
     pub filtered_tree: Option<Tree>,
@@ -65,6 +72,16 @@ impl BrowserState {
         i32::from(screen.height) - 2
     }
 
+    /// return the pattern being applied if there's one, or the pattern
+    /// of the displayed tree
+    fn current_pattern(&self) -> &InputPattern {
+        if self.pending_pattern.is_some() {
+            &self.pending_pattern
+        } else {
+            &self.displayed_tree().options.pattern
+        }
+    }
+
     /// return a reference to the currently displayed tree, which
     /// is the filtered tree if there's one, the base tree if not.
     pub fn displayed_tree(&self) -> &Tree {
@@ -111,6 +128,16 @@ impl BrowserState {
                 in_new_panel,
             ))
         } else {
+            #[cfg(feature = "archive")]
+            {
+                if path::ArchiveKind::of(&target).is_some() {
+                    return Ok(crate::archive::ArchiveState::open(
+                        &target,
+                        tree.options.without_pattern(),
+                        in_new_panel,
+                    ));
+                }
+            }
             match open::that(&target) {
                 Ok(exit_status) => {
                     info!("open returned with exit_status {:?}", exit_status);
@@ -153,6 +180,8 @@ impl AppState for BrowserState {
             Some("computing stats")
         } else if self.displayed_tree().is_missing_git_status_computation() {
             Some("computing git status")
+        } else if self.displayed_tree().is_missing_annotations() {
+            Some("computing annotations")
         } else {
             None
         }
@@ -170,6 +199,21 @@ impl AppState for BrowserState {
         self.displayed_tree().options.clone()
     }
 
+    fn clone_state(&self) -> Option<Box<dyn AppState>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn try_select_path(&mut self, path: &Path, screen: Screen) -> bool {
+        let page_height = BrowserState::page_height(screen);
+        let tree = self.displayed_tree_mut();
+        if tree.try_select_path(path) {
+            tree.make_selection_visible(page_height);
+            true
+        } else {
+            false
+        }
+    }
+
     /// build a cmdResult asking for the addition of a new state
     /// being a browser state similar to the current one but with
     /// different options
@@ -224,11 +268,22 @@ impl AppState for BrowserState {
     fn on_pattern(
         &mut self,
         pat: InputPattern,
-        _con: &AppContext,
+        con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
         if pat.is_none() {
             self.filtered_tree = None;
         }
+        // an edited pattern keeps the case sensitivity toggled with :toggle_case
+        let pat = if pat.is_some() && self.current_pattern().case_toggled && !pat.case_toggled {
+            match pat.with_toggled_case(con) {
+                Ok(pat) => pat,
+                Err(e) => {
+                    return Ok(AppStateCmdResult::DisplayError(e.to_string()));
+                }
+            }
+        } else {
+            pat
+        };
         self.pending_pattern = pat;
         Ok(AppStateCmdResult::Keep)
     }
@@ -380,6 +435,20 @@ impl AppState for BrowserState {
             Internal::print_tree => {
                 print::print_tree(&self.displayed_tree(), screen, &cc.panel_skin, con)?
             }
+            Internal::export => {
+                let format = get_string_arg(input_invocation, internal_exec)
+                    .map_or(Ok(print::OutputFormat::Json), |s| s.parse());
+                match format {
+                    Ok(format) => print::export_tree(
+                        self.displayed_tree(),
+                        format,
+                        screen,
+                        cc.panel_skin,
+                        con,
+                    )?,
+                    Err(e) => AppStateCmdResult::DisplayError(e),
+                }
+            }
             Internal::select_first => {
                 self.displayed_tree_mut().try_select_first();
                 AppStateCmdResult::Keep
@@ -389,6 +458,133 @@ impl AppState for BrowserState {
                 self.displayed_tree_mut().try_select_last(page_height);
                 AppStateCmdResult::Keep
             }
+            Internal::depth => match get_string_arg(input_invocation, internal_exec) {
+                None => self.with_new_options(screen, &|o| o.max_depth = None, bang, con),
+                Some(arg) => {
+                    // when the depth isn't limited yet, a change is
+                    // relative to the deepest displayed line
+                    let tree = self.displayed_tree();
+                    let current = tree.options.max_depth.unwrap_or_else(|| {
+                        tree.lines.iter().map(|line| line.depth).max().unwrap_or(1)
+                    });
+                    match adjusted_depth(current, arg) {
+                        Ok(depth) => self.with_new_options(
+                            screen,
+                            &|o| o.max_depth = Some(depth),
+                            bang,
+                            con,
+                        ),
+                        Err(e) => AppStateCmdResult::DisplayError(e),
+                    }
+                }
+            },
+            Internal::filter_modified => match get_string_arg(input_invocation, internal_exec) {
+                None => self.with_new_options(screen, &|o| o.modified_range = None, bang, con),
+                Some(arg) => match ModifiedRange::parse(arg, SystemTime::now()) {
+                    Ok(range) => self.with_new_options(
+                        screen,
+                        &|o| o.modified_range = Some(range),
+                        bang,
+                        con,
+                    ),
+                    Err(e) => AppStateCmdResult::DisplayError(e),
+                },
+            },
+            Internal::duplicate => {
+                let path = self.displayed_tree().selected_line().path.clone();
+                match path::duplicate(&path) {
+                    Ok(new_path) => {
+                        journal::record(journal::Operation::Copy {
+                            from: path,
+                            to: new_path.clone(),
+                        });
+                        self.refresh(screen, con);
+                        let page_height = BrowserState::page_height(screen);
+                        let tree = self.displayed_tree_mut();
+                        if tree.try_select_path(&new_path) {
+                            tree.make_selection_visible(page_height);
+                        }
+                        AppStateCmdResult::Keep
+                    }
+                    Err(e) => AppStateCmdResult::DisplayError(
+                        format!("duplication failed: {}", e),
+                    ),
+                }
+            }
+            Internal::first | Internal::last => {
+                let page_height = BrowserState::page_height(screen);
+                let last = internal_exec.internal == Internal::last;
+                self.displayed_tree_mut().try_select_in_dir(last, page_height);
+                AppStateCmdResult::Keep
+            }
+            Internal::goto_newest => internal_newest::goto_newest(
+                self.root(),
+                screen,
+                self.tree.options.without_pattern(),
+                bang,
+                con,
+            ),
+            Internal::select_largest | Internal::select_smallest => {
+                let page_height = BrowserState::page_height(screen);
+                let largest = internal_exec.internal == Internal::select_largest;
+                if self.displayed_tree_mut().try_select_by_size(largest, page_height) {
+                    AppStateCmdResult::Keep
+                } else {
+                    AppStateCmdResult::DisplayError("no file in the current directory".to_string())
+                }
+            }
+            Internal::toggle_case => {
+                let pattern = self.current_pattern();
+                if pattern.is_some() {
+                    match pattern.with_toggled_case(con) {
+                        Ok(pat) => {
+                            self.pending_pattern = pat;
+                            AppStateCmdResult::Keep
+                        }
+                        Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+                    }
+                } else {
+                    AppStateCmdResult::DisplayError("no search to toggle".to_string())
+                }
+            }
+            Internal::batch_rename => {
+                let paths: Vec<PathBuf> = if cc.stage.is_empty() {
+                    self.displayed_tree()
+                        .lines
+                        .iter()
+                        .skip(1) // the root can't be renamed here
+                        .filter(|line| line.is_selectable())
+                        .map(|line| line.path.clone())
+                        .collect()
+                } else {
+                    cc.stage.paths().to_vec()
+                };
+                internal_batch_rename::batch_rename(w, &paths, self.root())?
+            }
+            Internal::stage_content_matches => {
+                match get_string_arg(input_invocation, internal_exec) {
+                    Some(pattern) => {
+                        let needle = Needle::new(pattern);
+                        let result = time!(
+                            Debug,
+                            "subtree content search",
+                            search_subtree(
+                                self.root(),
+                                &needle,
+                                &self.displayed_tree().options,
+                                SubtreeSearchLimits::default(),
+                            ),
+                        );
+                        if !result.complete {
+                            warn!("subtree content search stopped by limits");
+                        }
+                        AppStateCmdResult::AddToStage(result.paths)
+                    }
+                    None => AppStateCmdResult::DisplayError(
+                        "a pattern to search is needed".to_string()
+                    ),
+                }
+            }
             Internal::start_end_panel => {
                 if cc.panel_purpose.is_arg_edition() {
                     debug!("start_end understood as end");
@@ -467,6 +663,7 @@ impl AppState for BrowserState {
         ssb.is_filtered = self.filtered_tree.is_some();
         ssb.has_removed_pattern = false;
         ssb.on_tree_root = self.displayed_tree().selection == 0;
+        ssb.sort = self.tree.options.sort;
         ssb.status()
     }
 
@@ -507,6 +704,10 @@ impl AppState for BrowserState {
             let root_path = self.displayed_tree().root();
             let git_status = git::get_tree_status(root_path, dam);
             self.displayed_tree_mut().git_status = git_status;
+        } else if self.displayed_tree().is_missing_annotations() && con.column_provider.is_some() {
+            if let Some(provider) = &con.column_provider {
+                self.displayed_tree_mut().fetch_annotations(provider, dam);
+            }
         } else {
             self.displayed_tree_mut().fetch_some_missing_dir_sum(dam);
         }
@@ -518,6 +719,7 @@ impl AppState for BrowserState {
         _screen: Screen,
         area: Area,
         panel_skin: &PanelSkin,
+        stage: &Stage,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
         let dp = DisplayableTree {
@@ -526,6 +728,7 @@ impl AppState for BrowserState {
             ext_colors: &con.ext_colors,
             area,
             in_app: true,
+            stage: Some(stage),
         };
         dp.write_on(w)
     }
@@ -548,6 +751,15 @@ impl AppState for BrowserState {
         })
     }
 
+    fn watched_dirs(&self) -> Vec<PathBuf> {
+        self.displayed_tree()
+            .lines
+            .iter()
+            .filter(|line| line.is_dir())
+            .map(|line| line.path.clone())
+            .collect()
+    }
+
     fn get_flags(&self) -> Vec<Flag> {
         let options = &self.displayed_tree().options;
         vec![
@@ -563,11 +775,93 @@ impl AppState for BrowserState {
     }
 
     fn get_starting_input(&self) -> String {
-        if self.pending_pattern.is_some() {
-            self.pending_pattern.raw.clone()
-        } else {
-            self.displayed_tree().options.pattern.raw.clone()
-        }
+        self.current_pattern().raw.clone()
+    }
+
+    fn tree_root(&self) -> Option<&Path> {
+        Some(self.root())
+    }
+
+    fn match_count(&self) -> usize {
+        self.displayed_tree().match_count()
+    }
+
+    fn search_mode(&self) -> Option<SearchMode> {
+        self.displayed_tree().options.pattern.pattern.search_mode()
     }
 }
 
+
+#[cfg(test)]
+mod browser_state_tests {
+
+    use {
+        super::*,
+        std::fs,
+    };
+
+    fn context(root: &Path) -> AppContext {
+        AppContext::for_test(root)
+    }
+
+    #[test]
+    fn test_selection_follows_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for name in &["a.jpg", "photo.jpg", "z.jpg"] {
+            fs::write(root.join(name), "").unwrap();
+        }
+        let con = context(root);
+        let screen = Screen { width: 80, height: 40 };
+        let mut state = BrowserState::new(
+            root.to_path_buf(),
+            TreeOptions::default(),
+            screen,
+            &con,
+            &Dam::unlimited(),
+        ).unwrap().unwrap();
+        let input = root.join("photo.jpg");
+        assert!(state.try_select_path(&input, screen));
+        // a missing output isn't selected
+        let output = root.join("photo.png");
+        assert!(!state.try_select_path(&output, screen));
+        assert_eq!(state.selected_path(), input);
+        // once produced, and the tree refreshed, the output is selected
+        fs::write(&output, "").unwrap();
+        state.refresh(screen, &con);
+        assert_eq!(state.selected_path(), input);
+        assert!(state.try_select_path(&output, screen));
+        assert_eq!(state.selected_path(), output);
+    }
+
+    #[test]
+    fn test_clone_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("src")).unwrap();
+        for name in &["a.txt", "b.txt", "src/main.rs"] {
+            fs::write(root.join(name), "").unwrap();
+        }
+        let con = context(root);
+        let screen = Screen { width: 80, height: 40 };
+        let mut state = BrowserState::new(
+            root.to_path_buf(),
+            TreeOptions::default(),
+            screen,
+            &con,
+            &Dam::unlimited(),
+        ).unwrap().unwrap();
+        let selected = root.join("b.txt");
+        assert!(state.try_select_path(&selected, screen));
+        let mut copy = state.clone_state().unwrap();
+        assert_eq!(copy.tree_root(), Some(root));
+        assert_eq!(copy.selected_path(), selected);
+        assert_eq!(copy.tree_options().show_hidden, state.tree_options().show_hidden);
+        // changing the copy doesn't change the original
+        let other = root.join("src/main.rs");
+        assert!(copy.try_select_path(&other, screen));
+        assert_eq!(copy.selected_path(), other);
+        assert_eq!(state.selected_path(), selected);
+        assert_eq!(state.tree_root(), Some(root));
+    }
+}
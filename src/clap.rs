@@ -177,6 +177,19 @@ pub fn clap_app() -> clap::App<'static, 'static> {
                 .takes_value(true)
                 .help("Where to write the produced path (if any)"),
         )
+        .arg(
+            clap::Arg::with_name("resume")
+                .long("resume")
+                .help("Restore the panels, with their patterns and options, of the last session"),
+        )
+        .arg(
+            clap::Arg::with_name("output-format")
+                .long("output-format")
+                .takes_value(true)
+                .value_name("format")
+                .possible_values(&["tree", "json", "paths0"])
+                .help("Format of the tree printed on :print_tree"),
+        )
         .arg(
             clap::Arg::with_name("install")
                 .long("install")
@@ -216,6 +229,11 @@ pub fn clap_app() -> clap::App<'static, 'static> {
             .long("get-root")
             .help("Ask for the current root of the remote broot")
         )
+        .arg(
+            clap::Arg::with_name("get-selection")
+            .long("get-selection")
+            .help("Ask for the current selection of the remote broot")
+        )
         .arg(
             clap::Arg::with_name("send")
             .long("send")
@@ -2,11 +2,13 @@
 /// the arguments passed on launch of the application.
 use {
     crate::{
-        app::{App, AppContext},
+        app::{App, AppContext, Session, LAST_SESSION},
         conf::Conf,
         display,
         errors::{ProgramError, TreeBuildError},
+        file_sum,
         launchable::Launchable,
+        print::OutputFormat,
         shell_install::{ShellInstall, ShellInstallState},
         tree::TreeOptions,
         verb::VerbStore,
@@ -77,6 +79,7 @@ pub struct AppLaunchArgs {
     pub commands: Option<String>,         // commands passed as cli argument, still unparsed
     pub height: Option<u16>,              // an optional height to replace the screen's one
     pub no_style: bool,                   // whether to remove all styles (including colors)
+    pub output_format: OutputFormat,      // the format of the trees printed with :print_tree
 
     #[cfg(feature = "client-server")]
     pub listen: Option<String>,
@@ -190,6 +193,13 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
     let commands = cli_matches.value_of("commands").map(str::to_string);
     let no_style = cli_matches.is_present("no-style");
     let height = cli_matches.value_of("height").and_then(|s| s.parse().ok());
+    let output_format = match cli_matches.value_of("output-format") {
+        Some(s) => s.parse().map_err(|_| ProgramError::ArgParse {
+            bad: s.to_string(),
+            valid: "tree, json, paths0".to_string(),
+        })?,
+        None => OutputFormat::default(),
+    };
 
     let root = get_root_path(&cli_matches)?;
 
@@ -203,13 +213,16 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
         if let Some(seq) = &commands {
             let message = Message::Sequence(Sequence::new_local(seq.to_string()));
             client.send(&message)?;
-        } else if !cli_matches.is_present("get-root") {
+        } else if !cli_matches.is_present("get-root") && !cli_matches.is_present("get-selection") {
             let message = Message::Command(format!(":focus {}", root.to_string_lossy()));
             client.send(&message)?;
         };
         if cli_matches.is_present("get-root") {
             client.send(&Message::GetRoot)?;
         }
+        if cli_matches.is_present("get-selection") {
+            client.send(&Message::GetSelection)?;
+        }
         return Ok(None);
     }
 
@@ -221,21 +234,34 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
         commands,
         height,
         no_style,
+        output_format,
 
         #[cfg(feature = "client-server")]
         listen: cli_matches.value_of("listen").map(str::to_string),
     };
 
-    let context = AppContext::from(launch_args, verb_store, &config)?;
+    let mut context = AppContext::from(launch_args, verb_store, &config)?;
+    if config.persist_sizes == Some(true) {
+        file_sum::load_dir_cache();
+    }
     let mut w = display::writer();
-    let app = App::new(&context)?;
+    let mut app = App::new(&context)?;
+    if cli_matches.is_present("resume") {
+        // the launch root is kept when the session can't be restored
+        let resumed = Session::load(LAST_SESSION)
+            .and_then(|session| app.apply_session(&session, &context));
+        if let Err(e) = resumed {
+            warn!("last session not restored: {}", e);
+        }
+    }
     w.queue(EnterAlternateScreen)?;
     w.queue(cursor::Hide)?;
     let capture_mouse = config.disable_mouse_capture != Some(true);
     if capture_mouse {
         w.queue(EnableMouseCapture)?;
     }
-    let r = app.run(&mut w, &context, &config);
+    let r = app.run(&mut w, &mut context, &config);
+    file_sum::save_dir_cache();
     if capture_mouse {
         w.queue(DisableMouseCapture)?;
     }
@@ -0,0 +1,86 @@
+//! copy strings to the clipboard, either the local one or, when
+//! there's none (e.g. in a SSH session), the one of the terminal
+//! with an OSC 52 escape sequence
+
+use {
+    std::{
+        env,
+        io::{self, Write},
+    },
+};
+
+/// tell whether broot runs on a remote server, where the
+/// local clipboard wouldn't be the one of the user
+fn is_remote_session() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .iter()
+        .any(|var| env::var_os(var).is_some())
+}
+
+fn copy_local(s: &str) -> Result<(), String> {
+    #[cfg(feature = "clipboard")]
+    {
+        terminal_clipboard::set_string(s)
+            .map_err(|_| "Clipboard error".to_string())
+    }
+    #[cfg(not(feature = "clipboard"))]
+    {
+        let _ = s;
+        Err("Clipboard feature not enabled at compilation".to_string())
+    }
+}
+
+/// build the OSC 52 sequence setting the clipboard of the terminal.
+///
+/// In tmux, the sequence is wrapped so that it's passed to the
+/// terminal (tmux must have `allow-passthrough` on).
+fn osc52_sequence(s: &str, in_tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64::encode(s));
+    if in_tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+fn copy_osc52(s: &str) -> io::Result<()> {
+    let sequence = osc52_sequence(s, env::var_os("TMUX").is_some());
+    let mut stderr = io::stderr();
+    stderr.write_all(sequence.as_bytes())?;
+    stderr.flush()
+}
+
+/// copy the string to the clipboard.
+///
+/// `osc52` tells whether the terminal's clipboard must be used: when
+/// it's None, it's used in SSH sessions, and when the local
+/// clipboard fails.
+pub fn copy(s: &str, osc52: Option<bool>) -> Result<(), String> {
+    let osc52_first = osc52.unwrap_or_else(is_remote_session);
+    if !osc52_first {
+        match copy_local(s) {
+            Ok(()) => return Ok(()),
+            Err(e) if osc52 == Some(false) => return Err(e),
+            Err(e) => debug!("{}, trying OSC 52", e),
+        }
+    }
+    copy_osc52(s).map_err(|e| format!("Clipboard error: {}", e))
+}
+
+#[cfg(test)]
+mod clipboard_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("/home/dys/dev", false),
+            "\x1b]52;c;L2hvbWUvZHlzL2Rldg==\x07",
+        );
+        assert_eq!(
+            osc52_sequence("/home/dys/dev", true),
+            "\x1bPtmux;\x1b\x1b]52;c;L2hvbWUvZHlzL2Rldg==\x07\x1b\\",
+        );
+    }
+}
@@ -6,8 +6,9 @@ use {
             AppContext,
             Selection,
         },
+        bookmark::Bookmarks,
         path::{self, PathAnchor},
-        verb::PrefixSearchResult,
+        verb::{Internal, PrefixSearchResult},
     },
     std::io,
 };
@@ -81,6 +82,10 @@ impl Completions {
     ) -> Self {
         match con.verb_store.search(start, Some(sel.stype)) {
             PrefixSearchResult::NoMatch => Self::None,
+            PrefixSearchResult::Match(name, _) if !name.starts_with(start) => {
+                // a fuzzy match can't be completed
+                Self::None
+            }
             PrefixSearchResult::Match(name, _) => {
                 if start.len() >= name.len() {
                     debug_assert!(name == start);
@@ -91,7 +96,7 @@ impl Completions {
             }
             PrefixSearchResult::Matches(completions) => Self::for_wholes(
                 start,
-                completions,
+                completions.into_iter().filter(|c| c.starts_with(start)).collect(),
             ),
         }
     }
@@ -129,22 +134,37 @@ impl Completions {
         Ok(Self::from_list(children))
     }
 
+    fn for_bookmark(start: &str) -> Self {
+        match Bookmarks::load() {
+            Ok(bookmarks) => Self::for_wholes(
+                start,
+                bookmarks.names_starting_with(start).collect(),
+            ),
+            Err(e) => {
+                warn!("Error while reading bookmarks: {:?}", e);
+                Self::None
+            }
+        }
+    }
+
     fn for_arg(
         verb_name: &str,
         arg: &str,
         con: &AppContext,
         sel: Selection<'_>,
     ) -> Self {
-        // in the future we might offer completion of other types
-        // of arguments, maybe user supplied, but there's no use case
-        // now so we'll just assume the user wants to complete a path.
+        // besides the names of bookmarks, only paths are completed
         if arg.contains(' ') {
             Self::None
         } else {
-            let anchor = match con.verb_store.search(verb_name, Some(sel.stype)) {
-                PrefixSearchResult::Match(_, verb) => verb.get_arg_anchor(),
-                _ => PathAnchor::Unspecified,
+            let verb = match con.verb_store.search(verb_name, Some(sel.stype)) {
+                PrefixSearchResult::Match(_, verb) => Some(verb),
+                _ => None,
             };
+            if verb.and_then(|verb| verb.get_internal()) == Some(Internal::jump) {
+                return Self::for_bookmark(arg);
+            }
+            let anchor = verb.map_or(PathAnchor::Unspecified, |verb| verb.get_arg_anchor());
             match Self::for_path(anchor, arg, con, sel) {
                 Ok(c) => c,
                 Err(e) => {
@@ -155,6 +175,17 @@ impl Completions {
         }
     }
 
+    fn has_path_arg(
+        verb_name: &str,
+        con: &AppContext,
+        sel: Selection<'_>,
+    ) -> bool {
+        match con.verb_store.search(verb_name, Some(sel.stype)) {
+            PrefixSearchResult::Match(_, verb) => verb.has_path_arg(),
+            _ => false,
+        }
+    }
+
     pub fn for_input(
         parts: &CommandParts,
         con: &AppContext,
@@ -171,6 +202,11 @@ impl Completions {
                         // looking into arg completion
                         Self::for_arg(&invocation.name, args, con, sel)
                     }
+                    Some(_) if Self::has_path_arg(&invocation.name, con, sel) => {
+                        // the argument isn't started but it's a path,
+                        // we propose the children of its anchor
+                        Self::for_arg(&invocation.name, "", con, sel)
+                    }
                     _ => {
                         // nothing possible
                         Self::None
@@ -1,13 +1,14 @@
 use {
     super::*,
     crate::{
-        app::{AppContext, Selection},
+        app::{AppContext, Selection, INPUT_MODE},
         display::W,
         errors::ProgramError,
         keys,
         skin::PanelSkin,
         verb::{Internal, Verb, VerbExecution},
     },
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
     termimad::{Area, Event, InputField},
 };
 
@@ -17,15 +18,19 @@ pub struct PanelInput {
     pub input_field: InputField,
     tab_cycle_count: usize,
     input_before_cycle: Option<String>,
+    /// the key mode, which restricts the verbs triggered by keys
+    /// and, when it's not the input one, prevents typing
+    pub mode: String,
 }
 
 impl PanelInput {
 
-    pub fn new(area: Area) -> Self {
+    pub fn new(area: Area, mode: &str) -> Self {
         Self {
             input_field: InputField::new(area),
             tab_cycle_count: 0,
             input_before_cycle: None,
+            mode: mode.to_string(),
         }
     }
 
@@ -139,6 +144,12 @@ impl PanelInput {
                 // we first handle the cases that MUST absolutely
                 // not be overriden by configuration
 
+                if key == keys::ESC && self.mode != con.initial_mode {
+                    // like in vim, esc first only goes back to the initial mode
+                    self.mode = con.initial_mode.clone();
+                    return Command::None;
+                }
+
                 if key == keys::ESC {
                     self.tab_cycle_count = 0;
                     if let Some(raw) = self.input_before_cycle.take() {
@@ -219,12 +230,22 @@ impl PanelInput {
 
                 // we now check if the key is the trigger key of one of the verbs
                 for (index, verb) in con.verb_store.verbs.iter().enumerate() {
+                    if verb.mode.as_ref().is_some_and(|mode| *mode != self.mode) {
+                        continue;
+                    }
                     for verb_key in &verb.keys {
                         if *verb_key == key {
                             if self.handle_input_related_verb(verb, con) {
                                 return Command::from_raw(self.input_field.get_content(), false);
                             }
                             if sel.stype.respects(verb.selection_condition) {
+                                if verb.needs_args_prompt(parts.verb_invocation.as_ref()) {
+                                    // the arguments are typed in the input, with
+                                    // the usual completion, before execution
+                                    let raw = format!("{}:{} ", parts.raw_pattern, verb.names[0]);
+                                    self.input_field.set_content(&raw);
+                                    return Command::from_raw(raw, false);
+                                }
                                 return Command::VerbTrigger {
                                     index,
                                     input_invocation: parts.verb_invocation,
@@ -251,6 +272,20 @@ impl PanelInput {
                     };
                 }
 
+                // out of the input mode, chars aren't typed, except the ones
+                // starting a verb or a regex pattern, which switch to it
+                if self.mode != INPUT_MODE {
+                    if let KeyEvent { code: KeyCode::Char(c), modifiers } = key {
+                        if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+                            return Command::None;
+                        }
+                        if c != ':' && c != ' ' && c != '/' {
+                            return Command::None;
+                        }
+                        self.mode = INPUT_MODE.to_string();
+                    }
+                }
+
                 // input field management
                 if self.input_field.apply_event(&event) {
                     return Command::from_raw(self.input_field.get_content(), false);
@@ -6,6 +6,7 @@ use {
     crate::{
         display::ColsConf,
         errors::ProgramError,
+        provider::CustomColumnConf,
         skin::SkinEntry,
         tree::*,
     },
@@ -56,6 +57,15 @@ pub struct Conf {
     pub syntax_theme: Option<String>,
     pub true_colors: Option<bool>,
     pub icon_theme: Option<String>,
+    pub verb_log: Option<String>,
+    pub fuzzy_verb_search: Option<bool>,
+    pub watch: Option<bool>,
+    pub initial_mode: Option<String>,
+    pub persist_sizes: Option<bool>,
+    #[serde(default)]
+    pub verb_providers: Vec<String>,
+    pub custom_column: Option<CustomColumnConf>,
+    pub osc52_clipboard: Option<bool>,
 }
 
 impl Conf {
@@ -116,7 +126,20 @@ impl Conf {
         overwrite!(self, cols_order, conf);
         overwrite!(self, skin, conf);
         overwrite!(self, search_modes, conf);
+        overwrite!(self, verb_log, conf);
+        overwrite!(self, fuzzy_verb_search, conf);
+        overwrite!(self, watch, conf);
+        overwrite!(self, initial_mode, conf);
+        overwrite!(self, persist_sizes, conf);
+        overwrite!(self, custom_column, conf);
+        overwrite!(self, osc52_clipboard, conf);
+        self.verb_providers.append(&mut conf.verb_providers);
         self.verbs.append(&mut conf.verbs);
+        if let Some(verbs_dir) = path.parent().map(|dir| dir.join(VERBS_DIR_NAME)) {
+            if verbs_dir.is_dir() {
+                self.read_verbs_dir(&verbs_dir)?;
+            }
+        }
         // the following maps are "additive": we can add entries from several
         // config files and they still make sense
         overwrite_map!(self, special_paths, conf);
@@ -124,5 +147,115 @@ impl Conf {
         self.files.push(path);
         Ok(())
     }
+
+    /// read the verbs of all the toml files of a verbs.d directory,
+    /// in the lexical order of their names. A verb replaces the
+    /// previously read one with the same name.
+    pub fn read_verbs_dir(&mut self, dir: &Path) -> Result<(), ProgramError> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("toml"))
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+        for path in paths {
+            let file_content = fs::read_to_string(&path)?;
+            let verbs_file = toml::from_str::<VerbsFile>(&file_content)
+                .map_err(|e| ProgramError::ConfFile {
+                    path: path.to_string_lossy().to_string(),
+                    details: e.into(),
+                })?;
+            for vc in verbs_file.verbs {
+                self.add_verb(vc);
+            }
+        }
+        Ok(())
+    }
+
+    /// add a verb, replacing the one with the same name if any
+    fn add_verb(&mut self, vc: VerbConf) {
+        if let Some(name) = vc.name() {
+            if let Some(idx) = self.verbs.iter().position(|v| v.name().as_ref() == Some(&name)) {
+                self.verbs[idx] = vc;
+                return;
+            }
+        }
+        self.verbs.push(vc);
+    }
+}
+
+/// name of the directory, next to a configuration file, whose
+/// toml files may define additional verbs
+pub const VERBS_DIR_NAME: &str = "verbs.d";
+
+/// the content of a file of the verbs.d directory
+#[derive(Deserialize)]
+struct VerbsFile {
+    #[serde(default)]
+    verbs: Vec<VerbConf>,
+}
+
+#[cfg(test)]
+mod conf_tests {
+
+    use super::*;
+
+    fn verb_names(conf: &Conf) -> Vec<String> {
+        conf.verbs.iter().filter_map(|vc| vc.name()).collect()
+    }
+
+    #[test]
+    fn test_verbs_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let conf_path = dir.path().join("conf.toml");
+        fs::write(&conf_path, r#"
+            [[verbs]]
+            invocation = "edit"
+            execution = "vi {file}"
+        "#).unwrap();
+        let verbs_dir = dir.path().join(VERBS_DIR_NAME);
+        fs::create_dir(&verbs_dir).unwrap();
+        // files are read in lexical order, whatever their creation order
+        fs::write(verbs_dir.join("20-git.toml"), r#"
+            [[verbs]]
+            invocation = "gs"
+            execution = "git status"
+            [[verbs]]
+            invocation = "edit"
+            execution = "emacs {file}"
+        "#).unwrap();
+        fs::write(verbs_dir.join("10-base.toml"), r#"
+            [[verbs]]
+            invocation = "gs"
+            execution = "git show"
+            [[verbs]]
+            invocation = "touch {new_file}"
+            execution = "touch {directory}/{new_file}"
+        "#).unwrap();
+        fs::write(verbs_dir.join("notes.txt"), "not a verbs file").unwrap();
+        let mut conf = Conf::default();
+        conf.read_file(conf_path).unwrap();
+        assert_eq!(verb_names(&conf), vec!["edit", "gs", "touch"]);
+        let desc = format!("{:?}", conf.verbs);
+        assert!(desc.contains("emacs {file}"));
+        assert!(!desc.contains("vi {file}"));
+        assert!(desc.contains("git status"));
+        assert!(!desc.contains("git show"));
+        // the directory's files aren't listed as configuration files
+        assert_eq!(conf.files.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_verbs_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let conf_path = dir.path().join("conf.toml");
+        fs::write(&conf_path, "verbs = []").unwrap();
+        let verbs_dir = dir.path().join(VERBS_DIR_NAME);
+        fs::create_dir(&verbs_dir).unwrap();
+        fs::write(verbs_dir.join("bad.toml"), "[[verbs]\ninvocation").unwrap();
+        let mut conf = Conf::default();
+        assert!(conf.read_file(conf_path).is_err());
+    }
 }
 
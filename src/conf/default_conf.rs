@@ -44,6 +44,45 @@ default_flags = ""
 #
 # show_selection_mark = true
 
+###############################################################
+# Whether to refresh the trees when files are created, removed
+# or renamed in the displayed directories (this can be toggled
+# with :toggle_watch)
+#
+# watch = true
+
+###############################################################
+# The key mode in which broot starts. In modes other than
+# "input", typed chars don't go to the input and trigger the
+# verbs defined with this mode (see :mode)
+#
+# initial_mode = "normal"
+
+###############################################################
+# Whether to keep the sizes of the directories between sessions,
+# so that only the changed directories are read again
+#
+# persist_sizes = true
+
+###############################################################
+# Commands writing [[verbs]] sections, read at launch
+#
+# verb_providers = ["project-tool broot-verbs"]
+
+###############################################################
+# A command annotating the paths, which it receives on its
+# standard input, by writing lines made of the path, a tab, and
+# the annotation, displayed in a custom column
+#
+# custom_column = { command = "project-tool annotate" }
+
+###############################################################
+# Whether to copy to the terminal's clipboard with OSC 52 (true),
+# never (false). When not set, it's done in SSH sessions and
+# when the local clipboard isn't available
+#
+# osc52_clipboard = true
+
 ###############################################################
 # Column order
 # cols_order, if specified, must be a permutation of the following
@@ -223,6 +262,7 @@ leave_broot = false
 # group = "ansi(131) None"
 # count = "ansi(136) gray(3)"
 # dates = "ansi(66) None"
+# annotation = "ansi(110) None"
 # sparse = "ansi(214) None"
 # content_extract = "ansi(29) None"
 # content_match = "ansi(34) None"
@@ -236,6 +276,7 @@ leave_broot = false
 # git_status_conflicted = "ansi(88) None"
 # git_status_other = "ansi(88) None"
 # selected_line = "None gray(5) / None gray(4)"
+# staged_marker = "ansi(178) None Bold"
 # char_match = "Yellow None"
 # file_error = "Red None"
 # flag_label = "gray(15) None"
@@ -243,6 +284,7 @@ leave_broot = false
 # input = "White None / gray(15) gray(2)"
 # status_error = "gray(22) ansi(124)"
 # status_job = "ansi(220) gray(5)"
+# status_mode = "gray(2) ansi(178) Bold"
 # status_normal = "gray(20) gray(3) / gray(2) gray(2)"
 # status_italic = "ansi(208) gray(3) / gray(2) gray(2)"
 # status_bold = "ansi(208) gray(3) Bold / gray(2) gray(2)"
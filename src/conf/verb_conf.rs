@@ -3,13 +3,30 @@ use {
         app::SelectionType,
         keys,
         command::Sequence,
+        launchable::StdinMode,
+        path::{self, PathAnchor, ShellEscaping},
         errors::ConfError,
         verb::*,
+        yank::ConflictPolicy,
     },
+    fnv::FnvHashMap,
     serde::Deserialize,
-    std::convert::TryFrom,
+    std::{
+        convert::TryFrom,
+        time::Duration,
+    },
 };
 
+/// the execution of a verb in the configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ExecutionConf {
+    /// an internal (starting with `:`) or an external
+    Single(String),
+    /// internals and externals executed in order
+    List(Vec<String>),
+}
+
 /// a deserializable verb entry in the configuration
 #[derive(Default, Debug, Clone, Deserialize)]
 pub struct VerbConf {
@@ -20,7 +37,15 @@ pub struct VerbConf {
 
     external: Option<String>,
 
-    execution: Option<String>,
+    toggle_external: Option<String>,
+
+    file_external: Option<String>,
+
+    directory_external: Option<String>,
+
+    execution: Option<ExecutionConf>,
+
+    stop_on_error: Option<bool>,
 
     cmd: Option<String>,
 
@@ -30,18 +55,77 @@ pub struct VerbConf {
 
     shortcut: Option<String>,
 
+    mode: Option<String>,
+
     leave_broot: Option<bool>,
 
     from_shell: Option<bool>,
 
+    background: Option<bool>,
+
+    capture_output: Option<bool>,
+
+    show_progress: Option<bool>,
+
+    select_output: Option<String>,
+
+    restage_modified: Option<bool>,
+
+    login_shell: Option<bool>,
+
+    stdin: Option<String>,
+
+    on_conflict: Option<String>,
+
     apply_to: Option<String>,
 
     set_working_dir: Option<bool>,
 
+    ensure_working_dir: Option<String>,
+
+    tail_in_panel: Option<bool>,
+
+    output_to_clipboard: Option<bool>,
+
+    output_to_filter: Option<bool>,
+
+    skip_on_network_fs: Option<bool>,
+
+    require_foreground: Option<bool>,
+
+    confirm_typing: Option<bool>,
+
+    stable_selection: Option<bool>,
+
+    batch_size: Option<usize>,
+
+    debounce_ms: Option<u64>,
+
+    log_file: Option<String>,
+
+    shell_escaping: Option<String>,
+
+    min_size: Option<String>,
+
+    max_size: Option<String>,
+
+    clean_env: Option<bool>,
+
+    env: Option<FnvHashMap<String, String>>,
+
     description: Option<String>,
 
 }
 
+impl VerbConf {
+    /// the name under which the verb is invoked, if it has an invocation
+    pub fn name(&self) -> Option<String> {
+        self.invocation.as_deref()
+            .map(|inv| VerbInvocation::from(inv).name)
+            .filter(|name| !name.is_empty())
+    }
+}
+
 /// read a deserialized verb conf item into a verb,
 /// checking a few basic things in the process
 impl TryFrom<&VerbConf> for Verb {
@@ -52,52 +136,307 @@ impl TryFrom<&VerbConf> for Verb {
                 details: "You can't simultaneously have leave_broot=false and from_shell=true".to_string(),
             });
         }
+        if matches!(vc.execution, Some(ExecutionConf::List(_)))
+            && (
+                vc.leave_broot == Some(true) || vc.from_shell == Some(true)
+                || vc.background == Some(true) || vc.tail_in_panel == Some(true)
+                || vc.output_to_clipboard == Some(true) || vc.output_to_filter == Some(true)
+                || vc.select_output.is_some() || vc.restage_modified == Some(true)
+                || vc.batch_size.is_some() || vc.capture_output == Some(true)
+            )
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with an execution list can't leave broot, be executed from the shell or in background, be tailed or batched, use the output of its commands, or restage".to_string(),
+            });
+        }
+        if vc.stop_on_error.is_some() && !matches!(vc.execution, Some(ExecutionConf::List(_))) {
+            return Err(ConfError::InvalidVerbConf {
+                details: "stop_on_error needs an execution list".to_string(),
+            });
+        }
+        if vc.tail_in_panel == Some(true) && (vc.leave_broot == Some(true) || vc.from_shell == Some(true)) {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with tail_in_panel=true can't leave broot".to_string(),
+            });
+        }
+        if vc.output_to_clipboard == Some(true)
+            && (vc.leave_broot != Some(false) || vc.tail_in_panel == Some(true) || vc.batch_size.is_some())
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with output_to_clipboard=true must have leave_broot=false and can't be tailed or batched".to_string(),
+            });
+        }
+        if vc.output_to_filter == Some(true)
+            && (
+                vc.leave_broot != Some(false) || vc.from_shell == Some(true)
+                || vc.tail_in_panel == Some(true) || vc.background == Some(true)
+                || vc.output_to_clipboard == Some(true) || vc.batch_size.is_some()
+            )
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with output_to_filter=true must have leave_broot=false and can't be executed from the shell, in background, tailed, batched or copy its output".to_string(),
+            });
+        }
+        if let Some(batch_size) = vc.batch_size {
+            if batch_size == 0 {
+                return Err(ConfError::InvalidVerbConf {
+                    details: "batch_size can't be 0".to_string(),
+                });
+            }
+            // batches are only executed by broot, waiting for each one
+            if vc.leave_broot != Some(false) || vc.from_shell == Some(true)
+                || vc.background == Some(true) || vc.tail_in_panel == Some(true)
+                || vc.output_to_clipboard == Some(true) || vc.output_to_filter == Some(true)
+            {
+                return Err(ConfError::InvalidVerbConf {
+                    details: "A verb with a batch_size must have leave_broot=false and can't be executed from the shell, in background, tailed, or copy or filter on its output".to_string(),
+                });
+            }
+        }
+        if vc.ensure_working_dir.is_some() && vc.from_shell == Some(true) {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb executed from the shell can't have an ensure_working_dir".to_string(),
+            });
+        }
+        if vc.background == Some(true)
+            && (
+                vc.leave_broot == Some(true) || vc.from_shell == Some(true) || vc.tail_in_panel == Some(true)
+                || vc.output_to_clipboard == Some(true) || vc.batch_size.is_some()
+            )
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with background=true can't leave broot, be tailed, batched or copy its output".to_string(),
+            });
+        }
+        if vc.capture_output == Some(true)
+            && (
+                vc.leave_broot == Some(true) || vc.from_shell == Some(true)
+                || vc.background == Some(true) || vc.tail_in_panel == Some(true)
+                || vc.output_to_clipboard == Some(true) || vc.output_to_filter == Some(true)
+                || vc.select_output.is_some() || vc.restage_modified == Some(true)
+                || vc.batch_size.is_some()
+            )
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with capture_output=true can't leave broot, be executed from the shell or in background, be tailed or batched, or use its output otherwise".to_string(),
+            });
+        }
+        if vc.show_progress == Some(true) && vc.background != Some(true) {
+            return Err(ConfError::InvalidVerbConf {
+                details: "show_progress needs background=true".to_string(),
+            });
+        }
+        if vc.select_output.is_some()
+            && (vc.leave_broot != Some(false) || vc.background == Some(true) || vc.tail_in_panel == Some(true))
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with a select_output must have leave_broot=false and can't be in background or tailed".to_string(),
+            });
+        }
+        if vc.restage_modified == Some(true)
+            && (
+                vc.leave_broot != Some(false) || vc.from_shell == Some(true)
+                || vc.background == Some(true) || vc.tail_in_panel == Some(true)
+                || vc.output_to_clipboard == Some(true) || vc.select_output.is_some()
+            )
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with restage_modified=true must have leave_broot=false and can't be in background, tailed, copy its output or have a select_output".to_string(),
+            });
+        }
+        if vc.login_shell == Some(true) && (vc.external.is_none() || vc.from_shell == Some(true)) {
+            return Err(ConfError::InvalidVerbConf {
+                details: "login_shell needs an external and can't be used with from_shell".to_string(),
+            });
+        }
+        if vc.stdin.is_some()
+            && (
+                vc.from_shell == Some(true) || vc.background == Some(true)
+                || vc.tail_in_panel == Some(true) || vc.output_to_clipboard == Some(true)
+                || vc.output_to_filter == Some(true) || vc.capture_output == Some(true)
+            )
+        {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with a stdin can't be executed from the shell, in background, tailed, or capture, copy or filter on its output".to_string(),
+            });
+        }
+        if vc.toggle_external.is_some() && vc.external.is_none() {
+            return Err(ConfError::InvalidVerbConf {
+                details: "toggle_external needs an external".to_string(),
+            });
+        }
+        if (vc.file_external.is_some() || vc.directory_external.is_some()) && vc.external.is_none() {
+            return Err(ConfError::InvalidVerbConf {
+                details: "file_external and directory_external need an external".to_string(),
+            });
+        }
+        if (vc.file_external.is_some() || vc.directory_external.is_some()) && vc.toggle_external.is_some() {
+            return Err(ConfError::InvalidVerbConf {
+                details: "A verb with a toggle_external can't have file_external or directory_external".to_string(),
+            });
+        }
         let invocation = vc.invocation.clone().filter(|i| !i.is_empty());
         let internal = vc.internal.as_ref().filter(|i| !i.is_empty());
         let external = vc.external.as_ref().filter(|i| !i.is_empty());
         let cmd = vc.cmd.as_ref().filter(|i| !i.is_empty());
         let cmd_separator = vc.cmd_separator.as_ref().filter(|i| !i.is_empty());
-        let execution = vc.execution.as_ref().filter(|i| !i.is_empty());
+        let (execution, execution_list) = match &vc.execution {
+            Some(ExecutionConf::Single(s)) => (Some(s).filter(|s| !s.is_empty()), None),
+            Some(ExecutionConf::List(list)) => (None, Some(list)),
+            None => (None, None),
+        };
         let key = vc.key.clone().map(|s| keys::parse_key(&s)).transpose()?;
+        let escaping = vc.shell_escaping.as_deref()
+            .map(|name| {
+                ShellEscaping::from_name(name).ok_or_else(|| ConfError::InvalidVerbConf {
+                    details: format!(
+                        "invalid shell_escaping {:?} (expected posix, cmd or powershell)",
+                        name,
+                    ),
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let stdin = vc.stdin.as_deref()
+            .map(|name| {
+                StdinMode::from_name(name).ok_or_else(|| ConfError::InvalidVerbConf {
+                    details: format!("invalid stdin {:?} (expected inherit or null)", name),
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
         let make_external_execution = |s| {
             ExternalExecution::new(
                 s,
-                ExternalExecutionMode::from_conf(vc.from_shell, vc.leave_broot),
+                ExternalExecutionMode::from_conf(
+                    vc.from_shell,
+                    vc.leave_broot,
+                    vc.background,
+                    vc.capture_output,
+                ),
             )
             .with_set_working_dir(vc.set_working_dir)
+            .with_ensure_working_dir(vc.ensure_working_dir.clone().filter(|s| !s.is_empty()))
+            .with_tail_in_panel(vc.tail_in_panel)
+            .with_output_to_clipboard(vc.output_to_clipboard)
+            .with_output_to_filter(vc.output_to_filter)
+            .with_show_progress(vc.show_progress)
+            .with_select_output(vc.select_output.clone().filter(|s| !s.is_empty()))
+            .with_restage_modified(vc.restage_modified)
+            .with_login_shell(vc.login_shell)
+            .with_stdin(stdin)
+            .with_env(vc.clean_env, vc.env.as_ref())
+            .with_batch_size(vc.batch_size)
+            .with_escaping(escaping)
+            .with_log_file(vc.log_file.as_ref().map(|s| path::path_from(super::dir(), PathAnchor::Unspecified, s)))
+            .with_toggle_exec_pattern(vc.toggle_external.clone().filter(|s| !s.is_empty()))
+            .with_stype_exec_pattern(
+                SelectionType::File,
+                vc.file_external.clone().filter(|s| !s.is_empty()),
+            )
+            .with_stype_exec_pattern(
+                SelectionType::Directory,
+                vc.directory_external.clone().filter(|s| !s.is_empty()),
+            )
         };
-        let execution = match (execution, internal, external, cmd) {
-            // old definition with "execution": we guess whether it's an internal or
-            // an external
-            (Some(s), None, None, None) => {
+        let mut execution = if let Some(list) = execution_list {
+            if internal.is_some() || external.is_some() || cmd.is_some() {
+                return Err(ConfError::InvalidVerbConf {
+                    details: "You must define either internal, external or cmd".to_string(),
+                });
+            }
+            let mut steps = Vec::new();
+            for s in list {
                 if s.starts_with(':') || s.starts_with(' ') {
-                    VerbExecution::Internal(InternalExecution::try_from(&s[1..])?)
+                    // a chain of internals gives a step per internal
+                    let internal_exec = InternalExecution::try_from(&s[1..])?;
+                    steps.extend(internal_exec.steps().map(|step| {
+                        VerbExecution::Internal(InternalExecution { then: Vec::new(), ..step.clone() })
+                    }));
                 } else {
+                    let mut external_exec = make_external_execution(s.to_string());
+                    external_exec.exec_mode = ExternalExecutionMode::StayInBroot;
+                    if external_exec.uses_response_file() {
+                        return Err(ConfError::InvalidVerbConf {
+                            details: "The externals of an execution list can't use {selection-response-file}".to_string(),
+                        });
+                    }
+                    steps.push(VerbExecution::External(external_exec));
+                }
+            }
+            if steps.is_empty() {
+                return Err(ConfError::InvalidVerbConf {
+                    details: "An execution list can't be empty".to_string(),
+                });
+            }
+            VerbExecution::Steps(StepsExecution {
+                steps,
+                stop_on_error: vc.stop_on_error.unwrap_or(false),
+            })
+        } else {
+            match (execution, internal, external, cmd) {
+                // old definition with "execution": we guess whether it's an internal or
+                // an external
+                (Some(s), None, None, None) => {
+                    if s.starts_with(':') || s.starts_with(' ') {
+                        VerbExecution::Internal(InternalExecution::try_from(&s[1..])?)
+                    } else {
+                        VerbExecution::External(make_external_execution(s.to_string()))
+                    }
+                }
+                // "internal": the leading `:` or ` ` is optional
+                (None, Some(s), None, None) => {
+                    VerbExecution::Internal(if s.starts_with(':') || s.starts_with(' ') {
+                        InternalExecution::try_from(&s[1..])?
+                    } else {
+                        InternalExecution::try_from(s)?
+                    })
+                }
+                // "external": it can be about any form
+                (None, None, Some(s), None) => {
                     VerbExecution::External(make_external_execution(s.to_string()))
                 }
+                // "cmd": it's a sequence
+                (None, None, None, Some(s)) => VerbExecution::Sequence(SequenceExecution {
+                    sequence: Sequence::new(s, cmd_separator),
+                }),
+                _ => {
+                    return Err(ConfError::InvalidVerbConf {
+                        details: "You must define either internal, external or cmd".to_string(),
+                    });
+                }
             }
-            // "internal": the leading `:` or ` ` is optional
-            (None, Some(s), None, None) => {
-                VerbExecution::Internal(if s.starts_with(':') || s.starts_with(' ') {
-                    InternalExecution::try_from(&s[1..])?
-                } else {
-                    InternalExecution::try_from(&s)?
-                })
+        };
+        if let Some(name) = vc.on_conflict.as_deref() {
+            if ConflictPolicy::from_name(name).is_none() {
+                return Err(ConfError::InvalidVerbConf {
+                    details: format!(
+                        "invalid on_conflict {:?} (expected ask, skip, overwrite or rename)",
+                        name,
+                    ),
+                });
             }
-            // "external": it can be about any form
-            (None, None, Some(s), None) => {
-                VerbExecution::External(make_external_execution(s.to_string()))
+            // the policy is given to :paste as its argument
+            match &mut execution {
+                VerbExecution::Internal(ie) if ie.internal == Internal::paste && !ie.is_chain() => {
+                    ie.arg = Some(name.to_string());
+                }
+                _ => {
+                    return Err(ConfError::InvalidVerbConf {
+                        details: "on_conflict is only valid for the :paste internal".to_string(),
+                    });
+                }
             }
-            // "cmd": it's a sequence
-            (None, None, None, Some(s)) => VerbExecution::Sequence(SequenceExecution {
-                sequence: Sequence::new(s, cmd_separator),
-            }),
-            _ => {
+        }
+        if let VerbExecution::External(external) = &execution {
+            if external.uses_response_file()
+                && (external.exec_mode.is_leave_broot() || external.tail_in_panel || external.batch_size.is_some())
+            {
                 return Err(ConfError::InvalidVerbConf {
-                    details: "You must define either internal, external or cmd".to_string(),
+                    details: "A verb using {selection-response-file} must have leave_broot=false and can't be tailed or batched".to_string(),
                 });
             }
-        };
+        }
         let description = vc
             .description
             .clone()
@@ -116,9 +455,35 @@ impl TryFrom<&VerbConf> for Verb {
             }
             verb = verb.with_key(key);
         }
+        if let Some(mode) = vc.mode.as_ref().filter(|m| !m.is_empty()) {
+            if verb.keys.is_empty() {
+                return Err(ConfError::InvalidVerbConf {
+                    details: "A verb with a mode needs a key".to_string(),
+                });
+            }
+            verb.mode = Some(mode.clone());
+        }
         if let Some(shortcut) = &vc.shortcut {
             verb.names.push(shortcut.clone());
         }
+        verb.skip_on_network_fs = vc.skip_on_network_fs.unwrap_or(false);
+        verb.require_foreground = vc.require_foreground.unwrap_or(false);
+        if vc.confirm_typing == Some(true) {
+            let has_args = verb.invocation_parser
+                .as_ref()
+                .and_then(|parser| parser.invocation_pattern.args.as_ref())
+                .is_some();
+            if has_args {
+                return Err(ConfError::InvalidVerbConf {
+                    details: "A verb with confirm_typing=true can't take arguments".to_string(),
+                });
+            }
+            verb.confirm_typing = true;
+        }
+        verb.stable_selection = vc.stable_selection.unwrap_or(false);
+        verb.debounce = vc.debounce_ms.filter(|&ms| ms > 0).map(Duration::from_millis);
+        verb.min_size = vc.min_size.as_deref().map(parse_size).transpose()?;
+        verb.max_size = vc.max_size.as_deref().map(parse_size).transpose()?;
         verb.selection_condition = match vc.apply_to.as_deref() {
             Some("file") => SelectionType::File,
             Some("directory") => SelectionType::Directory,
@@ -134,3 +499,153 @@ impl TryFrom<&VerbConf> for Verb {
     }
 }
 
+
+/// parse a size like `500K`, `1.5M` or `1234` into a number of bytes.
+/// Units are the ISO ones, as in the displayed sizes (1K is 1000 bytes)
+fn parse_size(s: &str) -> Result<u64, ConfError> {
+    let s = s.trim();
+    let (number, multiplier) = match s.chars().last() {
+        Some('K') | Some('k') => (&s[..s.len() - 1], 1_000.0),
+        Some('M') | Some('m') => (&s[..s.len() - 1], 1_000_000.0),
+        Some('G') | Some('g') => (&s[..s.len() - 1], 1_000_000_000.0),
+        Some('T') | Some('t') => (&s[..s.len() - 1], 1_000_000_000_000.0),
+        _ => (s, 1.0),
+    };
+    number.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n >= 0.0)
+        .map(|n| (n * multiplier).round() as u64)
+        .ok_or_else(|| ConfError::InvalidVerbConf {
+            details: format!("{:?} isn't a valid size", s),
+        })
+}
+
+#[cfg(test)]
+mod verb_conf_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1234").unwrap(), 1234);
+        assert_eq!(parse_size("500K").unwrap(), 500_000);
+        assert_eq!(parse_size("1M").unwrap(), 1_000_000);
+        assert_eq!(parse_size("1.5m").unwrap(), 1_500_000);
+        assert_eq!(parse_size(" 2G ").unwrap(), 2_000_000_000);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("-3K").is_err());
+        assert!(parse_size("12Mo").is_err());
+    }
+
+    fn verb_from_toml(conf: &str) -> Result<Verb, ConfError> {
+        let vc: VerbConf = toml::from_str(conf).unwrap();
+        Verb::try_from(&vc)
+    }
+
+    #[test]
+    fn test_execution_list() {
+        let verb = verb_from_toml(r#"
+            invocation = "gadd"
+            execution = [":focus {parent}; :toggle_hidden", "git add {file}", ":refresh"]
+            stop_on_error = true
+        "#).unwrap();
+        let steps_ex = match &verb.execution {
+            VerbExecution::Steps(steps_ex) => steps_ex,
+            _ => panic!("not a list of steps"),
+        };
+        assert!(steps_ex.stop_on_error);
+        assert_eq!(steps_ex.steps.len(), 4);
+        assert!(matches!(&steps_ex.steps[2], VerbExecution::External(ee) if !ee.exec_mode.is_leave_broot()));
+        assert_eq!(
+            verb.execution.to_string(),
+            ":focus {parent}; :toggle_hidden; git add {file}; :refresh",
+        );
+        // a single execution is still accepted
+        assert!(matches!(
+            verb_from_toml(r#"execution = ":toggle_hidden""#).unwrap().execution,
+            VerbExecution::Internal(_),
+        ));
+        // invalid lists
+        assert!(verb_from_toml(r#"execution = []"#).is_err());
+        assert!(verb_from_toml(r#"execution = [":not_an_internal"]"#).is_err());
+        assert!(verb_from_toml(r#"execution = ["ls"]
+            leave_broot = true"#).is_err());
+        assert!(verb_from_toml(r#"execution = ["ls"]
+            internal = ":refresh""#).is_err());
+        assert!(verb_from_toml(r#"execution = "ls"
+            stop_on_error = true"#).is_err());
+    }
+
+    #[test]
+    fn test_batch_size() {
+        assert!(verb_from_toml(r#"
+            external = "convert {selection} out.pdf"
+            leave_broot = false
+            batch_size = 10
+        "#).is_ok());
+        assert!(verb_from_toml(r#"
+            external = "ls"
+            leave_broot = false
+            batch_size = 0
+        "#).is_err());
+        for option in &[
+            "leave_broot = true",
+            "background = true",
+            "tail_in_panel = true",
+            "output_to_clipboard = true",
+            "output_to_filter = true",
+        ] {
+            let leave_broot = if option.starts_with("leave_broot") { "" } else { "leave_broot = false" };
+            let conf = format!("external = \"ls\"\nbatch_size = 2\n{}\n{}", leave_broot, option);
+            assert!(verb_from_toml(&conf).is_err(), "{} with batch_size", option);
+        }
+    }
+
+    #[test]
+    fn test_capture_output() {
+        let verb = verb_from_toml(r#"
+            invocation = "log"
+            external = "git log --oneline {file}"
+            capture_output = true
+        "#).unwrap();
+        assert!(matches!(
+            &verb.execution,
+            VerbExecution::External(ee) if ee.exec_mode.is_capture_output(),
+        ));
+        assert!(verb_from_toml(r#"
+            external = "ls -l {directory}"
+            capture_output = true
+            leave_broot = false
+        "#).is_ok());
+        for option in &[
+            "leave_broot = true",
+            "from_shell = true",
+            "background = true",
+            "tail_in_panel = true",
+            "output_to_clipboard = true",
+            "stdin = \"null\"",
+        ] {
+            let conf = format!("external = \"ls\"\ncapture_output = true\n{}", option);
+            assert!(verb_from_toml(&conf).is_err(), "{} with capture_output", option);
+        }
+    }
+
+    #[test]
+    fn test_mode() {
+        let verb = verb_from_toml(r#"
+            key = "d"
+            mode = "normal"
+            internal = ":trash"
+        "#).unwrap();
+        assert_eq!(verb.mode.as_deref(), Some("normal"));
+        assert!(verb_from_toml(r#"internal = ":trash""#).unwrap().mode.is_none());
+        // a mode is about keys
+        assert!(verb_from_toml(r#"
+            invocation = "tr"
+            mode = "normal"
+            internal = ":trash"
+        "#).is_err());
+    }
+}
@@ -4,11 +4,13 @@ mod content_search_result;
 mod magic_numbers;
 mod extensions;
 mod needle;
+mod subtree_search;
 
 pub use {
     content_match::ContentMatch,
     content_search_result::ContentSearchResult,
     needle::Needle,
+    subtree_search::*,
 };
 
 use {
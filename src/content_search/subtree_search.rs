@@ -0,0 +1,175 @@
+use {
+    super::*,
+    crate::{
+        git::GitIgnorer,
+        tree::TreeOptions,
+    },
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// bounds of a search of the content of all the files of a subtree.
+#[derive(Debug, Clone, Copy)]
+pub struct SubtreeSearchLimits {
+    /// bigger files aren't searched
+    pub max_file_size: u64,
+    /// the search stops after this number of files were searched
+    pub max_searched_files: usize,
+    /// the search stops after this number of files were found
+    pub max_matching_files: usize,
+}
+
+impl Default for SubtreeSearchLimits {
+    fn default() -> Self {
+        Self {
+            max_file_size: MAX_FILE_SIZE as u64,
+            max_searched_files: 50_000,
+            max_matching_files: 1_000,
+        }
+    }
+}
+
+/// the files whose content contains the needle
+#[derive(Debug, Clone, Default)]
+pub struct SubtreeSearchResult {
+    /// the matching files, in tree order
+    pub paths: Vec<PathBuf>,
+    /// false when the search was stopped by a limit
+    pub complete: bool,
+}
+
+/// search the needle in all the files of the subtree, hidden and
+/// git ignored files being filtered according to the tree options.
+///
+/// Symbolic links aren't followed and binary files aren't searched.
+pub fn search_subtree(
+    root: &Path,
+    needle: &Needle,
+    options: &TreeOptions,
+    limits: SubtreeSearchLimits,
+) -> SubtreeSearchResult {
+    let mut git_ignorer = GitIgnorer::default();
+    let root_chain = git_ignorer.root_chain(root);
+    let mut result = SubtreeSearchResult {
+        paths: Vec::new(),
+        complete: true,
+    };
+    let mut searched_files = 0;
+    // the stack of directories to explore, the last one being
+    // explored first so that paths come in tree order
+    let mut dirs = vec![(root.to_path_buf(), root_chain)];
+    while let Some((dir, chain)) = dirs.pop() {
+        let mut entries: Vec<fs::DirEntry> = match fs::read_dir(&dir) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).collect(),
+            Err(e) => {
+                debug!("can't read {:?} : {:?}", &dir, e);
+                continue;
+            }
+        };
+        entries.sort_by_key(|e| e.file_name().to_string_lossy().to_lowercase());
+        let mut sub_dirs = Vec::new();
+        for e in entries {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            if !options.show_hidden && name.starts_with('.') {
+                continue;
+            }
+            let file_type = match e.file_type() {
+                Ok(ft) => ft,
+                Err(_) => continue,
+            };
+            let path = e.path();
+            if options.respect_git_ignore
+                && !git_ignorer.accepts(&chain, &path, &name, file_type.is_dir())
+            {
+                continue;
+            }
+            if file_type.is_dir() {
+                let sub_chain = git_ignorer.deeper_chain(&chain, &path);
+                sub_dirs.push((path, sub_chain));
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            if e.metadata().map_or(true, |md| md.len() > limits.max_file_size) {
+                continue;
+            }
+            if searched_files >= limits.max_searched_files {
+                result.complete = false;
+                return result;
+            }
+            searched_files += 1;
+            if let Ok(ContentSearchResult::Found { .. }) = needle.search(&path) {
+                result.paths.push(path);
+                if result.paths.len() >= limits.max_matching_files {
+                    result.complete = false;
+                    return result;
+                }
+            }
+        }
+        // files of a directory come before the content of its sub
+        // directories, which are explored in order
+        dirs.extend(sub_dirs.into_iter().rev());
+    }
+    result
+}
+
+#[cfg(test)]
+mod subtree_search_test {
+
+    use super::*;
+
+    fn names(root: &Path, result: &SubtreeSearchResult) -> Vec<String> {
+        result.paths
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_subtree_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/deep")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::write(root.join("a.txt"), "nothing to see").unwrap();
+        fs::write(root.join("B.txt"), "a TODO here").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {} // TODO").unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod deep;").unwrap();
+        fs::write(root.join("src/deep/mod.rs"), "// TODO: more").unwrap();
+        fs::write(root.join(".hidden/notes"), "TODO").unwrap();
+        fs::write(root.join("z.txt"), "todo, lowercase").unwrap();
+        let needle = Needle::new("TODO");
+        let options = TreeOptions::default();
+        let result = search_subtree(root, &needle, &options, SubtreeSearchLimits::default());
+        assert!(result.complete);
+        assert_eq!(
+            names(root, &result),
+            vec!["B.txt", "src/main.rs", "src/deep/mod.rs"],
+        );
+        // hidden files are searched when the tree shows them
+        let options = TreeOptions {
+            show_hidden: true,
+            ..TreeOptions::default()
+        };
+        let result = search_subtree(root, &needle, &options, SubtreeSearchLimits::default());
+        assert_eq!(names(root, &result).len(), 4);
+        // limits
+        let limits = SubtreeSearchLimits {
+            max_matching_files: 2,
+            ..SubtreeSearchLimits::default()
+        };
+        let result = search_subtree(root, &needle, &TreeOptions::default(), limits);
+        assert!(!result.complete);
+        assert_eq!(names(root, &result), vec!["B.txt", "src/main.rs"]);
+        let limits = SubtreeSearchLimits {
+            max_file_size: 12,
+            ..SubtreeSearchLimits::default()
+        };
+        let result = search_subtree(root, &needle, &TreeOptions::default(), limits);
+        assert_eq!(names(root, &result), vec!["B.txt"]);
+    }
+}
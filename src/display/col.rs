@@ -11,7 +11,7 @@ use {
 };
 
 // number of columns in enum
-const COLS_COUNT: usize = 8;
+const COLS_COUNT: usize = 9;
 
 /// One of the "columns" of the tree view
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -37,6 +37,9 @@ pub enum Col {
     /// number of files in the directory
     Count,
 
+    /// annotation given by the column provider
+    Custom,
+
     /// name of the file, or subpath if relevant due to filtering mode
     Name,
 }
@@ -60,6 +63,7 @@ pub static DEFAULT_COLS: Cols = [
     Col::Date,
     Col::Permission,
     Col::Count,
+    Col::Custom,
     Col::Branch,
     Col::Name,
 ];
@@ -76,6 +80,7 @@ impl FromStr for Col {
             "d" | "date" => Ok(Self::Date),
             "s" | "size" => Ok(Self::Size),
             "c" | "count" => Ok(Self::Count),
+            "x" | "custom" => Ok(Self::Custom),
             "n" | "name" => Ok(Self::Name),
             _ => Err(ConfError::InvalidCols {
                 details: format!("column not recognized : {}", s),
@@ -103,6 +108,7 @@ impl Col {
             Col::Date => true,
             Col::Permission => true,
             Col::Count => false,
+            Col::Custom => true,
             Col::Branch => false,
             Col::Name => false,
         }
@@ -116,7 +122,8 @@ impl Col {
             Col::Date => tree_options.show_dates,
             Col::Permission => tree_options.show_permissions,
             Col::Count => tree_options.show_counts,
-            Col::Branch => true,
+            Col::Custom => tree_options.show_custom_column,
+            Col::Branch => !tree_options.flat_view,
             Col::Name => true,
         }
 
@@ -159,3 +166,60 @@ pub fn parse_cols_single_str(s: &str) -> Result<Cols, ConfError> {
     parse_cols(&s.chars().map(String::from).collect())
 }
 
+
+#[cfg(test)]
+mod col_test {
+
+    use {
+        super::*,
+        crate::{
+            task_sync::ComputationResult,
+            tree::TreeOptions,
+        },
+    };
+
+    fn empty_tree(options: TreeOptions) -> Tree {
+        Tree {
+            lines: Vec::new().into_boxed_slice(),
+            selection: 0,
+            options,
+            scroll: 0,
+            nb_gitignored: 0,
+            total_search: false,
+            git_status: ComputationResult::None,
+        }
+    }
+
+    fn visible_cols(options: &TreeOptions) -> Vec<Col> {
+        let tree = empty_tree(options.clone());
+        DEFAULT_COLS.iter().filter(|col| col.is_visible(&tree)).cloned().collect()
+    }
+
+    #[test]
+    fn test_toggle_sizes() {
+        let mut options = TreeOptions::default();
+        let before = visible_cols(&options);
+        assert!(!before.contains(&Col::Size));
+        options.toggle_sizes();
+        assert!(options.show_root_fs);
+        let after = visible_cols(&options);
+        assert!(after.contains(&Col::Size));
+        assert_eq!(after.len(), before.len() + 1);
+        options.toggle_sizes();
+        assert!(!options.show_root_fs);
+        assert_eq!(visible_cols(&options), before);
+    }
+
+    #[test]
+    fn test_toggle_permissions() {
+        let mut options = TreeOptions::default();
+        let before = visible_cols(&options);
+        assert!(!before.contains(&Col::Permission));
+        options.toggle_permissions();
+        let after = visible_cols(&options);
+        assert!(after.contains(&Col::Permission));
+        assert_eq!(after.len(), before.len() + 1);
+        options.toggle_permissions();
+        assert_eq!(visible_cols(&options), before);
+    }
+}
@@ -12,6 +12,7 @@ use {
         file_sum::FileSum,
         pattern::PatternObject,
         skin::{ExtColorMap, StyleMap},
+        stage::Stage,
         task_sync::ComputationResult,
         tree::{Tree, TreeLine, TreeLineType},
     },
@@ -40,6 +41,7 @@ pub struct DisplayableTree<'s, 't> {
     pub area: termimad::Area,
     pub in_app: bool, // if true we show the selection and scrollbar
     pub ext_colors: &'s ExtColorMap,
+    pub stage: Option<&'s Stage>, // the staged paths get a marker
 }
 
 impl<'s, 't> DisplayableTree<'s, 't> {
@@ -61,6 +63,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                 height: tree.lines.len() as u16,
             },
             in_app: false,
+            stage: None,
         }
     }
 
@@ -109,15 +112,36 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         })
     }
 
+    fn write_line_annotation<'w, W: Write>(
+        &self,
+        cw: &mut CropWriter<'w, W>,
+        line: &TreeLine,
+        annotation_len: usize,
+        selected: bool,
+    ) -> Result<usize, termimad::Error> {
+        Ok(match &line.annotation {
+            Some(annotation) if !annotation.is_empty() => {
+                cond_bg!(annotation_style, self, selected, self.skin.annotation);
+                cw.queue_str(annotation_style, annotation)?;
+                annotation_len + 1 - annotation.chars().count()
+            }
+            _ => annotation_len + 1,
+        })
+    }
+
     fn write_line_selection_mark<'w, W: Write>(
         &self,
         cw: &mut CropWriter<'w, W>,
+        line: &TreeLine,
         style: &CompoundStyle,
         selected: bool,
     ) -> Result<usize, termimad::Error> {
         Ok(if selected {
             cw.queue_char(&style, '▶')?;
             0
+        } else if self.stage.is_some_and(|stage| stage.contains(&line.path)) {
+            cw.queue_char(&self.skin.staged_marker, '+')?;
+            0
         } else {
             1
         })
@@ -244,6 +268,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
     }
 
     /// write the name or subpath, depending on the pattern_object
+    /// and on whether the tree is displayed flat
     fn write_line_label<'w, W: Write>(
         &self,
         cw: &mut CropWriter<'w, W>,
@@ -253,7 +278,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
         selected: bool,
     ) -> Result<usize, ProgramError> {
         cond_bg!(char_match_style, self, selected, self.skin.char_match);
-        let label = if pattern_object.subpath {
+        let label = if pattern_object.subpath || self.tree.options.flat_view {
             &line.subpath
         } else {
             &line.name
@@ -411,6 +436,18 @@ impl<'s, 't> DisplayableTree<'s, 't> {
             0
         };
 
+        // the custom column is as wide as the longest annotation
+        let annotation_len = if tree.options.show_custom_column {
+            tree.lines.iter()
+                .skip(1)
+                .filter_map(|l| l.annotation.as_ref())
+                .map(|a| a.chars().count())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
         // we compute the length of the dates, depending on the format
         let date_len = if tree.options.show_dates {
             let date_time: DateTime<Local> = Local::now();
@@ -449,7 +486,7 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                     let void_len = match col {
 
                         Col::Mark => {
-                            self.write_line_selection_mark(cw, &label_style, selected)?
+                            self.write_line_selection_mark(cw, line, &label_style, selected)?
                         }
 
                         Col::Git => {
@@ -490,6 +527,10 @@ impl<'s, 't> DisplayableTree<'s, 't> {
                             self.write_line_count(cw, line, count_len, selected)?
                         }
 
+                        Col::Custom => {
+                            self.write_line_annotation(cw, line, annotation_len, selected)?
+                        }
+
                         Col::Name => {
                             in_branch = false;
                             self.write_line_label(cw, line, &label_style, pattern_object, selected)?
@@ -534,3 +575,62 @@ impl<'s, 't> DisplayableTree<'s, 't> {
     }
 }
 
+
+#[cfg(test)]
+mod displayable_tree_test {
+
+    use {
+        super::*,
+        crate::{
+            app::AppContext,
+            conf::Conf,
+            task_sync::Dam,
+            tree::TreeOptions,
+            tree_build::TreeBuilder,
+        },
+        std::fs,
+    };
+
+    fn render(root: &std::path::Path, options: TreeOptions) -> Vec<String> {
+        let con = AppContext::for_test_with(root, options.clone(), &mut Conf::default());
+        let tree = TreeBuilder::from(root.to_path_buf(), options, 100, &con)
+            .unwrap()
+            .build(true, &Dam::unlimited())
+            .unwrap();
+        let skin = StyleMap::no_term();
+        let ext_colors = ExtColorMap::default();
+        let dp = DisplayableTree::out_of_app(&tree, &skin, &ext_colors, 80);
+        let mut out = Vec::new();
+        dp.write_on(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let escapes = regex::Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap();
+        escapes
+            .replace_all(&out, "")
+            .lines()
+            .skip(1) // the root line
+            .map(|l| l.trim().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn check_flat_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("b/inner")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b/c.txt"), "c").unwrap();
+        fs::write(root.join("b/inner/d.txt"), "d").unwrap();
+        fs::write(root.join("e.txt"), "e").unwrap();
+        let options = TreeOptions {
+            flat_view: true,
+            ..TreeOptions::default()
+        };
+        assert_eq!(
+            render(root, options),
+            vec!["a.txt", "b", "b/c.txt", "b/inner", "b/inner/d.txt", "e.txt"],
+        );
+        // the standard view draws branches and only shows names
+        let tree_view = render(root, TreeOptions::default());
+        assert_eq!(tree_view[2], "│  ├──c.txt");
+    }
+}
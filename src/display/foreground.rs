@@ -0,0 +1,49 @@
+/// tell whether broot is in the foreground of its terminal, i.e. whether
+/// its process group is the foreground one of the controlling terminal.
+///
+/// When this can't be known (e.g. when there's no controlling terminal),
+/// broot is considered in the foreground, as there's no terminal to
+/// stomp on.
+#[cfg(unix)]
+pub fn is_foreground() -> bool {
+    use {
+        libc::{getpgrp, tcgetpgrp},
+        std::{fs::File, os::unix::io::AsRawFd},
+    };
+    let tty = match File::open("/dev/tty") {
+        Ok(tty) => tty,
+        Err(_) => return true,
+    };
+    let terminal_group = unsafe { tcgetpgrp(tty.as_raw_fd()) };
+    let own_group = unsafe { getpgrp() };
+    is_foreground_group(own_group, terminal_group)
+}
+
+#[cfg(not(unix))]
+pub fn is_foreground() -> bool {
+    true
+}
+
+/// tell whether a process group is the foreground one, given the
+/// group returned by `tcgetpgrp`, which is negative on error
+#[cfg(unix)]
+fn is_foreground_group(own_group: libc::pid_t, terminal_group: libc::pid_t) -> bool {
+    terminal_group < 0 || terminal_group == own_group
+}
+
+#[cfg(all(test, unix))]
+mod foreground_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_is_foreground_group() {
+        // the terminal's foreground group is broot's one
+        assert!(is_foreground_group(1200, 1200));
+        // another group, e.g. the shell after a ctrl-z, is in the foreground
+        assert!(!is_foreground_group(1200, 1100));
+        assert!(!is_foreground_group(1200, 0));
+        // the foreground group can't be known
+        assert!(is_foreground_group(1200, -1));
+    }
+}
@@ -25,9 +25,11 @@ mod col;
 mod crop_writer;
 mod displayable_tree;
 mod filling;
+mod foreground;
 mod git_status_display;
 pub mod flags_display;
 pub mod status_line;
+pub mod verb_bar;
 mod matched_string;
 mod screen;
 mod cell_size;
@@ -41,6 +43,7 @@ pub use {
     crop_writer::CropWriter,
     displayable_tree::DisplayableTree,
     filling::*,
+    foreground::is_foreground,
     git_status_display::GitStatusDisplay,
     matched_string::MatchedString,
     screen::Screen,
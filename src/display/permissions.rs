@@ -18,6 +18,7 @@ pub struct PermWriter<'s> {
     pub skin: &'s StyleMap,
     max_user_len: usize,
     max_group_len: usize,
+    octal: bool, // whether the mode is written in octal (e.g. 755)
 }
 
 impl<'s> PermWriter<'s> {
@@ -26,8 +27,9 @@ impl<'s> PermWriter<'s> {
         skin: &'s StyleMap,
         max_user_len: usize,
         max_group_len: usize,
+        octal: bool,
     ) -> Self {
-        Self { skin, max_user_len, max_group_len }
+        Self { skin, max_user_len, max_group_len, octal }
     }
 
     pub fn for_tree(
@@ -35,7 +37,7 @@ impl<'s> PermWriter<'s> {
         tree: &Tree,
     ) -> Self {
         let (max_user_len, max_group_len) = user_group_max_lengths(tree);
-        Self::new(skin, max_user_len, max_group_len)
+        Self::new(skin, max_user_len, max_group_len, tree.options.octal_permissions)
     }
 
     fn write_mode<'w, W: Write>(
@@ -108,7 +110,12 @@ impl<'s> PermWriter<'s> {
         selected: bool,
     ) -> Result<usize, ProgramError> {
         Ok(if line.is_selectable() {
-            self.write_mode(cw, line.mode(), selected)?;
+            if self.octal {
+                cond_bg!(mode_style, self, selected, self.skin.perm_r);
+                cw.queue_g_string(mode_style, permissions::mode_octal(line.metadata.mode()))?;
+            } else {
+                self.write_mode(cw, line.mode(), selected)?;
+            }
             let owner = permissions::user_name(line.metadata.uid());
             cond_bg!(owner_style, self, selected, self.skin.owner);
             cw.queue_g_string(
@@ -123,7 +130,8 @@ impl<'s> PermWriter<'s> {
             )?;
             1
         } else {
-            9 + 1 + self.max_user_len + 1 + self.max_group_len + 1
+            let mode_len = if self.octal { 3 } else { 9 };
+            mode_len + 1 + self.max_user_len + 1 + self.max_group_len + 1
         })
     }
 
@@ -9,9 +9,10 @@ use {
     termimad::{Area, StyledChar},
 };
 
-/// write the whole status line (task + status)
+/// write the whole status line (mode + task + status)
 pub fn write(
     w: &mut W,
+    mode: Option<&str>,
     task: Option<&str>,
     status: &Status,
     area: &Area,
@@ -21,6 +22,11 @@ pub fn write(
     let y = area.top;
     screen.goto(w, area.left, y)?;
     let mut x = area.left;
+    if let Some(mode) = mode {
+        let mode = format!(" {} ", mode.to_uppercase());
+        x += mode.chars().count() as u16;
+        panel_skin.styles.status_mode.queue(w, mode)?;
+    }
     if let Some(pending_task) = task {
         let pending_task = format!(" {}… ", pending_task);
         x += pending_task.chars().count() as u16;
@@ -0,0 +1,34 @@
+use {
+    super::{CropWriter, Screen, SPACE_FILLING, W},
+    crate::{
+        errors::ProgramError,
+        keys,
+        skin::PanelSkin,
+        verb::Verb,
+    },
+    termimad::Area,
+};
+
+/// draw the bar listing the verbs, each one with its first
+/// name and the key triggering it, if any
+pub fn write(
+    w: &mut W,
+    verbs: &[&Verb],
+    area: &Area,
+    panel_skin: &PanelSkin,
+    screen: Screen,
+) -> Result<(), ProgramError> {
+    let styles = &panel_skin.styles;
+    screen.goto(w, area.left, area.top)?;
+    let mut cw = CropWriter::new(w, area.width as usize);
+    for verb in verbs {
+        cw.queue_str(&styles.purpose_normal, " ")?;
+        cw.queue_str(&styles.purpose_bold, &verb.names[0])?;
+        if let Some(&key) = verb.keys.first() {
+            cw.queue_str(&styles.purpose_italic, &format!(" {}", keys::key_event_desc(key)))?;
+        }
+        cw.queue_str(&styles.purpose_normal, " ")?;
+    }
+    cw.fill(&styles.purpose_normal, &SPACE_FILLING)?;
+    Ok(())
+}
@@ -28,6 +28,18 @@ custom_error! {pub ProgramError
     ImageError {source: ImageError } = "{}",
     Lfs {details: String} = "Failed to fetch mounts: {}",
     ZeroLenFile = "File seems empty",
+    UnknownSyntax {syntax: String} = "Unknown syntax: {syntax}",
+    Unrenderable {path: String} = "Only markdown and csv files can be rendered, not {path}",
+    InvalidLayoutName {name: String} = "Invalid layout name: {name:?}",
+    UnknownLayout {name: String} = "No layout named {name:?}",
+    LayoutFile {path: String, details: String} = "Bad layout file {path}: {details}",
+    InvalidSessionName {name: String} = "Invalid session name: {name:?}",
+    UnknownSession {name: String} = "No session named {name:?}",
+    SessionFile {path: String, details: String} = "Bad session file {path}: {details}",
+    InvalidMarkSetName {name: String} = "Invalid mark set name: {name:?}",
+    MarkSetFile {path: String, details: String} = "Bad mark set file {path}: {details}",
+    InvalidBookmarkName {name: String} = "Invalid bookmark name: {name:?}",
+    BookmarksFile {path: String, details: String} = "Bad bookmarks file {path}: {details}",
 }
 
 custom_error! {pub TreeBuildError
@@ -48,6 +60,8 @@ custom_error! {pub ConfError
     ReservedKey {key: String}                       = "reserved key: {}",
     UnexpectedInternalArg {invocation: String}      = "unexpected argument for internal: {}",
     InvalidCols {details: String}                   = "invalid cols definition: {}",
+    VerbProvider {command: String, details: String} = "invalid verb provider {:?}: {}",
+    InvalidCustomColumn {details: String}           = "invalid custom column: {}",
     InvalidSkin {source: InvalidSkinError}          = "invalid skin: {}",
 }
 
@@ -58,6 +72,7 @@ custom_error! {pub PatternError
         format!("Invalid Regular Expression: {}", source.to_string().lines().last().unwrap_or(""))
     },
     UnknownRegexFlag {bad: char} = "Unknown regular expression flag: {:?}",
+    InvalidPredicate {details: String} = "Invalid predicate: {}",
 }
 
 custom_error! {pub InvalidSkinError
@@ -0,0 +1,166 @@
+//! a cache of the sums of the entries of directories, which may be
+//! persisted so that the sizes of a big tree are computed again, in
+//! a later session, by reading only the directories which changed
+
+use {
+    super::FileSum,
+    fnv::FnvHashMap,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+        time::UNIX_EPOCH,
+    },
+};
+
+/// the modification time of a directory, in seconds and nanoseconds
+/// from Epoch. It changes when entries are added, removed or renamed.
+pub type Mtime = (u64, u32);
+
+pub fn mtime(md: &fs::Metadata) -> Option<Mtime> {
+    let d = md.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((d.as_secs(), d.subsec_nanos()))
+}
+
+/// what's known of a directory, valid as long as its
+/// modification time doesn't change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirEntrySum {
+    pub mtime: Mtime,
+    /// the sum of the direct entries, not including the
+    /// content of the subdirectories
+    pub own: FileSum,
+    /// the names of the subdirectories
+    pub dirs: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DirCache {
+    entries: FnvHashMap<PathBuf, DirEntrySum>,
+    /// whether there are changes not yet saved
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl DirCache {
+    /// read the cache file, an absent or invalid file
+    /// giving an empty cache
+    pub fn load(path: &Path) -> Self {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return Self::default(),
+        };
+        match serde_json::from_str(&content) {
+            Ok(cache) => cache,
+            Err(e) => {
+                warn!("invalid size cache {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+    /// write the cache file, if there are changes since it was read
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        let content = serde_json::to_string(self)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)?;
+        self.changed = false;
+        Ok(())
+    }
+    /// return the entry of the directory if it's still valid
+    pub fn get(&self, path: &Path, mtime: Option<Mtime>) -> Option<&DirEntrySum> {
+        self.entries.get(path)
+            .filter(|entry| Some(entry.mtime) == mtime)
+    }
+    /// store the entry of a directory, forgetting the subdirectories
+    /// it doesn't contain anymore
+    pub fn insert(&mut self, path: PathBuf, entry: DirEntrySum) {
+        if let Some(old) = self.entries.get(&path) {
+            let removed: Vec<PathBuf> = old.dirs.iter()
+                .filter(|name| !entry.dirs.contains(name))
+                .map(|name| path.join(name))
+                .collect();
+            for dir in removed {
+                self.entries.remove(&dir);
+            }
+        }
+        self.entries.insert(path, entry);
+        self.changed = true;
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// forget the entries of the directory and of its
+    /// subdirectories
+    pub fn remove_tree(&mut self, root: &Path) {
+        let len = self.entries.len();
+        self.entries.retain(|path, _| !path.starts_with(root));
+        if self.entries.len() != len {
+            self.changed = true;
+        }
+    }
+}
+
+/// return the path of the file where the cache is persisted
+pub fn dir_cache_path() -> PathBuf {
+    crate::conf::app_dirs().data_dir().join("sizes.json")
+}
+
+#[cfg(test)]
+mod dir_cache_tests {
+
+    use super::*;
+
+    fn entry(mtime: Mtime, dirs: &[&str]) -> DirEntrySum {
+        DirEntrySum {
+            mtime,
+            own: FileSum::new(4096, false, 3, 10),
+            dirs: dirs.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_dir_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("cache/sizes.json");
+        let mut cache = DirCache::load(&file);
+        assert!(cache.is_empty());
+        let root = PathBuf::from("/data");
+        cache.insert(root.clone(), entry((100, 5), &["a", "b"]));
+        cache.insert(root.join("a"), entry((101, 0), &[]));
+        cache.insert(root.join("b"), entry((102, 0), &[]));
+        assert!(cache.get(&root, Some((100, 5))).is_some());
+        assert!(cache.get(&root, Some((100, 6))).is_none()); // changed
+        assert!(cache.get(&root, None).is_none());
+        cache.save(&file).unwrap();
+        let mut cache = DirCache::load(&file);
+        assert_eq!(cache.len(), 3);
+        // the file isn't written again when nothing changed
+        fs::remove_file(&file).unwrap();
+        cache.save(&file).unwrap();
+        assert!(!file.exists());
+        assert_eq!(cache.get(&root, Some((100, 5))).unwrap().own.to_size(), 4096);
+        // a removed subdirectory is forgotten
+        cache.insert(root.clone(), entry((103, 0), &["a"]));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&root.join("b"), Some((102, 0))).is_none());
+        cache.save(&file).unwrap();
+        assert!(file.exists());
+        // only the refreshed tree is forgotten
+        cache.insert(PathBuf::from("/other"), entry((104, 0), &[]));
+        cache.remove_tree(&root);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(Path::new("/other"), Some((104, 0))).is_some());
+        // an invalid file gives an empty cache
+        fs::write(&file, "not json").unwrap();
+        assert!(DirCache::load(&file).is_empty());
+    }
+}
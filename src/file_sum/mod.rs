@@ -1,22 +1,28 @@
 /// compute consolidated data for directories: modified date, size, and count.
 /// A cache is used to avoid recomputing the same directories again and again.
 /// On unix, hard links are checked to avoid counting twice an inode.
-
+/// When enabled, the sums of the entries of the directories are persisted,
+/// so that only the directories which changed are read in the next sessions.
+mod dir_cache;
 mod sum_computation;
 
 use {
     crate::task_sync::Dam,
+    dir_cache::*,
     fnv::FnvHashMap,
+    serde::{Deserialize, Serialize},
     std::{
         ops::AddAssign,
         path::{Path, PathBuf},
-        sync::Mutex,
+        sync::{Arc, Mutex, RwLock},
     },
 };
 
 lazy_static! {
     static ref SUM_CACHE_MUTEX: Mutex<FnvHashMap<PathBuf, FileSum>> =
         Mutex::new(FnvHashMap::default());
+    /// None when the sums aren't persisted
+    static ref DIR_CACHE: RwLock<Option<Arc<RwLock<DirCache>>>> = RwLock::new(None);
 }
 
 pub fn clear_cache() {
@@ -24,8 +30,36 @@ pub fn clear_cache() {
     sum_cache.clear();
 }
 
+/// enable the persistence of the sums, reading the ones
+/// of the previous sessions
+pub fn load_dir_cache() {
+    let cache = DirCache::load(&dir_cache_path());
+    if !cache.is_empty() {
+        debug!("{} directories in size cache", cache.len());
+    }
+    *DIR_CACHE.write().unwrap() = Some(Arc::new(RwLock::new(cache)));
+}
+
+/// write the persisted sums, if they're enabled and changed
+pub fn save_dir_cache() {
+    if let Some(cache) = DIR_CACHE.read().unwrap().as_ref() {
+        if let Err(e) = cache.write().unwrap().save(&dir_cache_path()) {
+            warn!("size cache not saved: {}", e);
+        }
+    }
+}
+
+/// forget the persisted sums of the tree, so that they're computed
+/// again (files modified in place don't change the modification
+/// time of their directory)
+pub fn clear_dir_cache(root: &Path) {
+    if let Some(cache) = DIR_CACHE.read().unwrap().as_ref() {
+        cache.write().unwrap().remove_tree(root);
+    }
+}
+
 /// Reduction of counts, dates and sizes on a file or directory
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct FileSum {
     real_size: u64, // bytes, the space it takes on disk
     count: usize,   // number of files
@@ -69,7 +103,12 @@ impl FileSum {
                     Debug,
                     "sum computation",
                     path,
-                    sum_computation::compute_dir_sum(path, &mut sum_cache, dam),
+                    sum_computation::compute_dir_sum(
+                        path,
+                        &mut sum_cache,
+                        DIR_CACHE.read().unwrap().clone(),
+                        dam,
+                    ),
                 );
                 if let Some(sum) = sum {
                     sum_cache.insert(PathBuf::from(path), sum);
@@ -79,6 +118,12 @@ impl FileSum {
         }
     }
 
+    /// return the sum of the directory if it was already computed,
+    /// without computing it
+    pub fn cached_dir(path: &Path) -> Option<Self> {
+        SUM_CACHE_MUTEX.lock().unwrap().get(path).copied()
+    }
+
     pub fn part_of_size(self, total: Self) -> f32 {
         if total.real_size == 0 {
             0.0
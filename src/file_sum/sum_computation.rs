@@ -1,5 +1,8 @@
 use {
-    super::FileSum,
+    super::{
+        dir_cache::{mtime, DirCache, DirEntrySum, Mtime},
+        FileSum,
+    },
     crate::task_sync::Dam,
     crossbeam::channel,
     fnv::FnvHashMap,
@@ -10,7 +13,7 @@ use {
         path::{Path, PathBuf},
         sync::{
             atomic::{AtomicIsize, Ordering},
-            Arc,
+            Arc, RwLock,
         },
     },
 };
@@ -37,13 +40,95 @@ struct NodeId {
 // threads used by one computation
 const THREADS_COUNT: usize = 6;
 
+/// a subdirectory found when reading a directory
+struct SubDir {
+    path: PathBuf,
+    mtime: Option<Mtime>,
+    /// the sum of the directory itself, not of its content
+    sum: FileSum,
+}
+
+/// what's found when reading a directory
+struct DirContent {
+    /// the sum of the entries which aren't directories
+    files: FileSum,
+    subdirs: Vec<SubDir>,
+}
+
+impl DirContent {
+    /// the sum of the direct entries, including the
+    /// subdirectories themselves but not their content
+    fn own(&self) -> FileSum {
+        let mut own = self.files;
+        for subdir in &self.subdirs {
+            own += subdir.sum;
+        }
+        own
+    }
+    /// build the entry to persist, None if a name can't be stored
+    fn to_entry(&self, mtime: Option<Mtime>) -> Option<DirEntrySum> {
+        let dirs = self.subdirs.iter()
+            .map(|subdir| subdir.path.file_name()?.to_str().map(str::to_string))
+            .collect::<Option<Vec<String>>>()?;
+        Some(DirEntrySum { mtime: mtime?, own: self.own(), dirs })
+    }
+}
+
+/// read the direct entries of a directory
+fn read_dir_content(
+    dir: &Path,
+    #[cfg(unix)] nodes: &Mutex<FnvHashSet<NodeId>>,
+) -> DirContent {
+    let mut content = DirContent {
+        files: FileSum::zero(),
+        subdirs: Vec::new(),
+    };
+    if let Ok(entries) = fs::read_dir(dir) {
+        for e in entries.flatten() {
+            if let Ok(md) = e.metadata() {
+                if md.is_dir() {
+                    content.subdirs.push(SubDir {
+                        path: e.path(),
+                        mtime: mtime(&md),
+                        sum: md_sum(&md),
+                    });
+                    continue;
+                }
+
+                #[cfg(unix)]
+                if md.nlink() > 1 {
+                    let mut nodes = nodes.lock().unwrap();
+                    let node_id = NodeId {
+                        inode: md.ino(),
+                        dev: md.dev(),
+                    };
+                    if !nodes.insert(node_id) {
+                        // it was already in the set
+                        continue;
+                    }
+                }
+
+                content.files += md_sum(&md);
+            } else {
+                // we can't measure much but we can count the file
+                content.files.incr();
+            }
+        }
+    }
+    content
+}
+
 /// compute the consolidated numbers for a directory, with implementation
 /// varying depending on the OS:
 /// On unix, the computation is done on blocks of 512 bytes
 /// see https://doc.rust-lang.org/std/os/unix/fs/trait.MetadataExt.html#tymethod.blocks
+///
+/// When the sums are persisted in a dir cache, the directories whose
+/// modification time didn't change aren't read again.
 pub fn compute_dir_sum(
     path: &Path,
     cache: &mut FnvHashMap<PathBuf, FileSum>,
+    dir_cache: Option<Arc<RwLock<DirCache>>>,
     dam: &Dam,
 ) -> Option<FileSum> {
     //debug!("compute size of dir {:?} --------------- ", path);
@@ -55,6 +140,8 @@ pub fn compute_dir_sum(
             .unwrap();
     }
 
+    let persist = dir_cache.is_some();
+
     // to avoid counting twice a node, we store their id in a set
     #[cfg(unix)]
     let nodes = Arc::new(Mutex::new(FnvHashSet::<NodeId>::default()));
@@ -64,7 +151,8 @@ pub fn compute_dir_sum(
     let mut busy = 0;
     let mut sum = compute_file_sum(path);
 
-    // this MPMC channel contains the directory paths which must be handled.
+    // this MPMC channel contains the directory paths which must be handled,
+    // with their modification time.
     // A None means there's nothing left and the thread may send its result and stop
     let (dirs_sender, dirs_receiver) = channel::unbounded();
 
@@ -72,42 +160,33 @@ pub fn compute_dir_sum(
     // before adding. This enables faster computations in two cases:
     // - for the root line (assuming it's computed after the content)
     // - when we navigate up the tree
-    if let Ok(entries) = fs::read_dir(path) {
-        for e in entries.flatten() {
-            if let Ok(md) = e.metadata() {
-                if md.is_dir() {
-                    let entry_path = e.path();
-                    // we check the cache
-                    if let Some(entry_sum) = cache.get(&entry_path) {
-                        sum += *entry_sum;
-                        continue;
-                    }
-                    // we add the directory to the channel of dirs needing
-                    // processing
-                    busy += 1;
-                    dirs_sender.send(Some(entry_path)).unwrap();
-                } else {
-
-                    #[cfg(unix)]
-                    if md.nlink() > 1 {
-                        let mut nodes = nodes.lock().unwrap();
-                        let node_id = NodeId {
-                            inode: md.ino(),
-                            dev: md.dev(),
-                        };
-                        if !nodes.insert(node_id) {
-                            // it was already in the set
-                            continue;
-                        }
-                    }
-
-                }
-                sum += md_sum(&md);
-            }
+    let content = read_dir_content(
+        path,
+        #[cfg(unix)] &nodes,
+    );
+    let mut new_entries = Vec::new();
+    if persist {
+        let mtime = fs::symlink_metadata(path).ok().and_then(|md| mtime(&md));
+        if let Some(entry) = content.to_entry(mtime) {
+            new_entries.push((path.to_path_buf(), entry));
+        }
+    }
+    sum += content.files;
+    for subdir in content.subdirs {
+        // we check the cache
+        if let Some(entry_sum) = cache.get(&subdir.path) {
+            sum += *entry_sum;
+            continue;
         }
+        // we add the directory to the channel of dirs needing
+        // processing
+        sum += subdir.sum;
+        busy += 1;
+        dirs_sender.send(Some((subdir.path, subdir.mtime))).unwrap();
     }
 
     if busy == 0 {
+        store_entries(dir_cache.as_deref(), new_entries);
         return Some(sum);
     }
 
@@ -127,43 +206,47 @@ pub fn compute_dir_sum(
         #[cfg(unix)]
         let nodes = nodes.clone();
 
+        let dir_cache = dir_cache.clone();
         let observer = dam.observer();
         let thread_sum_sender = thread_sum_sender.clone();
         THREAD_POOL.spawn(move || {
             let mut thread_sum = FileSum::zero();
+            let mut thread_entries = Vec::new();
             loop {
                 let o = dirs_receiver.recv();
-                if let Ok(Some(open_dir)) = o {
-                    if let Ok(entries) = fs::read_dir(&open_dir) {
-                        for e in entries.flatten() {
-                            if let Ok(md) = e.metadata() {
-                                if md.is_dir() {
-                                    // we add the directory to the channel of dirs needing
-                                    // processing
-                                    busy.fetch_add(1, Ordering::Relaxed);
-                                    dirs_sender.send(Some(e.path())).unwrap();
-                                } else {
-
-                                    #[cfg(unix)]
-                                    if md.nlink() > 1 {
-                                        let mut nodes = nodes.lock().unwrap();
-                                        let node_id = NodeId {
-                                            inode: md.ino(),
-                                            dev: md.dev(),
-                                        };
-                                        if !nodes.insert(node_id) {
-                                            // it was already in the set
-                                            continue;
-                                        }
-                                    }
-
-                                }
-                                thread_sum += md_sum(&md);
-                            } else {
-                                // we can't measure much but we can count the file
-                                thread_sum.incr();
+                if let Ok(Some((open_dir, dir_mtime))) = o {
+                    let cached = dir_cache.as_ref().and_then(|c| {
+                        c.read().unwrap().get(&open_dir, dir_mtime).cloned()
+                    });
+                    if let Some(entry) = cached {
+                        // the directory didn't change, only its
+                        // subdirectories may have
+                        thread_sum += entry.own;
+                        for name in entry.dirs {
+                            let subdir = open_dir.join(name);
+                            let subdir_mtime = fs::symlink_metadata(&subdir)
+                                .ok()
+                                .and_then(|md| mtime(&md));
+                            busy.fetch_add(1, Ordering::Relaxed);
+                            dirs_sender.send(Some((subdir, subdir_mtime))).unwrap();
+                        }
+                    } else {
+                        let content = read_dir_content(
+                            &open_dir,
+                            #[cfg(unix)] &nodes,
+                        );
+                        if persist {
+                            if let Some(entry) = content.to_entry(dir_mtime) {
+                                thread_entries.push((open_dir, entry));
                             }
                         }
+                        thread_sum += content.own();
+                        for subdir in content.subdirs {
+                            // we add the directory to the channel of dirs needing
+                            // processing
+                            busy.fetch_add(1, Ordering::Relaxed);
+                            dirs_sender.send(Some((subdir.path, subdir.mtime))).unwrap();
+                        }
                     }
                     busy.fetch_sub(1, Ordering::Relaxed);
                 }
@@ -176,26 +259,43 @@ pub fn compute_dir_sum(
                     break;
                 }
             }
-            thread_sum_sender.send(thread_sum).unwrap();
+            thread_sum_sender.send((thread_sum, thread_entries)).unwrap();
         });
     }
     // Wait for the threads to finish and consolidate their results
     for _ in 0..THREADS_COUNT {
         match thread_sum_receiver.recv() {
-            Ok(thread_sum) => {
+            Ok((thread_sum, thread_entries)) => {
                 sum += thread_sum;
+                new_entries.extend(thread_entries);
             }
             Err(e) => {
                 warn!("Error while recv summing thread result : {:?}", e);
             }
         }
     }
+    // the directories which were completely read are stored
+    // even when the computation was interrupted
+    store_entries(dir_cache.as_deref(), new_entries);
     if dam.has_event() {
         return None;
     }
     Some(sum)
 }
 
+/// put the entries of the read directories in the persisted cache
+fn store_entries(dir_cache: Option<&RwLock<DirCache>>, entries: Vec<(PathBuf, DirEntrySum)>) {
+    if entries.is_empty() {
+        return;
+    }
+    if let Some(dir_cache) = dir_cache {
+        let mut dir_cache = dir_cache.write().unwrap();
+        for (path, entry) in entries {
+            dir_cache.insert(path, entry);
+        }
+    }
+}
+
 /// compute the sum for a regular file (not a folder)
 pub fn compute_file_sum(path: &Path) -> FileSum {
     match fs::symlink_metadata(path) {
@@ -252,3 +352,39 @@ fn md_sum(md: &fs::Metadata) -> FileSum {
     let seconds = extract_seconds(&md);
     FileSum::new(size, false, 1, seconds)
 }
+
+#[cfg(test)]
+mod sum_computation_tests {
+
+    use super::*;
+
+    fn dir_sum(path: &Path, dir_cache: Option<&Arc<RwLock<DirCache>>>) -> FileSum {
+        compute_dir_sum(
+            path,
+            &mut FnvHashMap::default(),
+            dir_cache.cloned(),
+            &Dam::unlimited(),
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_persisted_sums() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::create_dir_all(root.join("d")).unwrap();
+        fs::write(root.join("a/b/c/big"), vec![1; 50_000]).unwrap();
+        fs::write(root.join("d/small"), "small").unwrap();
+        let dir_cache = Arc::new(RwLock::new(DirCache::default()));
+        let first = dir_sum(root, Some(&dir_cache));
+        assert_eq!(first.to_count(), 7); // the root, 4 directories and 2 files
+        // a change deep in the tree is seen without the
+        // unchanged directories being read again
+        fs::write(root.join("a/b/c/other"), vec![1; 20_000]).unwrap();
+        let second = dir_sum(root, Some(&dir_cache));
+        let third = dir_sum(root, None);
+        assert_eq!(second.to_count(), 8);
+        assert_eq!(second.to_size(), third.to_size());
+        assert!(second.to_size() > first.to_size());
+    }
+}
@@ -8,6 +8,7 @@ use {
         errors::ProgramError,
         pattern::*,
         skin::PanelSkin,
+        stage::Stage,
         task_sync::Dam,
         tree::TreeOptions,
         verb::*,
@@ -178,6 +179,7 @@ impl AppState for FilesystemState {
         _screen: Screen,
         area: Area,
         panel_skin: &PanelSkin,
+        _stage: &Stage,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
         self.page_height = area.height as usize;
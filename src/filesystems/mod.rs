@@ -3,11 +3,13 @@
 mod filesystems_state;
 mod mount_list;
 mod mount_space_display;
+mod network_fs;
 
 pub use {
     filesystems_state::FilesystemState,
     mount_list::MountList,
     mount_space_display::MountSpaceDisplay,
+    network_fs::*,
 };
 
 use {
@@ -0,0 +1,150 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// types of file systems whose content isn't local
+static NETWORK_FS_TYPES: &[&str] = &[
+    "9p",
+    "afs",
+    "ceph",
+    "cifs",
+    "coda",
+    "davfs",
+    "fuse.rclone",
+    "fuse.sshfs",
+    "glusterfs",
+    "lustre",
+    "ncpfs",
+    "nfs",
+    "nfs4",
+    "smb3",
+    "smbfs",
+    "sshfs",
+];
+
+/// a mount point with the type of its file system, as read
+/// from the `/proc/mounts` format
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountEntry {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+}
+
+pub fn is_network_fs_type(fs_type: &str) -> bool {
+    NETWORK_FS_TYPES.contains(&fs_type)
+}
+
+/// decode the octal escapes (like `\040` for a space) used
+/// in the fields of `/proc/mounts`
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let is_escape = bytes[i] == b'\\'
+            && i + 3 < bytes.len()
+            && bytes[i + 1..i + 4].iter().all(|b| (b'0'..=b'7').contains(b));
+        if is_escape {
+            let code = bytes[i + 1..i + 4]
+                .iter()
+                .fold(0u8, |code, b| code.wrapping_mul(8) + (b - b'0'));
+            decoded.push(code);
+            i += 4;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// parse the content of a file in the `/proc/mounts` format
+pub fn parse_mounts(content: &str) -> Vec<MountEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = fields.next()?;
+            let fs_type = fields.next()?;
+            Some(MountEntry {
+                mount_point: PathBuf::from(unescape_mount_field(mount_point)),
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// return the entry of the mount containing the path, that is
+/// the one with the longest mount point being an ancestor of the path
+pub fn mount_of<'m>(path: &Path, mounts: &'m [MountEntry]) -> Option<&'m MountEntry> {
+    mounts
+        .iter()
+        .filter(|m| path.starts_with(&m.mount_point))
+        .max_by_key(|m| m.mount_point.as_os_str().len())
+}
+
+/// tell whether the path is on a network file system.
+///
+/// Mounts are read from `/proc/mounts` (which isn't available on
+/// all unixes, in which case the answer is always false)
+pub fn is_on_network_fs(path: &Path) -> bool {
+    let content = match fs::read_to_string("/proc/mounts") {
+        Ok(content) => content,
+        Err(e) => {
+            debug!("can't read mounts: {:?}", e);
+            return false;
+        }
+    };
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mounts = parse_mounts(&content);
+    matches!(mount_of(&path, &mounts), Some(m) if is_network_fs_type(&m.fs_type))
+}
+
+#[cfg(test)]
+mod network_fs_tests {
+
+    use super::*;
+
+    static MOUNTS: &str = r#"
+sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+/dev/nvme0n1p1 /boot/efi vfat rw,relatime 0 0
+nas:/export/media /mnt/media nfs4 rw,relatime,vers=4.2 0 0
+//server/share\040docs /mnt/share\040docs cifs rw,relatime 0 0
+/dev/sdb1 /mnt/media/local ext4 rw,relatime 0 0
+user@host:/home /home/dys/remote fuse.sshfs rw,nosuid,nodev 0 0
+"#;
+
+    fn check(path: &str, network: bool) {
+        let mounts = parse_mounts(MOUNTS);
+        let mount = mount_of(Path::new(path), &mounts).unwrap();
+        assert_eq!(is_network_fs_type(&mount.fs_type), network, "path: {:?}", path);
+    }
+
+    #[test]
+    fn test_parse_mounts() {
+        let mounts = parse_mounts(MOUNTS);
+        assert_eq!(mounts.len(), 7);
+        assert_eq!(
+            mounts[4],
+            MountEntry {
+                mount_point: PathBuf::from("/mnt/share docs"),
+                fs_type: "cifs".to_string(),
+            },
+        );
+    }
+
+    #[test]
+    fn test_network_fs_detection() {
+        check("/home/dys/dev/broot", false);
+        check("/boot/efi/EFI", false);
+        check("/mnt/media/films/a.mkv", true);
+        check("/mnt/media", true);
+        check("/mnt/media/local/b.mkv", false); // a local disk mounted in a network one
+        check("/mnt/mediatheque", false); // not in /mnt/media
+        check("/mnt/share docs/report.odt", true);
+        check("/home/dys/remote/a", true);
+    }
+}
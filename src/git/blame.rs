@@ -0,0 +1,73 @@
+use {
+    git2::Repository,
+    std::path::Path,
+};
+
+/// return the name of the author of the last change of a line of
+/// a file, according to git blame on the committed content.
+///
+/// The line is 1-based. None is returned when the file isn't in a
+/// repository, when the line doesn't exist, or on any git error.
+pub fn line_author(path: &Path, line: usize) -> Option<String> {
+    if line == 0 {
+        return None;
+    }
+    let path = path.canonicalize().ok()?;
+    let repo = Repository::discover(&path).ok()?;
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let relative_path = path.strip_prefix(workdir).ok()?;
+    let blame = repo.blame_file(relative_path, None).ok()?;
+    let hunk = blame.get_line(line)?;
+    let signature = hunk.final_signature();
+    signature.name().map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod blame_tests {
+
+    use {
+        super::*,
+        git2::Signature,
+        std::fs,
+    };
+
+    /// commit the file in the repository, as the given author
+    fn commit(repo: &Repository, file_name: &str, author: &str) {
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file_name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now(author, &format!("{}@example.com", author)).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, "change", &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_line_author() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let file = dir.path().join("notes.md");
+        fs::write(&file, "one\ntwo\nthree\n").unwrap();
+        commit(&repo, "notes.md", "Alice");
+        fs::write(&file, "one\n2\nthree\nfour\n").unwrap();
+        commit(&repo, "notes.md", "Bob Smith");
+        assert_eq!(line_author(&file, 1).as_deref(), Some("Alice"));
+        assert_eq!(line_author(&file, 2).as_deref(), Some("Bob Smith"));
+        assert_eq!(line_author(&file, 3).as_deref(), Some("Alice"));
+        assert_eq!(line_author(&file, 4).as_deref(), Some("Bob Smith"));
+        // no line, or a line which doesn't exist
+        assert_eq!(line_author(&file, 0), None);
+        assert_eq!(line_author(&file, 5), None);
+        // a file which isn't committed
+        let other = dir.path().join("other.md");
+        fs::write(&other, "one\n").unwrap();
+        assert_eq!(line_author(&other, 1), None);
+        // a file outside any repository
+        let outside = tempfile::tempdir().unwrap();
+        let file = outside.path().join("notes.md");
+        fs::write(&file, "one\n").unwrap();
+        assert_eq!(line_author(&file, 1), None);
+    }
+}
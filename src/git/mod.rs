@@ -1,13 +1,18 @@
+#[cfg(feature = "git-blame")]
+mod blame;
 mod ignore;
 mod status;
 mod status_computer;
 
 pub use {
     ignore::{GitIgnoreChain, GitIgnorer},
-    status::{LineGitStatus, LineStatusComputer, TreeGitStatus},
+    status::{short_status_code, LineGitStatus, LineStatusComputer, TreeGitStatus},
     status_computer::{clear_status_computer_cache, get_tree_status},
 };
 
+#[cfg(feature = "git-blame")]
+pub use blame::line_author;
+
 use std::path::{Path, PathBuf};
 
 /// return the closest parent (or self) containing a .git file
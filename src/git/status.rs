@@ -26,6 +26,50 @@ impl LineGitStatus {
     pub fn is_interesting(self) -> bool {
         self.status.intersects(INTERESTING)
     }
+    /// return a short code, similar to the ones of `git status --short`:
+    /// "M", "A", "D", "R", "U" (conflicted), "??" (untracked), "!!" (ignored),
+    /// or an empty string when the file is clean
+    pub fn short_code(self) -> &'static str {
+        let status = self.status;
+        if status.is_conflicted() {
+            "U"
+        } else if status.is_ignored() {
+            "!!"
+        } else if status.is_wt_new() {
+            "??"
+        } else if status.is_index_new() {
+            "A"
+        } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+            "D"
+        } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+            "R"
+        } else if status.intersects(
+            Status::INDEX_MODIFIED | Status::WT_MODIFIED
+            | Status::INDEX_TYPECHANGE | Status::WT_TYPECHANGE
+        ) {
+            "M"
+        } else {
+            ""
+        }
+    }
+}
+
+/// return the short git status code of a file (see `LineGitStatus::short_code`),
+/// or an empty string when it's clean or not in a repository
+pub fn short_status_code(path: &Path) -> &'static str {
+    let path = match path.canonicalize() {
+        Ok(path) => path,
+        Err(_) => return "",
+    };
+    let repo = match Repository::discover(&path) {
+        Ok(repo) => repo,
+        Err(_) => return "",
+    };
+    repo.workdir()
+        .and_then(|workdir| workdir.canonicalize().ok())
+        .and_then(|workdir| path.strip_prefix(workdir).ok().map(Path::to_path_buf))
+        .and_then(|relative_path| LineGitStatus::from(&repo, &relative_path))
+        .map_or("", LineGitStatus::short_code)
 }
 
 pub struct LineStatusComputer {
@@ -98,3 +142,42 @@ impl TreeGitStatus {
         })
     }
 }
+
+#[cfg(test)]
+mod status_tests {
+
+    use {
+        super::*,
+        std::fs,
+    };
+
+    #[test]
+    fn test_short_status_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo = Repository::init(root).unwrap();
+        fs::write(root.join("clean.txt"), "clean").unwrap();
+        fs::write(root.join("modified.txt"), "before").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("clean.txt")).unwrap();
+        index.add_path(Path::new("modified.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[]).unwrap();
+        fs::write(root.join("modified.txt"), "after").unwrap();
+        fs::write(root.join("untracked.txt"), "new").unwrap();
+        fs::write(root.join("added.txt"), "added").unwrap();
+        index.add_path(Path::new("added.txt")).unwrap();
+        index.write().unwrap();
+        assert_eq!(short_status_code(&root.join("clean.txt")), "");
+        assert_eq!(short_status_code(&root.join("modified.txt")), "M");
+        assert_eq!(short_status_code(&root.join("untracked.txt")), "??");
+        assert_eq!(short_status_code(&root.join("added.txt")), "A");
+        assert_eq!(short_status_code(&root.join("missing.txt")), "");
+        // outside of any repository
+        let other_dir = tempfile::tempdir().unwrap();
+        fs::write(other_dir.path().join("alone.txt"), "alone").unwrap();
+        assert_eq!(short_status_code(&other_dir.path().join("alone.txt")), "");
+    }
+}
@@ -9,6 +9,7 @@ use {
         launchable::Launchable,
         pattern::*,
         skin::PanelSkin,
+        stage::Stage,
         tree::TreeOptions,
         verb::*,
     },
@@ -98,6 +99,7 @@ impl AppState for HelpState {
         screen: Screen,
         state_area: Area,
         panel_skin: &PanelSkin,
+        _stage: &Stage,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
         let mut text_area = state_area.clone();
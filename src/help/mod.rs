@@ -3,5 +3,9 @@ mod help_features;
 mod help_search_modes;
 mod help_state;
 mod help_verbs;
+mod palette_state;
 
-pub use help_state::HelpState;
+pub use {
+    help_state::HelpState,
+    palette_state::PaletteState,
+};
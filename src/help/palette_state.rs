@@ -0,0 +1,364 @@
+use {
+    crate::{
+        app::*,
+        command::{Command, Sequence, TriggerType},
+        display::{CropWriter, Screen, SPACE_FILLING, W},
+        errors::ProgramError,
+        pattern::*,
+        skin::PanelSkin,
+        stage::Stage,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::path::{Path, PathBuf},
+    termimad::Area,
+};
+
+/// return the indexes of the verbs matching the pattern, either by one
+/// of their names or by their description, the best matching first.
+///
+/// Verbs without name, which can't be invoked from the palette, and verbs
+/// not applying to the given selection type are excluded.
+pub fn filter_verbs(verbs: &[Verb], pattern: &Pattern, stype: SelectionType) -> Vec<usize> {
+    let mut scored: Vec<(usize, i32)> = verbs
+        .iter()
+        .enumerate()
+        .filter(|(_, verb)| !verb.names.is_empty())
+        .filter(|(_, verb)| stype.respects(verb.selection_condition))
+        .filter_map(|(idx, verb)| {
+            if pattern.is_none() {
+                return Some((idx, 0));
+            }
+            verb.names
+                .iter()
+                .map(|name| name.as_str())
+                .chain(std::iter::once(verb.description.content.as_str()))
+                .filter_map(|s| pattern.score_of_string(s))
+                .max()
+                .map(|score| (idx, score))
+        })
+        .collect();
+    // the sort is stable: verbs with the same score stay in the store's order
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(idx, _)| idx).collect()
+}
+
+/// an application state listing all the verbs, filtered with
+/// the input, and executing the chosen one on the selection of
+/// the previous state
+pub struct PaletteState {
+    path: PathBuf, // the selection of the previous state
+    stype: SelectionType,
+    is_exe: bool,
+    line: usize,
+    pattern: Pattern,
+    rows: Vec<usize>, // indexes of the matching verbs in the verb store
+    selection_idx: usize, // index in rows
+    scroll: usize,
+    page_height: usize,
+    tree_options: TreeOptions,
+    dirty: bool,
+}
+
+impl PaletteState {
+    pub fn new(
+        sel: Selection<'_>,
+        tree_options: TreeOptions,
+        con: &AppContext,
+    ) -> PaletteState {
+        let rows = filter_verbs(&con.verb_store.verbs, &Pattern::None, sel.stype);
+        PaletteState {
+            path: sel.path.to_path_buf(),
+            stype: sel.stype,
+            is_exe: sel.is_exe,
+            line: sel.line,
+            pattern: Pattern::None,
+            rows,
+            selection_idx: 0,
+            scroll: 0,
+            page_height: 0,
+            tree_options,
+            dirty: true,
+        }
+    }
+
+    fn move_selection(&mut self, dy: i32) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let max = self.rows.len() as i32 - 1;
+        self.selection_idx = (self.selection_idx as i32 + dy).max(0).min(max) as usize;
+        if self.selection_idx < self.scroll {
+            self.scroll = self.selection_idx;
+        } else if self.page_height > 0 && self.selection_idx >= self.scroll + self.page_height {
+            self.scroll = self.selection_idx + 1 - self.page_height;
+        }
+    }
+
+    /// build the result executing the selected verb on the selection
+    /// of the previous state
+    fn execute_selected_verb(
+        &self,
+        other_path: &Option<PathBuf>,
+        con: &AppContext,
+    ) -> AppStateCmdResult {
+        let verb = match self.rows.get(self.selection_idx) {
+            Some(&idx) => &con.verb_store.verbs[idx],
+            None => {
+                return AppStateCmdResult::DisplayError("no matching verb".to_string());
+            }
+        };
+        let name = &verb.names[0];
+        let invocation = VerbInvocation::from(name.as_str());
+        if let Some(err) = verb.check_args(&invocation, other_path) {
+            return AppStateCmdResult::DisplayError(format!(
+                "{} (type `:{} ...` to give the arguments)",
+                err,
+                name,
+            ));
+        }
+        // we close the palette, then execute the verb on the previous state
+        AppStateCmdResult::ExecuteSequence {
+            sequence: Sequence::new(format!(":back;:{}", name), Some(";".to_string())),
+        }
+    }
+}
+
+impl AppState for PaletteState {
+
+    fn selected_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.path,
+            stype: self.stype,
+            is_exe: self.is_exe,
+            line: self.line,
+        }
+    }
+
+    fn tree_options(&self) -> TreeOptions {
+        self.tree_options.clone()
+    }
+
+    fn with_new_options(
+        &mut self,
+        _screen: Screen,
+        change_options: &dyn Fn(&mut TreeOptions),
+        _in_new_panel: bool,
+        _con: &AppContext,
+    ) -> AppStateCmdResult {
+        change_options(&mut self.tree_options);
+        AppStateCmdResult::Keep
+    }
+
+    fn refresh(&mut self, _screen: Screen, _con: &AppContext) -> Command {
+        self.dirty = true;
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pat: InputPattern,
+        con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = pat.pattern;
+        self.rows = filter_verbs(&con.verb_store.verbs, &self.pattern, self.stype);
+        self.selection_idx = 0;
+        self.scroll = 0;
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _stage: &Stage,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height < 2 {
+            warn!("area too small for the palette");
+            return Ok(());
+        }
+        if self.dirty {
+            panel_skin.styles.default.queue_bg(w)?;
+            screen.clear_area_to_right(w, &state_area)?;
+            self.dirty = false;
+        }
+        let styles = &panel_skin.styles;
+        let mut selected_style = styles.default.clone();
+        if let Some(c) = styles.selected_line.get_bg() {
+            selected_style.set_bg(c);
+        }
+        let width = state_area.width as usize;
+        let verbs = &con.verb_store.verbs;
+        let names_width = self.rows.iter()
+            .map(|&idx| verbs[idx].names.join(", ").chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(width / 2);
+        w.queue(cursor::MoveTo(state_area.left, state_area.top))?;
+        let mut cw = CropWriter::new(w, width);
+        cw.queue_str(
+            &styles.help_headers,
+            &format!("{} verbs - enter to execute", self.rows.len()),
+        )?;
+        cw.fill(&styles.default, &SPACE_FILLING)?;
+        self.page_height = state_area.height as usize - 1;
+        for y in 0..self.page_height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + 1 + y as u16))?;
+            let mut cw = CropWriter::new(w, width);
+            let row_idx = self.scroll + y;
+            let style = if row_idx == self.selection_idx {
+                &selected_style
+            } else {
+                &styles.default
+            };
+            if let Some(&idx) = self.rows.get(row_idx) {
+                let verb = &verbs[idx];
+                let names = verb.names.join(", ");
+                cw.queue_str(style, &format!("{:<1$}", names, names_width))?;
+                cw.queue_str(style, "  ")?;
+                if !verb.keys_desc.is_empty() {
+                    cw.queue_str(style, &verb.keys_desc)?;
+                    cw.queue_str(style, "  ")?;
+                }
+                cw.queue_str(style, &verb.description.content)?;
+            }
+            cw.fill(style, &SPACE_FILLING)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        Status::from_message(
+            "Type to filter the verbs, *enter* to execute the selected one, *esc* to close"
+        )
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        Ok(match internal_exec.internal {
+            Internal::commands => AppStateCmdResult::Keep,
+            Internal::line_down => {
+                self.move_selection(get_arg(input_invocation, internal_exec, 1));
+                AppStateCmdResult::Keep
+            }
+            Internal::line_up => {
+                self.move_selection(-get_arg(input_invocation, internal_exec, 1));
+                AppStateCmdResult::Keep
+            }
+            Internal::page_down => {
+                self.move_selection(self.page_height as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::page_up => {
+                self.move_selection(-(self.page_height as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_first => {
+                self.move_selection(-(self.rows.len() as i32));
+                AppStateCmdResult::Keep
+            }
+            Internal::select_last => {
+                self.move_selection(self.rows.len() as i32);
+                AppStateCmdResult::Keep
+            }
+            Internal::open_stay | Internal::open_leave => {
+                self.execute_selected_verb(cc.other_path, cc.con)
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod palette_state_tests {
+
+    use {
+        super::*,
+        crate::conf::Conf,
+    };
+
+    fn verbs() -> Vec<Verb> {
+        let mut verb_store = VerbStore::default();
+        verb_store.init(&mut Conf::default()).unwrap();
+        verb_store.verbs
+    }
+
+    fn first_names(verbs: &[Verb], pattern: &str) -> Vec<String> {
+        let pattern = if pattern.is_empty() {
+            Pattern::None
+        } else {
+            Pattern::NameFuzzy(FuzzyPattern::from(pattern))
+        };
+        filter_verbs(verbs, &pattern, SelectionType::Any)
+            .into_iter()
+            .map(|idx| verbs[idx].names[0].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_verbs() {
+        let verbs = verbs();
+        // without pattern, all named verbs are listed in the store's order
+        let all = first_names(&verbs, "");
+        let applicable = verbs.iter()
+            .filter(|v| !v.names.is_empty())
+            .filter(|v| SelectionType::Any.respects(v.selection_condition))
+            .count();
+        assert_eq!(all.len(), applicable);
+        assert_eq!(all[0], verbs[0].names[0]);
+        // the best match on a name comes first
+        let found = first_names(&verbs, "toggle_hid");
+        assert_eq!(found[0], "toggle_hidden");
+        // shortcuts are searched too
+        let found = first_names(&verbs, "sizes");
+        assert_eq!(found[0], "toggle_sizes");
+        // and descriptions
+        let found = first_names(&verbs, "mounted");
+        assert!(found.iter().any(|name| name == "filesystems"));
+        // filtering is fuzzy and case insensitive
+        let found = first_names(&verbs, "TOGHID");
+        assert!(found.iter().any(|name| name == "toggle_hidden"));
+        assert!(!found.iter().any(|name| name == "toggle_sizes"));
+        // nothing is found for a pattern matching nothing
+        assert!(first_names(&verbs, "zzzzzzzz").is_empty());
+    }
+
+    #[test]
+    fn test_filter_verbs_by_selection_type() {
+        let verbs = verbs();
+        let for_files = filter_verbs(&verbs, &Pattern::None, SelectionType::File);
+        assert!(for_files
+            .iter()
+            .all(|&idx| SelectionType::File.respects(verbs[idx].selection_condition)));
+        assert!(for_files.len() < verbs.len());
+    }
+}
@@ -0,0 +1,133 @@
+use {
+    crate::pattern::InputPattern,
+};
+
+/// a sequence of bytes searched in a hex view, given either
+/// in hexadecimal with a `0x` prefix (eg `0x89504e47`) or as
+/// a string whose exact bytes are searched
+#[derive(Debug, Clone)]
+pub struct HexPattern {
+    pub pattern: InputPattern,
+    needle: Vec<u8>,
+}
+
+/// parse hexadecimal digits into bytes, None if they're
+/// not valid or not in an even number
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if s.is_empty() || !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+impl HexPattern {
+    /// build the pattern from the raw input, None if
+    /// there's nothing to search
+    pub fn new(pattern: InputPattern) -> Option<Self> {
+        let raw = &pattern.raw;
+        let needle = match raw.strip_prefix("0x") {
+            Some(digits) => parse_hex(digits).unwrap_or_else(|| raw.as_bytes().to_vec()),
+            None => raw.as_bytes().to_vec(),
+        };
+        if needle.is_empty() {
+            None
+        } else {
+            Some(Self { pattern, needle })
+        }
+    }
+    pub fn byte_count(&self) -> usize {
+        self.needle.len()
+    }
+    fn is_at(&self, hay: &[u8], pos: usize) -> bool {
+        hay[pos..pos + self.needle.len()] == self.needle[..]
+    }
+    /// return the position of the first occurence
+    /// starting at or after `start`
+    pub fn find_from(&self, hay: &[u8], start: usize) -> Option<usize> {
+        if hay.len() < self.needle.len() {
+            return None;
+        }
+        let max_pos = hay.len() - self.needle.len();
+        let first = self.needle[0];
+        let mut pos = start;
+        while pos <= max_pos {
+            pos += hay[pos..=max_pos].iter().position(|&b| b == first)?;
+            if self.is_at(hay, pos) {
+                return Some(pos);
+            }
+            pos += 1;
+        }
+        None
+    }
+    /// return the position of the last occurence
+    /// starting strictly before `end`
+    pub fn rfind_before(&self, hay: &[u8], end: usize) -> Option<usize> {
+        if hay.len() < self.needle.len() {
+            return None;
+        }
+        let first = self.needle[0];
+        let mut end = end.min(hay.len() - self.needle.len() + 1);
+        while end > 0 {
+            let pos = hay[..end].iter().rposition(|&b| b == first)?;
+            if self.is_at(hay, pos) {
+                return Some(pos);
+            }
+            end = pos;
+        }
+        None
+    }
+    /// return the position of the first occurence
+    /// starting in `[start, end)`
+    pub fn find_in(&self, hay: &[u8], start: usize, end: usize) -> Option<usize> {
+        let hay_end = (end + self.needle.len() - 1).min(hay.len());
+        self.find_from(&hay[..hay_end], start)
+    }
+    /// return the position of the last occurence
+    /// starting in `[start, end)`
+    pub fn rfind_in(&self, hay: &[u8], start: usize, end: usize) -> Option<usize> {
+        self.rfind_before(&hay[start..], end - start)
+            .map(|pos| pos + start)
+    }
+}
+
+#[cfg(test)]
+mod hex_pattern_tests {
+
+    use super::*;
+
+    fn pattern(raw: &str) -> HexPattern {
+        let input_pattern = InputPattern {
+            raw: raw.to_string(),
+            ..InputPattern::none()
+        };
+        HexPattern::new(input_pattern).unwrap()
+    }
+
+    #[test]
+    fn test_hex_pattern_search() {
+        let hay = b"\x89PNG\r\n\x1a\nIHDR and more IHDR";
+        let png = pattern("0x89504e47");
+        assert_eq!(png.byte_count(), 4);
+        assert_eq!(png.find_from(hay, 0), Some(0));
+        assert_eq!(png.find_from(hay, 1), None);
+        let ihdr = pattern("IHDR");
+        assert_eq!(ihdr.find_from(hay, 0), Some(8));
+        assert_eq!(ihdr.find_from(hay, 9), Some(22));
+        assert_eq!(ihdr.rfind_before(hay, hay.len()), Some(22));
+        assert_eq!(ihdr.rfind_before(hay, 22), Some(8));
+        assert_eq!(ihdr.rfind_before(hay, 8), None);
+        assert_eq!(ihdr.find_in(hay, 0, 8), None);
+        assert_eq!(ihdr.find_in(hay, 0, 9), Some(8));
+        assert_eq!(ihdr.find_in(hay, 9, 23), Some(22));
+        assert_eq!(ihdr.rfind_in(hay, 9, 22), None);
+        assert_eq!(ihdr.rfind_in(hay, 8, 22), Some(8));
+        assert_eq!(ihdr.rfind_in(hay, 0, hay.len()), Some(22));
+        // not valid hexadecimal: searched as a string
+        let s = pattern("0xZZ");
+        assert_eq!(s.byte_count(), 4);
+        assert!(HexPattern::new(InputPattern::none()).is_none());
+    }
+}
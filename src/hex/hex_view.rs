@@ -1,10 +1,15 @@
 use {
-    super::byte::Byte,
+    super::{
+        byte::Byte,
+        HexPattern,
+    },
     crate::{
         command::ScrollCommand,
         display::{CropWriter, Screen, SPACE_FILLING, W},
         errors::ProgramError,
+        pattern::InputPattern,
         skin::PanelSkin,
+        task_sync::Dam,
     },
     crossterm::{
         cursor,
@@ -20,17 +25,73 @@ use {
     termimad::{Area},
 };
 
+/// the number of positions searched between two checks of the dam
+const SEARCH_CHUNK_SIZE: usize = 1 << 20;
 
 pub struct HexLine {
     pub bytes: Vec<u8>, // from 1 to 16 bytes
 }
 
-/// a preview showing the content of a file in hexa
+/// a preview showing the content of a file in hexa.
+///
+/// Only the displayed lines are read, which makes it
+/// fast even on huge files.
 pub struct HexView {
     path: PathBuf,
     len: usize,
     scroll: usize,
     page_height: usize,
+    search: Option<HexPattern>,
+    match_pos: Option<usize>, // position of the selected occurence of the search
+    match_search: Option<MatchSearch>, // search of an occurence, not yet done
+}
+
+/// the search of an occurence of the pattern, looping over
+/// the file from a start position, and done chunk by chunk
+/// so that it can be interrupted
+struct MatchSearch {
+    forward: bool,
+    start: usize, // when going backward, the search starts before it
+    scanned: usize, // number of positions already searched
+}
+
+impl MatchSearch {
+    /// search the next chunk of positions, return `Ok(pos)` when the
+    /// search is over, `Err(())` when there remain positions to search
+    fn search_chunk(&mut self, search: &HexPattern, hay: &[u8]) -> Result<Option<usize>, ()> {
+        let len = hay.len();
+        if self.scanned >= len {
+            return Ok(None);
+        }
+        let count = SEARCH_CHUNK_SIZE.min(len - self.scanned);
+        let found = if self.forward {
+            let start = (self.start + self.scanned) % len;
+            let end = start + count;
+            if end <= len {
+                search.find_in(hay, start, end)
+            } else {
+                search.find_in(hay, start, len)
+                    .or_else(|| search.find_in(hay, 0, end - len)) // looping
+            }
+        } else {
+            let end = match (self.start + len - self.scanned % len) % len {
+                0 => len,
+                end => end,
+            };
+            if end >= count {
+                search.rfind_in(hay, end - count, end)
+            } else {
+                search.rfind_in(hay, 0, end)
+                    .or_else(|| search.rfind_in(hay, len + end - count, len)) // looping
+            }
+        };
+        self.scanned += count;
+        match found {
+            Some(pos) => Ok(Some(pos)),
+            None if self.scanned >= len => Ok(None),
+            None => Err(()),
+        }
+    }
 }
 
 impl HexView {
@@ -41,8 +102,110 @@ impl HexView {
             len,
             scroll: 0,
             page_height: 0,
+            search: None,
+            match_pos: None,
+            match_search: None,
         })
     }
+    /// build a view of the same file, where the first occurence
+    /// of the searched bytes is to be selected by `do_match_search`
+    pub fn searched(&self, search: HexPattern) -> io::Result<Self> {
+        let mut hv = Self::new(self.path.clone())?;
+        hv.page_height = self.page_height;
+        hv.search = Some(search);
+        hv.match_search = Some(MatchSearch {
+            forward: true,
+            start: 0,
+            scanned: 0,
+        });
+        Ok(hv)
+    }
+    pub fn has_match_search(&self) -> bool {
+        self.match_search.is_some()
+    }
+    /// search the occurence to select, until it's found or
+    /// the dam gets an event
+    pub fn do_match_search(&mut self, dam: &mut Dam) {
+        if self.match_search.is_none() {
+            return;
+        }
+        let mmap = match self.mmap() {
+            Ok(Some(mmap)) => mmap,
+            Ok(None) => {
+                self.match_search = None;
+                return;
+            }
+            Err(e) => {
+                warn!("error while searching {:?} : {:?}", self.path, e);
+                self.match_search = None;
+                return;
+            }
+        };
+        let found = match (&self.search, &mut self.match_search) {
+            (Some(search), Some(match_search)) => loop {
+                if let Ok(pos) = match_search.search_chunk(search, &mmap) {
+                    break pos;
+                }
+                if dam.has_event() {
+                    return; // the search goes on at the next call
+                }
+            },
+            _ => return,
+        };
+        self.match_search = None;
+        if found.is_some() || self.match_pos.is_none() {
+            self.match_pos = found;
+            self.show_match();
+        }
+    }
+    pub fn pattern(&self) -> InputPattern {
+        self.search
+            .as_ref()
+            .map_or_else(InputPattern::none, |search| search.pattern.clone())
+    }
+    fn mmap(&self) -> io::Result<Option<Mmap>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        let file = File::open(&self.path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Some(mmap))
+    }
+    /// scroll so that the selected match is visible
+    fn show_match(&mut self) {
+        if let Some(pos) = self.match_pos {
+            let line_idx = pos / 16;
+            if line_idx < self.scroll || line_idx >= self.scroll + self.page_height {
+                self.scroll = line_idx.saturating_sub(self.page_height / 3);
+            }
+        }
+    }
+    /// start the search of the next (or previous) occurence of
+    /// the searched bytes, return false when there's no search
+    pub fn try_select_match(&mut self, forward: bool) -> bool {
+        if self.search.is_none() || self.len == 0 {
+            return false;
+        }
+        let start = match (forward, self.match_pos) {
+            (true, Some(pos)) => pos + 1,
+            (false, Some(pos)) => pos,
+            (_, None) => 0,
+        };
+        self.match_search = Some(MatchSearch {
+            forward,
+            start,
+            scanned: 0,
+        });
+        true
+    }
+    /// tell whether the byte at the given position is part
+    /// of the selected match
+    fn is_in_match(&self, pos: usize) -> bool {
+        match (self.match_pos, &self.search) {
+            (Some(start), Some(search)) => start <= pos && pos < start + search.byte_count(),
+            _ => false,
+        }
+    }
     pub fn line_count(&self) -> usize {
         self.len / 16 + if self.len % 16 != 0 { 1 } else { 0 }
     }
@@ -67,12 +230,13 @@ impl HexView {
         start_line_idx: usize,
         line_count: usize,
     ) -> io::Result<Vec<HexLine>> {
-        let file = File::open(&self.path)?;
         let mut lines = Vec::new();
-        if self.len == 0 {
-            return Ok(lines);
-        }
-        let mmap = unsafe { Mmap::map(&file)? };
+        let mmap = match self.mmap()? {
+            Some(mmap) => mmap,
+            None => {
+                return Ok(lines);
+            }
+        };
         let new_len = mmap.len();
         if new_len != self.len {
             warn!("previewed file len changed from {} to {}", self.len, new_len);
@@ -172,16 +336,27 @@ impl HexView {
                     cw.queue_char(&styles.default, ' ')?;
                 }
                 let line = &page[y];
+                let line_start = (self.scroll + y) * 16;
+                let byte_style = |x: usize, byte: Byte| {
+                    let mut style = byte.style(styles).clone();
+                    if self.is_in_match(line_start + x) {
+                        if let Some(c) = styles.preview_match.get_bg() {
+                            style.set_bg(c);
+                        }
+                    }
+                    style
+                };
                 for x in 0..16 {
                     if x == 8 && hex_middle_space {
                         cw.queue_char(&styles.default, ' ')?;
                     }
                     if let Some(b) = line.bytes.get(x) {
                         let byte = Byte::from(*b);
+                        let style = byte_style(x, byte);
                         if inter_hex {
-                            cw.queue_g_string(byte.style(styles), format!("{:02x} ", b))?;
+                            cw.queue_g_string(&style, format!("{:02x} ", b))?;
                         } else {
-                            cw.queue_g_string(byte.style(styles), format!("{:02x}", b))?;
+                            cw.queue_g_string(&style, format!("{:02x}", b))?;
                         }
                     } else {
                         cw.queue_str(&styles.default, if inter_hex { "   " } else { "  " })?;
@@ -195,7 +370,7 @@ impl HexView {
                         }
                         if let Some(b) = line.bytes.get(x) {
                             let byte = Byte::from(*b);
-                            cw.queue_char(byte.style(styles), byte.as_char())?;
+                            cw.queue_char(&byte_style(x, byte), byte.as_char())?;
                         }
                     }
                 }
@@ -219,7 +394,12 @@ impl HexView {
         area: &Area,
     ) -> Result<(), ProgramError> {
         let width = area.width as usize;
-        let mut s = format!("{}", self.len);
+        let mut s = match (&self.search, self.match_pos) {
+            _ if self.match_search.is_some() => format!("searching - {}", self.len),
+            (Some(_), Some(pos)) => format!("match at {:x} - {}", pos, self.len),
+            (Some(_), None) => format!("not found - {}", self.len),
+            (None, _) => format!("{}", self.len),
+        };
         if s.len() > width {
             return Ok(());
         }
@@ -246,3 +426,54 @@ fn is_thumb(y: usize, scrollbar: Option<(u16, u16)>) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod hex_view_tests {
+
+    use super::*;
+
+    fn search(raw: &str) -> HexPattern {
+        let input_pattern = InputPattern {
+            raw: raw.to_string(),
+            ..InputPattern::none()
+        };
+        HexPattern::new(input_pattern).unwrap()
+    }
+
+    #[test]
+    fn test_match_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.bin");
+        let mut content = vec![0u8; 3 * SEARCH_CHUNK_SIZE];
+        content[10..14].copy_from_slice(b"IHDR");
+        // an occurence across the limit of two chunks
+        content[2 * SEARCH_CHUNK_SIZE - 2..2 * SEARCH_CHUNK_SIZE + 2].copy_from_slice(b"IHDR");
+        std::fs::write(&path, &content).unwrap();
+        let mut dam = Dam::unlimited();
+        let mut hv = HexView::new(path).unwrap()
+            .searched(search("IHDR")).unwrap();
+        assert!(hv.has_match_search());
+        hv.do_match_search(&mut dam);
+        assert!(!hv.has_match_search());
+        assert_eq!(hv.match_pos, Some(10));
+        assert!(hv.try_select_match(true));
+        hv.do_match_search(&mut dam);
+        assert_eq!(hv.match_pos, Some(2 * SEARCH_CHUNK_SIZE - 2));
+        // looping forward
+        assert!(hv.try_select_match(true));
+        hv.do_match_search(&mut dam);
+        assert_eq!(hv.match_pos, Some(10));
+        // looping backward
+        assert!(hv.try_select_match(false));
+        hv.do_match_search(&mut dam);
+        assert_eq!(hv.match_pos, Some(2 * SEARCH_CHUNK_SIZE - 2));
+        assert!(hv.try_select_match(false));
+        hv.do_match_search(&mut dam);
+        assert_eq!(hv.match_pos, Some(10));
+        // nothing found
+        let mut hv = hv.searched(search("PNG")).unwrap();
+        hv.do_match_search(&mut dam);
+        assert_eq!(hv.match_pos, None);
+        assert!(!hv.has_match_search());
+    }
+}
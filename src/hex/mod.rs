@@ -1,6 +1,10 @@
 
 
 mod byte;
+mod hex_pattern;
 mod hex_view;
 
-pub use hex_view::HexView;
+pub use {
+    hex_pattern::HexPattern,
+    hex_view::HexView,
+};
@@ -0,0 +1,98 @@
+use {
+    std::{
+        env,
+        str::FromStr,
+    },
+};
+
+/// The way images are drawn in the terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphicsProtocol {
+    /// the kitty graphics protocol, the images being
+    /// drawn over the text
+    Kitty,
+    /// the inline images of iTerm2 (also understood by WezTerm)
+    Iterm2,
+    /// DEC sixels, as supported by xterm, mlterm, foot, etc.
+    Sixel,
+    /// no real image but colored half blocks, which works
+    /// everywhere
+    Blocks,
+}
+
+impl FromStr for GraphicsProtocol {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "kitty" => Ok(Self::Kitty),
+            "iterm2" | "iterm" => Ok(Self::Iterm2),
+            "sixel" | "sixels" => Ok(Self::Sixel),
+            "blocks" | "none" => Ok(Self::Blocks),
+            _ => Err(format!("unknown graphics protocol: {:?}", s)),
+        }
+    }
+}
+
+impl GraphicsProtocol {
+    /// determine the protocol from the values of the `TERM` and
+    /// `TERM_PROGRAM` environment variables.
+    ///
+    /// There's no reliable way to query the terminal without
+    /// waiting for an answer it may never give, so we just
+    /// recognize the terminals we know.
+    pub fn from_term(term: &str, term_program: &str) -> Self {
+        if term.contains("kitty") {
+            Self::Kitty
+        } else if term_program == "iTerm.app" || term_program == "WezTerm" {
+            Self::Iterm2
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.starts_with("yaft")
+            || term.starts_with("contour")
+            || term_program == "mintty"
+        {
+            Self::Sixel
+        } else {
+            Self::Blocks
+        }
+    }
+    /// determine the protocol to use in the current terminal,
+    /// the `BROOT_IMAGE_PROTOCOL` environment variable, when set,
+    /// overriding the detection
+    pub fn detect() -> Self {
+        if let Ok(s) = env::var("BROOT_IMAGE_PROTOCOL") {
+            match s.parse() {
+                Ok(protocol) => {
+                    return protocol;
+                }
+                Err(e) => {
+                    warn!("{} in BROOT_IMAGE_PROTOCOL", e);
+                }
+            }
+        }
+        let term = env::var("TERM").unwrap_or_default();
+        let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+        let protocol = Self::from_term(&term, &term_program);
+        debug!("detected graphics protocol: {:?}", protocol);
+        protocol
+    }
+}
+
+#[cfg(test)]
+mod graphics_protocol_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_protocol_detection() {
+        assert_eq!(GraphicsProtocol::from_term("xterm-kitty", ""), GraphicsProtocol::Kitty);
+        assert_eq!(GraphicsProtocol::from_term("xterm-256color", "iTerm.app"), GraphicsProtocol::Iterm2);
+        assert_eq!(GraphicsProtocol::from_term("xterm-256color", "WezTerm"), GraphicsProtocol::Iterm2);
+        assert_eq!(GraphicsProtocol::from_term("foot", ""), GraphicsProtocol::Sixel);
+        assert_eq!(GraphicsProtocol::from_term("mlterm", ""), GraphicsProtocol::Sixel);
+        assert_eq!(GraphicsProtocol::from_term("xterm-256color", "vscode"), GraphicsProtocol::Blocks);
+        assert_eq!("Sixel".parse(), Ok(GraphicsProtocol::Sixel));
+        assert!("png".parse::<GraphicsProtocol>().is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use {
+    super::{
+        GraphicsProtocol,
+        Iterm2ImageRenderer,
+        SixelImageRenderer,
+    },
+    crate::{
+        display::{
+            cell_size_in_pixels,
+            W,
+        },
+        errors::ProgramError,
+        kitty::KittyImageRenderer,
+    },
+    image::DynamicImage,
+    std::sync::Mutex,
+    termimad::Area,
+};
+
+/// A backend drawing real images in the terminal, which
+/// needs the dimensions of the cells in pixels
+pub trait ImageRenderer: Send {
+    /// draw the image, keeping its ratio, centered in the area
+    fn print(
+        &mut self,
+        w: &mut W,
+        src: &DynamicImage,
+        area: &Area,
+    ) -> Result<(), ProgramError>;
+    /// return the ids of the displayed images which must be erased
+    /// once the screen is redrawn, when they're not removed by
+    /// writing over them
+    fn take_current_images(&mut self) -> Option<Vec<usize>> {
+        None
+    }
+    fn erase(
+        &mut self,
+        _w: &mut W,
+        _ids: Vec<usize>,
+    ) -> Result<(), ProgramError> {
+        Ok(())
+    }
+}
+
+lazy_static! {
+    static ref RENDERER: Option<Mutex<Box<dyn ImageRenderer>>> = new_renderer().map(Mutex::new);
+}
+
+fn new_renderer() -> Option<Box<dyn ImageRenderer>> {
+    let protocol = GraphicsProtocol::detect();
+    if protocol == GraphicsProtocol::Blocks {
+        return None;
+    }
+    let (cell_width, cell_height) = match cell_size_in_pixels() {
+        Ok(dim) => dim,
+        Err(e) => {
+            warn!("no image rendering with {:?}: {}", protocol, e);
+            return None;
+        }
+    };
+    match protocol {
+        GraphicsProtocol::Kitty => Some(Box::new(KittyImageRenderer::new(cell_width, cell_height))),
+        GraphicsProtocol::Iterm2 => Some(Box::new(Iterm2ImageRenderer::new(cell_width, cell_height))),
+        GraphicsProtocol::Sixel => Some(Box::new(SixelImageRenderer::new(cell_width, cell_height))),
+        GraphicsProtocol::Blocks => None,
+    }
+}
+
+// TODO try to find another way (making app_context mut ?) to pass this
+// around without the mutex gymnastic, and also to make it really lazy
+// (ie only initialized when an image must be rendered)
+/// return the renderer of real images, or None when the terminal
+/// isn't known to support any graphics protocol, in which case
+/// images are drawn with half blocks
+pub fn image_renderer() -> &'static Option<Mutex<Box<dyn ImageRenderer>>> {
+    &RENDERER
+}
+
+/// compute the dimensions, in cells, of an image displayed with
+/// at most one image pixel per screen pixel and fitting the area
+pub fn rendering_dim(
+    cell_width: u32,
+    cell_height: u32,
+    img_width: u32,
+    img_height: u32,
+    area_cols: u32,
+    area_rows: u32,
+) -> (u32, u32) {
+    let optimal_cols = img_width.div_ceil(cell_width);
+    let optimal_rows = img_height.div_ceil(cell_height);
+    debug!("area: {:?}", (area_cols, area_rows));
+    debug!("optimal: {:?}", (optimal_cols, optimal_rows));
+    if optimal_cols <= area_cols && optimal_rows <= area_rows {
+        // no constraint
+        (optimal_cols, optimal_rows)
+    } else if optimal_cols * area_rows > optimal_rows * area_cols {
+        // we're constrained in width
+        debug!("constrained in width");
+        (area_cols, (optimal_rows * area_cols / optimal_cols).max(1))
+    } else {
+        // we're constrained in height
+        debug!("constrained in height");
+        ((optimal_cols * area_rows / optimal_rows).max(1), area_rows)
+    }
+}
+
+/// compute the area, centered in the available one, where
+/// an image should be drawn
+pub fn rendering_area(
+    cell_width: u32,
+    cell_height: u32,
+    img_width: u32,
+    img_height: u32,
+    area: &Area,
+) -> Area {
+    let area_cols: u32 = area.width.into();
+    let area_rows: u32 = area.height.into();
+    let rdim = rendering_dim(cell_width, cell_height, img_width, img_height, area_cols, area_rows);
+    Area::new(
+        area.left + ((area_cols - rdim.0) / 2) as u16,
+        area.top + ((area_rows - rdim.1) / 2) as u16,
+        rdim.0 as u16,
+        rdim.1 as u16,
+    )
+}
+
+#[cfg(test)]
+mod image_renderer_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rendering_dim() {
+        // small image: one image pixel per screen pixel
+        assert_eq!(rendering_dim(10, 20, 95, 40, 80, 30), (10, 2));
+        // wide image, constrained in width
+        assert_eq!(rendering_dim(10, 20, 1600, 400, 80, 30), (80, 10));
+        // tall image, constrained in height
+        assert_eq!(rendering_dim(10, 20, 400, 1200, 80, 30), (20, 30));
+    }
+}
@@ -75,13 +75,15 @@ impl ImageView {
             .unwrap_or(Color::AnsiValue(238));
 
         #[cfg(unix)]
-        if let Some(renderer) = crate::kitty::image_renderer() {
-            let mut renderer = renderer.lock().unwrap();
-            renderer.print(w, &self.source_img, area)?;
+        if let Some(renderer) = super::image_renderer() {
+            // the background is drawn first as writing over the cells
+            // of the image would erase it with some protocols
             for y in area.top..area.top + area.height {
                 w.queue(cursor::MoveTo(area.left, y))?;
                 fill_bg(w, area.width as usize, bg)?;
             }
+            let mut renderer = renderer.lock().unwrap();
+            renderer.print(w, &self.source_img, area)?;
             return Ok(());
         }
 
@@ -0,0 +1,80 @@
+use {
+    super::{
+        rendering_area,
+        ImageRenderer,
+    },
+    crate::{
+        display::W,
+        errors::ProgramError,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    image::{
+        imageops::FilterType,
+        DynamicImage,
+        GenericImageView,
+        ImageOutputFormat,
+    },
+    std::io::Write,
+    termimad::Area,
+};
+
+/// the image renderer for terminals supporting the inline
+/// images of iTerm2 (eg iTerm2 and WezTerm).
+///
+/// The image is sent as a base64 encoded PNG in an OSC 1337
+/// sequence and is removed by the terminal when the cells it
+/// covers are written over.
+pub struct Iterm2ImageRenderer {
+    cell_width: u32,
+    cell_height: u32,
+}
+
+impl Iterm2ImageRenderer {
+    pub fn new(cell_width: u32, cell_height: u32) -> Self {
+        Self {
+            cell_width,
+            cell_height,
+        }
+    }
+}
+
+impl ImageRenderer for Iterm2ImageRenderer {
+    fn print(
+        &mut self,
+        w: &mut W,
+        src: &DynamicImage,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        let (img_width, img_height) = src.dimensions();
+        let area = rendering_area(self.cell_width, self.cell_height, img_width, img_height, area);
+        // there's no need to send more pixels than the terminal can show
+        let max_width = area.width as u32 * self.cell_width;
+        let max_height = area.height as u32 * self.cell_height;
+        let resized;
+        let img = if img_width > max_width || img_height > max_height {
+            resized = time!(
+                Debug,
+                "resize image",
+                src.resize(max_width, max_height, FilterType::Triangle),
+            );
+            &resized
+        } else {
+            src
+        };
+        let mut png = Vec::new();
+        img.write_to(&mut png, ImageOutputFormat::Png)?;
+        w.queue(cursor::MoveTo(area.left, area.top))?;
+        write!(
+            w,
+            "\u{1b}]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\u{7}",
+            png.len(),
+            area.width,
+            area.height,
+            base64::encode(&png),
+        )?;
+        Ok(())
+    }
+}
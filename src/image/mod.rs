@@ -1,7 +1,23 @@
 
 mod double_line;
+mod graphics_protocol;
 mod image_view;
 
+#[cfg(unix)]
+mod image_renderer;
+#[cfg(unix)]
+mod iterm2;
+#[cfg(unix)]
+mod sixel;
+
 pub use {
+    graphics_protocol::GraphicsProtocol,
     image_view::ImageView,
 };
+
+#[cfg(unix)]
+pub use {
+    image_renderer::*,
+    iterm2::Iterm2ImageRenderer,
+    sixel::SixelImageRenderer,
+};
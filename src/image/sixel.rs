@@ -0,0 +1,178 @@
+use {
+    super::{
+        rendering_area,
+        ImageRenderer,
+    },
+    crate::{
+        display::W,
+        errors::ProgramError,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    image::{
+        imageops::FilterType,
+        DynamicImage,
+        GenericImageView,
+        RgbaImage,
+    },
+    std::{
+        fmt::Write as _,
+        io::Write,
+    },
+    termimad::Area,
+};
+
+/// number of levels per color component in the palette
+const LEVELS: u32 = 6;
+
+/// the image renderer for terminals supporting DEC sixels.
+///
+/// Colors are mapped to a fixed 216 colors palette, which
+/// is fast and good enough for a preview.
+pub struct SixelImageRenderer {
+    cell_width: u32,
+    cell_height: u32,
+}
+
+impl SixelImageRenderer {
+    pub fn new(cell_width: u32, cell_height: u32) -> Self {
+        Self {
+            cell_width,
+            cell_height,
+        }
+    }
+}
+
+impl ImageRenderer for SixelImageRenderer {
+    fn print(
+        &mut self,
+        w: &mut W,
+        src: &DynamicImage,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        let (img_width, img_height) = src.dimensions();
+        let area = rendering_area(self.cell_width, self.cell_height, img_width, img_height, area);
+        let img = time!(
+            Debug,
+            "resize image",
+            src.resize(
+                area.width as u32 * self.cell_width,
+                area.height as u32 * self.cell_height,
+                FilterType::Triangle,
+            ),
+        );
+        let sixels = time!(Debug, "encode sixels", encode(&img.to_rgba8()));
+        w.queue(cursor::MoveTo(area.left, area.top))?;
+        w.write_all(sixels.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// return the index in the palette of the color nearest
+/// to the given one
+fn palette_idx(r: u8, g: u8, b: u8) -> usize {
+    let level = |c: u8| (c as u32 * (LEVELS - 1) + 127) / 255;
+    (level(r) * LEVELS * LEVELS + level(g) * LEVELS + level(b)) as usize
+}
+
+/// append the sixel chars of a color of a band, run-length encoded
+fn push_run(s: &mut String, bits: u8, count: usize) {
+    let c = (bits + 63) as char;
+    if count > 3 {
+        let _ = write!(s, "!{}{}", count, c);
+    } else {
+        for _ in 0..count {
+            s.push(c);
+        }
+    }
+}
+
+/// build the DCS sequence drawing the image with sixels, the
+/// transparent pixels leaving the background unchanged
+pub fn encode(img: &RgbaImage) -> String {
+    let (width, height) = img.dimensions();
+    let palette_len = (LEVELS * LEVELS * LEVELS) as usize;
+    let mut s = String::new();
+    let _ = write!(s, "\u{1b}P0;1;0q\"1;1;{};{}", width, height);
+    for idx in 0..palette_len as u32 {
+        let percent = |level: u32| level * 100 / (LEVELS - 1);
+        let _ = write!(
+            s,
+            "#{};2;{};{};{}",
+            idx,
+            percent(idx / (LEVELS * LEVELS)),
+            percent(idx / LEVELS % LEVELS),
+            percent(idx % LEVELS),
+        );
+    }
+    // for each color, the sixel bits of every column of the band
+    let mut band: Vec<Option<Vec<u8>>> = vec![None; palette_len];
+    for band_top in (0..height).step_by(6) {
+        for dy in 0..6.min(height - band_top) {
+            for x in 0..width {
+                let pixel = img.get_pixel(x, band_top + dy);
+                if pixel[3] < 128 {
+                    continue;
+                }
+                let idx = palette_idx(pixel[0], pixel[1], pixel[2]);
+                let columns = band[idx].get_or_insert_with(|| vec![0; width as usize]);
+                columns[x as usize] |= 1 << dy;
+            }
+        }
+        for (idx, columns) in band.iter_mut().enumerate() {
+            if let Some(columns) = columns.take() {
+                let _ = write!(s, "#{}", idx);
+                let mut run_bits = columns[0];
+                let mut run_len = 0;
+                for &bits in &columns {
+                    if bits == run_bits {
+                        run_len += 1;
+                    } else {
+                        push_run(&mut s, run_bits, run_len);
+                        run_bits = bits;
+                        run_len = 1;
+                    }
+                }
+                if run_bits != 0 {
+                    push_run(&mut s, run_bits, run_len);
+                }
+                s.push('$'); // back to the start of the band
+            }
+        }
+        s.push('-'); // next band
+    }
+    s.push_str("\u{1b}\\");
+    s
+}
+
+#[cfg(test)]
+mod sixel_tests {
+
+    use {
+        super::*,
+        image::Rgba,
+    };
+
+    #[test]
+    fn test_sixel_encoding() {
+        // a 8x2 image, red on the left half, transparent on the right one
+        let img = RgbaImage::from_fn(8, 2, |x, _| {
+            if x < 4 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+        let s = encode(&img);
+        assert!(s.starts_with("\u{1b}P0;1;0q\"1;1;8;2"));
+        assert!(s.ends_with("\u{1b}\\"));
+        // red is the 180th color of the palette, the two top rows of
+        // the band are set for 4 pixels, the transparent end is trimmed
+        assert!(s.contains("#180;2;100;0;0"));
+        assert!(s.ends_with("#180!4B$-\u{1b}\\"));
+        assert_eq!(palette_idx(255, 255, 255), 215);
+        assert_eq!(palette_idx(0, 0, 0), 0);
+    }
+}
@@ -0,0 +1,239 @@
+use {
+    super::*,
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, Screen, SPACE_FILLING, W},
+        errors::ProgramError,
+        pattern::*,
+        skin::PanelSkin,
+        stage::Stage,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::path::Path,
+    termimad::Area,
+};
+
+/// an application state listing the operations of the journal,
+/// the ones which can be redone first, the directory of the
+/// selected one being focused on `:open_stay`
+pub struct JournalState {
+    entries: Vec<(Operation, bool)>, // the operations, with whether they're undone
+    pattern: Option<Pattern>,
+    list: ListSelection,
+    tree_options: TreeOptions,
+}
+
+impl JournalState {
+    pub fn new(tree_options: TreeOptions) -> JournalState {
+        let mut state = JournalState {
+            entries: Vec::new(),
+            pattern: None,
+            list: ListSelection::default(),
+            tree_options,
+        };
+        state.reload();
+        state
+    }
+    /// read again the journal, keeping the current filter
+    fn reload(&mut self) {
+        self.entries = journal().entries();
+        let filtered = self.pattern.as_ref().map(|pattern| {
+            self.entries
+                .iter()
+                .enumerate()
+                .filter(|(_, (op, _))| pattern.score_of_string(&op.to_string()).is_some())
+                .map(|(idx, _)| idx)
+                .collect()
+        });
+        self.list.set_items(self.entries.len(), filtered);
+    }
+}
+
+impl AppState for JournalState {
+
+    fn selected_path(&self) -> &Path {
+        match self.list.selected_item_idx() {
+            Some(idx) => self.entries[idx].0.path(),
+            None => Path::new("/"),
+        }
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: self.selected_path(),
+            stype: if self.selected_path().is_dir() {
+                SelectionType::Directory
+            } else {
+                SelectionType::File
+            },
+            is_exe: false,
+            line: 0,
+        }
+    }
+
+    fn tree_options(&self) -> TreeOptions {
+        self.tree_options.clone()
+    }
+
+    fn with_new_options(
+        &mut self,
+        _screen: Screen,
+        change_options: &dyn Fn(&mut TreeOptions),
+        _in_new_panel: bool,
+        _con: &AppContext,
+    ) -> AppStateCmdResult {
+        change_options(&mut self.tree_options);
+        AppStateCmdResult::Keep
+    }
+
+    fn refresh(&mut self, _screen: Screen, _con: &AppContext) -> Command {
+        self.reload();
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pattern: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = if pattern.is_none() {
+            None
+        } else {
+            Some(pattern.pattern)
+        };
+        self.list.reset();
+        self.reload();
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn match_count(&self) -> usize {
+        self.list.count()
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _stage: &Stage,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height < 2 {
+            warn!("area too small for the journal");
+            return Ok(());
+        }
+        let styles = &panel_skin.styles;
+        w.queue(cursor::MoveTo(state_area.left, state_area.top))?;
+        let mut cw = CropWriter::new(w, state_area.width as usize);
+        let undone_count = self.entries.iter().filter(|(_, undone)| *undone).count();
+        cw.queue_g_string(
+            &styles.default,
+            format!(
+                "{} operations, {} undone",
+                self.entries.len(),
+                undone_count,
+            ),
+        )?;
+        cw.fill(&styles.default, &SPACE_FILLING)?;
+        self.list.set_page_height(state_area.height as usize - 1);
+        for y in 0..self.list.page_height() {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + 1 + y as u16))?;
+            let mut cw = CropWriter::new(w, state_area.width as usize);
+            let idx = self.list.idx_at(y);
+            let selected = idx == self.list.selection_idx();
+            let bg_style = if selected { &styles.selected_line } else { &styles.default };
+            if let Some(entry_idx) = self.list.item_idx(idx) {
+                let (operation, undone) = &self.entries[entry_idx];
+                let mut state_style = if *undone {
+                    styles.file_error.clone()
+                } else {
+                    styles.file.clone()
+                };
+                let mut op_style = styles.default.clone();
+                if selected {
+                    if let Some(c) = styles.selected_line.get_bg() {
+                        state_style.set_bg(c);
+                        op_style.set_bg(c);
+                    }
+                }
+                cw.queue_str(&state_style, if *undone { "undone " } else { "done   " })?;
+                cw.queue_g_string(&op_style, operation.to_string())?;
+            }
+            cw.fill(bg_style, &SPACE_FILLING)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        if self.entries.is_empty() {
+            Status::from_message(
+                "No file operation done yet. Hit *esc* to go back",
+            )
+        } else {
+            Status::from_message(
+                "`:undo` reverts the last done operation, `:redo` does again the last undone one",
+            )
+        }
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        if let Some(res) = self.list.on_internal(internal_exec, input_invocation) {
+            return Ok(res);
+        }
+        let con = cc.con;
+        Ok(match internal_exec.internal {
+            Internal::back => {
+                if self.pattern.is_some() {
+                    self.pattern = None;
+                    self.reload();
+                    AppStateCmdResult::Keep
+                } else {
+                    AppStateCmdResult::PopState
+                }
+            }
+            Internal::open_stay => match self.list.selected_item_idx() {
+                Some(idx) => {
+                    let bang = input_invocation
+                        .map(|inv| inv.bang)
+                        .unwrap_or(internal_exec.bang);
+                    let path = self.entries[idx].0.path();
+                    internal_focus::on_path(
+                        path.parent().unwrap_or(path).to_path_buf(),
+                        screen,
+                        self.tree_options(),
+                        bang && cc.preview.is_none(),
+                        con,
+                    )
+                }
+                None => AppStateCmdResult::DisplayError("no operation selected".to_string()),
+            },
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+}
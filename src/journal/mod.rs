@@ -0,0 +1,158 @@
+//! a journal of the file operations done from broot, which
+//! can be undone with `:undo` and redone with `:redo`
+
+mod journal_state;
+mod operation;
+
+pub use {
+    journal_state::JournalState,
+    operation::Operation,
+};
+
+use std::{
+    io,
+    sync::{Mutex, MutexGuard},
+};
+
+/// the max number of operations kept in the journal
+const MAX_LEN: usize = 100;
+
+/// the operations which can be undone, and the ones which
+/// were undone and can be redone, the most recent last
+#[derive(Debug, Default)]
+pub struct Journal {
+    done: Vec<Operation>,
+    undone: Vec<Operation>,
+}
+
+impl Journal {
+    /// add a just done operation, which makes it impossible
+    /// to redo the undone ones
+    pub fn record(&mut self, operation: Operation) {
+        debug!("recording operation: {}", &operation);
+        if self.done.len() >= MAX_LEN {
+            self.done.remove(0);
+        }
+        self.done.push(operation);
+        self.undone.clear();
+    }
+    /// revert the last done operation and return its
+    /// description, or None if there's nothing to undo.
+    ///
+    /// An operation which can't be undone is kept.
+    pub fn undo(&mut self) -> io::Result<Option<String>> {
+        let mut operation = match self.done.pop() {
+            Some(operation) => operation,
+            None => return Ok(None),
+        };
+        let result = operation.undo();
+        let description = operation.to_string();
+        if result.is_ok() {
+            self.undone.push(operation);
+        } else {
+            self.done.push(operation);
+        }
+        result.map(|_| Some(description))
+    }
+    /// do again the last undone operation and return its
+    /// description, or None if there's nothing to redo
+    pub fn redo(&mut self) -> io::Result<Option<String>> {
+        let mut operation = match self.undone.pop() {
+            Some(operation) => operation,
+            None => return Ok(None),
+        };
+        let result = operation.redo();
+        let description = operation.to_string();
+        if result.is_ok() {
+            self.done.push(operation);
+        } else {
+            self.undone.push(operation);
+        }
+        result.map(|_| Some(description))
+    }
+    /// the operations, the next one to redo first, then the
+    /// next one to undo, etc., with whether they're undone
+    pub fn entries(&self) -> Vec<(Operation, bool)> {
+        self.undone
+            .iter()
+            .map(|op| (op.clone(), true))
+            .chain(self.done.iter().rev().map(|op| (op.clone(), false)))
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref JOURNAL: Mutex<Journal> = Mutex::new(Journal::default());
+}
+
+/// the journal of the application, shared by all panels and
+/// fed by the internals and the verbs calling `mv`, `cp` or `mkdir`
+pub fn journal() -> MutexGuard<'static, Journal> {
+    JOURNAL.lock().unwrap()
+}
+
+/// add a just done operation to the journal of the application
+pub fn record(operation: Operation) {
+    journal().record(operation);
+}
+
+#[cfg(test)]
+mod journal_tests {
+
+    use {
+        super::*,
+        std::fs,
+    };
+
+    #[test]
+    fn test_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a"), "").unwrap();
+        let mut journal = Journal::default();
+        assert_eq!(journal.undo().unwrap(), None);
+        fs::rename(root.join("a"), root.join("b")).unwrap();
+        journal.record(Operation::Move { from: root.join("a"), to: root.join("b") });
+        fs::rename(root.join("b"), root.join("c")).unwrap();
+        journal.record(Operation::Move { from: root.join("b"), to: root.join("c") });
+        assert!(journal.undo().unwrap().is_some());
+        assert!(journal.undo().unwrap().is_some());
+        assert!(root.join("a").exists());
+        assert_eq!(journal.undo().unwrap(), None);
+        assert!(journal.redo().unwrap().is_some());
+        assert!(root.join("b").exists());
+        let entries = journal.entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].1); // b -> c is undone
+        assert!(!entries[1].1);
+        // a new operation prevents redoing the undone ones
+        fs::create_dir(root.join("d")).unwrap();
+        journal.record(Operation::CreateDirs { paths: vec![root.join("d")] });
+        assert_eq!(journal.redo().unwrap(), None);
+        assert_eq!(journal.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_irreversible_operation() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a"), "").unwrap();
+        let mut journal = Journal::default();
+        fs::rename(root.join("a"), root.join("b")).unwrap();
+        journal.record(Operation::Move { from: root.join("a"), to: root.join("b") });
+        journal.record(Operation::Irreversible {
+            path: root.to_path_buf(),
+            description: "paste overwriting 1 paths".to_string(),
+        });
+        // the operations done before can't be undone anymore
+        assert!(journal.undo().is_err());
+        assert!(journal.undo().is_err());
+        assert!(root.join("b").exists());
+        // but the following ones can
+        fs::create_dir(root.join("c")).unwrap();
+        journal.record(Operation::CreateDirs { paths: vec![root.join("c")] });
+        assert!(journal.undo().unwrap().is_some());
+        assert!(!root.join("c").exists());
+        assert!(journal.undo().is_err());
+    }
+}
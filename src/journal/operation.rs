@@ -0,0 +1,546 @@
+use {
+    crate::{
+        path,
+        verb::internal_batch_rename::{apply_renames, Rename},
+    },
+    std::{
+        fmt,
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+};
+
+/// a file operation done from broot, with what's needed
+/// to revert it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// a move or a rename
+    Move { from: PathBuf, to: PathBuf },
+    Copy { from: PathBuf, to: PathBuf },
+    /// the directories created, the deepest ones last
+    CreateDirs { paths: Vec<PathBuf> },
+    /// the renames of a `:batch_rename`
+    Renames { renames: Vec<Rename> },
+    /// a path moved to the trash, under the given name
+    #[cfg(unix)]
+    Trash { path: PathBuf, name: String },
+    /// a path restored from the trash, where it was under the given name
+    #[cfg(unix)]
+    Restore { path: PathBuf, name: String },
+    /// a symbolic link created with `:link_to_other`
+    Link { target: PathBuf, link: PathBuf },
+    /// a file created with `:from_template` or `:zip_to_other`,
+    /// with its name in the trash once undone
+    Create { path: PathBuf, trashed: Option<String> },
+    /// the owner's executable bit switched with `:toggle_exec`
+    #[cfg(not(any(target_family = "windows", target_os = "android")))]
+    ToggleExec { path: PathBuf },
+    /// operations done together, like the moves or copies of a
+    /// `:paste`, which are undone together
+    Several { operations: Vec<Operation> },
+    /// a change which can't be reverted, and prevents undoing
+    /// the operations done before it
+    Irreversible { path: PathBuf, description: String },
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+fn not_reversible(reason: String) -> io::Error {
+    io::Error::other(reason)
+}
+
+/// remove a file or a directory with all its content
+fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// move the path to the trash and return its name there,
+/// or remove it and return None when there's no trash
+fn trash_or_remove(path: &Path) -> io::Result<Option<String>> {
+    #[cfg(unix)]
+    if let Some(trash) = crate::trash::TrashDir::home() {
+        return trash.trash(path).map(Some);
+    }
+    remove(path).map(|_| None)
+}
+
+/// move the path to the trash and return its name there
+#[cfg(unix)]
+fn move_to_trash(path: &Path) -> io::Result<String> {
+    crate::trash::TrashDir::home()
+        .ok_or_else(|| not_reversible("no trash directory found".to_string()))?
+        .trash(path)
+}
+
+/// move back the path from the trash, where it's under the given name
+#[cfg(unix)]
+fn restore_from_trash(path: &Path, name: &str) -> io::Result<()> {
+    crate::trash::TrashDir::home()
+        .ok_or_else(|| not_reversible("no trash directory found".to_string()))?
+        .restore(&crate::trash::TrashItem {
+            name: name.to_string(),
+            original_path: path.to_path_buf(),
+            deletion_date: String::new(),
+        })
+}
+
+/// remove the link, provided it's still a symbolic link
+fn remove_link(link: &Path) -> io::Result<()> {
+    if !fs::symlink_metadata(link)?.file_type().is_symlink() {
+        return Err(not_reversible(format!("{:?} isn't a link anymore", link)));
+    }
+    // on windows, a link to a directory is removed as a directory
+    fs::remove_file(link).or_else(|_| fs::remove_dir(link))
+}
+
+/// return the path where `mv` or `cp` puts the source, and
+/// None when the destination would be overwritten
+fn destination(src: &Path, dst: &Path) -> Option<PathBuf> {
+    let dst = if dst.is_dir() {
+        dst.join(src.file_name()?)
+    } else {
+        dst.to_path_buf()
+    };
+    if exists(&dst) {
+        None
+    } else {
+        Some(dst)
+    }
+}
+
+/// tell whether the option of the command doesn't change what
+/// it does to the paths, and doesn't take a value.
+///
+/// Other options (like `mv -t dir` or `cp -S suffix`) make the
+/// command not reversible by the journal.
+fn is_harmless_option(program: &str, option: &str) -> bool {
+    let (short_flags, long_options): (&str, &[&str]) = match program {
+        "mv" => ("fv", &["--force", "--verbose"]),
+        "cp" => ("aprRfv", &["--archive", "--recursive", "--force", "--verbose"]),
+        "mkdir" => ("pv", &["--parents", "--verbose"]),
+        _ => return false,
+    };
+    if option.starts_with("--") {
+        long_options.contains(&option)
+    } else {
+        option.len() > 1 && option[1..].chars().all(|c| short_flags.contains(c))
+    }
+}
+
+impl Operation {
+    /// build the operation a command (`mv`, `cp` or `mkdir`) is about
+    /// to do, when it can be reverted.
+    ///
+    /// This must be called before the execution, as the effect of
+    /// those commands depends on the existing paths.
+    pub fn from_command(tokens: &[String], working_dir: Option<&Path>) -> Option<Self> {
+        let program = Path::new(tokens.first()?).file_name()?.to_str()?;
+        let mut args = Vec::new();
+        let mut options_ended = false;
+        for token in &tokens[1..] {
+            if !options_ended && token.starts_with('-') {
+                if token == "--" {
+                    options_ended = true;
+                } else if !is_harmless_option(program, token) {
+                    return None;
+                }
+                continue;
+            }
+            let arg = Path::new(token);
+            let arg = if arg.is_absolute() {
+                arg.to_path_buf()
+            } else {
+                working_dir?.join(arg)
+            };
+            args.push(path::normalize_path(arg));
+        }
+        match (program, args.len()) {
+            ("mv", 2) => {
+                let to = destination(&args[0], &args[1])?;
+                Some(Self::Move { from: args.swap_remove(0), to })
+            }
+            ("cp", 2) => {
+                let to = destination(&args[0], &args[1])?;
+                Some(Self::Copy { from: args.swap_remove(0), to })
+            }
+            ("mkdir", n) if n > 0 => {
+                let mut paths = Vec::new();
+                for arg in &args {
+                    let mut missing: Vec<PathBuf> = arg
+                        .ancestors()
+                        .take_while(|p| !exists(p))
+                        .map(Path::to_path_buf)
+                        .collect();
+                    missing.reverse();
+                    for p in missing {
+                        if !paths.contains(&p) {
+                            paths.push(p);
+                        }
+                    }
+                }
+                if paths.is_empty() {
+                    None
+                } else {
+                    Some(Self::CreateDirs { paths })
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// revert the operation, provided nothing prevents it: a path
+    /// is never overwritten, and only empty directories are removed
+    pub fn undo(&mut self) -> io::Result<()> {
+        match self {
+            Self::Move { from, to } => {
+                if exists(from) {
+                    return Err(not_reversible(format!("{:?} exists", from)));
+                }
+                fs::rename(to, from)
+            }
+            Self::Copy { to, .. } => {
+                // the copy goes to the trash when possible, in case
+                // it was modified since
+                trash_or_remove(to).map(|_| ())
+            }
+            Self::CreateDirs { paths } => {
+                for path in paths.iter().rev() {
+                    fs::remove_dir(path)?;
+                }
+                Ok(())
+            }
+            Self::Renames { renames } => {
+                let reverted: Vec<Rename> = renames
+                    .iter()
+                    .map(|r| Rename { from: r.to.clone(), to: r.from.clone() })
+                    .collect();
+                check_renames(&reverted)?;
+                apply_renames(&reverted)
+            }
+            #[cfg(unix)]
+            Self::Trash { path, name } => restore_from_trash(path, name),
+            #[cfg(unix)]
+            Self::Restore { path, name } => {
+                *name = move_to_trash(path)?;
+                Ok(())
+            }
+            Self::Link { link, .. } => remove_link(link),
+            Self::Create { path, trashed } => {
+                *trashed = trash_or_remove(path)?;
+                Ok(())
+            }
+            #[cfg(not(any(target_family = "windows", target_os = "android")))]
+            Self::ToggleExec { path } => {
+                crate::permissions::toggle_owner_exec(path).map(|_| ())
+            }
+            Self::Several { operations } => {
+                for i in (0..operations.len()).rev() {
+                    if let Err(e) = operations[i].undo() {
+                        // the already undone operations are done again
+                        for operation in &mut operations[i + 1..] {
+                            let _ = operation.redo();
+                        }
+                        return Err(e);
+                    }
+                }
+                Ok(())
+            }
+            Self::Irreversible { description, .. } => Err(not_reversible(format!(
+                "{} can't be undone",
+                description,
+            ))),
+        }
+    }
+
+    /// do again the operation after it was undone
+    pub fn redo(&mut self) -> io::Result<()> {
+        match self {
+            Self::Move { from, to } => {
+                if exists(to) {
+                    return Err(not_reversible(format!("{:?} exists", to)));
+                }
+                fs::rename(from, to)
+            }
+            Self::Copy { from, to } => {
+                if exists(to) {
+                    return Err(not_reversible(format!("{:?} exists", to)));
+                }
+                path::copy_recursively(from, to)
+            }
+            Self::CreateDirs { paths } => {
+                for path in paths.iter() {
+                    fs::create_dir(path)?;
+                }
+                Ok(())
+            }
+            Self::Renames { renames } => {
+                check_renames(renames)?;
+                apply_renames(renames)
+            }
+            #[cfg(unix)]
+            Self::Trash { path, name } => {
+                *name = move_to_trash(path)?;
+                Ok(())
+            }
+            #[cfg(unix)]
+            Self::Restore { path, name } => restore_from_trash(path, name),
+            Self::Link { target, link } => {
+                if exists(link) {
+                    return Err(not_reversible(format!("{:?} exists", link)));
+                }
+                path::symlink(target, link)
+            }
+            Self::Create { path, trashed } => match trashed {
+                #[cfg(unix)]
+                Some(name) => {
+                    restore_from_trash(path, name)?;
+                    *trashed = None;
+                    Ok(())
+                }
+                _ => Err(not_reversible(format!("{:?} was removed", path))),
+            },
+            #[cfg(not(any(target_family = "windows", target_os = "android")))]
+            Self::ToggleExec { path } => {
+                crate::permissions::toggle_owner_exec(path).map(|_| ())
+            }
+            Self::Several { operations } => {
+                for i in 0..operations.len() {
+                    if let Err(e) = operations[i].redo() {
+                        // the already redone operations are undone again
+                        for operation in operations[..i].iter_mut().rev() {
+                            let _ = operation.undo();
+                        }
+                        return Err(e);
+                    }
+                }
+                Ok(())
+            }
+            Self::Irreversible { description, .. } => Err(not_reversible(format!(
+                "{} can't be redone",
+                description,
+            ))),
+        }
+    }
+
+    /// the path the operation is about, after it's done
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Move { to, .. } => to,
+            Self::Copy { to, .. } => to,
+            Self::CreateDirs { paths } => &paths[0],
+            Self::Renames { renames } => &renames[0].to,
+            #[cfg(unix)]
+            Self::Trash { path, .. } => path,
+            #[cfg(unix)]
+            Self::Restore { path, .. } => path,
+            Self::Link { link, .. } => link,
+            Self::Create { path, .. } => path,
+            #[cfg(not(any(target_family = "windows", target_os = "android")))]
+            Self::ToggleExec { path } => path,
+            Self::Several { operations } => operations[0].path(),
+            Self::Irreversible { path, .. } => path,
+        }
+    }
+}
+
+/// check the sources of the renames exist and that
+/// they wouldn't overwrite anything
+fn check_renames(renames: &[Rename]) -> io::Result<()> {
+    for rename in renames {
+        if !exists(&rename.from) {
+            return Err(not_reversible(format!("{:?} doesn't exist", rename.from)));
+        }
+        let replaced = renames.iter().any(|r| r.from == rename.to);
+        if !replaced && exists(&rename.to) {
+            return Err(not_reversible(format!("{:?} exists", rename.to)));
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Move { from, to } => write!(f, "move {:?} to {:?}", from, to),
+            Self::Copy { from, to } => write!(f, "copy {:?} to {:?}", from, to),
+            Self::CreateDirs { paths } => {
+                write!(f, "create {:?}", paths[paths.len() - 1])
+            }
+            Self::Renames { renames } if renames.len() == 1 => {
+                write!(f, "rename {:?} to {:?}", renames[0].from, renames[0].to)
+            }
+            Self::Renames { renames } => write!(f, "rename {} paths", renames.len()),
+            #[cfg(unix)]
+            Self::Trash { path, .. } => write!(f, "trash {:?}", path),
+            #[cfg(unix)]
+            Self::Restore { path, .. } => write!(f, "restore {:?}", path),
+            Self::Link { target, link } => write!(f, "link {:?} to {:?}", link, target),
+            Self::Create { path, .. } => write!(f, "create {:?}", path),
+            #[cfg(not(any(target_family = "windows", target_os = "android")))]
+            Self::ToggleExec { path } => write!(f, "toggle the executable bit of {:?}", path),
+            Self::Several { operations } if operations.len() == 1 => operations[0].fmt(f),
+            Self::Several { operations } => {
+                write!(f, "{} and {} more", operations[0], operations.len() - 1)
+            }
+            Self::Irreversible { description, .. } => f.write_str(description),
+        }
+    }
+}
+
+#[cfg(test)]
+mod operation_tests {
+
+    use super::*;
+
+    fn tokens(s: &str) -> Vec<String> {
+        s.split(' ').map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_command_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+        let cmd = |s: &str| Operation::from_command(&tokens(s), Some(root));
+        // moving to a directory keeps the name
+        assert_eq!(
+            cmd("mv a.txt sub"),
+            Some(Operation::Move { from: root.join("a.txt"), to: root.join("sub/a.txt") }),
+        );
+        assert_eq!(
+            cmd("cp -r a.txt c.txt"),
+            Some(Operation::Copy { from: root.join("a.txt"), to: root.join("c.txt") }),
+        );
+        // overwriting isn't reversible
+        assert_eq!(cmd("mv a.txt b.txt"), None);
+        assert_eq!(
+            cmd("mkdir -p sub/x/y"),
+            Some(Operation::CreateDirs { paths: vec![root.join("sub/x"), root.join("sub/x/y")] }),
+        );
+        assert_eq!(cmd("mkdir sub"), None);
+        assert_eq!(cmd("rm -rf sub"), None);
+        // options which aren't understood make the command not reversible
+        assert_eq!(cmd("mv -t sub a.txt"), None);
+        assert_eq!(cmd("cp -S .bak a.txt c.txt"), None);
+        assert_eq!(cmd("mkdir -m 700 x"), None);
+        assert_eq!(cmd("cp --suffix=.bak a.txt c.txt"), None);
+        assert_eq!(
+            cmd("cp -rv --force -- a.txt -c.txt"),
+            Some(Operation::Copy { from: root.join("a.txt"), to: root.join("-c.txt") }),
+        );
+        assert_eq!(Operation::from_command(&tokens("mv a.txt c.txt"), None), None);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        let mut mv = Operation::Move { from: root.join("a.txt"), to: root.join("b.txt") };
+        mv.redo().unwrap();
+        assert!(root.join("b.txt").exists());
+        mv.undo().unwrap();
+        assert!(root.join("a.txt").exists());
+        assert!(!root.join("b.txt").exists());
+        // the move can't be undone when it would overwrite a file
+        mv.redo().unwrap();
+        fs::write(root.join("a.txt"), "other").unwrap();
+        assert!(mv.undo().is_err());
+        assert_eq!(fs::read_to_string(root.join("b.txt")).unwrap(), "a");
+        let mut mkdir = Operation::from_command(
+            &tokens("mkdir -p x/y"),
+            Some(root),
+        ).unwrap();
+        mkdir.redo().unwrap();
+        assert!(root.join("x/y").is_dir());
+        fs::write(root.join("x/y/file"), "").unwrap();
+        assert!(mkdir.undo().is_err()); // not empty
+        fs::remove_file(root.join("x/y/file")).unwrap();
+        mkdir.undo().unwrap();
+        assert!(!root.join("x").exists());
+        let mut renames = Operation::Renames {
+            renames: vec![
+                Rename { from: root.join("a.txt"), to: root.join("b.txt") },
+                Rename { from: root.join("b.txt"), to: root.join("a.txt") },
+            ],
+        };
+        renames.redo().unwrap(); // swap
+        assert_eq!(fs::read_to_string(root.join("b.txt")).unwrap(), "other");
+        renames.undo().unwrap();
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "other");
+    }
+
+    #[test]
+    fn test_several_undo_redo() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("dst")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+        let mut paste = Operation::Several {
+            operations: vec![
+                Operation::Move { from: root.join("a.txt"), to: root.join("dst/a.txt") },
+                Operation::Move { from: root.join("b.txt"), to: root.join("dst/b.txt") },
+            ],
+        };
+        assert_eq!(paste.to_string(), format!("{} and 1 more", Operation::Move {
+            from: root.join("a.txt"),
+            to: root.join("dst/a.txt"),
+        }));
+        paste.redo().unwrap();
+        assert!(root.join("dst/a.txt").exists());
+        assert!(root.join("dst/b.txt").exists());
+        paste.undo().unwrap();
+        assert!(root.join("a.txt").exists());
+        assert!(root.join("b.txt").exists());
+        // when one operation fails, the other ones are reverted
+        fs::write(root.join("dst/b.txt"), "other").unwrap();
+        assert!(paste.redo().is_err());
+        assert!(root.join("a.txt").exists());
+        assert!(!root.join("dst/a.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_link_undo_redo() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        let link = path::link_in_dir(&root.join("a.txt"), root, Some("link.txt")).unwrap();
+        let mut operation = Operation::Link { target: root.join("a.txt"), link };
+        operation.undo().unwrap();
+        assert!(fs::symlink_metadata(root.join("link.txt")).is_err());
+        operation.redo().unwrap();
+        assert_eq!(fs::read_to_string(root.join("link.txt")).unwrap(), "a");
+        // a file which replaced the link isn't removed
+        fs::remove_file(root.join("link.txt")).unwrap();
+        fs::write(root.join("link.txt"), "not a link").unwrap();
+        assert!(operation.undo().is_err());
+        assert!(root.join("link.txt").exists());
+    }
+
+    #[cfg(not(any(target_family = "windows", target_os = "android")))]
+    #[test]
+    fn test_toggle_exec_undo_redo() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("script.sh");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        let mode = || fs::metadata(&file).unwrap().permissions().mode() & 0o777;
+        let mut operation = Operation::ToggleExec { path: file.clone() };
+        operation.redo().unwrap();
+        assert_eq!(mode(), 0o744);
+        operation.undo().unwrap();
+        assert_eq!(mode(), 0o644);
+    }
+}
@@ -1,10 +1,11 @@
 use {
     crate::{
-        display::{
-            cell_size_in_pixels,
-            W,
-        },
+        display::W,
         errors::ProgramError,
+        image::{
+            rendering_area,
+            ImageRenderer,
+        },
     },
     base64,
     crossterm::{
@@ -17,10 +18,7 @@ use {
         RgbImage,
         RgbaImage,
     },
-    std::{
-        env,
-        io::{self, Write},
-    },
+    std::io::{self, Write},
     tempfile,
     termimad::Area,
 };
@@ -83,23 +81,8 @@ impl<'i> ImageData<'i> {
 /// according to kitty's documentation
 const CHUNK_SIZE: usize = 4096;
 
-/// until I'm told there's another terminal supporting the kitty
-/// terminal, I think I can just check the name
-pub fn is_term_kitty() -> bool {
-    if let Ok(term_name) = env::var("TERM") {
-        if term_name.contains("kitty") {
-            return true;
-        }
-    }
-    false
-}
-
-fn div_ceil(a: u32, b: u32) -> u32 {
-    a / b + (0 != a % b) as u32
-}
-
 /// the image renderer, with knowledge of the
-/// console cells dimensions, and built only on Kitty.
+/// console cells dimensions, used on Kitty.
 ///
 pub struct KittyImageRenderer {
     cell_width: u32,
@@ -125,7 +108,13 @@ impl<'i> KittyImage<'i> {
         renderer: &'r mut KittyImageRenderer,
     ) -> Self {
         let (img_width, img_height) = src.dimensions();
-        let area = renderer.rendering_area(img_width, img_height, available_area);
+        let area = rendering_area(
+            renderer.cell_width,
+            renderer.cell_height,
+            img_width,
+            img_height,
+            available_area,
+        );
         let data = src.into();
         let id = renderer.new_id();
         Self {
@@ -209,22 +198,14 @@ impl<'i> KittyImage<'i> {
 }
 
 impl KittyImageRenderer {
-    pub fn new() -> Option<Self> {
-        if !is_term_kitty() {
-            return None;
+    pub fn new(cell_width: u32, cell_height: u32) -> Self {
+        Self {
+            cell_width,
+            cell_height,
+            current_images: None,
+            next_id: 1,
+            transmission_medium: TransmissionMedium::Chunks,
         }
-        cell_size_in_pixels()
-            .ok()
-            .map(|(cell_width, cell_height)| Self {
-                cell_width,
-                cell_height,
-                current_images: None,
-                next_id: 1,
-                transmission_medium: TransmissionMedium::Chunks,
-            })
-    }
-    pub fn take_current_images(&mut self) -> Option<KittyImageSet> {
-        self.current_images.take()
     }
     /// return a new image id which is assumed will be used
     fn new_id(&mut self) -> usize {
@@ -235,7 +216,22 @@ impl KittyImageRenderer {
             .push(new_id);
         new_id
     }
-    pub fn print(
+    /// erase all kitty images, even the forgetted ones
+    pub fn erase_all(
+        &mut self,
+        w: &mut W,
+    ) -> Result<(), ProgramError> {
+        write!(w, "\u{1b}_Ga=d,d=A\u{1b}\\")?;
+        self.current_images = None;
+        Ok(())
+    }
+}
+
+impl ImageRenderer for KittyImageRenderer {
+    fn take_current_images(&mut self) -> Option<KittyImageSet> {
+        self.current_images.take()
+    }
+    fn print(
         &mut self,
         w: &mut W,
         src: &DynamicImage,
@@ -247,7 +243,7 @@ impl KittyImageRenderer {
             TransmissionMedium::Chunks => img.print_with_chunks(w),
         }
     }
-    pub fn erase(
+    fn erase(
         &mut self,
         w: &mut W,
         ids: KittyImageSet,
@@ -258,54 +254,4 @@ impl KittyImageRenderer {
         }
         Ok(())
     }
-    /// erase all kitty images, even the forgetted ones
-    pub fn erase_all(
-        &mut self,
-        w: &mut W,
-    ) -> Result<(), ProgramError> {
-        write!(w, "\u{1b}_Ga=d,d=A\u{1b}\\")?;
-        self.current_images = None;
-        Ok(())
-    }
-    fn rendering_area(
-        &self,
-        img_width: u32,
-        img_height: u32,
-        area: &Area,
-    ) -> Area {
-        let area_cols: u32 = area.width.into();
-        let area_rows: u32 = area.height.into();
-        let rdim = self.rendering_dim(img_width, img_height, area_cols, area_rows);
-        Area::new(
-            area.left + ((area_cols - rdim.0) / 2) as u16,
-            area.top + ((area_rows - rdim.1) / 2) as u16,
-            rdim.0 as u16,
-            rdim.1 as u16,
-        )
-    }
-    fn rendering_dim(
-        &self,
-        img_width: u32,
-        img_height: u32,
-        area_cols: u32,
-        area_rows: u32,
-    ) -> (u32, u32) {
-        let optimal_cols = div_ceil(img_width, self.cell_width);
-        let optimal_rows = div_ceil(img_height, self.cell_height);
-        debug!("area: {:?}", (area_cols, area_rows));
-        debug!("optimal: {:?}", (optimal_cols, optimal_rows));
-        if optimal_cols <= area_cols && optimal_rows <= area_rows {
-            // no constraint (TODO center?)
-            (optimal_cols, optimal_rows)
-        } else if optimal_cols * area_rows > optimal_rows * area_cols {
-            // we're constrained in width
-            debug!("constrained in width");
-            (area_cols, optimal_rows * area_cols / optimal_cols)
-        } else {
-            // we're constrained in height
-            debug!("constrained in height");
-            (optimal_cols * area_rows / optimal_rows, area_rows)
-        }
-    }
 }
-
@@ -22,7 +22,7 @@ use {
         env,
         io::{self, Write},
         path::PathBuf,
-        process::Command,
+        process::{Command, Stdio},
     },
 };
 
@@ -34,6 +34,7 @@ pub enum Launchable {
     /// just print something on stdout on end of broot
     Printer {
         to_print: String,
+        newline: bool, // whether a newline must be added
     },
 
     /// print the tree on end of broot
@@ -49,6 +50,8 @@ pub enum Launchable {
         exe: String,
         args: Vec<String>,
         working_dir: Option<PathBuf>,
+        env: ProgramEnv,
+        stdin: StdinMode,
     },
 
     /// open a path
@@ -57,6 +60,63 @@ pub enum Launchable {
     },
 }
 
+/// the environment variables which are kept when the environment
+/// of a program is cleared
+pub const KEPT_ENV_VARS: &[&str] = &["PATH", "HOME"];
+
+/// how the environment of a launched program must be built from
+/// the one of broot
+#[derive(Debug, Clone, Default)]
+pub struct ProgramEnv {
+    /// whether to remove all variables but the ones of KEPT_ENV_VARS
+    pub clear: bool,
+    /// variables to set
+    pub vars: Vec<(String, String)>,
+}
+
+impl ProgramEnv {
+    pub fn apply_to(&self, command: &mut Command) {
+        if self.clear {
+            command.env_clear();
+            for name in KEPT_ENV_VARS {
+                if let Some(value) = env::var_os(name) {
+                    command.env(name, value);
+                }
+            }
+        }
+        for (name, value) in &self.vars {
+            command.env(name, value);
+        }
+    }
+}
+
+/// what the standard input of a launched program is connected to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StdinMode {
+    /// the standard input of broot, usually the terminal
+    #[default]
+    Inherit,
+    /// nothing: the program reads the end of its input at once,
+    /// instead of waiting for some
+    Null,
+}
+
+impl StdinMode {
+    /// parse the value of the `stdin` verb option
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "inherit" => Some(Self::Inherit),
+            "null" => Some(Self::Null),
+            _ => None,
+        }
+    }
+    pub fn apply_to(self, command: &mut Command) {
+        if self == Self::Null {
+            command.stdin(Stdio::null());
+        }
+    }
+}
+
 /// If a part starts with a '$', replace it by the environment variable of the same name.
 /// This part is splitted too (because of https://github.com/Canop/broot/issues/114)
 fn resolve_env_variables(parts: Vec<String>) -> Vec<String> {
@@ -78,7 +138,11 @@ impl Launchable {
         Launchable::SystemOpen { path }
     }
     pub fn printer(to_print: String) -> Launchable {
-        Launchable::Printer { to_print }
+        Launchable::Printer { to_print, newline: true }
+    }
+    /// print the string as is, with no added newline
+    pub fn raw_printer(to_print: String) -> Launchable {
+        Launchable::Printer { to_print, newline: false }
     }
     pub fn tree_printer(
         tree: &Tree,
@@ -104,22 +168,50 @@ impl Launchable {
                 exe,
                 args: parts.collect(),
                 working_dir,
+                env: ProgramEnv::default(),
+                stdin: StdinMode::default(),
             }),
             None => Err(io::Error::new(io::ErrorKind::Other, "Empty launch string")),
         }
     }
 
-    pub fn execute(&self, mut w: Option<&mut W>) -> Result<(), ProgramError> {
+    /// set the environment of the program, if the launchable is one
+    pub fn with_env(mut self, program_env: ProgramEnv) -> Self {
+        if let Launchable::Program { env, .. } = &mut self {
+            *env = program_env;
+        }
+        self
+    }
+
+    /// set the standard input of the program, if the launchable is one
+    pub fn with_stdin(mut self, stdin_mode: StdinMode) -> Self {
+        if let Launchable::Program { stdin, .. } = &mut self {
+            *stdin = stdin_mode;
+        }
+        self
+    }
+
+    pub fn execute(&self, w: Option<&mut W>) -> Result<(), ProgramError> {
+        self.execute_for_exit_code(w).map(|_| ())
+    }
+    /// execute the launchable and return the exit code of the
+    /// program, if it's a program and it gave one
+    pub fn execute_for_exit_code(&self, mut w: Option<&mut W>) -> Result<Option<i32>, ProgramError> {
         match self {
-            Launchable::Printer { to_print } => {
-                println!("{}", to_print);
-                Ok(())
+            Launchable::Printer { to_print, newline } => {
+                if *newline {
+                    println!("{}", to_print);
+                } else {
+                    print!("{}", to_print);
+                    std::io::stdout().flush()?;
+                }
+                Ok(None)
             }
             Launchable::TreePrinter { tree, skin, ext_colors, width } => {
                 let dp = DisplayableTree::out_of_app(&tree, &skin, &ext_colors, *width);
-                dp.write_on(&mut std::io::stdout())
+                dp.write_on(&mut std::io::stdout()).map(|_| None)
             }
-            Launchable::Program { working_dir, exe, args } => {
+            Launchable::Program { working_dir, exe, args, env, stdin } => {
                 // we restore the normal terminal in case the executable
                 // is a terminal application, and we'll switch back to
                 // broot's alternate terminal when we're back to broot
@@ -136,8 +228,11 @@ impl Launchable {
                     old_working_dir = std::env::current_dir().ok();
                     std::env::set_current_dir(working_dir).unwrap();
                 }
-                Command::new(&exe)
-                    .args(args.iter())
+                let mut command = Command::new(exe);
+                command.args(args.iter());
+                env.apply_to(&mut command);
+                stdin.apply_to(&mut command);
+                let status = command
                     .spawn()
                     .and_then(|mut p| p.wait())
                     .map_err(|source| ProgramError::LaunchError {
@@ -154,12 +249,84 @@ impl Launchable {
                 if let Some(old_working_dir) = old_working_dir {
                     std::env::set_current_dir(old_working_dir).unwrap();
                 }
-                Ok(())
+                Ok(status.code())
             }
             Launchable::SystemOpen { path } => {
                 open::that(&path)?;
-                Ok(())
+                Ok(None)
             }
         }
     }
 }
+
+#[cfg(test)]
+mod launchable_test {
+
+    use super::*;
+
+    /// run `env` with the given program env and return the names
+    /// of the variables it sees
+    #[cfg(unix)]
+    fn env_var_names(program_env: &ProgramEnv) -> Vec<String> {
+        let mut command = Command::new("env");
+        program_env.apply_to(&mut command);
+        let output = command.output().unwrap();
+        let mut names: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split('=').next())
+            .map(|name| name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_clean_env() {
+        let program_env = ProgramEnv {
+            clear: true,
+            vars: vec![
+                ("BROOT_TEST_A".to_string(), "a".to_string()),
+                ("LANG".to_string(), "C".to_string()),
+            ],
+        };
+        let mut expected: Vec<String> = KEPT_ENV_VARS
+            .iter()
+            .filter(|name| env::var_os(name).is_some())
+            .map(|name| name.to_string())
+            .collect();
+        expected.push("BROOT_TEST_A".to_string());
+        expected.push("LANG".to_string());
+        expected.sort();
+        assert_eq!(env_var_names(&program_env), expected);
+        // without clearing, broot's environment is passed
+        let program_env = ProgramEnv {
+            clear: false,
+            vars: vec![("BROOT_TEST_A".to_string(), "a".to_string())],
+        };
+        let names = env_var_names(&program_env);
+        assert!(names.len() > 1);
+        assert!(names.contains(&"BROOT_TEST_A".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stdin_null() {
+        // with a null stdin, a program reading its input gets its end at once
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "if [ -t 0 ]; then echo tty; fi; cat; echo end"])
+            .stdout(Stdio::piped());
+        StdinMode::Null.apply_to(&mut command);
+        let output = command.spawn().unwrap().wait_with_output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "end\n");
+        // the mode is given to the launched program
+        let launchable = Launchable::program(vec!["cat".to_string()], None)
+            .unwrap()
+            .with_stdin(StdinMode::Null);
+        assert!(matches!(launchable, Launchable::Program { stdin: StdinMode::Null, .. }));
+        assert_eq!(StdinMode::from_name("null"), Some(StdinMode::Null));
+        assert_eq!(StdinMode::from_name("inherit"), Some(StdinMode::Inherit));
+        assert_eq!(StdinMode::from_name("none"), None);
+    }
+}
@@ -16,9 +16,13 @@ mod time;
 pub mod display;
 
 pub mod app;
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod bookmark;
 pub mod browser;
 pub mod clap;
 pub mod cli;
+pub mod clipboard;
 pub mod command;
 pub mod conf;
 pub mod content_search;
@@ -30,6 +34,7 @@ pub mod hex;
 pub mod help;
 pub mod icon;
 pub mod image;
+pub mod journal;
 pub mod keys;
 pub mod launchable;
 pub mod path;
@@ -37,13 +42,18 @@ pub mod pattern;
 pub mod permissions;
 pub mod preview;
 pub mod print;
+pub mod provider;
 pub mod shell_install;
 pub mod skin;
+pub mod stage;
 pub mod syntactic;
+pub mod tail;
 pub mod task_sync;
+pub mod template;
 pub mod tree;
 pub mod tree_build;
 pub mod verb;
+pub mod yank;
 
 #[cfg(unix)]
 pub mod filesystems;
@@ -51,5 +61,8 @@ pub mod filesystems;
 #[cfg(unix)]
 pub mod kitty;
 
+#[cfg(unix)]
+pub mod trash;
+
 #[cfg(feature="client-server")]
 pub mod net;
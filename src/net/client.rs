@@ -28,14 +28,14 @@ impl Client {
         debug!("try connecting {:?}", &self.path);
         let mut stream = UnixStream::connect(&self.path)?;
         message.write(&mut stream)?;
-        if let Message::GetRoot = message {
+        if let Message::GetRoot | Message::GetSelection = message {
             // we wait for the answer
             let mut br = BufReader::new(&stream);
             match Message::read(&mut br) {
                 Ok(answer) => {
                     debug!("got an answer: {:?}", &answer);
-                    if let Message::Root(root) = answer {
-                        println!("{}", root);
+                    if let Message::Root(path) | Message::Selection(path) = answer {
+                        println!("{}", path);
                     }
                 }
                 Err(e) => {
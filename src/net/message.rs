@@ -19,6 +19,8 @@ pub enum Message {
     Hi,
     GetRoot,
     Root(String),
+    GetSelection,
+    Selection(String),
     Sequence(Sequence),
 }
 
@@ -39,6 +41,8 @@ impl Message {
             "CMD" => Ok(Self::Command(read_line(r)?)),
             "GET_ROOT" => Ok(Self::GetRoot),
             "ROOT" => Ok(Self::Root(read_line(r)?)),
+            "GET_SELECTION" => Ok(Self::GetSelection),
+            "SELECTION" => Ok(Self::Selection(read_line(r)?)),
             "SEQ" => Ok(Self::Sequence(Sequence::new(
                 read_line(r)?,
                 Some(read_line(r)?),
@@ -62,6 +66,13 @@ impl Message {
                 writeln!(w, "ROOT")?;
                 writeln!(w, "{}", path)
             }
+            Self::GetSelection => {
+                writeln!(w, "GET_SELECTION")
+            }
+            Self::Selection(path) => {
+                writeln!(w, "SELECTION")?;
+                writeln!(w, "{}", path)
+            }
             Self::Sequence(Sequence { separator, raw }) => {
                 writeln!(w, "SEQ")?;
                 writeln!(w, "{}", separator)?;
@@ -70,3 +81,31 @@ impl Message {
         }
     }
 }
+
+#[cfg(test)]
+mod message_tests {
+
+    use {
+        super::*,
+        std::io::Cursor,
+    };
+
+    fn round_trip(message: &Message) -> Message {
+        let mut buffer = Vec::new();
+        message.write(&mut buffer).unwrap();
+        Message::read(&mut Cursor::new(buffer)).unwrap()
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        assert!(matches!(round_trip(&Message::GetSelection), Message::GetSelection));
+        assert!(matches!(
+            round_trip(&Message::Selection("/home/dys/some file.txt".to_string())),
+            Message::Selection(path) if path == "/home/dys/some file.txt"
+        ));
+        assert!(matches!(
+            round_trip(&Message::Command(":focus /tmp".to_string())),
+            Message::Command(cmd) if cmd == ":focus /tmp"
+        ));
+    }
+}
@@ -24,6 +24,7 @@ impl Server {
         name: &str,
         tx: Sender<Sequence>,
         root: Arc<Mutex<PathBuf>>,
+        selection: Arc<Mutex<PathBuf>>,
     ) -> Result<Self, NetError> {
         let path = super::socket_file_path(name);
         if fs::metadata(&path).is_ok() {
@@ -54,6 +55,18 @@ impl Server {
                                 }
                                 None
                             }
+                            Ok(Message::GetSelection) => {
+                                debug!("got get selection query");
+                                let selection = selection.lock().unwrap();
+                                let answer = Message::Selection(
+                                    selection.to_string_lossy().to_string(),
+                                );
+                                match answer.write(&mut stream) {
+                                    Ok(()) => debug!("selection successfuly returned"),
+                                    Err(e) => warn!("error while answering: {:?}", e),
+                                }
+                                None
+                            }
                             Ok(Message::Sequence(sequence)) => {
                                 debug!("got sequence {:?}", &sequence);
                                 Some(sequence)
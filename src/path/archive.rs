@@ -0,0 +1,500 @@
+use {
+    super::tar::{self, TarEntryKind},
+    flate2::read::MultiGzDecoder,
+    std::{
+        collections::BTreeMap,
+        fs,
+        io::{self, Read},
+        path::{Path, PathBuf},
+    },
+    zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter},
+};
+
+/// the formats of the archives which can be browsed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// determine the format of the archive from its extension
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// an entry of an archive, as listed when browsing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    /// the path of the entry in the archive, with `/` as separator
+    /// and without the trailing `/` of directories
+    pub name: String,
+    pub is_dir: bool,
+    /// the uncompressed size, 0 for directories
+    pub size: u64,
+}
+
+/// list the entries of an archive, sorted by name. Archives whose
+/// extension isn't known are read as zip archives (which is the
+/// format of jar or epub files).
+///
+/// Entries whose name would escape the archive (absolute paths, or
+/// with `..`) are ignored, and so are the links. Directories implied
+/// by the names of files but without an entry of their own are
+/// listed too.
+pub fn archive_listing(archive: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let entries = match ArchiveKind::of(archive).unwrap_or(ArchiveKind::Zip) {
+        ArchiveKind::Zip => zip_entries(archive)?,
+        kind => tar_entries(archive, kind)?,
+    };
+    // a later entry of the same name replaces the previous one
+    let mut listing: BTreeMap<String, ArchiveEntry> = BTreeMap::new();
+    for entry in entries {
+        let mut name = entry.name.as_str();
+        while let Some(idx) = name.rfind('/') {
+            name = &name[..idx];
+            listing.entry(name.to_string()).or_insert_with(|| ArchiveEntry {
+                name: name.to_string(),
+                is_dir: true,
+                size: 0,
+            });
+        }
+        listing.insert(entry.name.clone(), entry);
+    }
+    Ok(listing.into_values().collect())
+}
+
+/// extract an entry of an archive, with all its content when it's a
+/// directory, into `dir`, keeping only the last component of its name
+/// (`src/app/mod.rs` is extracted as `mod.rs`).
+///
+/// When the destination is already taken, a number is inserted in the
+/// name, as existing files are never overwritten. Return the path
+/// of the extracted file or directory.
+pub fn extract_archive_entry(archive: &Path, name: &str, dir: &Path) -> io::Result<PathBuf> {
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    let dest = super::unique_path(&dir.join(file_name));
+    let found = match ArchiveKind::of(archive).unwrap_or(ArchiveKind::Zip) {
+        ArchiveKind::Zip => extract_zip_entry(archive, name, &dest)?,
+        kind => extract_tar_entry(archive, kind, name, &dest)?,
+    };
+    if !found {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no entry {:?} in the archive", name),
+        ));
+    }
+    Ok(dest)
+}
+
+/// return the path of an entry of the archive relative to `dest`,
+/// where the entry `name` is extracted
+fn dest_path(entry_name: &str, name: &str, dest: &Path) -> Option<PathBuf> {
+    if entry_name == name {
+        Some(dest.to_path_buf())
+    } else {
+        entry_name
+            .strip_prefix(name)
+            .and_then(|rel| rel.strip_prefix('/'))
+            .map(|rel| dest.join(rel))
+    }
+}
+
+fn zip_entries(archive: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let mut zip = ZipArchive::new(fs::File::open(archive)?)?;
+    let mut entries: Vec<ArchiveEntry> = Vec::new();
+    for i in 0..zip.len() {
+        let file = zip.by_index(i)?;
+        if file.enclosed_name().is_none() {
+            continue;
+        }
+        let name = file.name().trim_end_matches('/').to_string();
+        if name.is_empty() {
+            continue;
+        }
+        entries.push(ArchiveEntry {
+            name,
+            is_dir: file.is_dir(),
+            size: if file.is_dir() { 0 } else { file.size() },
+        });
+    }
+    Ok(entries)
+}
+
+fn extract_zip_entry(archive: &Path, name: &str, dest: &Path) -> io::Result<bool> {
+    let mut zip = ZipArchive::new(fs::File::open(archive)?)?;
+    let mut found = false;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        if file.enclosed_name().is_none() {
+            continue;
+        }
+        let path = match dest_path(file.name().trim_end_matches('/'), name, dest) {
+            Some(path) => path,
+            None => continue,
+        };
+        found = true;
+        if file.is_dir() {
+            fs::create_dir_all(&path)?;
+        } else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut file, &mut fs::File::create(&path)?)?;
+        }
+    }
+    Ok(found)
+}
+
+fn tar_reader(archive: &Path, kind: ArchiveKind) -> io::Result<Box<dyn Read>> {
+    let file = io::BufReader::new(fs::File::open(archive)?);
+    Ok(match kind {
+        ArchiveKind::TarGz => Box::new(MultiGzDecoder::new(file)),
+        _ => Box::new(file),
+    })
+}
+
+/// normalize the name of a tar entry, removing the leading `./`
+/// and the trailing `/`, or return `None` when the name would
+/// escape the archive
+fn enclosed_tar_name(name: &str) -> Option<String> {
+    if name.starts_with('/') {
+        return None;
+    }
+    let mut parts = Vec::new();
+    for part in name.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                return None;
+            }
+            _ => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+fn tar_entries(archive: &Path, kind: ArchiveKind) -> io::Result<Vec<ArchiveEntry>> {
+    let mut entries: Vec<ArchiveEntry> = Vec::new();
+    tar::for_each_entry(tar_reader(archive, kind)?, |header, _| {
+        if header.kind != TarEntryKind::Other {
+            if let Some(name) = enclosed_tar_name(&header.name) {
+                entries.push(ArchiveEntry {
+                    name,
+                    is_dir: header.kind == TarEntryKind::Dir,
+                    size: header.size,
+                });
+            }
+        }
+        Ok(())
+    })?;
+    Ok(entries)
+}
+
+fn extract_tar_entry(
+    archive: &Path,
+    kind: ArchiveKind,
+    name: &str,
+    dest: &Path,
+) -> io::Result<bool> {
+    let mut found = false;
+    tar::for_each_entry(tar_reader(archive, kind)?, |header, data| {
+        let path = match enclosed_tar_name(&header.name)
+            .and_then(|entry_name| dest_path(&entry_name, name, dest))
+        {
+            Some(path) => path,
+            None => {
+                return Ok(());
+            }
+        };
+        match header.kind {
+            TarEntryKind::Dir => {
+                found = true;
+                fs::create_dir_all(&path)?;
+            }
+            TarEntryKind::File => {
+                found = true;
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                io::copy(data, &mut fs::File::create(&path)?)?;
+            }
+            TarEntryKind::Other => {} // links aren't extracted
+        }
+        Ok(())
+    })?;
+    Ok(found)
+}
+
+/// return the name of the archive of the path when none is given:
+/// `src` gives `src.zip` and `notes.md` gives `notes.zip`
+pub fn default_archive_name(path: &Path) -> String {
+    let stem = if path.is_dir() {
+        path.file_name()
+    } else {
+        path.file_stem()
+    };
+    let stem = stem
+        .map(|stem| stem.to_string_lossy().to_string())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| "archive".to_string());
+    format!("{}.zip", stem)
+}
+
+/// list the entries of the archive of the path, with the paths of
+/// their sources: the file itself, or the directory and all its
+/// content, hidden files included, with names relative to the parent
+/// of the path. Names of directories end with a `/`. Symbolic links
+/// aren't followed and aren't archived.
+pub fn archive_entries(path: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let base = path.parent().unwrap_or(path);
+    let mut entries = Vec::new();
+    add_entries(path, base, &mut entries)?;
+    Ok(entries)
+}
+
+fn add_entries(path: &Path, base: &Path, entries: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(path)?.file_type();
+    let name = path
+        .strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if file_type.is_dir() {
+        entries.push((format!("{}/", name), path.to_path_buf()));
+        let mut children: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        children.sort();
+        for child in children {
+            add_entries(&child, base, entries)?;
+        }
+    } else if file_type.is_file() {
+        entries.push((name, path.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// create in `dir` a zip archive of the path (a file, or a directory
+/// with all its content), named either `name` (to which `.zip` is added
+/// when missing) or, by default, after the path.
+///
+/// An existing file is never overwritten: when the archive's path is
+/// already taken, an error of kind `AlreadyExists` is returned.
+/// Return the path of the created archive.
+pub fn zip_in_dir(path: &Path, dir: &Path, name: Option<&str>) -> io::Result<PathBuf> {
+    let name = match name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => {
+            if name == "." || name == ".." || name.contains('/') || name.contains(std::path::MAIN_SEPARATOR) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid archive name: {:?}", name),
+                ));
+            }
+            if name.to_lowercase().ends_with(".zip") {
+                name.to_string()
+            } else {
+                format!("{}.zip", name)
+            }
+        }
+        None => default_archive_name(path),
+    };
+    let archive = dir.join(name);
+    let entries = archive_entries(path)?;
+    // create_new so that a file created in the meantime isn't overwritten
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&archive)?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, source) in entries {
+        if name.ends_with('/') {
+            zip.add_directory(name, options)?;
+        } else {
+            zip.start_file(name, options)?;
+            io::copy(&mut fs::File::open(&source)?, &mut zip)?;
+        }
+    }
+    zip.finish()?;
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod archive_tests {
+
+    use {
+        super::*,
+        zip::ZipArchive,
+    };
+
+    fn zipped_names(archive: &Path) -> Vec<String> {
+        let mut archive = ZipArchive::new(fs::File::open(archive).unwrap()).unwrap();
+        (0..archive.len())
+            .map(|i| archive.by_index(i).unwrap().name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_zip_in_dir() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let project = src_dir.path().join("project");
+        fs::create_dir_all(project.join("src/app")).unwrap();
+        fs::create_dir(project.join("empty")).unwrap();
+        for name in &["Cargo.toml", ".gitignore", "src/main.rs", "src/app/mod.rs"] {
+            fs::write(project.join(name), name).unwrap();
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(project.join("src"), project.join("link")).unwrap();
+        let expected = vec![
+            "project/",
+            "project/.gitignore",
+            "project/Cargo.toml",
+            "project/empty/",
+            "project/src/",
+            "project/src/app/",
+            "project/src/app/mod.rs",
+            "project/src/main.rs",
+        ];
+        let names: Vec<String> = archive_entries(&project)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, expected);
+        // by default the archive is named after the directory
+        let archive = zip_in_dir(&project, dst_dir.path(), None).unwrap();
+        assert_eq!(archive, dst_dir.path().join("project.zip"));
+        assert_eq!(zipped_names(&archive), expected);
+        let mut zip = ZipArchive::new(fs::File::open(&archive).unwrap()).unwrap();
+        let mut content = String::new();
+        io::Read::read_to_string(&mut zip.by_name("project/src/main.rs").unwrap(), &mut content).unwrap();
+        assert_eq!(content, "src/main.rs");
+        // an existing file isn't overwritten
+        let err = zip_in_dir(&project, dst_dir.path(), None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        // a file is archived alone, the extension being replaced
+        let file = project.join("src/main.rs");
+        let archive = zip_in_dir(&file, dst_dir.path(), None).unwrap();
+        assert_eq!(archive, dst_dir.path().join("main.zip"));
+        assert_eq!(zipped_names(&archive), vec!["main.rs"]);
+        // the given name is completed with the extension
+        let archive = zip_in_dir(&file, dst_dir.path(), Some("backup")).unwrap();
+        assert_eq!(archive, dst_dir.path().join("backup.zip"));
+        let err = zip_in_dir(&file, dst_dir.path(), Some("../escape")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_zip_listing_and_extraction() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let project = src_dir.path().join("project");
+        fs::create_dir_all(project.join("src/app")).unwrap();
+        for name in &["Cargo.toml", "src/main.rs", "src/app/mod.rs"] {
+            fs::write(project.join(name), name).unwrap();
+        }
+        let archive = zip_in_dir(&project, src_dir.path(), None).unwrap();
+        let entries = archive_listing(&archive).unwrap();
+        let listed: Vec<(&str, bool)> = entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.is_dir))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("project", true),
+                ("project/Cargo.toml", false),
+                ("project/src", true),
+                ("project/src/app", true),
+                ("project/src/app/mod.rs", false),
+                ("project/src/main.rs", false),
+            ],
+        );
+        assert_eq!(entries[1].size, "Cargo.toml".len() as u64);
+        // a file is extracted with its name only
+        let extracted = extract_archive_entry(&archive, "project/src/main.rs", dst_dir.path()).unwrap();
+        assert_eq!(extracted, dst_dir.path().join("main.rs"));
+        assert_eq!(fs::read_to_string(&extracted).unwrap(), "src/main.rs");
+        // an existing file isn't overwritten
+        let extracted = extract_archive_entry(&archive, "project/src/main.rs", dst_dir.path()).unwrap();
+        assert_eq!(extracted, dst_dir.path().join("main (1).rs"));
+        // a directory is extracted with its content
+        let extracted = extract_archive_entry(&archive, "project/src", dst_dir.path()).unwrap();
+        assert_eq!(extracted, dst_dir.path().join("src"));
+        assert_eq!(fs::read_to_string(extracted.join("app/mod.rs")).unwrap(), "src/app/mod.rs");
+        let err = extract_archive_entry(&archive, "project/missing", dst_dir.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_tar_listing_and_extraction() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let tar = super::super::tar::tar_tests::tar(&[
+            ("./project/", b'5', ""),
+            ("./project/src/main.rs", b'0', "src/main.rs"),
+            ("./project/src/app/mod.rs", b'0', "src/app/mod.rs"),
+            ("./project/link", b'2', ""),
+            ("../escape.rs", b'0', "escape"),
+            ("./project/src/main.rs", b'0', "fn main() {}"),
+        ]);
+        let archive = src_dir.path().join("project.tar");
+        fs::write(&archive, &tar).unwrap();
+        let tgz = src_dir.path().join("project.tgz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&tgz).unwrap(),
+            flate2::Compression::default(),
+        );
+        io::Write::write_all(&mut encoder, &tar).unwrap();
+        encoder.finish().unwrap();
+        assert_eq!(ArchiveKind::of(&tgz), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::of(Path::new("a.TAR.GZ")), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::of(Path::new("a.tar")), Some(ArchiveKind::Tar));
+        assert_eq!(ArchiveKind::of(Path::new("a.gz")), None);
+        for archive in &[archive, tgz] {
+            let entries = archive_listing(archive).unwrap();
+            let listed: Vec<(&str, bool, u64)> = entries
+                .iter()
+                .map(|e| (e.name.as_str(), e.is_dir, e.size))
+                .collect();
+            // the links and the escaping entries aren't listed, and
+            // the last entry of a name replaces the previous ones
+            assert_eq!(
+                listed,
+                vec![
+                    ("project", true, 0),
+                    ("project/src", true, 0),
+                    ("project/src/app", true, 0),
+                    ("project/src/app/mod.rs", false, 14),
+                    ("project/src/main.rs", false, 12),
+                ],
+            );
+            let extracted = extract_archive_entry(archive, "project/src", dst_dir.path()).unwrap();
+            assert_eq!(fs::read_to_string(extracted.join("main.rs")).unwrap(), "fn main() {}");
+            assert_eq!(fs::read_to_string(extracted.join("app/mod.rs")).unwrap(), "src/app/mod.rs");
+            let err = extract_archive_entry(archive, "project/link", dst_dir.path()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        }
+        assert!(!dst_dir.path().join("escape.rs").exists());
+        assert!(!src_dir.path().join("escape.rs").exists());
+    }
+}
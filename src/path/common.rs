@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+
+/// return the longest directory containing all the given paths,
+/// or None when there's no path or they don't share any (for
+/// example when they're on different drives)
+pub fn common_parent(paths: &[&Path]) -> Option<PathBuf> {
+    let mut parents = paths.iter().map(|p| p.parent().unwrap_or(p));
+    let mut common: Vec<_> = parents.next()?.components().collect();
+    for parent in parents {
+        let shared = common
+            .iter()
+            .zip(parent.components())
+            .take_while(|(a, b)| *a == b)
+            .count();
+        common.truncate(shared);
+    }
+    if common.is_empty() {
+        None
+    } else {
+        Some(common.iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod path_common_tests {
+
+    use super::*;
+
+    fn check(paths: &[&str], chk: Option<&str>) {
+        let paths: Vec<&Path> = paths.iter().map(Path::new).collect();
+        assert_eq!(common_parent(&paths), chk.map(PathBuf::from));
+    }
+
+    #[test]
+    fn test_common_parent() {
+        check(&[], None);
+        check(&["/home/dys/a.txt"], Some("/home/dys"));
+        check(&["/home/dys/a.txt", "/home/dys/b.txt"], Some("/home/dys"));
+        check(&["/home/dys/dev/a.rs", "/home/dys/b.txt"], Some("/home/dys"));
+        check(&["/home/dys/dev", "/home/dys/dev/src/a.rs"], Some("/home/dys"));
+        check(&["/home/dysa/a.txt", "/home/dysb/b.txt"], Some("/home"));
+        check(&["/home/a.txt", "/tmp/b.txt"], Some("/"));
+        check(&["a/b.txt", "/tmp/b.txt"], None);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_common_parent_on_different_drives() {
+        check(&[r"C:\Users\dys\a.txt", r"C:\Users\dys\b.txt"], Some(r"C:\Users\dys"));
+        check(&[r"C:\Users\dys\a.txt", r"D:\Users\dys\b.txt"], None);
+    }
+}
@@ -0,0 +1,218 @@
+use std::path::{Component, Path, PathBuf};
+
+/// return the file name without all its extensions (`archive.tar.gz`
+/// gives `archive`). A leading dot isn't the start of an extension, so
+/// `.bashrc` is kept as is and `.config.toml` gives `.config`
+pub fn file_base(file_name: &str) -> &str {
+    match file_name.char_indices().skip(1).find(|&(_, c)| c == '.') {
+        Some((idx, _)) => &file_name[..idx],
+        None => file_name,
+    }
+}
+
+/// keep only the last `max_depth` components of the path, the trimmed
+/// start being replaced with `…`, e.g. `…/verb/mod.rs` for
+/// `src/verb/mod.rs` with a max depth of 2. The root of an absolute
+/// path doesn't count as a component.
+pub fn trim_depth(path: &Path, max_depth: usize) -> PathBuf {
+    let components: Vec<Component<'_>> = path
+        .components()
+        .filter(|c| !matches!(c, Component::Prefix(_) | Component::RootDir))
+        .collect();
+    if components.len() <= max_depth {
+        return path.to_path_buf();
+    }
+    let mut trimmed = PathBuf::from("…");
+    for component in &components[components.len() - max_depth..] {
+        trimmed.push(component);
+    }
+    trimmed
+}
+
+/// canonicalize the parent of the path, resolving the symbolic links
+/// of the directories, but keep the last component as is, even when
+/// it's a link. The path is returned unchanged when its parent can't
+/// be canonicalized.
+pub fn resolve_parents(path: &Path) -> PathBuf {
+    let name = match path.file_name() {
+        Some(name) => name,
+        None => {
+            // e.g. `/` or a path ending in `..`, which has no final name
+            return path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        }
+    };
+    let parent = match path.parent() {
+        Some(parent) if parent.components().next().is_some() => parent,
+        _ => Path::new("."),
+    };
+    match parent.canonicalize() {
+        Ok(parent) => parent.join(name),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// the length, in UTF-16 units, from which a path is too long for
+/// the classic windows API (`MAX_PATH`, which counts the final nul)
+#[cfg(windows)]
+const MAX_PATH_LEN: usize = 260;
+
+/// prefix the path with `\\?\` (or `\\?\UNC\` for a network path)
+/// when it's an absolute path too long for the classic windows API
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    let s = super::with_native_separators(&path.to_string_lossy());
+    if !path.is_absolute() || s.starts_with(r"\\?\") || s.encode_utf16().count() < MAX_PATH_LEN {
+        return path.to_path_buf();
+    }
+    PathBuf::from(match s.strip_prefix(r"\\") {
+        Some(unc) => format!(r"\\?\UNC\{}", unc),
+        None => format!(r"\\?\{}", s),
+    })
+}
+
+/// paths don't need a prefix when they're long, out of windows
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// return the path relative to the base, `.` when it's the base
+/// itself, or the path unchanged when it can't be made relative
+pub fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    match pathdiff::diff_paths(path, base) {
+        Some(relative) if relative.components().next().is_none() => PathBuf::from("."),
+        Some(relative) => relative,
+        None => path.to_path_buf(),
+    }
+}
+
+/// return the path relative to the home directory, or None when
+/// it's not inside. The home directory itself gives `.`
+pub fn home_relative(path: &Path, home: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(home).ok()?;
+    Some(if relative.components().next().is_none() {
+        PathBuf::from(".")
+    } else {
+        relative.to_path_buf()
+    })
+}
+
+/// return the last `n` components of the path, joined with `/`.
+/// When the path has fewer components, they're all kept, but
+/// not the root nor the drive.
+pub fn path_tail(path: &Path, n: usize) -> String {
+    let names: Vec<_> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect();
+    names[names.len().saturating_sub(n)..].join("/")
+}
+
+#[cfg(test)]
+mod path_components_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_file_base() {
+        assert_eq!(file_base("README"), "README");
+        assert_eq!(file_base("main.rs"), "main");
+        assert_eq!(file_base("archive.tar.gz"), "archive");
+        assert_eq!(file_base(".bashrc"), ".bashrc");
+        assert_eq!(file_base(".config.toml"), ".config");
+        assert_eq!(file_base("trailing."), "trailing");
+    }
+
+    #[test]
+    fn test_trim_depth() {
+        let path = Path::new("src/verb/execution/mod.rs");
+        assert_eq!(trim_depth(path, 2), PathBuf::from("…/execution/mod.rs"));
+        assert_eq!(trim_depth(path, 1), PathBuf::from("…/mod.rs"));
+        // paths which aren't deeper than the limit are kept
+        assert_eq!(trim_depth(path, 4), PathBuf::from("src/verb/execution/mod.rs"));
+        assert_eq!(trim_depth(path, 10), PathBuf::from("src/verb/execution/mod.rs"));
+        assert_eq!(trim_depth(Path::new("mod.rs"), 2), PathBuf::from("mod.rs"));
+        assert_eq!(trim_depth(Path::new("."), 2), PathBuf::from("."));
+        #[cfg(unix)]
+        assert_eq!(trim_depth(Path::new("/etc/hosts"), 2), PathBuf::from("/etc/hosts"));
+        #[cfg(unix)]
+        assert_eq!(trim_depth(Path::new("/etc/ssh/sshd_config"), 2), PathBuf::from("…/ssh/sshd_config"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let real_dir = root.join("real");
+        std::fs::create_dir(&real_dir).unwrap();
+        std::fs::write(real_dir.join("target.txt"), "content").unwrap();
+        std::os::unix::fs::symlink(real_dir.join("target.txt"), real_dir.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(&real_dir, root.join("linked-dir")).unwrap();
+        // the symlinked parent is resolved, the final link is kept
+        let link = root.join("linked-dir").join("link.txt");
+        assert_eq!(resolve_parents(&link), real_dir.join("link.txt"));
+        // a symlinked directory as final component is kept too
+        assert_eq!(resolve_parents(&root.join("linked-dir")), root.join("linked-dir"));
+        // the final component doesn't need to exist
+        assert_eq!(
+            resolve_parents(&root.join("linked-dir").join("new.txt")),
+            real_dir.join("new.txt"),
+        );
+        // neither does the parent, but then nothing is resolved
+        let missing = root.join("missing").join("file.txt");
+        assert_eq!(resolve_parents(&missing), missing);
+        // relative paths are made absolute
+        let current_dir = std::env::current_dir().unwrap().canonicalize().unwrap();
+        assert_eq!(resolve_parents(Path::new("Cargo.toml")), current_dir.join("Cargo.toml"));
+        assert_eq!(resolve_parents(Path::new("/")), PathBuf::from("/"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_long_path() {
+        let short = PathBuf::from(r"C:\Users\dys\notes.txt");
+        assert_eq!(long_path(&short), short);
+        let long = format!(r"C:\Users\dys\{}\notes.txt", "a".repeat(250));
+        assert_eq!(long_path(Path::new(&long)), PathBuf::from(format!(r"\\?\{}", long)));
+        // an already prefixed path is kept as is
+        let prefixed = PathBuf::from(format!(r"\\?\{}", long));
+        assert_eq!(long_path(&prefixed), prefixed);
+        let unc = format!(r"\\server\share\{}", "b".repeat(260));
+        assert_eq!(
+            long_path(Path::new(&unc)),
+            PathBuf::from(format!(r"\\?\UNC\server\share\{}", "b".repeat(260))),
+        );
+        // a relative path can't be prefixed
+        let relative = PathBuf::from("c".repeat(300));
+        assert_eq!(long_path(&relative), relative);
+    }
+
+    #[test]
+    fn test_home_relative() {
+        let home = Path::new("/home/dys");
+        assert_eq!(
+            home_relative(Path::new("/home/dys/.config/nvim/init.vim"), home),
+            Some(PathBuf::from(".config/nvim/init.vim")),
+        );
+        assert_eq!(home_relative(home, home), Some(PathBuf::from(".")));
+        assert_eq!(home_relative(Path::new("/etc/hosts"), home), None);
+        // a sibling sharing the prefix of the home isn't inside it
+        assert_eq!(home_relative(Path::new("/home/dys2/notes.md"), home), None);
+        assert_eq!(home_relative(Path::new("/home"), home), None);
+    }
+
+    #[test]
+    fn test_path_tail() {
+        let path = Path::new("/home/dys/dev/broot/src/main.rs");
+        assert_eq!(path_tail(path, 1), "main.rs");
+        assert_eq!(path_tail(path, 3), "broot/src/main.rs");
+        // with fewer components than asked, all of them are kept
+        assert_eq!(path_tail(path, 6), "home/dys/dev/broot/src/main.rs");
+        assert_eq!(path_tail(path, 20), "home/dys/dev/broot/src/main.rs");
+        assert_eq!(path_tail(Path::new("/"), 2), "");
+    }
+}
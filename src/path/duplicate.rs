@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// return a path, in the same directory, which isn't used yet, by
+/// inserting a number before the extension: `file.txt` gives
+/// `file (1).txt`, or `file (2).txt` if the former already exists.
+/// A path which doesn't exist is returned as is.
+pub fn unique_path(path: &Path) -> PathBuf {
+    if fs::symlink_metadata(path).is_err() {
+        return path.to_path_buf();
+    }
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => return path.to_path_buf(),
+    };
+    // the extension of a dot file (e.g. ".bashrc") is part of its stem
+    let (stem, extension) = match name.rfind('.') {
+        Some(idx) if idx > 0 && !path.is_dir() => name.split_at(idx),
+        _ => (name.as_str(), ""),
+    };
+    let mut counter = 1;
+    loop {
+        let candidate = path.with_file_name(format!("{} ({}){}", stem, counter, extension));
+        if fs::symlink_metadata(&candidate).is_err() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+/// copy the file, or the directory and all its content, to dst.
+/// Symbolic links are copied as links on unix.
+pub fn copy_recursively(src: &Path, dst: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(src)?.file_type();
+    if file_type.is_dir() {
+        fs::create_dir(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        #[cfg(unix)]
+        if file_type.is_symlink() {
+            return std::os::unix::fs::symlink(fs::read_link(src)?, dst);
+        }
+        fs::copy(src, dst).map(|_| ())
+    }
+}
+
+/// copy the file or directory to a unique path in the same
+/// directory, and return this new path
+pub fn duplicate(path: &Path) -> io::Result<PathBuf> {
+    let dst = unique_path(path);
+    if dst == path {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "nothing to duplicate"));
+    }
+    copy_recursively(path, &dst)?;
+    Ok(dst)
+}
+
+#[cfg(test)]
+mod duplicate_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_unique_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(unique_path(&root.join("file.txt")), root.join("file.txt"));
+        fs::write(root.join("file.txt"), "").unwrap();
+        assert_eq!(unique_path(&root.join("file.txt")), root.join("file (1).txt"));
+        fs::write(root.join("file (1).txt"), "").unwrap();
+        assert_eq!(unique_path(&root.join("file.txt")), root.join("file (2).txt"));
+        fs::write(root.join("archive.tar.gz"), "").unwrap();
+        assert_eq!(unique_path(&root.join("archive.tar.gz")), root.join("archive.tar (1).gz"));
+        fs::write(root.join(".bashrc"), "").unwrap();
+        assert_eq!(unique_path(&root.join(".bashrc")), root.join(".bashrc (1)"));
+        fs::create_dir(root.join("src.d")).unwrap();
+        assert_eq!(unique_path(&root.join("src.d")), root.join("src.d (1)"));
+    }
+
+    #[test]
+    fn test_duplicate_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.md");
+        fs::write(&file, "content").unwrap();
+        let copy = duplicate(&file).unwrap();
+        assert_eq!(copy, dir.path().join("notes (1).md"));
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "content");
+        assert_eq!(fs::read_to_string(&file).unwrap(), "content");
+        assert!(duplicate(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("project");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        fs::write(src.join("sub/b.txt"), "b").unwrap();
+        let copy = duplicate(&src).unwrap();
+        assert_eq!(copy, dir.path().join("project (1)"));
+        assert_eq!(fs::read_to_string(copy.join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(copy.join("sub/b.txt")).unwrap(), "b");
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+/// the environment variables which `{file:env-collapse}` may
+/// put in place of the start of a path
+const ENV_COLLAPSE_VAR_NAMES: &[&str] = &[
+    "HOME",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+];
+
+/// return the names and values of the variables which may be
+/// collapsed, ignoring the unset, empty or relative ones
+pub fn env_collapse_vars() -> Vec<(&'static str, PathBuf)> {
+    ENV_COLLAPSE_VAR_NAMES
+        .iter()
+        .filter_map(|&name| {
+            std::env::var_os(name)
+                .map(PathBuf::from)
+                .filter(|value| value.is_absolute())
+                .map(|value| (name, value))
+        })
+        .collect()
+}
+
+/// find the variable whose value is the longest start of the path,
+/// and return its name with the rest of the path (starting with a
+/// separator, or empty when the path is the value of the variable)
+pub fn env_collapse(path: &Path, vars: &[(&'static str, PathBuf)]) -> Option<(&'static str, PathBuf)> {
+    vars.iter()
+        .filter(|(_, value)| path.starts_with(value))
+        .max_by_key(|(_, value)| value.components().count())
+        .map(|(name, value)| {
+            let rest = path.strip_prefix(value).unwrap_or(path);
+            if rest.components().next().is_some() {
+                (*name, Path::new(&std::path::MAIN_SEPARATOR.to_string()).join(rest))
+            } else {
+                (*name, PathBuf::new())
+            }
+        })
+}
+
+/// return the home directory of the user, as given by `$HOME`
+/// on unix
+pub fn home_dir() -> Option<PathBuf> {
+    directories::UserDirs::new().map(|user_dirs| user_dirs.home_dir().to_path_buf())
+}
+
+#[cfg(test)]
+mod env_collapse_tests {
+
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_env_collapse() {
+        let vars = vec![
+            ("HOME", PathBuf::from("/home/dys")),
+            ("XDG_CONFIG_HOME", PathBuf::from("/home/dys/.config")),
+        ];
+        assert_eq!(
+            env_collapse(Path::new("/home/dys/dev/broot"), &vars),
+            Some(("HOME", PathBuf::from("/dev/broot"))),
+        );
+        // the longest value wins
+        assert_eq!(
+            env_collapse(Path::new("/home/dys/.config/broot/conf.hjson"), &vars),
+            Some(("XDG_CONFIG_HOME", PathBuf::from("/broot/conf.hjson"))),
+        );
+        assert_eq!(env_collapse(Path::new("/home/dys"), &vars), Some(("HOME", PathBuf::new())));
+        // only whole components are collapsed
+        assert_eq!(env_collapse(Path::new("/home/dys2/notes.md"), &vars), None);
+        assert_eq!(env_collapse(Path::new("/etc/hosts"), &vars), None);
+        assert_eq!(env_collapse(Path::new("/etc/hosts"), &[]), None);
+    }
+}
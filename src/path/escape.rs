@@ -12,3 +12,104 @@ pub fn escape_for_shell(path: &Path) -> String {
         format!("'{}'", &path.replace('\'', r"'\''"))
     }
 }
+
+/// the quoting rules of the shell a command is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellEscaping {
+    Posix,
+    WindowsCmd,
+    PowerShell,
+}
+
+impl Default for ShellEscaping {
+    /// the escaping of the platform's usual shell
+    fn default() -> Self {
+        if cfg!(windows) {
+            Self::WindowsCmd
+        } else {
+            Self::Posix
+        }
+    }
+}
+
+impl ShellEscaping {
+    /// parse the value of the `shell_escaping` verb option
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "posix" => Some(Self::Posix),
+            "cmd" => Some(Self::WindowsCmd),
+            "powershell" => Some(Self::PowerShell),
+            _ => None,
+        }
+    }
+    /// build a string usable in a command of this shell, quoting
+    /// the path only when necessary
+    pub fn escape(self, path: &Path) -> String {
+        match self {
+            Self::Posix => escape_for_shell(path),
+            Self::WindowsCmd => {
+                let path = path.to_string_lossy();
+                if regex!(r"^[\w\\/.:-]*$").is_match(&path) {
+                    path.to_string()
+                } else {
+                    // backslashes are separators, not escapes, in cmd
+                    format!("\"{}\"", &path.replace('"', "\"\""))
+                }
+            }
+            Self::PowerShell => {
+                let path = path.to_string_lossy();
+                if regex!(r"^[\w\\/.:-]*$").is_match(&path) {
+                    path.to_string()
+                } else {
+                    // nothing is interpreted in single quotes, except
+                    // the single quote which is doubled
+                    format!("'{}'", &path.replace('\'', "''"))
+                }
+            }
+        }
+    }
+}
+
+/// build a line of shell variable assignments, for example
+/// `LEFT=/some/path RIGHT='/other path'`
+pub fn shell_assignments(assignments: &[(&str, &Path)]) -> String {
+    assignments
+        .iter()
+        .map(|(name, path)| format!("{}={}", name, escape_for_shell(path)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod path_escape_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_escaping_modes() {
+        let check = |escaping: ShellEscaping, path: &str, escaped: &str| {
+            assert_eq!(escaping.escape(Path::new(path)), escaped);
+        };
+        check(ShellEscaping::Posix, "/home/dys/dev", "/home/dys/dev");
+        check(ShellEscaping::Posix, "/home/dys/my dev", "'/home/dys/my dev'");
+        check(ShellEscaping::Posix, "/home/dys/it's", r"'/home/dys/it'\''s'");
+        check(ShellEscaping::WindowsCmd, r"C:\dev\app", r"C:\dev\app");
+        check(ShellEscaping::WindowsCmd, r"C:\Program Files\app", r#""C:\Program Files\app""#);
+        check(ShellEscaping::WindowsCmd, r"C:\dev\it's", r#""C:\dev\it's""#);
+        check(ShellEscaping::PowerShell, r"C:\dev\app", r"C:\dev\app");
+        check(ShellEscaping::PowerShell, r"C:\Program Files\app", r"'C:\Program Files\app'");
+        check(ShellEscaping::PowerShell, r"C:\dev\it's", r"'C:\dev\it''s'");
+        check(ShellEscaping::PowerShell, r"C:\my $HOME", r"'C:\my $HOME'");
+    }
+
+    #[test]
+    fn test_shell_assignments() {
+        assert_eq!(
+            shell_assignments(&[
+                ("LEFT", Path::new("/home/dys/dev/broot")),
+                ("RIGHT", Path::new("/home/dys/it's a file.txt")),
+            ]),
+            r"LEFT=/home/dys/dev/broot RIGHT='/home/dys/it'\''s a file.txt'",
+        );
+    }
+}
@@ -72,3 +72,32 @@ pub fn do_exec_replacement(
         format!("{{{}}}", name)
     }
 }
+
+/// make the path absolute, relative to the current directory
+/// if necessary, without resolving symlinks
+pub fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match std::env::current_dir() {
+            Ok(current_dir) => current_dir.join(path),
+            Err(e) => {
+                warn!("no current dir: {:?}", e);
+                path.to_path_buf()
+            }
+        }
+    }
+}
+
+/// make the path relative to the current directory (the one broot
+/// was launched from, where external commands run by default)
+pub fn relative_to_current_dir(path: &Path) -> PathBuf {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|current_dir| pathdiff::diff_paths(path, &current_dir));
+    match relative {
+        Some(relative) if relative.components().next().is_none() => PathBuf::from("."),
+        Some(relative) => relative,
+        None => path.to_path_buf(),
+    }
+}
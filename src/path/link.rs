@@ -0,0 +1,87 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// create, in the `dir` directory, a symbolic link to `target` named
+/// either `name` or, by default, like the target.
+///
+/// An existing file is never overwritten: when the link's path is
+/// already taken, an error of kind `AlreadyExists` is returned.
+/// Return the path of the created link.
+pub fn link_in_dir(target: &Path, dir: &Path, name: Option<&str>) -> io::Result<PathBuf> {
+    let name = match name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => {
+            if name == "." || name == ".." || name.contains('/') || name.contains(std::path::MAIN_SEPARATOR) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid link name: {:?}", name),
+                ));
+            }
+            name.as_ref()
+        }
+        None => target.file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?,
+    };
+    let link = dir.join(name);
+    // symlink_metadata so that dangling links are considered too
+    if link.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{:?} already exists", link),
+        ));
+    }
+    symlink(target, &link)?;
+    Ok(link)
+}
+
+/// create a symbolic link at `link`, pointing to `target`
+#[cfg(unix)]
+pub fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+/// create a symbolic link at `link`, pointing to `target`
+#[cfg(windows)]
+pub fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod link_tests {
+
+    use {
+        super::*,
+        std::fs,
+    };
+
+    #[test]
+    fn test_link_in_dir() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let target = src_dir.path().join("notes.txt");
+        fs::write(&target, "content").unwrap();
+        // by default the link has the name of the target
+        let link = link_in_dir(&target, dst_dir.path(), None).unwrap();
+        assert_eq!(link, dst_dir.path().join("notes.txt"));
+        assert_eq!(fs::read_link(&link).unwrap(), target);
+        assert_eq!(fs::read_to_string(&link).unwrap(), "content");
+        // an existing file isn't overwritten
+        let err = link_in_dir(&target, dst_dir.path(), None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        // but another name can be given
+        let link = link_in_dir(&target, dst_dir.path(), Some("other.txt")).unwrap();
+        assert_eq!(link, dst_dir.path().join("other.txt"));
+        assert_eq!(fs::read_link(&link).unwrap(), target);
+        // names aren't paths
+        let err = link_in_dir(&target, dst_dir.path(), Some("../escape")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        // directories can be linked too
+        let link = link_in_dir(src_dir.path(), dst_dir.path(), Some("src")).unwrap();
+        assert!(link.join("notes.txt").exists());
+    }
+}
@@ -0,0 +1,173 @@
+use {
+    crate::tree::TreeOptions,
+    chrono::{DateTime, Local},
+    std::{
+        fs,
+        path::Path,
+        time::Duration,
+    },
+};
+
+/// summarize the paths by extension, e.g. `rs:3 toml:1`. Extensions
+/// are lowercased, the paths without extension are counted as `(none)`,
+/// and the most frequent extensions come first, ties being sorted by name
+pub fn count_by_extension(paths: &[&Path]) -> String {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for path in paths {
+        let extension = path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "(none)".to_string());
+        match counts.iter_mut().find(|(ext, _)| *ext == extension) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((extension, 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+        .iter()
+        .map(|(ext, count)| format!("{}:{}", ext, count))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// count the subdirectories and the plain files directly in the
+/// directory, hidden ones included. Symbolic links aren't followed
+/// and are counted in neither, as are the other special files
+pub fn count_children(dir: &Path) -> Option<(usize, usize)> {
+    let mut dirs = 0;
+    let mut files = 0;
+    for entry in fs::read_dir(dir).ok()? {
+        let file_type = match entry.and_then(|entry| entry.file_type()) {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+        if file_type.is_dir() {
+            dirs += 1;
+        } else if file_type.is_file() {
+            files += 1;
+        }
+    }
+    Some((dirs, files))
+}
+
+/// describe the file in a compact `ls -l` like way: type, permissions
+/// (on unix), size, modification date and name, e.g.
+/// `- rw-r--r-- 12K 2020/12/19 14:32 notes.md`
+pub fn file_meta(path: &Path) -> Option<String> {
+    let md = fs::symlink_metadata(path).ok()?;
+    let file_type = md.file_type();
+    let type_char = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else {
+        '-'
+    };
+    let mut meta = type_char.to_string();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        meta.push(' ');
+        meta.push_str(&crate::permissions::mode_string(md.mode()));
+    }
+    meta.push(' ');
+    meta.push_str(&file_size::fit_4(md.len()));
+    if let Ok(mtime) = md.modified() {
+        let date_time: DateTime<Local> = mtime.into();
+        meta.push(' ');
+        meta.push_str(&date_time.format(TreeOptions::default().date_time_format).to_string());
+    }
+    meta.push(' ');
+    meta.push_str(&path.file_name().unwrap_or(path.as_os_str()).to_string_lossy());
+    Some(meta)
+}
+
+/// return the signed difference, in bytes, between the size of the
+/// path and the one of the other path, e.g. `+120`, `-3` or `0`,
+/// or None when one of them can't be read
+pub fn size_delta(path: &Path, other: &Path) -> Option<String> {
+    let size = fs::metadata(path).ok()?.len() as i128;
+    let other_size = fs::metadata(other).ok()?.len() as i128;
+    Some(match size - other_size {
+        0 => "0".to_string(),
+        delta if delta > 0 => format!("+{}", delta),
+        delta => delta.to_string(),
+    })
+}
+
+/// return the number of hard links to the path, or None
+/// when it can't be read or on non unix systems
+#[cfg(unix)]
+pub fn nlink(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|md| md.nlink())
+}
+#[cfg(not(unix))]
+pub fn nlink(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// write a duration in a human friendly way, like "3 days ago",
+/// only keeping the biggest unit
+pub fn time_ago(duration: Duration) -> String {
+    const UNITS: &[(u64, &str)] = &[
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (7 * 24 * 60 * 60, "week"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+    let secs = duration.as_secs();
+    for (unit_secs, unit_name) in UNITS {
+        let n = secs / unit_secs;
+        if n > 0 {
+            return format!("{} {}{} ago", n, unit_name, if n > 1 { "s" } else { "" });
+        }
+    }
+    "just now".to_string()
+}
+
+#[cfg(test)]
+mod path_meta_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_time_ago() {
+        assert_eq!(time_ago(Duration::from_millis(300)), "just now");
+        assert_eq!(time_ago(Duration::from_secs(1)), "1 second ago");
+        assert_eq!(time_ago(Duration::from_secs(59)), "59 seconds ago");
+        assert_eq!(time_ago(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(time_ago(Duration::from_secs(2 * 3600 + 59 * 60)), "2 hours ago");
+        assert_eq!(time_ago(Duration::from_secs(3 * 24 * 3600 + 5)), "3 days ago");
+        assert_eq!(time_ago(Duration::from_secs(15 * 24 * 3600)), "2 weeks ago");
+        assert_eq!(time_ago(Duration::from_secs(400 * 24 * 3600)), "1 year ago");
+    }
+
+    #[test]
+    fn test_file_meta() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.md");
+        fs::write(&file, "hello").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let meta = file_meta(&file).unwrap();
+        #[cfg(unix)]
+        assert!(meta.starts_with("- rw-r----- 5 "), "unexpected meta: {:?}", meta);
+        #[cfg(not(unix))]
+        assert!(meta.starts_with("- 5 "), "unexpected meta: {:?}", meta);
+        assert!(meta.ends_with(" notes.md"));
+        let date: DateTime<Local> = fs::metadata(&file).unwrap().modified().unwrap().into();
+        assert!(meta.contains(&date.format("%Y/%m/%d %R").to_string()));
+        let dir_meta = file_meta(dir.path()).unwrap();
+        assert!(dir_meta.starts_with("d "));
+        assert_eq!(file_meta(&dir.path().join("missing")), None);
+        #[cfg(unix)]
+        assert_eq!(crate::permissions::mode_string(0o100755), "rwxr-xr-x");
+    }
+}
@@ -1,13 +1,40 @@
 mod anchor;
+#[cfg(feature = "archive")]
+mod archive;
 mod closest;
+mod common;
+mod components;
+mod duplicate;
+mod env_collapse;
 mod escape;
 mod from;
+mod link;
+mod meta;
 mod normalize;
+mod sanitize;
+mod separators;
+mod swap;
+#[cfg(feature = "archive")]
+mod tar;
+mod url;
 
 pub use {
     anchor::*,
     closest::*,
+    common::*,
+    components::*,
+    duplicate::*,
+    env_collapse::*,
     escape::*,
     from::*,
+    link::*,
+    meta::*,
     normalize::*,
+    sanitize::*,
+    separators::*,
+    swap::*,
+    url::*,
 };
+
+#[cfg(feature = "archive")]
+pub use archive::*;
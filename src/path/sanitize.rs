@@ -0,0 +1,82 @@
+/// make a name usable as a file name on all common platforms:
+/// characters forbidden on Windows are replaced with underscores
+/// and trailing dots and spaces are removed
+pub fn fs_safe_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    safe.trim_end_matches(['.', ' ']).to_string()
+}
+
+/// make a lowercase ASCII slug of a name, usable in URLs: diacritics
+/// are removed and all sequences of other characters (spaces,
+/// underscores, punctuation) become single hyphens
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    let mut pending_hyphen = false;
+    for c in secular::lower_lay_string(name).chars() {
+        if c.is_ascii_alphanumeric() {
+            if pending_hyphen && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_hyphen = false;
+            slug.push(c.to_ascii_lowercase());
+        } else {
+            pending_hyphen = true;
+        }
+    }
+    slug
+}
+
+/// make a valid shell variable name of a name: it's lowercased, all
+/// characters other than ASCII letters and digits become underscores,
+/// and an underscore is prepended when it starts with a digit
+pub fn shell_var_name(name: &str) -> String {
+    let mut var: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if var.is_empty() || var.starts_with(|c: char| c.is_ascii_digit()) {
+        var.insert(0, '_');
+    }
+    var
+}
+
+#[cfg(test)]
+mod path_sanitize_tests {
+
+    use super::{fs_safe_name, shell_var_name, slugify};
+
+    #[test]
+    fn test_fs_safe_name() {
+        assert_eq!(fs_safe_name("report.txt"), "report.txt");
+        assert_eq!(fs_safe_name("a<b>c:d\"e/f\\g|h?i*j"), "a_b_c_d_e_f_g_h_i_j");
+        assert_eq!(fs_safe_name("what? notes: draft. "), "what_ notes_ draft");
+        assert_eq!(fs_safe_name("dots..."), "dots");
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("hello"), "hello");
+        assert_eq!(slugify("Été à la Plage"), "ete-a-la-plage");
+        assert_eq!(slugify("My_Great  Post"), "my-great-post");
+        assert_eq!(slugify("CamelCase_2020"), "camelcase-2020");
+        assert_eq!(slugify(" -- Crème brûlée! -- "), "creme-brulee");
+        assert_eq!(slugify("___"), "");
+    }
+
+    #[test]
+    fn test_shell_var_name() {
+        assert_eq!(shell_var_name("notes"), "notes");
+        assert_eq!(shell_var_name("My Notes"), "my_notes");
+        assert_eq!(shell_var_name("2021-report"), "_2021_report");
+        assert_eq!(shell_var_name("v1.2 (final)!"), "v1_2__final__");
+        assert_eq!(shell_var_name("Été"), "_t_");
+        assert_eq!(shell_var_name(""), "_");
+    }
+}
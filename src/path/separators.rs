@@ -0,0 +1,47 @@
+use std::path::MAIN_SEPARATOR;
+
+/// replace the path separators of a path string with the given one.
+///
+/// When the native separator is a backslash (i.e. on Windows),
+/// slashes are understood as separators too. Elsewhere backslashes
+/// are valid in file names and are kept.
+pub fn with_separators(path: &str, native_sep: char, sep: char) -> String {
+    path.chars()
+        .map(|c| {
+            if c == native_sep || (native_sep == '\\' && c == '/') {
+                sep
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// make the path string use the platform's separator
+pub fn with_native_separators(path: &str) -> String {
+    with_separators(path, MAIN_SEPARATOR, MAIN_SEPARATOR)
+}
+
+/// make the path string use slashes, whatever the platform
+pub fn with_slash_separators(path: &str) -> String {
+    with_separators(path, MAIN_SEPARATOR, '/')
+}
+
+#[cfg(test)]
+mod path_separators_tests {
+
+    use super::with_separators;
+
+    #[test]
+    fn test_windows_separators() {
+        let path = r"src\verb/mod.rs";
+        assert_eq!(with_separators(path, '\\', '\\'), r"src\verb\mod.rs");
+        assert_eq!(with_separators(path, '\\', '/'), "src/verb/mod.rs");
+    }
+
+    #[test]
+    fn test_unix_separators() {
+        let path = r"src/a\b.rs"; // a backslash is a valid char in a unix name
+        assert_eq!(with_separators(path, '/', '/'), r"src/a\b.rs");
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// swap the names of two files, each one staying in its directory.
+///
+/// The swap is done in three renames, through a temporary name. When
+/// a rename fails, the previous ones are reverted.
+/// Return the new paths of the files initially at `a` and `b`.
+pub fn swap_names(a: &Path, b: &Path) -> io::Result<(PathBuf, PathBuf)> {
+    swap_names_with(a, b, |from, to| fs::rename(from, to))
+}
+
+fn invalid(details: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, details)
+}
+
+fn swap_names_with<R>(a: &Path, b: &Path, rename: R) -> io::Result<(PathBuf, PathBuf)>
+where
+    R: Fn(&Path, &Path) -> io::Result<()>,
+{
+    if !a.is_file() || !b.is_file() {
+        return Err(invalid("only files can have their names swapped"));
+    }
+    let (a_dir, a_name) = a.parent().zip(a.file_name())
+        .ok_or_else(|| invalid("no file name"))?;
+    let (b_dir, b_name) = b.parent().zip(b.file_name())
+        .ok_or_else(|| invalid("no file name"))?;
+    if a_name == b_name {
+        return Err(invalid("the files have the same name"));
+    }
+    let new_a = a_dir.join(b_name);
+    let new_b = b_dir.join(a_name);
+    if a_dir != b_dir {
+        // in the same directory, both targets are freed by the swap
+        if new_a.exists() || new_b.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "a file with the swapped name already exists",
+            ));
+        }
+    }
+    let mut tmp = a_dir.join(format!(".{}.broot-swap", a_name.to_string_lossy()));
+    let mut counter = 1;
+    while tmp.exists() {
+        counter += 1;
+        tmp = a_dir.join(format!(".{}.broot-swap-{}", a_name.to_string_lossy(), counter));
+    }
+    rename(a, &tmp)?;
+    if let Err(e) = rename(b, &new_b) {
+        let _ = rename(&tmp, a);
+        return Err(e);
+    }
+    if let Err(e) = rename(&tmp, &new_a) {
+        let _ = rename(&new_b, b);
+        let _ = rename(&tmp, a);
+        return Err(e);
+    }
+    Ok((new_a, new_b))
+}
+
+#[cfg(test)]
+mod swap_tests {
+
+    use {
+        super::*,
+        std::cell::Cell,
+    };
+
+    fn content(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_swap_in_same_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "A").unwrap();
+        fs::write(&b, "B").unwrap();
+        let (new_a, new_b) = swap_names(&a, &b).unwrap();
+        assert_eq!(new_a, b);
+        assert_eq!(new_b, a);
+        assert_eq!(content(&a), "B");
+        assert_eq!(content(&b), "A");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_swap_across_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("left")).unwrap();
+        fs::create_dir(dir.path().join("right")).unwrap();
+        let a = dir.path().join("left/a.txt");
+        let b = dir.path().join("right/b.txt");
+        fs::write(&a, "A").unwrap();
+        fs::write(&b, "B").unwrap();
+        swap_names(&a, &b).unwrap();
+        assert_eq!(content(&dir.path().join("left/b.txt")), "A");
+        assert_eq!(content(&dir.path().join("right/a.txt")), "B");
+        assert!(!a.exists());
+        assert!(!b.exists());
+        // a file with the target name prevents the swap
+        fs::write(dir.path().join("left/a.txt"), "other").unwrap();
+        let a = dir.path().join("left/b.txt");
+        let b = dir.path().join("right/a.txt");
+        assert!(swap_names(&a, &b).is_err());
+        assert_eq!(content(&a), "A");
+        assert_eq!(content(&b), "B");
+    }
+
+    #[test]
+    fn test_swap_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "A").unwrap();
+        fs::write(&b, "B").unwrap();
+        // we make the second, then the third, rename fail
+        for failing in 2..=3 {
+            let calls = Cell::new(0);
+            let res = swap_names_with(&a, &b, |from, to| {
+                calls.set(calls.get() + 1);
+                if calls.get() == failing {
+                    Err(io::Error::other("failing rename"))
+                } else {
+                    fs::rename(from, to)
+                }
+            });
+            assert!(res.is_err());
+            assert_eq!(content(&a), "A");
+            assert_eq!(content(&b), "B");
+            assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+        }
+        let a_dir = tempfile::tempdir().unwrap();
+        assert!(swap_names(a_dir.path(), &b).is_err());
+    }
+}
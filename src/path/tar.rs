@@ -0,0 +1,252 @@
+//! a minimal reader of tar archives (ustar, with the GNU and pax
+//! long names), enough to list and extract their entries
+
+use std::io::{self, Read};
+
+const BLOCK_SIZE: usize = 512;
+
+/// the kind of a tar entry, links and devices being `Other`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TarEntryKind {
+    File,
+    Dir,
+    Other,
+}
+
+/// the header of an entry of a tar archive
+#[derive(Debug, Clone)]
+pub struct TarHeader {
+    /// the path of the entry, as written in the archive
+    pub name: String,
+    pub kind: TarEntryKind,
+    pub size: u64,
+}
+
+/// read a NUL terminated string of a header field
+fn field_str(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// read a numeric field, either in octal or, in GNU
+/// archives, in base 256 when the high bit is set
+fn field_number(field: &[u8]) -> io::Result<u64> {
+    if field.first().is_some_and(|&b| b & 0x80 != 0) {
+        let mut n: u64 = u64::from(field[0] & 0x7f);
+        for &b in &field[1..] {
+            n = n.checked_shl(8)
+                .ok_or_else(|| invalid_data("too big number in tar header"))?
+                | u64::from(b);
+        }
+        return Ok(n);
+    }
+    let s = field_str(field);
+    let s = s.trim_matches(|c: char| c == ' ' || c == '\0');
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).map_err(|_| invalid_data("invalid number in tar header"))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// the `path` of pax extended header records, which are
+/// lines like `30 path=some/long/file/name.rs`
+fn pax_path(records: &[u8]) -> Option<String> {
+    let records = String::from_utf8_lossy(records);
+    records
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .filter_map(|(_, record)| record.strip_prefix("path="))
+        .next_back()
+        .map(|path| path.to_string())
+}
+
+/// read the whole data of an entry, padding included
+fn read_data<R: Read>(reader: &mut R, size: u64) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.by_ref().take(size).read_to_end(&mut data)?;
+    if (data.len() as u64) < size {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    skip(reader, padding(size))?;
+    Ok(data)
+}
+
+fn padding(size: u64) -> u64 {
+    let block = BLOCK_SIZE as u64;
+    (block - size % block) % block
+}
+
+fn skip<R: Read>(reader: &mut R, n: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut reader.by_ref().take(n), &mut io::sink())?;
+    if skipped < n {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+/// call `f` with the header and the data of every entry of the
+/// tar archive, in order. What `f` doesn't read of the data is
+/// skipped.
+pub fn for_each_entry<R, F>(mut reader: R, mut f: F) -> io::Result<()>
+where
+    R: Read,
+    F: FnMut(&TarHeader, &mut dyn Read) -> io::Result<()>,
+{
+    let mut long_name: Option<String> = None;
+    let mut block = [0u8; BLOCK_SIZE];
+    loop {
+        // the end of the archive is marked by empty blocks, which
+        // some writers omit
+        let mut filled = 0;
+        while filled < BLOCK_SIZE {
+            match reader.read(&mut block[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        if filled == 0 || block.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+        if filled < BLOCK_SIZE {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let size = field_number(&block[124..136])?;
+        let type_flag = block[156];
+        match type_flag {
+            b'L' => {
+                // GNU long name of the next entry
+                let data = read_data(&mut reader, size)?;
+                long_name = Some(field_str(&data));
+                continue;
+            }
+            b'x' => {
+                // pax extended header of the next entry
+                let data = read_data(&mut reader, size)?;
+                if let Some(path) = pax_path(&data) {
+                    long_name = Some(path);
+                }
+                continue;
+            }
+            b'g' | b'K' => {
+                // global pax header, GNU long link name
+                skip(&mut reader, size + padding(size))?;
+                continue;
+            }
+            _ => {}
+        }
+        let name = match long_name.take() {
+            Some(name) => name,
+            None => {
+                let name = field_str(&block[0..100]);
+                let prefix = if &block[257..263] == b"ustar\0" {
+                    field_str(&block[345..500])
+                } else {
+                    String::new()
+                };
+                if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", prefix, name)
+                }
+            }
+        };
+        let kind = match type_flag {
+            b'0' | b'\0' | b'7' => TarEntryKind::File,
+            b'5' => TarEntryKind::Dir,
+            _ => TarEntryKind::Other,
+        };
+        let kind = if kind == TarEntryKind::File && name.ends_with('/') {
+            TarEntryKind::Dir // old archives
+        } else {
+            kind
+        };
+        // links and devices have no data
+        let size = if kind == TarEntryKind::File { size } else { 0 };
+        let header = TarHeader { name, kind, size };
+        let mut data = reader.by_ref().take(size);
+        f(&header, &mut data)?;
+        let unread = data.limit();
+        skip(&mut reader, unread + padding(size))?;
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tar_tests {
+
+    use super::*;
+
+    /// build the header block of an entry
+    fn header(name: &str, type_flag: u8, size: usize) -> Vec<u8> {
+        let mut block = vec![0u8; BLOCK_SIZE];
+        block[..name.len()].copy_from_slice(name.as_bytes());
+        let size = format!("{:011o}\0", size);
+        block[124..136].copy_from_slice(size.as_bytes());
+        block[156] = type_flag;
+        block[257..263].copy_from_slice(b"ustar\0");
+        block
+    }
+
+    /// build a tar archive from entries of a name, a type
+    /// flag and a content
+    pub fn tar(entries: &[(&str, u8, &str)]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        for (name, type_flag, content) in entries {
+            archive.extend(header(name, *type_flag, content.len()));
+            archive.extend(content.as_bytes());
+            archive.extend(vec![0u8; padding(content.len() as u64) as usize]);
+        }
+        archive.extend(vec![0u8; 2 * BLOCK_SIZE]);
+        archive
+    }
+
+    fn read_entries(archive: &[u8]) -> Vec<(String, TarEntryKind, String)> {
+        let mut entries = Vec::new();
+        for_each_entry(archive, |header, data| {
+            let mut content = String::new();
+            data.read_to_string(&mut content)?;
+            entries.push((header.name.clone(), header.kind, content));
+            Ok(())
+        }).unwrap();
+        entries
+    }
+
+    #[test]
+    fn test_tar_entries() {
+        let long_name = format!("project/{}.rs", "x".repeat(120));
+        let pax_record = format!("{} path=project/pax.rs\n", 21);
+        let archive = tar(&[
+            ("project/", b'5', ""),
+            ("project/a.txt", b'0', "some content, longer than nothing"),
+            ("././@LongLink", b'L', &long_name),
+            ("project/truncated", b'0', "long"),
+            ("PaxHeaders/pax", b'x', &pax_record),
+            ("project/short", b'0', "pax"),
+            ("project/link", b'2', ""),
+        ]);
+        assert_eq!(
+            read_entries(&archive),
+            vec![
+                ("project/".to_string(), TarEntryKind::Dir, String::new()),
+                ("project/a.txt".to_string(), TarEntryKind::File, "some content, longer than nothing".to_string()),
+                (long_name, TarEntryKind::File, "long".to_string()),
+                ("project/pax.rs".to_string(), TarEntryKind::File, "pax".to_string()),
+                ("project/link".to_string(), TarEntryKind::Other, String::new()),
+            ],
+        );
+        // unread data is skipped
+        let mut names = Vec::new();
+        for_each_entry(&archive[..], |header, _| {
+            names.push(header.name.clone());
+            Ok(())
+        }).unwrap();
+        assert_eq!(names.len(), 5);
+        // a truncated archive is an error
+        assert!(for_each_entry(&archive[..BLOCK_SIZE + 10], |_, _| Ok(())).is_err());
+        assert_eq!(field_number(b"00000001750\0").unwrap(), 1000);
+        assert_eq!(field_number(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x03, 0xe8]).unwrap(), 1000);
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+/// the maximal size of a file in which a URL is looked for
+const MAX_URL_FILE_SIZE: u64 = 64 * 1024;
+
+/// return the URL a file points to, if any:
+/// - the `URL` key of the `[InternetShortcut]` section of a `.url` file
+/// - the `URL` key of the `[Desktop Entry]` section of a `.desktop` file
+/// - the first non empty line of any other file, when it's a URL
+pub fn url_of_file(path: &Path) -> io::Result<Option<String>> {
+    if fs::metadata(path)?.len() > MAX_URL_FILE_SIZE {
+        return Ok(None);
+    }
+    let bytes = fs::read(path)?;
+    let content = match std::str::from_utf8(&bytes) {
+        Ok(content) => content,
+        Err(_) => {
+            return Ok(None); // not a text file
+        }
+    };
+    let extension = path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    Ok(match extension.as_str() {
+        "url" => url_in_section(content, "InternetShortcut"),
+        "desktop" => url_in_section(content, "Desktop Entry"),
+        _ => url_in_first_line(content),
+    })
+}
+
+/// tell whether the string is a URL, with a scheme and no whitespace
+fn is_url(s: &str) -> bool {
+    regex!(r"^[a-zA-Z][a-zA-Z0-9+.\-]*://\S+$").is_match(s)
+}
+
+/// read the `URL` key in a section of an ini like content
+fn url_in_section(content: &str, section: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name == section;
+        } else if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "URL" {
+                    return Some(value.trim()).filter(|v| is_url(v)).map(str::to_string);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn url_in_first_line(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .filter(|line| is_url(line))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod url_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_url_in_url_file() {
+        let content = "[InternetShortcut]\r\nURL=https://dystroy.org/broot/\r\nIconIndex=0\r\n";
+        assert_eq!(
+            url_in_section(content, "InternetShortcut"),
+            Some("https://dystroy.org/broot/".to_string()),
+        );
+        // the key must be in the right section
+        let content = "[Other]\nURL=https://a.org\n[InternetShortcut]\nIconFile=x\n";
+        assert_eq!(url_in_section(content, "InternetShortcut"), None);
+        assert_eq!(url_in_section("[InternetShortcut]\nURL=not a url\n", "InternetShortcut"), None);
+    }
+
+    #[test]
+    fn test_url_in_desktop_file() {
+        let content = "\
+[Desktop Entry]
+Version=1.0
+Type=Link
+Name=Broot
+URL = https://github.com/Canop/broot
+Icon=text-html
+
+[Desktop Action Other]
+URL=https://dystroy.org
+";
+        assert_eq!(
+            url_in_section(content, "Desktop Entry"),
+            Some("https://github.com/Canop/broot".to_string()),
+        );
+        // an application launcher has no URL
+        let content = "[Desktop Entry]\nType=Application\nExec=firefox %u\n";
+        assert_eq!(url_in_section(content, "Desktop Entry"), None);
+    }
+
+    #[test]
+    fn test_url_in_first_line() {
+        assert_eq!(
+            url_in_first_line("\n  https://dystroy.org/blog?a=b#c  \nsome notes\n"),
+            Some("https://dystroy.org/blog?a=b#c".to_string()),
+        );
+        assert_eq!(url_in_first_line("ftp://example.com/file"), Some("ftp://example.com/file".to_string()));
+        assert_eq!(url_in_first_line("see https://dystroy.org\n"), None);
+        assert_eq!(url_in_first_line("fn main() {}\n"), None);
+        assert_eq!(url_in_first_line(""), None);
+    }
+
+    #[test]
+    fn test_url_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let url_file = dir.path().join("Broot.URL");
+        fs::write(&url_file, "[InternetShortcut]\nURL=https://dystroy.org/broot\n").unwrap();
+        assert_eq!(url_of_file(&url_file).unwrap(), Some("https://dystroy.org/broot".to_string()));
+        // the ini syntax is only read for .url and .desktop files
+        let txt_file = dir.path().join("link.txt");
+        fs::write(&txt_file, "[InternetShortcut]\nURL=https://dystroy.org/broot\n").unwrap();
+        assert_eq!(url_of_file(&txt_file).unwrap(), None);
+        fs::write(&txt_file, "https://dystroy.org/broot\n").unwrap();
+        assert_eq!(url_of_file(&txt_file).unwrap(), Some("https://dystroy.org/broot".to_string()));
+        let bin_file = dir.path().join("bin");
+        fs::write(&bin_file, [0xff, 0xfe, 0x00]).unwrap();
+        assert_eq!(url_of_file(&bin_file).unwrap(), None);
+        assert!(url_of_file(&dir.path().join("missing")).is_err());
+    }
+}
@@ -20,8 +20,9 @@ const BONUS_NB_HOLES: i32 = -30; // there's also a max on that number
 /// A pattern for fuzzy matching
 #[derive(Debug, Clone)]
 pub struct FuzzyPattern {
-    chars: Box<[char]>, // secularized characters
+    chars: Box<[char]>, // secularized characters, unless case sensitive
     max_nb_holes: usize,
+    case_sensitive: bool,
 }
 
 impl fmt::Display for FuzzyPattern {
@@ -45,6 +46,15 @@ enum ScoreSearchResult {
     NoneToEnd,
 }
 
+/// prepare a char for comparison
+fn normalize(c: char, case_sensitive: bool) -> char {
+    if case_sensitive {
+        c
+    } else {
+        secular::lower_lay_char(c)
+    }
+}
+
 fn is_word_separator(c: char) -> bool {
     matches!(c, '_' | ' ' | '-')
 }
@@ -53,9 +63,18 @@ impl FuzzyPattern {
     /// build a pattern which will later be usable for fuzzy search.
     /// A pattern should be reused
     pub fn from(pat: &str) -> Self {
+        Self::build(pat, false)
+    }
+
+    /// build a pattern not ignoring case nor diacritics
+    pub fn case_sensitive(pat: &str) -> Self {
+        Self::build(pat, true)
+    }
+
+    fn build(pat: &str, case_sensitive: bool) -> Self {
         let chars = pat
             .chars()
-            .map(secular::lower_lay_char)
+            .map(|c| normalize(c, case_sensitive))
             .collect::<Vec<char>>()
             .into_boxed_slice();
         let max_nb_holes = match chars.len() {
@@ -72,6 +91,7 @@ impl FuzzyPattern {
         FuzzyPattern {
             chars,
             max_nb_holes,
+            case_sensitive,
         }
     }
 
@@ -140,7 +160,7 @@ impl FuzzyPattern {
             return None;
         }
         let mut cand_chars: Vec<char> = Vec::with_capacity(candidate.len());
-        cand_chars.extend(candidate.chars().map(secular::lower_lay_char));
+        cand_chars.extend(candidate.chars().map(|c| normalize(c, self.case_sensitive)));
         if cand_chars.len() < self.chars.len() {
             return None;
         }
@@ -166,7 +186,7 @@ impl FuzzyPattern {
 
     /// compute the score in the specific case the pattern is of length 1
     fn score_1_char(&self, candidate: &str, pat_chr: char) -> Option<i32> {
-        let mut cand_chars = candidate.chars().map(secular::lower_lay_char);
+        let mut cand_chars = candidate.chars().map(|c| normalize(c, self.case_sensitive));
         match cand_chars.next() {
             None => None, // empty candidate: this looks pathological but might be valid
             Some(chr) if pat_chr == chr => {
@@ -273,7 +293,7 @@ impl FuzzyPattern {
             return None;
         }
         let mut cand_chars: Vec<char> = Vec::with_capacity(candidate.len());
-        cand_chars.extend(candidate.chars().map(secular::lower_lay_char));
+        cand_chars.extend(candidate.chars().map(|c| normalize(c, self.case_sensitive)));
         if cand_chars.len() < self.chars.len() {
             return None;
         }
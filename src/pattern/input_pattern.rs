@@ -2,6 +2,7 @@ use {
     super::*,
     crate::{
         app::AppContext,
+        command::CommandParts,
         errors::PatternError,
         pattern::{Pattern, PatternParts},
     },
@@ -16,6 +17,7 @@ use {
 pub struct InputPattern {
     pub raw: String,
     pub pattern: Pattern,
+    pub case_toggled: bool, // whether the case sensitivity of the raw pattern is inverted
 }
 
 impl InputPattern {
@@ -23,6 +25,7 @@ impl InputPattern {
         Self {
             raw: String::new(),
             pattern: Pattern::None,
+            case_toggled: false,
         }
     }
     pub fn new(
@@ -30,8 +33,15 @@ impl InputPattern {
         parts_expr: &BeTree<PatternOperator, PatternParts>,
         con: &AppContext,
     ) -> Result<Self, PatternError> {
-        let pattern = Pattern::new(parts_expr, con)?;
-        Ok(Self { raw, pattern })
+        let pattern = Pattern::new(parts_expr, con, false)?;
+        Ok(Self { raw, pattern, case_toggled: false })
+    }
+    /// build the same pattern, but with its case sensitivity inverted
+    pub fn with_toggled_case(&self, con: &AppContext) -> Result<Self, PatternError> {
+        let parts = CommandParts::from(self.raw.clone());
+        let case_toggled = !self.case_toggled;
+        let pattern = Pattern::new(&parts.pattern, con, case_toggled)?;
+        Ok(Self { raw: self.raw.clone(), pattern, case_toggled })
     }
     pub fn is_none(&self) -> bool {
         self.raw.is_empty()
@@ -64,7 +74,97 @@ impl InputPattern {
             .map(|rp| InputPattern {
                 raw: rp.to_string(),
                 pattern: Pattern::NameRegex(rp),
+                case_toggled: false,
             })
             .unwrap_or_else(InputPattern::none)
     }
 }
+
+#[cfg(test)]
+mod input_pattern_tests {
+
+    use {
+        super::*,
+        std::path::Path,
+    };
+
+    static NAMES: &[&str] = &["README.md", "readme.txt", "Cargo.toml", "src"];
+
+    fn context() -> AppContext {
+        AppContext::for_test(Path::new("."))
+    }
+
+    fn input_pattern(raw: &str, con: &AppContext) -> InputPattern {
+        let parts = CommandParts::from(raw.to_string());
+        InputPattern::new(parts.raw_pattern, &parts.pattern, con).unwrap()
+    }
+
+    fn matches(pattern: &InputPattern) -> Vec<&'static str> {
+        NAMES.iter()
+            .copied()
+            .filter(|name| pattern.pattern.score_of_string(name).is_some())
+            .collect()
+    }
+
+    #[test]
+    fn test_toggle_case() {
+        let con = context();
+        // fuzzy patterns are case insensitive by default
+        let fuzzy = input_pattern("READ", &con);
+        assert_eq!(matches(&fuzzy), vec!["README.md", "readme.txt"]);
+        let toggled = fuzzy.with_toggled_case(&con).unwrap();
+        assert!(toggled.case_toggled);
+        assert_eq!(toggled.raw, "READ");
+        assert_eq!(matches(&toggled), vec!["README.md"]);
+        let back = toggled.with_toggled_case(&con).unwrap();
+        assert!(!back.case_toggled);
+        assert_eq!(matches(&back), vec!["README.md", "readme.txt"]);
+        // exact patterns are case sensitive by default
+        let exact = input_pattern("e/read", &con);
+        assert_eq!(matches(&exact), vec!["readme.txt"]);
+        let toggled = exact.with_toggled_case(&con).unwrap();
+        assert_eq!(matches(&toggled), vec!["README.md", "readme.txt"]);
+        // regexes get their `i` flag toggled
+        let regex = input_pattern("/^c/i", &con);
+        assert_eq!(matches(&regex), vec!["Cargo.toml"]);
+        let toggled = regex.with_toggled_case(&con).unwrap();
+        assert!(matches(&toggled).is_empty());
+        let regex = input_pattern("/^c", &con);
+        assert!(matches(&regex).is_empty());
+        let toggled = regex.with_toggled_case(&con).unwrap();
+        assert_eq!(matches(&toggled), vec!["Cargo.toml"]);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let con = context();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("report.txt"), vec![b'a'; 2_000]).unwrap();
+        std::fs::write(dir.path().join("report.md"), "short").unwrap();
+        std::fs::create_dir(dir.path().join("reports")).unwrap();
+        let matching = |raw: &str| -> Vec<&'static str> {
+            let pattern = input_pattern(raw, &con).pattern;
+            ["report.txt", "report.md", "reports"]
+                .iter()
+                .copied()
+                .filter(|name| {
+                    let path = dir.path().join(name);
+                    let candidate = Candidate {
+                        path: &path,
+                        subpath: name,
+                        name,
+                        regular_file: path.is_file(),
+                    };
+                    pattern.score_of(candidate).is_some()
+                })
+                .collect()
+        };
+        assert_eq!(matching("size/>1K"), vec!["report.txt"]);
+        // the core of a predicate doesn't need to be closed by a `/`
+        assert_eq!(matching("rep&size/<1K&mtime/<1d"), vec!["report.md"]);
+        assert_eq!(matching("rep&!type/dir"), vec!["report.txt", "report.md"]);
+        assert_eq!(matching("type/d|md"), vec!["report.md", "reports"]);
+        let parts = CommandParts::from("size/>2X".to_string());
+        assert!(InputPattern::new(parts.raw_pattern, &parts.pattern, &con).is_err());
+    }
+}
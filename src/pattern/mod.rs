@@ -11,6 +11,7 @@ mod operator;
 mod pattern;
 mod pattern_object;
 mod pattern_parts;
+mod predicate_pattern;
 mod regex_pattern;
 mod search_mode;
 
@@ -27,6 +28,7 @@ pub use {
     pattern_object::PatternObject,
     pattern_parts::PatternParts,
     operator::PatternOperator,
+    predicate_pattern::{FileKind, Predicate, PredicatePattern},
     regex_pattern::RegexPattern,
     search_mode::*,
 };
@@ -23,14 +23,21 @@ pub enum Pattern {
     PathRegex(RegexPattern),
     ContentExact(ContentExactPattern),
     ContentRegex(ContentRegexPattern),
+    Predicate(PredicatePattern),
     Composite(CompositePattern),
 }
 
 impl Pattern {
 
+    /// build a pattern from its parsed parts. When `toggle_case` is
+    /// true, the case sensitivity of every atom is inverted: exact
+    /// patterns become case insensitive (and are searched as regexes),
+    /// fuzzy ones become case sensitive and the `i` flag of regexes
+    /// is toggled
     pub fn new(
         raw_expr: &BeTree<PatternOperator, PatternParts>,
         con: &AppContext,
+        toggle_case: bool,
     ) -> Result<Self, PatternError> {
         let expr: BeTree<PatternOperator, Pattern> = raw_expr
             .try_map_atoms::<_, PatternError, _>(|pattern_parts| {
@@ -38,35 +45,41 @@ impl Pattern {
                 Ok(
                     if core.is_empty() {
                         Pattern::None
+                    } else if let Some(mode) = pattern_parts.predicate_mode() {
+                        Self::Predicate(PredicatePattern::new(mode, core)?)
                     } else {
                         let parts_mode = pattern_parts.mode();
                         let mode = con.search_modes.search_mode(parts_mode)?;
-                        let flags = pattern_parts.flags();
-                        match mode {
-                            SearchMode::NameExact => Self::NameExact(
-                                ExactPattern::from(core)
-                            ),
-                            SearchMode::NameFuzzy => Self::NameFuzzy(
-                                FuzzyPattern::from(core)
-                            ),
-                            SearchMode::NameRegex => Self::NameRegex(
-                                RegexPattern::from(core, flags.unwrap_or(""))?
-                            ),
-                            SearchMode::PathExact => Self::PathExact(
-                                ExactPattern::from(core)
-                            ),
-                            SearchMode::PathFuzzy => Self::PathFuzzy(
-                                FuzzyPattern::from(core)
-                            ),
-                            SearchMode::PathRegex => Self::PathRegex(
-                                RegexPattern::from(core, flags.unwrap_or(""))?
-                            ),
-                            SearchMode::ContentExact => Self::ContentExact(
-                                ContentExactPattern::from(core)
-                            ),
-                            SearchMode::ContentRegex => Self::ContentRegex(
-                                ContentRegexPattern::from(core, flags.unwrap_or(""))?
-                            ),
+                        let flags = pattern_parts.flags().unwrap_or("");
+                        if toggle_case {
+                            Self::case_toggled_atom(mode, core, flags)?
+                        } else {
+                            match mode {
+                                SearchMode::NameExact => Self::NameExact(
+                                    ExactPattern::from(core)
+                                ),
+                                SearchMode::NameFuzzy => Self::NameFuzzy(
+                                    FuzzyPattern::from(core)
+                                ),
+                                SearchMode::NameRegex => Self::NameRegex(
+                                    RegexPattern::from(core, flags)?
+                                ),
+                                SearchMode::PathExact => Self::PathExact(
+                                    ExactPattern::from(core)
+                                ),
+                                SearchMode::PathFuzzy => Self::PathFuzzy(
+                                    FuzzyPattern::from(core)
+                                ),
+                                SearchMode::PathRegex => Self::PathRegex(
+                                    RegexPattern::from(core, flags)?
+                                ),
+                                SearchMode::ContentExact => Self::ContentExact(
+                                    ContentExactPattern::from(core)
+                                ),
+                                SearchMode::ContentRegex => Self::ContentRegex(
+                                    ContentRegexPattern::from(core, flags)?
+                                ),
+                            }
                         }
                     }
                 )
@@ -80,6 +93,44 @@ impl Pattern {
         })
     }
 
+    fn case_toggled_atom(
+        mode: SearchMode,
+        core: &str,
+        flags: &str,
+    ) -> Result<Self, PatternError> {
+        let toggled_flags = if flags.contains('i') {
+            flags.replace('i', "")
+        } else {
+            format!("{}i", flags)
+        };
+        Ok(match mode {
+            SearchMode::NameExact => Self::NameRegex(
+                RegexPattern::from(&regex::escape(core), "i")?
+            ),
+            SearchMode::NameFuzzy => Self::NameFuzzy(
+                FuzzyPattern::case_sensitive(core)
+            ),
+            SearchMode::NameRegex => Self::NameRegex(
+                RegexPattern::from(core, &toggled_flags)?
+            ),
+            SearchMode::PathExact => Self::PathRegex(
+                RegexPattern::from(&regex::escape(core), "i")?
+            ),
+            SearchMode::PathFuzzy => Self::PathFuzzy(
+                FuzzyPattern::case_sensitive(core)
+            ),
+            SearchMode::PathRegex => Self::PathRegex(
+                RegexPattern::from(core, &toggled_flags)?
+            ),
+            SearchMode::ContentExact => Self::ContentRegex(
+                ContentRegexPattern::from(&regex::escape(core), "i")?
+            ),
+            SearchMode::ContentRegex => Self::ContentRegex(
+                ContentRegexPattern::from(core, &toggled_flags)?
+            ),
+        })
+    }
+
     pub fn object(&self) -> PatternObject {
         let mut object = PatternObject::default();
         match self {
@@ -93,6 +144,7 @@ impl Pattern {
             Self::ContentExact(_) | Self::ContentRegex(_) => {
                 object.content = true;
             }
+            Self::Predicate(_) => {}
             Self::Composite(cp) => {
                 for atom in cp.expr.iter_atoms() {
                     object |= atom.object();
@@ -143,6 +195,7 @@ impl Pattern {
             Self::PathRegex(rp) => rp.find(&candidate.subpath).map(|m| m.score),
             Self::ContentExact(cp) => cp.score_of(candidate),
             Self::ContentRegex(cp) => cp.score_of(candidate),
+            Self::Predicate(pp) => pp.score_of(candidate),
             Self::Composite(cp) => cp.score_of(candidate),
             Self::None => Some(1),
         }
@@ -158,6 +211,7 @@ impl Pattern {
             Self::PathRegex(rp) => rp.find(&candidate).map(|m| m.score),
             Self::ContentExact(_) => None, // this isn't suitable
             Self::ContentRegex(_) => None, // this isn't suitable
+            Self::Predicate(_) => None, // there's no file
             Self::Composite(cp) => cp.score_of_string(candidate),
             Self::None => Some(1),
         }
@@ -167,6 +221,24 @@ impl Pattern {
         !matches!(&self, Pattern::None)
     }
 
+    /// return the search mode of the pattern (the one of
+    /// the first atom for a composite pattern)
+    pub fn search_mode(&self) -> Option<SearchMode> {
+        match self {
+            Self::None => None,
+            Self::NameExact(_) => Some(SearchMode::NameExact),
+            Self::NameFuzzy(_) => Some(SearchMode::NameFuzzy),
+            Self::NameRegex(_) => Some(SearchMode::NameRegex),
+            Self::PathExact(_) => Some(SearchMode::PathExact),
+            Self::PathFuzzy(_) => Some(SearchMode::PathFuzzy),
+            Self::PathRegex(_) => Some(SearchMode::PathRegex),
+            Self::ContentExact(_) => Some(SearchMode::ContentExact),
+            Self::ContentRegex(_) => Some(SearchMode::ContentRegex),
+            Self::Predicate(_) => None,
+            Self::Composite(cp) => cp.expr.iter_atoms().find_map(|p| p.search_mode()),
+        }
+    }
+
     pub fn is_none(&self) -> bool {
         matches!(&self, Pattern::None)
     }
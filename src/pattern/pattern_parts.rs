@@ -1,4 +1,5 @@
 use {
+    super::PredicatePattern,
     std::fmt,
 };
 
@@ -36,7 +37,9 @@ impl PatternParts {
         self.parts.push(String::new());
     }
     pub fn allow_inter_pattern_token(&self) -> bool {
-        self.parts.len() != 2
+        // the core of a predicate can't contain operators, so
+        // it doesn't have to be closed by a `/`
+        self.parts.len() != 2 || self.predicate_mode().is_some()
     }
     pub fn is_empty(&self) -> bool {
         self.core().is_empty()
@@ -55,6 +58,13 @@ impl PatternParts {
             None
         }
     }
+    /// return the mode when it introduces a predicate
+    /// (e.g. `size` in `size/>10M`)
+    pub fn predicate_mode(&self) -> Option<&str> {
+        self.mode()
+            .map(|mode| mode.as_str())
+            .filter(|mode| PredicatePattern::is_predicate_mode(mode))
+    }
     pub fn flags(&self) -> Option<&str> {
         if self.parts.len() > 2 {
             self.parts.get(2).map(|s| s.as_str())
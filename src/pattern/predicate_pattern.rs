@@ -0,0 +1,208 @@
+use {
+    super::*,
+    crate::{
+        errors::PatternError,
+        tree::parse_duration,
+    },
+    std::{
+        cmp::Ordering,
+        fmt,
+        fs,
+        path::Path,
+        time::{Duration, SystemTime},
+    },
+};
+
+/// the kinds of file a `type` predicate may ask for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileKind {
+    File,
+    Dir,
+    Link,
+}
+
+/// a condition on the metadata of the files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Predicate {
+    /// the size of a file compared to the given number of bytes
+    /// (directories never match)
+    Size(Ordering, u64),
+    /// the time since the last modification compared to the
+    /// given duration
+    Age(Ordering, Duration),
+    Kind(FileKind),
+}
+
+/// A pattern filtering on the size, the modification time,
+/// or the type of the files (e.g. `size/>10M`, `mtime/<7d`
+/// or `type/dir`)
+#[derive(Debug, Clone)]
+pub struct PredicatePattern {
+    raw: String,
+    predicate: Predicate,
+}
+
+impl fmt::Display for PredicatePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl PredicatePattern {
+
+    /// tell whether the mode of a pattern (the part before the
+    /// first `/`) introduces a predicate rather than a search
+    pub fn is_predicate_mode(mode: &str) -> bool {
+        matches!(mode, "size" | "mtime" | "type")
+    }
+
+    pub fn new(mode: &str, core: &str) -> Result<Self, PatternError> {
+        let invalid = |details: String| PatternError::InvalidPredicate { details };
+        let predicate = match mode {
+            "size" => {
+                let (ordering, value) = split_comparison(core);
+                Predicate::Size(ordering, parse_size(value).map_err(invalid)?)
+            }
+            "mtime" => {
+                let (ordering, value) = split_comparison(core);
+                Predicate::Age(ordering, parse_duration(value).map_err(invalid)?)
+            }
+            "type" => Predicate::Kind(match core {
+                "f" | "file" => FileKind::File,
+                "d" | "dir" => FileKind::Dir,
+                "l" | "link" => FileKind::Link,
+                _ => {
+                    return Err(invalid(format!(
+                        "{:?} isn't a valid type (expected file, dir or link)",
+                        core,
+                    )));
+                }
+            }),
+            _ => {
+                return Err(PatternError::InvalidMode { mode: mode.to_string() });
+            }
+        };
+        Ok(Self {
+            raw: format!("{}/{}", mode, core),
+            predicate,
+        })
+    }
+
+    pub fn predicate(&self) -> Predicate {
+        self.predicate
+    }
+
+    /// tell whether the file at this path verifies the predicate
+    pub fn is_match(&self, path: &Path, now: SystemTime) -> bool {
+        match self.predicate {
+            Predicate::Size(ordering, size) => fs::metadata(path)
+                .is_ok_and(|md| md.is_file() && md.len().cmp(&size) == ordering),
+            Predicate::Age(ordering, age) => fs::metadata(path)
+                .and_then(|md| md.modified())
+                .is_ok_and(|mtime| {
+                    // a file modified in the future has a zero age
+                    let file_age = now.duration_since(mtime).unwrap_or_default();
+                    file_age.cmp(&age) == ordering
+                }),
+            Predicate::Kind(kind) => fs::symlink_metadata(path).is_ok_and(|md| {
+                let ft = md.file_type();
+                match kind {
+                    FileKind::File => ft.is_file(),
+                    FileKind::Dir => ft.is_dir(),
+                    FileKind::Link => ft.is_symlink(),
+                }
+            }),
+        }
+    }
+
+    pub fn score_of(&self, candidate: Candidate) -> Option<i32> {
+        if self.is_match(candidate.path, SystemTime::now()) {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+/// split the optional comparison operator from the value,
+/// no operator meaning equality
+fn split_comparison(core: &str) -> (Ordering, &str) {
+    if let Some(value) = core.strip_prefix('>') {
+        (Ordering::Greater, value)
+    } else if let Some(value) = core.strip_prefix('<') {
+        (Ordering::Less, value)
+    } else {
+        (Ordering::Equal, core.strip_prefix('=').unwrap_or(core))
+    }
+}
+
+/// parse a size made of a number and an optional unit among
+/// `K`, `M`, `G` and `T`, which are powers of 1000 (e.g. `10M`)
+fn parse_size(s: &str) -> Result<u64, String> {
+    let err = || format!("{:?} isn't a valid size (expected e.g. 500, 20K or 10M)", s);
+    let (digits, multiplier) = match s.char_indices().last() {
+        Some((idx, c)) if c.is_ascii_alphabetic() => {
+            let multiplier: u64 = match c.to_ascii_uppercase() {
+                'K' => 1_000,
+                'M' => 1_000_000,
+                'G' => 1_000_000_000,
+                'T' => 1_000_000_000_000,
+                _ => return Err(err()),
+            };
+            (&s[..idx], multiplier)
+        }
+        _ => (s, 1),
+    };
+    let count: u64 = digits.parse().map_err(|_| err())?;
+    Ok(count.saturating_mul(multiplier))
+}
+
+#[cfg(test)]
+mod predicate_pattern_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            PredicatePattern::new("size", ">10M").unwrap().predicate(),
+            Predicate::Size(Ordering::Greater, 10_000_000),
+        );
+        assert_eq!(
+            PredicatePattern::new("size", "2k").unwrap().predicate(),
+            Predicate::Size(Ordering::Equal, 2_000),
+        );
+        assert_eq!(
+            PredicatePattern::new("mtime", "<7d").unwrap().predicate(),
+            Predicate::Age(Ordering::Less, Duration::from_secs(7 * 24 * 60 * 60)),
+        );
+        assert_eq!(
+            PredicatePattern::new("type", "dir").unwrap().predicate(),
+            Predicate::Kind(FileKind::Dir),
+        );
+        assert!(PredicatePattern::new("size", ">10X").is_err());
+        assert!(PredicatePattern::new("size", ">").is_err());
+        assert!(PredicatePattern::new("mtime", "<7").is_err());
+        assert!(PredicatePattern::new("type", "pipe").is_err());
+    }
+
+    #[test]
+    fn test_is_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data.bin");
+        fs::write(&file, vec![0; 3_000]).unwrap();
+        let now = SystemTime::now();
+        let is_match = |mode, core, path: &Path| {
+            PredicatePattern::new(mode, core).unwrap().is_match(path, now)
+        };
+        assert!(is_match("size", ">2K", &file));
+        assert!(!is_match("size", "<2K", &file));
+        assert!(is_match("size", "=3000", &file));
+        assert!(!is_match("size", "<2K", dir.path())); // directories have no size
+        assert!(is_match("mtime", "<1h", &file));
+        assert!(!is_match("mtime", ">1h", &file));
+        assert!(is_match("type", "file", &file));
+        assert!(!is_match("type", "file", dir.path()));
+        assert!(is_match("type", "d", dir.path()));
+    }
+}
@@ -90,6 +90,17 @@ impl SearchMode {
             Self::ContentRegex => SearchKind::Regex,
         }
     }
+    /// a short description of the search, as given to verbs
+    /// by `{search-kind}`: "content" for searches in file contents,
+    /// otherwise "fuzzy", "regex" or "name" (for exact searches)
+    pub fn kind_name(&self) -> &'static str {
+        match (self.object(), self.kind()) {
+            (SearchObject::Content, _) => "content",
+            (_, SearchKind::Fuzzy) => "fuzzy",
+            (_, SearchKind::Regex) => "regex",
+            _ => "name",
+        }
+    }
 }
 
 /// define a mapping from a search mode which can be typed in
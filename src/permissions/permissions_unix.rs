@@ -1,12 +1,50 @@
 use {
     fnv::FnvHashMap,
-    std::sync::Mutex,
+    std::{
+        fs,
+        io,
+        os::unix::fs::PermissionsExt,
+        path::Path,
+        sync::Mutex,
+    },
 };
 
 pub fn supported() -> bool {
     true
 }
 
+/// write the permission bits of a mode the way `ls -l` does, e.g. `rwxr-x---`
+pub fn mode_string(mode: u32) -> String {
+    const BITS: [(u32, char); 9] = [
+        (0o400, 'r'), (0o200, 'w'), (0o100, 'x'),
+        (0o040, 'r'), (0o020, 'w'), (0o010, 'x'),
+        (0o004, 'r'), (0o002, 'w'), (0o001, 'x'),
+    ];
+    BITS.iter()
+        .map(|&(bit, c)| if mode & bit != 0 { c } else { '-' })
+        .collect()
+}
+
+/// write the permission bits of a mode in octal, e.g. `750`
+pub fn mode_octal(mode: u32) -> String {
+    format!("{:03o}", mode & 0o777)
+}
+
+/// return the mode with the owner-executable bit flipped
+fn with_toggled_owner_exec(mode: u32) -> u32 {
+    mode ^ 0o100
+}
+
+/// flip the owner-executable bit of the file, return whether
+/// the owner can now execute it
+pub fn toggle_owner_exec(path: &Path) -> io::Result<bool> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    let mode = with_toggled_owner_exec(permissions.mode());
+    permissions.set_mode(mode);
+    fs::set_permissions(path, permissions)?;
+    Ok(mode & 0o100 != 0)
+}
+
 pub fn user_name(uid: u32) -> String {
     lazy_static! {
         static ref USERS_CACHE_MUTEX: Mutex<FnvHashMap<u32, String>> =
@@ -39,3 +77,42 @@ pub fn group_name(gid: u32) -> String {
         });
     (*name).to_string()
 }
+
+#[cfg(test)]
+mod permissions_unix_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_mode_formats() {
+        // the file type bits are ignored
+        assert_eq!(mode_string(0o100755), "rwxr-xr-x");
+        assert_eq!(mode_octal(0o100755), "755");
+        assert_eq!(mode_string(0o640), "rw-r-----");
+        assert_eq!(mode_octal(0o640), "640");
+        assert_eq!(mode_string(0o004), "------r--");
+        assert_eq!(mode_octal(0o004), "004");
+        // so are the setuid, setgid and sticky bits
+        assert_eq!(mode_octal(0o41777), "777");
+    }
+
+    #[test]
+    fn test_toggled_owner_exec() {
+        assert_eq!(with_toggled_owner_exec(0o644), 0o744);
+        assert_eq!(with_toggled_owner_exec(0o755), 0o655);
+        assert_eq!(with_toggled_owner_exec(0o100644), 0o100744);
+        assert_eq!(with_toggled_owner_exec(with_toggled_owner_exec(0o640)), 0o640);
+    }
+
+    #[test]
+    fn test_toggle_owner_exec() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, "echo hi").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(toggle_owner_exec(&path).unwrap());
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o744);
+        assert!(!toggle_owner_exec(&path).unwrap());
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o644);
+    }
+}
@@ -1,10 +1,12 @@
 mod preview;
 mod preview_state;
+mod rendered_view;
 mod zero_len_file_view;
 
 pub use {
     preview::Preview,
     preview_state::PreviewState,
+    rendered_view::RenderedView,
     zero_len_file_view::ZeroLenFileView,
 };
 
@@ -20,4 +22,7 @@ pub enum PreviewMode {
 
     /// show the content of the file as hex
     Hex,
+
+    /// show a markdown file formatted, or a csv file as a table
+    Rendered,
 }
@@ -5,11 +5,11 @@ use {
         command::ScrollCommand,
         display::*,
         errors::ProgramError,
-        hex::HexView,
+        hex::{HexPattern, HexView},
         image::ImageView,
         pattern::InputPattern,
         skin::PanelSkin,
-        syntactic::SyntacticView,
+        syntactic::{SyntacticView, SYNTAXER},
         task_sync::Dam,
     },
     crossterm::{cursor, QueueableCommand},
@@ -24,6 +24,7 @@ pub enum Preview {
     Image(ImageView),
     Syntactic(SyntacticView),
     Hex(HexView),
+    Rendered(RenderedView),
     ZeroLen(ZeroLenFileView),
     IOError(io::Error),
 }
@@ -40,6 +41,9 @@ impl Preview {
             Some(PreviewMode::Hex) => Self::hex(path),
             Some(PreviewMode::Image) => Self::image(path),
             Some(PreviewMode::Text) => Self::unfiltered_text(path, con),
+            Some(PreviewMode::Rendered) => RenderedView::new(path)
+                .map(Self::Rendered)
+                .unwrap_or_else(|_| Self::unfiltered_text(path, con)),
             None => {
                 // automatic behavior: image, text, hex
                 ImageView::new(path)
@@ -62,9 +66,12 @@ impl Preview {
             PreviewMode::Image => {
                 ImageView::new(path).map(Self::Image)
             }
+            PreviewMode::Rendered => {
+                RenderedView::new(path).map(Self::Rendered)
+            }
             PreviewMode::Text => {
                 Ok(
-                    SyntacticView::new(path, InputPattern::none(), &mut Dam::unlimited(), con)
+                    SyntacticView::new(path, InputPattern::none(), None, &mut Dam::unlimited(), con)
                         .transpose()
                         .expect("syntactic view without pattern shouldn't be none")
                         .map(Self::Syntactic)?,
@@ -72,6 +79,29 @@ impl Preview {
             }
         }
     }
+    /// build a text view highlighted with the given syntax (found
+    /// by name or extension), return an error if that wasn't possible
+    pub fn with_syntax(
+        path: &Path,
+        syntax: &str,
+        con: &AppContext,
+    ) -> Result<Self, ProgramError> {
+        if SYNTAXER.find_syntax(path, Some(syntax)).is_none() {
+            return Err(ProgramError::UnknownSyntax {
+                syntax: syntax.to_string(),
+            });
+        }
+        SyntacticView::new(
+            path,
+            InputPattern::none(),
+            Some(syntax.to_string()),
+            &mut Dam::unlimited(),
+            con,
+        )
+        .transpose()
+        .expect("syntactic view without pattern shouldn't be none")
+        .map(Self::Syntactic)
+    }
     /// build an image view, unless the file can't be interpreted
     /// as an image, in which case a hex view is used
     pub fn image(path: &Path) -> Self {
@@ -88,7 +118,7 @@ impl Preview {
         path: &Path,
         con: &AppContext,
     ) -> Self {
-        match SyntacticView::new(path, InputPattern::none(), &mut Dam::unlimited(), con) {
+        match SyntacticView::new(path, InputPattern::none(), None, &mut Dam::unlimited(), con) {
             Ok(Some(sv)) => Self::Syntactic(sv),
             Err(ProgramError::ZeroLenFile) => {
                 debug!("zero len file - check if system file");
@@ -109,8 +139,8 @@ impl Preview {
         con: &AppContext,
     ) -> Option<Self> {
         match self {
-            Self::Syntactic(_) => {
-                match SyntacticView::new(path, pattern, dam, con) {
+            Self::Syntactic(sv) => {
+                match SyntacticView::new(path, pattern, sv.syntax.clone(), dam, con) {
 
                     // normal finished loading
                     Ok(Some(sv)) => Some(Self::Syntactic(sv)),
@@ -123,6 +153,14 @@ impl Preview {
                     Err(_) => Some(Self::hex(path)),
                 }
             }
+            Self::Hex(hv) => {
+                // the hex view isn't filtered but the searched bytes are selected
+                match HexPattern::new(pattern).map(|search| hv.searched(search)) {
+                    Some(Ok(hv)) => Some(Self::Hex(hv)),
+                    Some(Err(e)) => Some(Self::IOError(e)),
+                    None => None,
+                }
+            }
             _ => None, // not filterable
         }
     }
@@ -145,12 +183,14 @@ impl Preview {
             Self::Syntactic(_) => Some(PreviewMode::Text),
             Self::ZeroLen(_) => Some(PreviewMode::Text),
             Self::Hex(_) => Some(PreviewMode::Hex),
+            Self::Rendered(_) => Some(PreviewMode::Rendered),
             Self::IOError(_) => None,
         }
     }
     pub fn pattern(&self) -> InputPattern {
         match self {
             Self::Syntactic(sv) => sv.pattern.clone(),
+            Self::Hex(hv) => hv.pattern(),
             _ => InputPattern::none(),
         }
     }
@@ -161,11 +201,34 @@ impl Preview {
         match self {
             Self::Syntactic(sv) => sv.try_scroll(cmd),
             Self::Hex(hv) => hv.try_scroll(cmd),
+            Self::Rendered(rv) => rv.try_scroll(cmd),
             _ => false,
         }
     }
     pub fn is_filterable(&self) -> bool {
-        matches!(self, Self::Syntactic(_))
+        matches!(self, Self::Syntactic(_) | Self::Hex(_))
+    }
+    /// start the search of the next or previous match, when
+    /// the preview is a searched hex view
+    pub fn try_select_match(&mut self, forward: bool) -> bool {
+        match self {
+            Self::Hex(hv) => hv.try_select_match(forward),
+            _ => false,
+        }
+    }
+    /// tell whether there's a search of a match to do
+    pub fn has_match_search(&self) -> bool {
+        match self {
+            Self::Hex(hv) => hv.has_match_search(),
+            _ => false,
+        }
+    }
+    /// search the match to select, until it's found or the
+    /// dam gets an event
+    pub fn do_match_search(&mut self, dam: &mut Dam) {
+        if let Self::Hex(hv) = self {
+            hv.do_match_search(dam);
+        }
     }
 
     pub fn get_selected_line_number(&self) -> Option<LineNumber> {
@@ -197,6 +260,9 @@ impl Preview {
             Self::Hex(hv) => {
                 hv.try_scroll(ScrollCommand::Lines(dy));
             }
+            Self::Rendered(rv) => {
+                rv.try_scroll(ScrollCommand::Lines(dy));
+            }
             _ => {}
         }
     }
@@ -204,6 +270,7 @@ impl Preview {
         match self {
             Self::Syntactic(sv) => sv.select_first(),
             Self::Hex(hv) => hv.select_first(),
+            Self::Rendered(rv) => rv.select_first(),
             _ => {}
         }
     }
@@ -211,6 +278,7 @@ impl Preview {
         match self {
             Self::Syntactic(sv) => sv.select_last(),
             Self::Hex(hv) => hv.select_last(),
+            Self::Rendered(rv) => rv.select_last(),
             _ => {}
         }
     }
@@ -227,6 +295,7 @@ impl Preview {
             Self::Syntactic(sv) => sv.display(w, screen, panel_skin, area, con),
             Self::ZeroLen(zlv) => zlv.display(w, screen, panel_skin, area),
             Self::Hex(hv) => hv.display(w, screen, panel_skin, area),
+            Self::Rendered(rv) => rv.display(w, screen, panel_skin, area),
             Self::IOError(err) => {
                 let mut y = area.top;
                 w.queue(cursor::MoveTo(area.left, y))?;
@@ -8,6 +8,7 @@ use {
         flag::Flag,
         pattern::InputPattern,
         skin::PanelSkin,
+        stage::Stage,
         task_sync::Dam,
         tree::TreeOptions,
         verb::*,
@@ -82,12 +83,25 @@ impl PreviewState {
             }
         })
     }
+    /// display the file as text highlighted with the given syntax.
+    /// The syntax isn't kept when the previewed file changes.
+    pub fn set_syntax(
+        &mut self,
+        syntax: &str,
+        con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        self.preview = Preview::with_syntax(&self.path, syntax, con)?;
+        self.filtered_preview = None;
+        self.prefered_mode = Some(PreviewMode::Text);
+        Ok(())
+    }
 }
 
 impl AppState for PreviewState {
 
     fn get_pending_task(&self) -> Option<&'static str> {
-        if self.pending_pattern.is_some() {
+        let preview = self.filtered_preview.as_ref().unwrap_or(&self.preview);
+        if self.pending_pattern.is_some() || preview.has_match_search() {
             Some("searching")
         } else {
             None
@@ -142,6 +156,8 @@ impl AppState for PreviewState {
                     filtered_preview.try_select_line_number(number);
                 }
             }
+        } else {
+            self.mut_preview().do_match_search(dam);
         }
     }
 
@@ -207,6 +223,7 @@ impl AppState for PreviewState {
         screen: Screen,
         state_area: Area,
         panel_skin: &PanelSkin,
+        _stage: &Stage,
         con: &AppContext,
     ) -> Result<(), ProgramError> {
         if state_area.height < 3 {
@@ -250,7 +267,7 @@ impl AppState for PreviewState {
                 if let ProgramError::Io { source } = err {
                     // we mutate the preview to Preview::IOError
                     self.preview = Preview::IOError(source);
-                    return self.display(w, screen, state_area, panel_skin, con);
+                    return self.display(w, screen, state_area, panel_skin, _stage, con);
                 }
             }
             return Err(err);
@@ -321,6 +338,14 @@ impl AppState for PreviewState {
             Internal::panel_right if self.filtered_preview.is_some() => {
                 self.on_pattern(InputPattern::none(), &cc.con)
             }
+            Internal::next_match => {
+                self.mut_preview().try_select_match(true);
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::previous_match => {
+                self.mut_preview().try_select_match(false);
+                Ok(AppStateCmdResult::Keep)
+            }
             Internal::select_first => {
                 self.mut_preview().select_first();
                 Ok(AppStateCmdResult::Keep)
@@ -332,6 +357,15 @@ impl AppState for PreviewState {
             Internal::preview_image => self.set_mode(PreviewMode::Image, cc.con),
             Internal::preview_text => self.set_mode(PreviewMode::Text, cc.con),
             Internal::preview_binary => self.set_mode(PreviewMode::Hex, cc.con),
+            Internal::render => self.set_mode(PreviewMode::Rendered, cc.con),
+            Internal::preview_as => {
+                let syntax = get_string_arg(input_invocation, internal_exec);
+                Ok(match syntax.map(|syntax| self.set_syntax(syntax, cc.con)) {
+                    Some(Ok(())) => AppStateCmdResult::Keep,
+                    Some(Err(e)) => AppStateCmdResult::DisplayError(e.to_string()),
+                    None => AppStateCmdResult::DisplayError("a syntax is needed".to_string()),
+                })
+            }
             _ => self.on_internal_generic(
                 w,
                 internal_exec,
@@ -0,0 +1,316 @@
+use {
+    crate::{
+        command::ScrollCommand,
+        display::{CropWriter, Screen, SPACE_FILLING, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+    termimad::{Area, FmtText, MadSkin, TextView},
+    unicode_width::UnicodeWidthStr,
+};
+
+/// the maximal size of a file which can be rendered
+const MAX_RENDERED_FILE_SIZE: u64 = 1024 * 1024;
+
+/// what a rendered preview shows
+enum Rendered {
+    /// the markdown source, formatted at display time
+    /// as it depends on the width
+    Markdown(String),
+    /// the lines of a table, the first two ones being
+    /// the header and its separator
+    Table(Vec<String>),
+}
+
+/// a preview showing a markdown file formatted, or a csv
+/// file as an aligned table
+pub struct RenderedView {
+    path: PathBuf,
+    rendered: Rendered,
+    scroll: usize,
+    page_height: usize,
+}
+
+impl RenderedView {
+    /// read and render the file, which must be a markdown
+    /// (`.md`, `.markdown`) or csv (`.csv`) file
+    pub fn new(path: &Path) -> Result<Self, ProgramError> {
+        let extension = path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let is_markdown = match extension.as_str() {
+            "md" | "markdown" => true,
+            "csv" => false,
+            _ => {
+                return Err(ProgramError::Unrenderable {
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+        };
+        if path.metadata()?.len() > MAX_RENDERED_FILE_SIZE {
+            return Err(io::Error::other("file too big to be rendered").into());
+        }
+        let content = fs::read_to_string(path)?;
+        let rendered = if is_markdown {
+            Rendered::Markdown(without_front_matter(&content).to_string())
+        } else {
+            Rendered::Table(csv_table(&parse_csv(&content, ',')))
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            rendered,
+            scroll: 0,
+            page_height: 0,
+        })
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    pub fn try_scroll(
+        &mut self,
+        cmd: ScrollCommand,
+    ) -> bool {
+        let old_scroll = self.scroll;
+        self.scroll = match &self.rendered {
+            Rendered::Table(lines) => cmd.apply(self.scroll, lines.len(), self.page_height),
+            // the markdown scroll is bounded at display
+            Rendered::Markdown(_) => (self.scroll as i32 + cmd.to_lines(self.page_height)).max(0) as usize,
+        };
+        self.scroll != old_scroll
+    }
+    pub fn select_first(&mut self) {
+        self.scroll = 0;
+    }
+    pub fn select_last(&mut self) {
+        self.scroll = match &self.rendered {
+            Rendered::Table(lines) => lines.len().max(self.page_height) - self.page_height,
+            Rendered::Markdown(_) => usize::MAX / 2,
+        };
+    }
+    pub fn display(
+        &mut self,
+        w: &mut W,
+        _screen: Screen,
+        panel_skin: &PanelSkin,
+        area: &Area,
+    ) -> Result<(), ProgramError> {
+        self.page_height = area.height as usize;
+        match &self.rendered {
+            Rendered::Markdown(md) => {
+                let fmt_text = render_markdown(&panel_skin.help_skin, md, area.width as usize - 1);
+                let mut text_view = TextView::from(area, &fmt_text);
+                self.scroll = text_view.set_scroll(self.scroll as i32).max(0) as usize;
+                text_view.write_on(w)?;
+            }
+            Rendered::Table(lines) => {
+                let styles = &panel_skin.styles;
+                for y in 0..self.page_height {
+                    w.queue(cursor::MoveTo(area.left, y as u16 + area.top))?;
+                    let mut cw = CropWriter::new(w, area.width as usize);
+                    let idx = self.scroll + y;
+                    if let Some(line) = lines.get(idx) {
+                        let style = match idx {
+                            0 => &styles.help_headers,
+                            1 => &styles.help_table_border,
+                            _ => &styles.preview,
+                        };
+                        cw.queue_str(style, line)?;
+                    }
+                    cw.fill(&styles.preview, &SPACE_FILLING)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// remove the YAML front matter, if any, from a markdown text
+fn without_front_matter(md: &str) -> &str {
+    if let Some(rest) = md.strip_prefix("---\n") {
+        if let Some(end) = rest.find("\n---\n") {
+            return &rest[end + 5..];
+        }
+    }
+    md
+}
+
+/// format the markdown for the given width
+fn render_markdown<'k, 's>(skin: &'k MadSkin, md: &'s str, width: usize) -> FmtText<'k, 's> {
+    FmtText::from(skin, md, Some(width))
+}
+
+/// split a csv text in records, each one being a vec of fields.
+///
+/// Fields may be quoted, in which case they may contain separators,
+/// newlines, and quotes (doubled). Empty lines are skipped.
+fn parse_csv(content: &str, separator: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == separator {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            if !(record.len() == 1 && record[0].is_empty()) {
+                records.push(std::mem::take(&mut record));
+            }
+            record.clear();
+        } else {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// tell whether the cell is a number, to be right aligned
+fn is_number(cell: &str) -> bool {
+    !cell.is_empty() && cell.parse::<f64>().is_ok()
+}
+
+/// build the lines of a table showing the records, the first one being
+/// the header, followed by a separator line.
+///
+/// Columns are as wide as their widest cell. Numbers are right aligned,
+/// other cells left aligned. The newlines of a cell are replaced with
+/// spaces, and missing cells of short records are empty.
+fn csv_table(records: &[Vec<String>]) -> Vec<String> {
+    let records: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| record.iter().map(|cell| cell.replace(&['\r', '\n'][..], " ")).collect())
+        .collect();
+    let column_count = records.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; column_count];
+    for record in &records {
+        for (idx, cell) in record.iter().enumerate() {
+            widths[idx] = widths[idx].max(UnicodeWidthStr::width(cell.as_str()));
+        }
+    }
+    let mut lines = Vec::new();
+    for (record_idx, record) in records.iter().enumerate() {
+        let mut line = String::new();
+        for (idx, width) in widths.iter().enumerate() {
+            if idx > 0 {
+                line.push_str(" │ ");
+            }
+            let cell = record.get(idx).map_or("", String::as_str);
+            let padding = " ".repeat(width - UnicodeWidthStr::width(cell));
+            if record_idx > 0 && is_number(cell) {
+                line.push_str(&padding);
+                line.push_str(cell);
+            } else {
+                line.push_str(cell);
+                line.push_str(&padding);
+            }
+        }
+        lines.push(line.trim_end().to_string());
+        if record_idx == 0 {
+            let separator: Vec<String> = widths.iter().map(|&width| "─".repeat(width)).collect();
+            lines.push(separator.join("─┼─"));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod rendered_view_tests {
+
+    use super::*;
+
+    fn csv_lines(csv: &str) -> Vec<String> {
+        csv_table(&parse_csv(csv, ','))
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        assert_eq!(
+            parse_csv("name,age\r\n\"Doe, John\",42\n\n\"say \"\"hi\"\"\",\n\"two\nlines\",7", ','),
+            vec![
+                vec!["name", "age"],
+                vec!["Doe, John", "42"],
+                vec!["say \"hi\"", ""],
+                vec!["two\nlines", "7"],
+            ],
+        );
+        assert!(parse_csv("", ',').is_empty());
+    }
+
+    #[test]
+    fn test_csv_table_alignment() {
+        assert_eq!(
+            csv_lines("city,population,country\nParis,2161000,France\nSão Paulo,12330000,Brazil\nLyon,513275,France\n"),
+            vec![
+                "city      │ population │ country",
+                "──────────┼────────────┼────────",
+                "Paris     │    2161000 │ France",
+                "São Paulo │   12330000 │ Brazil",
+                "Lyon      │     513275 │ France",
+            ],
+        );
+        // short records, wide chars, and a header narrower than its cells
+        assert_eq!(
+            csv_lines("a,b\n東京,1.5,extra\nx\n"),
+            vec![
+                "a    │ b   │",
+                "─────┼─────┼──────",
+                "東京 │ 1.5 │ extra",
+                "x    │     │",
+            ],
+        );
+        assert!(csv_lines("").is_empty());
+    }
+
+    #[test]
+    fn test_markdown_render() {
+        let md = "---\ntitle: notes\n---\n# Title\n\nSome **bold** text which is a little long.\n\n* first\n* second\n";
+        let md = without_front_matter(md);
+        assert!(md.starts_with("# Title"));
+        let skin = MadSkin::default();
+        let rendered = render_markdown(&skin, md, 20).to_string();
+        // the styles are removed to check the text
+        let rendered = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap().replace_all(&rendered, "");
+        let lines: Vec<&str> = rendered.lines().map(str::trim_end).collect();
+        assert!(lines[0].contains("Title"), "lines: {:?}", lines);
+        assert!(!rendered.contains("**"));
+        assert!(!rendered.contains("title: notes"));
+        // the paragraph is wrapped
+        assert!(lines.iter().all(|line| UnicodeWidthStr::width(*line) <= 20), "lines: {:?}", lines);
+        assert!(lines.iter().any(|line| line.trim_start().starts_with("Some bold text")));
+        // bullets
+        assert_eq!(lines.iter().filter(|line| line.contains("first") || line.contains("second")).count(), 2);
+        assert!(lines.iter().all(|line| !line.starts_with('*')));
+        // no front matter: the text is kept
+        assert_eq!(without_front_matter("# A\n---\nb\n---\n"), "# A\n---\nb\n---\n");
+    }
+}
@@ -7,16 +7,117 @@ use {
         errors::ProgramError,
         launchable::Launchable,
         skin::{ExtColorMap, PanelSkin, StyleMap},
-        tree::Tree,
+        tree::{Tree, TreeLineType},
     },
     pathdiff,
+    serde::Serialize,
     std::{
         fs::OpenOptions,
         io::{self, Write},
         path::Path,
+        str::FromStr,
     },
 };
 
+/// the format in which trees are printed by `:print_tree`
+/// and `:export`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// the tree as displayed, with its branches
+    #[default]
+    Tree,
+    /// a JSON object describing the root, the selection and
+    /// the entries with their sizes and git statuses
+    Json,
+    /// the paths of the entries, each one followed by a NUL char
+    Paths0,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tree" => Ok(Self::Tree),
+            "json" => Ok(Self::Json),
+            "paths0" => Ok(Self::Paths0),
+            _ => Err(format!(
+                "{:?} isn't a valid output format (expected tree, json or paths0)",
+                s,
+            )),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonTree {
+    root: String,
+    pattern: String,
+    selection: String,
+    entries: Vec<JsonEntry>,
+}
+
+#[derive(Serialize)]
+struct JsonEntry {
+    path: String,
+    depth: u16,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    size: Option<u64>, // None for directories whose size wasn't computed
+    git_status: Option<&'static str>,
+    selected: bool,
+}
+
+/// return the tree as a JSON object, ending with a newline
+pub fn tree_to_json(tree: &Tree) -> String {
+    let entries = tree.lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.line_type != TreeLineType::Pruning)
+        .map(|(idx, line)| JsonEntry {
+            path: line.path.to_string_lossy().to_string(),
+            depth: line.depth,
+            kind: match line.line_type {
+                TreeLineType::Dir => "dir",
+                TreeLineType::SymLink { .. } => "link",
+                TreeLineType::BrokenSymLink(_) => "broken_link",
+                _ => "file",
+            },
+            size: match line.sum {
+                Some(sum) => Some(sum.to_size()),
+                None if line.is_file() => Some(line.metadata.len()),
+                None => None,
+            },
+            git_status: line.git_status
+                .map(|s| s.short_code())
+                .filter(|code| !code.is_empty()),
+            selected: idx == tree.selection,
+        })
+        .collect();
+    let json_tree = JsonTree {
+        root: tree.root().to_string_lossy().to_string(),
+        pattern: tree.options.pattern.raw.clone(),
+        selection: tree.selected_line().path.to_string_lossy().to_string(),
+        entries,
+    };
+    let mut json = serde_json::to_string_pretty(&json_tree)
+        .expect("a tree can always be serialized");
+    json.push('\n');
+    json
+}
+
+/// return the paths of the tree (the root excluded), each one
+/// followed by a NUL char, as expected by `xargs -0`
+pub fn tree_to_paths0(tree: &Tree) -> String {
+    let mut paths = String::new();
+    for line in tree.lines.iter().skip(1) {
+        if line.line_type != TreeLineType::Pruning {
+            paths.push_str(&line.path.to_string_lossy());
+            paths.push('\0');
+        }
+    }
+    paths
+}
+
 pub fn print_path(path: &Path, con: &AppContext) -> io::Result<AppStateCmdResult> {
     let path = path.to_string_lossy().to_string();
     Ok(
@@ -68,11 +169,49 @@ fn print_tree_to_file(
     Ok(AppStateCmdResult::Quit)
 }
 
+/// print the tree in the format given at launch
 pub fn print_tree(
     tree: &Tree,
     screen: Screen,
     panel_skin: &PanelSkin,
     con: &AppContext,
+) -> Result<AppStateCmdResult, ProgramError> {
+    export_tree(tree, con.launch_args.output_format, screen, panel_skin, con)
+}
+
+/// print the tree in the given format, either in the output file
+/// or, after broot's end, on stdout
+pub fn export_tree(
+    tree: &Tree,
+    format: OutputFormat,
+    screen: Screen,
+    panel_skin: &PanelSkin,
+    con: &AppContext,
+) -> Result<AppStateCmdResult, ProgramError> {
+    let content = match format {
+        OutputFormat::Tree => {
+            return print_displayable_tree(tree, screen, panel_skin, con);
+        }
+        OutputFormat::Json => tree_to_json(tree),
+        OutputFormat::Paths0 => tree_to_paths0(tree),
+    };
+    Ok(if let Some(ref output_path) = con.launch_args.file_export_path {
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(output_path)?;
+        f.write_all(content.as_bytes())?;
+        AppStateCmdResult::Quit
+    } else {
+        AppStateCmdResult::from(Launchable::raw_printer(content))
+    })
+}
+
+fn print_displayable_tree(
+    tree: &Tree,
+    screen: Screen,
+    panel_skin: &PanelSkin,
+    con: &AppContext,
 ) -> Result<AppStateCmdResult, ProgramError> {
     if let Some(ref output_path) = con.launch_args.file_export_path {
         // an output path was provided, we write to it
@@ -93,3 +232,58 @@ pub fn print_tree(
         )))
     }
 }
+
+#[cfg(test)]
+mod print_tests {
+
+    use {
+        super::*,
+        crate::{
+            task_sync::Dam,
+            tree::TreeOptions,
+            tree_build::TreeBuilder,
+        },
+        std::fs,
+    };
+
+    fn build_tree(root: &Path) -> Tree {
+        let con = AppContext::for_test(root);
+        TreeBuilder::from(root.to_path_buf(), TreeOptions::default(), 100, &con)
+            .unwrap()
+            .build(true, &Dam::unlimited())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tree_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("sub/a.txt"), "abc").unwrap();
+        fs::write(root.join("b.txt"), "").unwrap();
+        let mut tree = build_tree(root);
+        assert!(tree.try_select_path(&root.join("sub/a.txt")));
+        assert_eq!(
+            tree_to_paths0(&tree),
+            format!(
+                "{}\0{}\0{}\0",
+                root.join("b.txt").to_string_lossy(),
+                root.join("sub").to_string_lossy(),
+                root.join("sub/a.txt").to_string_lossy(),
+            ),
+        );
+        let json: serde_json::Value = serde_json::from_str(&tree_to_json(&tree)).unwrap();
+        assert_eq!(json["root"], root.to_string_lossy().as_ref());
+        assert_eq!(json["selection"], root.join("sub/a.txt").to_string_lossy().as_ref());
+        let entries = json["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[1]["type"], "file");
+        assert_eq!(entries[1]["selected"], false);
+        assert_eq!(entries[2]["type"], "dir");
+        assert_eq!(entries[3]["depth"], 2);
+        assert_eq!(entries[3]["size"], 3);
+        assert_eq!(entries[3]["selected"], true);
+        assert_eq!("paths0".parse(), Ok(OutputFormat::Paths0));
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use {
+    super::*,
+    crate::errors::ConfError,
+    fnv::FnvHashMap,
+    serde::Deserialize,
+    std::{
+        path::PathBuf,
+        sync::Mutex,
+    },
+};
+
+/// the definition, in the configuration, of the custom column
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomColumnConf {
+    command: String,
+    max_width: Option<usize>,
+}
+
+const DEFAULT_MAX_WIDTH: usize = 20;
+
+lazy_static! {
+    static ref ANNOTATIONS: Mutex<FnvHashMap<PathBuf, String>> =
+        Mutex::new(FnvHashMap::default());
+}
+
+/// forget the annotations, so that they're asked again
+pub fn clear_annotation_cache() {
+    ANNOTATIONS.lock().unwrap().clear();
+}
+
+/// a command annotating paths, its annotations being
+/// displayed in the custom column of the trees.
+///
+/// The command receives the paths on its standard input, one
+/// per line, and writes lines made of a path, a tab, and the
+/// annotation of the path.
+#[derive(Debug, Clone)]
+pub struct ColumnProvider {
+    tokens: Vec<String>,
+    max_width: usize,
+}
+
+impl ColumnProvider {
+    pub fn new(conf: &CustomColumnConf) -> Result<Self, ConfError> {
+        let tokens = tokens(&conf.command);
+        if tokens.is_empty() {
+            return Err(ConfError::InvalidCustomColumn {
+                details: "a command is needed".to_string(),
+            });
+        }
+        Ok(Self {
+            tokens,
+            max_width: conf.max_width.unwrap_or(DEFAULT_MAX_WIDTH),
+        })
+    }
+
+    /// return the annotations of the paths (an empty string when
+    /// there's none), running the command, in the given directory,
+    /// for the ones which aren't in cache.
+    ///
+    /// Return None when the command was interrupted by an event.
+    pub fn annotations(&self, dir: &Path, paths: &[&Path], dam: &Dam) -> Option<Vec<String>> {
+        let missing: Vec<&Path> = {
+            let cache = ANNOTATIONS.lock().unwrap();
+            paths.iter()
+                .filter(|path| !cache.contains_key(**path))
+                .copied()
+                .collect()
+        };
+        if !missing.is_empty() {
+            let mut input = String::new();
+            for path in &missing {
+                input.push_str(&path.to_string_lossy());
+                input.push('\n');
+            }
+            let output = time!(
+                Debug,
+                "column provider",
+                run_interruptible(&self.tokens, Some(dir), input, dam),
+            );
+            let mut annotations = FnvHashMap::default();
+            match output {
+                Ok(None) => {
+                    return None;
+                }
+                Ok(Some(output)) => {
+                    for line in output.lines() {
+                        if let Some((path, annotation)) = line.split_once('\t') {
+                            let annotation: String = annotation.trim()
+                                .chars()
+                                .filter(|c| !c.is_control())
+                                .take(self.max_width)
+                                .collect();
+                            annotations.insert(PathBuf::from(path), annotation);
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("column provider {:?} failed: {}", &self.tokens, e);
+                }
+            }
+            // paths without annotation aren't asked again
+            let mut cache = ANNOTATIONS.lock().unwrap();
+            for path in missing {
+                let annotation = annotations.remove(path).unwrap_or_default();
+                cache.insert(path.to_path_buf(), annotation);
+            }
+        }
+        let cache = ANNOTATIONS.lock().unwrap();
+        Some(paths.iter()
+            .map(|path| cache.get(*path).cloned().unwrap_or_default())
+            .collect())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod column_provider_tests {
+
+    use {
+        super::*,
+        std::time::Instant,
+    };
+
+    fn provider(command: &str) -> ColumnProvider {
+        ColumnProvider::new(&CustomColumnConf {
+            command: command.to_string(),
+            max_width: Some(6),
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_annotations() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.rs");
+        let b = dir.path().join("b.rs");
+        // annotate every path with "reviewed by alice"
+        let reviews = provider("sed \"s/$/\treviewed by alice/\"");
+        let dam = Dam::unlimited();
+        let annotations = reviews.annotations(dir.path(), &[&a, &b], &dam);
+        assert_eq!(annotations, Some(vec!["review".to_string(), "review".to_string()])); // cropped
+        // a failing provider gives empty annotations
+        let c = dir.path().join("c.rs");
+        assert_eq!(provider("false").annotations(dir.path(), &[&c], &dam), Some(vec![String::new()]));
+        // a long command is killed when the user does something,
+        // and its annotations are asked again later
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        sender.send(termimad::Event::Wheel(1)).unwrap();
+        let start = Instant::now();
+        let d = dir.path().join("d.rs");
+        assert_eq!(provider("sleep 10").annotations(dir.path(), &[&d], &Dam::from(receiver)), None);
+        assert!(start.elapsed().as_secs() < 5);
+        assert!(!ANNOTATIONS.lock().unwrap().contains_key(&d));
+        assert!(ColumnProvider::new(&CustomColumnConf {
+            command: " ".to_string(),
+            max_width: None,
+        }).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! hooks letting external commands extend broot: verb providers
+//! whose output defines verbs, and a column provider annotating
+//! the paths of the trees
+
+mod column_provider;
+mod verb_provider;
+
+pub use {
+    column_provider::*,
+    verb_provider::*,
+};
+
+use {
+    crate::task_sync::Dam,
+    splitty::split_unquoted_whitespace,
+    std::{
+        io::{self, Read},
+        path::Path,
+        process::{Child, Command, ExitStatus, Stdio},
+        thread,
+        time::Duration,
+    },
+};
+
+/// the period between two checks of the end of an interruptible command
+const POLL_PERIOD: Duration = Duration::from_millis(20);
+
+/// split a command into its executable and arguments
+fn tokens(command: &str) -> Vec<String> {
+    split_unquoted_whitespace(command)
+        .unwrap_quotes(true)
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// launch the command, feeding its standard input
+fn spawn(tokens: &[String], dir: Option<&Path>, input: String) -> io::Result<Child> {
+    let exe = tokens.first()
+        .ok_or_else(|| io::Error::other("empty command"))?;
+    let mut command = Command::new(exe);
+    command
+        .args(&tokens[1..])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    crate::verb::internal_pipe_listing::spawn_with_input(&mut command, input)
+}
+
+fn check_status(status: ExitStatus) -> io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("exit status: {}", status)))
+    }
+}
+
+/// run the command, feeding its standard input, and return its
+/// standard output, failing when the command isn't successful
+fn run(tokens: &[String], dir: Option<&Path>, input: String) -> io::Result<String> {
+    let output = spawn(tokens, dir, input)?.wait_with_output()?;
+    check_status(output.status)?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// run the command like `run`, but kill it and return `Ok(None)`
+/// as soon as the dam gets an event
+fn run_interruptible(
+    tokens: &[String],
+    dir: Option<&Path>,
+    input: String,
+    dam: &Dam,
+) -> io::Result<Option<String>> {
+    let mut child = spawn(tokens, dir, input)?;
+    // the output is read by another thread so that the
+    // command isn't blocked on a full pipe
+    let mut stdout = child.stdout.take()
+        .ok_or_else(|| io::Error::other("no output"))?;
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if dam.has_event() {
+            debug!("command {:?} interrupted", tokens);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(POLL_PERIOD);
+    };
+    let output = reader.join()
+        .map_err(|_| io::Error::other("output not read"))??;
+    check_status(status)?;
+    Ok(Some(String::from_utf8_lossy(&output).to_string()))
+}
@@ -0,0 +1,48 @@
+use {
+    super::*,
+    crate::{
+        conf::VerbConf,
+        errors::ConfError,
+    },
+    serde::Deserialize,
+};
+
+/// the output of a verb provider, which is in the
+/// format of the verbs of the configuration
+#[derive(Deserialize)]
+struct ProvidedVerbs {
+    #[serde(default)]
+    verbs: Vec<VerbConf>,
+}
+
+/// run a verb provider and read the `[[verbs]]` it writes
+/// on its standard output, in TOML
+pub fn provided_verbs(command: &str) -> Result<Vec<VerbConf>, ConfError> {
+    let error = |details: String| ConfError::VerbProvider {
+        command: command.to_string(),
+        details,
+    };
+    let output = run(&tokens(command), None, String::new())
+        .map_err(|e| error(e.to_string()))?;
+    let provided: ProvidedVerbs = toml::from_str(&output)
+        .map_err(|e| error(e.to_string()))?;
+    info!("{} verbs provided by {:?}", provided.verbs.len(), command);
+    Ok(provided.verbs)
+}
+
+#[cfg(all(test, unix))]
+mod verb_provider_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_provided_verbs() {
+        let verbs = provided_verbs(r#"printf "[[verbs]]\ninvocation = 'build'\nexternal = 'make'\n""#)
+            .unwrap();
+        assert_eq!(verbs.len(), 1);
+        assert_eq!(verbs[0].name().as_deref(), Some("build"));
+        assert!(provided_verbs("true").unwrap().is_empty());
+        assert!(provided_verbs("false").is_err());
+        assert!(provided_verbs("echo not toml").is_err());
+    }
+}
@@ -126,6 +126,7 @@ StyleMap! {
     group: ansi(131), None, []
     count: ansi(138), gray(4), []
     dates: ansi(66), None, []
+    annotation: ansi(110), None, []
     sparse: ansi(214), None, []
     content_extract: ansi(29), None, []
     content_match: ansi(34), None, []
@@ -139,6 +140,7 @@ StyleMap! {
     git_status_conflicted: ansi(88), None, []
     git_status_other: ansi(88), None, []
     selected_line: None, gray(6), [] / None, gray(4), []
+    staged_marker: ansi(178), None, [Bold]
     char_match: Some(Green), None, []
     file_error: Some(Red), None, []
     flag_label: gray(15), gray(2), []
@@ -146,6 +148,7 @@ StyleMap! {
     input: Some(White), gray(2), [] / gray(15), None, []
     status_error: gray(22), ansi(124), []
     status_job: ansi(220), gray(5), []
+    status_mode: gray(2), ansi(178), [Bold]
     status_normal: gray(20), gray(4), [] / gray(2), gray(2), []
     status_italic: ansi(178), gray(4), [] / gray(2), gray(2), []
     status_bold: ansi(178), gray(4), [Bold] / gray(2), gray(2), []
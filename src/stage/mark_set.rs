@@ -0,0 +1,263 @@
+//! named sets of marked paths, saved in broot's data directory,
+//! managed with `:mark` and usable as the selection of verbs
+
+use {
+    crate::errors::ProgramError,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// a persistent set of paths, kept in the order they were marked.
+///
+/// Unlike the stage, a mark set outlives the broot session and
+/// several ones can coexist, each one under its name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarkSet {
+    paths: Vec<PathBuf>,
+}
+
+/// what a `:mark` command asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkCommand {
+    /// add the selection to the mark set
+    Add { name: String },
+    /// remove the selection from the mark set
+    Remove { name: String },
+    /// execute a verb with the paths of the mark set as `{selection}`
+    Run { name: String, verb: String },
+}
+
+impl MarkCommand {
+    /// the name of the mark set
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Add { name } | Self::Remove { name } | Self::Run { name, .. } => name,
+        }
+    }
+    /// parse the arguments of `:mark`, e.g. `add photos` or
+    /// `run photos :cp {other-panel-directory}`
+    pub fn parse(args: &str) -> Result<Self, String> {
+        let (action, rest) = split_first_word(args);
+        let (name, rest) = split_first_word(rest);
+        if name.is_empty() {
+            return Err("expected `:mark add|remove|run {name}`".to_string());
+        }
+        let name = name.to_string();
+        let rest = Some(rest.trim_start_matches(':').trim_start()).filter(|s| !s.is_empty());
+        match (action, rest) {
+            ("add", None) => Ok(Self::Add { name }),
+            ("remove", None) => Ok(Self::Remove { name }),
+            ("run", Some(verb)) => {
+                if verb.split_whitespace().next() == Some("mark") {
+                    return Err("a mark set can't be used to run :mark".to_string());
+                }
+                Ok(Self::Run { name, verb: verb.to_string() })
+            }
+            ("run", _) => Err("expected `:mark run {name} {verb}`".to_string()),
+            ("add", Some(_)) | ("remove", Some(_)) => Err(format!(
+                "expected `:mark {} {{name}}`", action,
+            )),
+            _ => Err(format!("unknown mark action: {:?}", action)),
+        }
+    }
+}
+
+/// split the first word of the string from the (trimmed) rest
+fn split_first_word(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    match s.find(char::is_whitespace) {
+        Some(idx) => (&s[..idx], s[idx..].trim_start()),
+        None => (s, ""),
+    }
+}
+
+/// return the directory where mark sets are saved
+pub fn mark_sets_dir() -> PathBuf {
+    crate::conf::app_dirs().data_dir().join("marks")
+}
+
+/// return the path of the file of a mark set, checking the name
+/// is a simple one
+fn mark_set_path(dir: &Path, name: &str) -> Result<PathBuf, ProgramError> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !name.starts_with('.');
+    if valid {
+        Ok(dir.join(format!("{}.toml", name)))
+    } else {
+        Err(ProgramError::InvalidMarkSetName { name: name.to_string() })
+    }
+}
+
+impl MarkSet {
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|p| p == path)
+    }
+    /// add a path, return false if it was already marked
+    pub fn add(&mut self, path: PathBuf) -> bool {
+        if self.contains(&path) {
+            false
+        } else {
+            self.paths.push(path);
+            true
+        }
+    }
+    /// remove a path, return false if it wasn't marked
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let len = self.paths.len();
+        self.paths.retain(|p| p != path);
+        self.paths.len() != len
+    }
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// write the mark set in the given directory, replacing the
+    /// previous set of the same name. An empty set is removed.
+    pub fn save_in(&self, dir: &Path, name: &str) -> Result<(), ProgramError> {
+        let path = mark_set_path(dir, name)?;
+        if self.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+            return Ok(());
+        }
+        let content = toml::to_string(self)
+            .map_err(|e| ProgramError::MarkSetFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })?;
+        fs::create_dir_all(dir)?;
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    /// read the mark set of the given name in the given directory,
+    /// a missing set being an empty one
+    pub fn load_from(dir: &Path, name: &str) -> Result<Self, ProgramError> {
+        let path = mark_set_path(dir, name)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        toml::from_str(&content)
+            .map_err(|e| ProgramError::MarkSetFile {
+                path: path.to_string_lossy().to_string(),
+                details: e.to_string(),
+            })
+    }
+
+    pub fn save(&self, name: &str) -> Result<(), ProgramError> {
+        self.save_in(&mark_sets_dir(), name)
+    }
+
+    pub fn load(name: &str) -> Result<Self, ProgramError> {
+        Self::load_from(&mark_sets_dir(), name)
+    }
+}
+
+#[cfg(test)]
+mod mark_set_tests {
+
+    use {
+        super::*,
+        crate::{
+            app::{Selection, SelectionType},
+            verb::ExecutionStringBuilder,
+        },
+    };
+
+    #[test]
+    fn test_mark_set_add_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let marks = dir.path().join("marks");
+        let mut set = MarkSet::load_from(&marks, "photos").unwrap();
+        assert!(set.is_empty());
+        assert!(set.add(PathBuf::from("/home/dys/b.jpg")));
+        assert!(set.add(PathBuf::from("/home/dys/a.jpg")));
+        assert!(!set.add(PathBuf::from("/home/dys/b.jpg")));
+        set.save_in(&marks, "photos").unwrap();
+        let mut set = MarkSet::load_from(&marks, "photos").unwrap();
+        assert_eq!(
+            set.paths(),
+            &[PathBuf::from("/home/dys/b.jpg"), PathBuf::from("/home/dys/a.jpg")],
+        );
+        // other sets are independent
+        assert!(MarkSet::load_from(&marks, "docs").unwrap().is_empty());
+        assert!(set.remove(Path::new("/home/dys/b.jpg")));
+        assert!(!set.remove(Path::new("/home/dys/b.jpg")));
+        set.save_in(&marks, "photos").unwrap();
+        let mut set = MarkSet::load_from(&marks, "photos").unwrap();
+        assert_eq!(set.paths(), &[PathBuf::from("/home/dys/a.jpg")]);
+        // an emptied set is removed
+        assert!(set.remove(Path::new("/home/dys/a.jpg")));
+        set.save_in(&marks, "photos").unwrap();
+        assert!(!marks.join("photos.toml").exists());
+        for name in &["", "../photos", "a/b", ".hidden"] {
+            assert!(matches!(
+                MarkSet::load_from(&marks, name),
+                Err(ProgramError::InvalidMarkSetName { .. }),
+            ));
+        }
+    }
+
+    #[test]
+    fn test_mark_set_as_selection() {
+        let mut set = MarkSet::default();
+        set.add(PathBuf::from("/home/dys/my notes.md"));
+        set.add(PathBuf::from("/home/dys/todo.md"));
+        let path = PathBuf::from("/home/dys/dev");
+        let sel = Selection {
+            path: &path,
+            line: 0,
+            stype: SelectionType::Directory,
+            is_exe: false,
+        };
+        let builder = ExecutionStringBuilder::from_selection(sel).with_staged_paths(set.paths());
+        assert_eq!(
+            builder.exec_token("cp {selection} /backup"),
+            vec!["cp", "/home/dys/my notes.md", "/home/dys/todo.md", "/backup"],
+        );
+        assert_eq!(
+            builder.shell_exec_string("cp {selection} /backup"),
+            "cp '/home/dys/my notes.md' /home/dys/todo.md /backup",
+        );
+    }
+
+    #[test]
+    fn test_parse_mark_command() {
+        assert_eq!(
+            MarkCommand::parse("add photos"),
+            Ok(MarkCommand::Add { name: "photos".to_string() }),
+        );
+        assert_eq!(
+            MarkCommand::parse(" remove  photos "),
+            Ok(MarkCommand::Remove { name: "photos".to_string() }),
+        );
+        assert_eq!(
+            MarkCommand::parse("run photos :cp {other-panel-directory}"),
+            Ok(MarkCommand::Run {
+                name: "photos".to_string(),
+                verb: "cp {other-panel-directory}".to_string(),
+            }),
+        );
+        assert_eq!(
+            MarkCommand::parse("run photos rm"),
+            Ok(MarkCommand::Run { name: "photos".to_string(), verb: "rm".to_string() }),
+        );
+        assert!(MarkCommand::parse("add").is_err());
+        assert!(MarkCommand::parse("add photos more").is_err());
+        assert!(MarkCommand::parse("run photos").is_err());
+        assert!(MarkCommand::parse("run photos :mark run photos rm").is_err());
+        assert!(MarkCommand::parse("clear photos").is_err());
+    }
+}
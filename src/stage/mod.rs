@@ -0,0 +1,52 @@
+mod mark_set;
+mod mtimes;
+
+pub use {
+    mark_set::{mark_sets_dir, MarkCommand, MarkSet},
+    mtimes::MtimeSnapshot,
+};
+
+use std::path::{Path, PathBuf};
+
+/// a set of paths the user gathered from anywhere in the tree,
+/// to later apply verbs on them.
+///
+/// The stage belongs to the app and is shared by all panels.
+/// Paths are kept in the order they were staged.
+#[derive(Debug, Clone, Default)]
+pub struct Stage {
+    paths: Vec<PathBuf>,
+}
+
+impl Stage {
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|p| p == path)
+    }
+    /// add a path, return false if it was already staged
+    pub fn add(&mut self, path: PathBuf) -> bool {
+        if self.contains(&path) {
+            false
+        } else {
+            self.paths.push(path);
+            true
+        }
+    }
+    /// remove a path, return false if it wasn't staged
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let len = self.paths.len();
+        self.paths.retain(|p| p != path);
+        self.paths.len() != len
+    }
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
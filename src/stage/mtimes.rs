@@ -0,0 +1,120 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// the modification times of a set of paths, taken before the
+/// execution of a verb so that the paths it modified can be found
+#[derive(Debug, Clone)]
+pub struct MtimeSnapshot {
+    /// the paths, with their modification time (None when the
+    /// path doesn't exist, or its time can't be read)
+    mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|md| md.modified()).ok()
+}
+
+impl MtimeSnapshot {
+    pub fn of<P: AsRef<Path>>(paths: &[P]) -> Self {
+        Self::with_mtimes(paths, mtime)
+    }
+    fn with_mtimes<P, F>(paths: &[P], mtime: F) -> Self
+    where
+        P: AsRef<Path>,
+        F: Fn(&Path) -> Option<SystemTime>,
+    {
+        let mtimes = paths
+            .iter()
+            .map(|path| (path.as_ref().to_path_buf(), mtime(path.as_ref())))
+            .collect();
+        Self { mtimes }
+    }
+    /// return the paths whose modification time changed since the
+    /// snapshot, in the order of the snapshot
+    pub fn modified_paths(&self) -> Vec<PathBuf> {
+        self.changed_paths(mtime)
+    }
+    /// return the paths whose modification time, as given by the
+    /// `mtime` function, differs from the one of the snapshot.
+    ///
+    /// A path which didn't exist at the snapshot and exists now
+    /// was created, and is considered modified. A path which
+    /// doesn't exist anymore isn't.
+    fn changed_paths<F>(&self, mtime: F) -> Vec<PathBuf>
+    where
+        F: Fn(&Path) -> Option<SystemTime>,
+    {
+        self.mtimes
+            .iter()
+            .filter(|(path, before)| match (before, mtime(path)) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(before), Some(after)) => *before != after,
+            })
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod mtimes_tests {
+
+    use {
+        super::*,
+        std::{
+            collections::HashMap,
+            time::Duration,
+        },
+    };
+
+    #[test]
+    fn test_changed_paths() {
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let t1 = t0 + Duration::from_millis(5);
+        let paths: Vec<PathBuf> = ["a.txt", "b.txt", "c.txt", "new.txt", "removed.txt", "missing.txt"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let before: HashMap<PathBuf, SystemTime> = vec![
+            ("a.txt", t0),
+            ("b.txt", t0),
+            ("c.txt", t1),
+            ("removed.txt", t0),
+        ]
+        .into_iter()
+        .map(|(name, t)| (PathBuf::from(name), t))
+        .collect();
+        let snapshot = MtimeSnapshot::with_mtimes(&paths, |p| before.get(p).copied());
+        // nothing changed
+        assert!(snapshot.changed_paths(|p| before.get(p).copied()).is_empty());
+        let mut after = before.clone();
+        after.insert(PathBuf::from("b.txt"), t1);
+        after.insert(PathBuf::from("c.txt"), t0); // going back in time is a change too
+        after.insert(PathBuf::from("new.txt"), t1);
+        after.remove(Path::new("removed.txt"));
+        assert_eq!(
+            snapshot.changed_paths(|p| after.get(p).copied()),
+            vec![PathBuf::from("b.txt"), PathBuf::from("c.txt"), PathBuf::from("new.txt")],
+        );
+    }
+
+    #[test]
+    fn test_modified_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept.txt");
+        let touched = dir.path().join("touched.txt");
+        let created = dir.path().join("created.txt");
+        fs::write(&kept, "a").unwrap();
+        fs::write(&touched, "a").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(60);
+        let file = fs::OpenOptions::new().write(true).open(&touched).unwrap();
+        file.set_modified(old).unwrap();
+        let snapshot = MtimeSnapshot::of(&[&kept, &touched, &created]);
+        fs::write(&touched, "b").unwrap();
+        fs::write(&created, "b").unwrap();
+        assert_eq!(snapshot.modified_paths(), vec![touched, created]);
+    }
+}
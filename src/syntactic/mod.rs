@@ -2,6 +2,6 @@ mod syntaxer;
 mod syntactic_view;
 
 pub use {
-    syntaxer::Syntaxer,
+    syntaxer::{Syntaxer, SYNTAXER},
     syntactic_view::SyntacticView,
 };
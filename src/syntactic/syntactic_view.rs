@@ -61,6 +61,7 @@ pub struct Line {
 pub struct SyntacticView {
     pub path: PathBuf,
     pub pattern: InputPattern,
+    pub syntax: Option<String>, // forced syntax, if any
     lines: Vec<Line>,
     scroll: usize,
     page_height: usize,
@@ -76,12 +77,14 @@ impl SyntacticView {
     pub fn new(
         path: &Path,
         pattern: InputPattern,
+        syntax: Option<String>,
         dam: &mut Dam,
         con: &AppContext,
     ) -> Result<Option<Self>, ProgramError> {
         let mut sv = Self {
             path: path.to_path_buf(),
             pattern,
+            syntax,
             lines: Vec::new(),
             scroll: 0,
             page_height: 0,
@@ -114,11 +117,8 @@ impl SyntacticView {
         self.total_lines_count = 0;
         let mut offset = 0;
         let mut number = 0;
-        lazy_static! {
-            static ref SYNTAXER: Syntaxer = Syntaxer::default();
-        }
         let mut highlighter = if with_style {
-            SYNTAXER.highlighter_for(&self.path, con)
+            SYNTAXER.highlighter_for(&self.path, self.syntax.as_deref(), con)
         } else {
             None
         };
@@ -5,11 +5,15 @@ use {
     std::path::Path,
     syntect::{
         easy::HighlightLines,
-        parsing::SyntaxSet,
+        parsing::{SyntaxReference, SyntaxSet},
         highlighting::ThemeSet,
     },
 };
 
+lazy_static! {
+    pub static ref SYNTAXER: Syntaxer = Syntaxer::default();
+}
+
 /// wrap heavy to initialize syntect things
 pub struct Syntaxer {
     pub syntax_set: SyntaxSet,
@@ -25,14 +29,28 @@ impl Default for Syntaxer {
 }
 
 impl Syntaxer {
+    /// return the syntax to use for the file: the forced one if
+    /// any (given by name or extension), else the one matching the
+    /// extension of the file
+    pub fn find_syntax(
+        &self,
+        path: &Path,
+        forced_syntax: Option<&str>,
+    ) -> Option<&SyntaxReference> {
+        match forced_syntax {
+            Some(token) => self.syntax_set.find_syntax_by_token(token),
+            None => path.extension()
+                .and_then(|e| e.to_str())
+                .and_then(|ext| self.syntax_set.find_syntax_by_extension(ext)),
+        }
+    }
     pub fn highlighter_for<'s, 'p>(
         &'s self,
         path: &'p Path,
+        forced_syntax: Option<&str>,
         con: &AppContext,
     ) -> Option<HighlightLines<'s>> {
-        path.extension()
-            .and_then(|e| e.to_str())
-            .and_then(|ext| self.syntax_set.find_syntax_by_extension(ext))
+        self.find_syntax(path, forced_syntax)
             .map(|syntax| {
                 // some OK themes:
                 //  "base16-ocean.dark"
@@ -47,3 +65,30 @@ impl Syntaxer {
             })
     }
 }
+
+#[cfg(test)]
+mod syntaxer_tests {
+
+    use {
+        super::*,
+        std::path::PathBuf,
+    };
+
+    fn syntax_name(path: &str, forced_syntax: Option<&str>) -> Option<String> {
+        SYNTAXER
+            .find_syntax(&PathBuf::from(path), forced_syntax)
+            .map(|syntax| syntax.name.clone())
+    }
+
+    #[test]
+    fn test_find_syntax() {
+        assert_eq!(syntax_name("src/main.rs", None).as_deref(), Some("Rust"));
+        assert_eq!(syntax_name("broot.unknown-ext", None), None);
+        // the forced syntax may be given by name or by extension
+        assert_eq!(syntax_name("broot.unknown-ext", Some("rust")).as_deref(), Some("Rust"));
+        assert_eq!(syntax_name("broot.unknown-ext", Some("py")).as_deref(), Some("Python"));
+        // and it has precedence over the extension of the file
+        assert_eq!(syntax_name("src/main.rs", Some("python")).as_deref(), Some("Python"));
+        assert_eq!(syntax_name("src/main.rs", Some("klingon")), None);
+    }
+}
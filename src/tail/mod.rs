@@ -0,0 +1,7 @@
+mod tail_lines;
+mod tail_state;
+
+pub use {
+    tail_lines::TailLines,
+    tail_state::TailState,
+};
@@ -0,0 +1,170 @@
+use {
+    std::{
+        collections::VecDeque,
+        fs::File,
+        io::{self, Read, Seek, SeekFrom},
+        path::{Path, PathBuf},
+    },
+};
+
+/// the last lines of a file which is being written by another
+/// process, incrementally read.
+///
+/// Only complete lines are exposed: the bytes after the last
+/// newline are kept until the rest of the line arrives (or
+/// until `finish` is called).
+pub struct TailLines {
+    path: PathBuf,
+    offset: u64, // number of bytes of the file already read
+    partial: Vec<u8>, // start of a line whose end wasn't read yet
+    lines: VecDeque<String>,
+    max_lines: usize,
+    dropped: usize, // number of lines removed to respect max_lines
+}
+
+impl TailLines {
+    pub fn new(path: PathBuf, max_lines: usize) -> Self {
+        Self {
+            path,
+            offset: 0,
+            partial: Vec::new(),
+            lines: VecDeque::new(),
+            max_lines,
+            dropped: 0,
+        }
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// read what was appended to the file since the last call.
+    ///
+    /// Return true when new lines are available. A file shorter
+    /// than what was already read is considered as truncated and
+    /// read again from its start.
+    pub fn update(&mut self) -> io::Result<bool> {
+        let mut file = File::open(&self.path)?;
+        let len = file.metadata()?.len();
+        let mut changed = false;
+        if len < self.offset {
+            self.clear();
+            changed = true;
+        }
+        if len == self.offset {
+            return Ok(changed);
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(self.push_bytes(&bytes) || changed)
+    }
+    /// split the bytes into lines, return true when at least one
+    /// line was completed
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> bool {
+        let mut changed = false;
+        for &b in bytes {
+            if b == b'\n' {
+                let mut line = std::mem::take(&mut self.partial);
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                self.push_line(line);
+                changed = true;
+            } else {
+                self.partial.push(b);
+            }
+        }
+        changed
+    }
+    /// make the incomplete last line, if any, a real line.
+    /// To be called when the writer is known to be done.
+    pub fn finish(&mut self) -> bool {
+        if self.partial.is_empty() {
+            false
+        } else {
+            let line = std::mem::take(&mut self.partial);
+            self.push_line(line);
+            true
+        }
+    }
+    fn push_line(&mut self, line: Vec<u8>) {
+        self.lines.push_back(String::from_utf8_lossy(&line).replace('\t', "    "));
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+    }
+    fn clear(&mut self) {
+        self.offset = 0;
+        self.partial.clear();
+        self.lines.clear();
+        self.dropped = 0;
+    }
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+    /// number of lines which were read but aren't kept anymore
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+    pub fn get(&self, idx: usize) -> Option<&str> {
+        self.lines.get(idx).map(|s| s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tail_lines_test {
+
+    use {
+        super::*,
+        std::io::Write,
+    };
+
+    fn lines(tl: &TailLines) -> Vec<&str> {
+        (0..tl.len()).filter_map(|i| tl.get(i)).collect()
+    }
+
+    #[test]
+    fn check_line_framing() {
+        let mut tl = TailLines::new(PathBuf::new(), 100);
+        assert!(!tl.push_bytes(b"incompl"));
+        assert!(tl.is_empty());
+        assert!(tl.push_bytes(b"ete\r\nsecond\n\nthi"));
+        assert_eq!(lines(&tl), vec!["incomplete", "second", ""]);
+        assert!(tl.finish());
+        assert!(!tl.finish());
+        assert_eq!(lines(&tl), vec!["incomplete", "second", "", "thi"]);
+    }
+
+    #[test]
+    fn check_max_lines() {
+        let mut tl = TailLines::new(PathBuf::new(), 2);
+        tl.push_bytes(b"a\nb\nc\nd\n");
+        assert_eq!(lines(&tl), vec!["c", "d"]);
+        assert_eq!(tl.dropped(), 2);
+    }
+
+    #[test]
+    fn check_file_tailing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.log");
+        let mut file = File::create(&path).unwrap();
+        let mut tl = TailLines::new(path.clone(), 100);
+        assert!(!tl.update().unwrap());
+        write!(file, "one\ntw").unwrap();
+        assert!(tl.update().unwrap());
+        assert_eq!(lines(&tl), vec!["one"]);
+        assert!(!tl.update().unwrap());
+        write!(file, "o\nthree\n").unwrap();
+        assert!(tl.update().unwrap());
+        assert_eq!(lines(&tl), vec!["one", "two", "three"]);
+        // the file is truncated then rewritten: we start again
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "new").unwrap();
+        assert!(tl.update().unwrap());
+        assert_eq!(lines(&tl), vec!["new"]);
+    }
+}
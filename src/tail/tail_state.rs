@@ -0,0 +1,308 @@
+use {
+    super::TailLines,
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, Screen, SPACE_FILLING, W},
+        errors::ProgramError,
+        skin::PanelSkin,
+        stage::Stage,
+        task_sync::Dam,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::{
+        io,
+        path::Path,
+        process::{Child, ExitStatus},
+        thread,
+        time::Duration,
+    },
+    tempfile::TempPath,
+    termimad::Area,
+};
+
+/// how many lines of the output we keep
+const MAX_LINES: usize = 10_000;
+
+/// delay between two checks of the output file
+const POLL_PERIOD: Duration = Duration::from_millis(50);
+
+/// an application state displaying the output of a command
+/// while it runs, the output being written in a temporary file
+/// which is followed like with `tail -f`.
+///
+/// It's usually the only state of its panel. The process is
+/// killed if it's still running when the state is dropped.
+pub struct TailState {
+    command: String, // the executed command, as displayed
+    child: Option<Child>, // None when the process is finished
+    exit_status: Option<ExitStatus>,
+    output: TempPath, // the file is removed on drop
+    lines: TailLines,
+    scroll: Option<usize>, // index of the top line, None when following the end
+    height: usize, // height of the area for the lines, set on display
+    dirty: bool, // true when background must be cleared
+    tree_options: TreeOptions,
+}
+
+impl TailState {
+    pub fn new(
+        command: String,
+        child: Child,
+        output: TempPath,
+        tree_options: TreeOptions,
+    ) -> Self {
+        let lines = TailLines::new(output.to_path_buf(), MAX_LINES);
+        Self {
+            command,
+            child: Some(child),
+            exit_status: None,
+            output,
+            lines,
+            scroll: None,
+            height: 0,
+            dirty: true,
+            tree_options,
+        }
+    }
+    /// build a state displaying, from its start, the output
+    /// of a finished process
+    pub fn of_finished(
+        command: String,
+        exit_status: ExitStatus,
+        output: TempPath,
+        tree_options: TreeOptions,
+    ) -> io::Result<Self> {
+        let mut lines = TailLines::new(output.to_path_buf(), MAX_LINES);
+        lines.update()?;
+        lines.finish();
+        Ok(Self {
+            command,
+            child: None,
+            exit_status: Some(exit_status),
+            output,
+            lines,
+            scroll: Some(0),
+            height: 0,
+            dirty: true,
+            tree_options,
+        })
+    }
+    fn top(&self) -> usize {
+        let max_top = self.lines.len().saturating_sub(self.height);
+        self.scroll.unwrap_or(max_top).min(max_top)
+    }
+    /// scroll by the given count of lines, following again the end
+    /// of the output when it's reached
+    fn scroll_by(&mut self, dy: i32) {
+        let max_top = self.lines.len().saturating_sub(self.height);
+        let top = (self.top() as i32 + dy).max(0) as usize;
+        self.scroll = if top >= max_top { None } else { Some(top) };
+    }
+    fn check_end(&mut self) {
+        if let Some(child) = &mut self.child {
+            match child.try_wait() {
+                Ok(Some(exit_status)) => {
+                    self.exit_status = Some(exit_status);
+                    self.child = None;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("error while waiting for tailed process: {:?}", e);
+                    self.child = None;
+                }
+            }
+            if self.child.is_none() {
+                // there may be a last write we haven't seen yet
+                if let Err(e) = self.lines.update() {
+                    warn!("error while reading tailed file: {:?}", e);
+                }
+                self.lines.finish();
+            }
+        }
+    }
+}
+
+impl Drop for TailState {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            debug!("killing tailed process");
+            if let Err(e) = child.kill() {
+                warn!("failed to kill tailed process: {:?}", e);
+            }
+            let _ = child.wait();
+        }
+    }
+}
+
+impl AppState for TailState {
+
+    fn get_pending_task(&self) -> Option<&'static str> {
+        if self.child.is_some() {
+            Some("tailing")
+        } else {
+            None
+        }
+    }
+
+    /// wait until there's something new in the file, the
+    /// process ends, or the user does something
+    fn do_pending_task(
+        &mut self,
+        _screen: Screen,
+        _con: &AppContext,
+        dam: &mut Dam,
+    ) {
+        while self.child.is_some() && !dam.has_event() {
+            match self.lines.update() {
+                Ok(true) => {
+                    return;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("error while reading tailed file: {:?}", e);
+                }
+            }
+            self.check_end();
+            thread::sleep(POLL_PERIOD);
+        }
+    }
+
+    fn selected_path(&self) -> &Path {
+        &self.output
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        Selection {
+            path: &self.output,
+            stype: SelectionType::File,
+            is_exe: false,
+            line: 0,
+        }
+    }
+
+    fn tree_options(&self) -> TreeOptions {
+        self.tree_options.clone()
+    }
+
+    fn with_new_options(
+        &mut self,
+        _screen: Screen,
+        change_options: &dyn Fn(&mut TreeOptions),
+        _in_new_panel: bool,
+        _con: &AppContext,
+    ) -> AppStateCmdResult {
+        change_options(&mut self.tree_options);
+        AppStateCmdResult::Keep
+    }
+
+    fn refresh(&mut self, _screen: Screen, _con: &AppContext) -> Command {
+        self.dirty = true;
+        Command::empty()
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        screen: Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _stage: &Stage,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height < 2 {
+            warn!("area too small for tail");
+            return Ok(());
+        }
+        if self.dirty {
+            panel_skin.styles.default.queue_bg(w)?;
+            screen.clear_area_to_right(w, &state_area)?;
+            self.dirty = false;
+        }
+        let styles = &panel_skin.styles;
+        w.queue(cursor::MoveTo(state_area.left, state_area.top))?;
+        let mut cw = CropWriter::new(w, state_area.width as usize);
+        cw.queue_str(&styles.default, &self.command)?;
+        cw.fill(&styles.default, &SPACE_FILLING)?;
+        self.height = state_area.height as usize - 1;
+        let top = self.top();
+        for y in 0..self.height {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + 1 + y as u16))?;
+            let mut cw = CropWriter::new(w, state_area.width as usize);
+            if let Some(line) = self.lines.get(top + y) {
+                cw.queue_str(&styles.default, line)?;
+            }
+            cw.fill(&styles.default, &SPACE_FILLING)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        match (&self.child, self.exit_status) {
+            (Some(_), _) => Status::from_message("Process running. *esc* to kill it and close"),
+            (None, Some(exit_status)) if exit_status.success() => {
+                Status::from_message("Process finished. *esc* to close")
+            }
+            (None, Some(exit_status)) => Status::from_error(format!(
+                "Process failed ({}). *esc* to close",
+                exit_status,
+            )),
+            (None, None) => Status::from_error("Process lost. *esc* to close"),
+        }
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        match internal_exec.internal {
+            Internal::back => Ok(AppStateCmdResult::PopState),
+            Internal::line_down => {
+                self.scroll_by(get_arg(input_invocation, internal_exec, 1));
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::line_up => {
+                self.scroll_by(-get_arg(input_invocation, internal_exec, 1));
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::page_down => {
+                self.scroll_by(self.height as i32);
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::page_up => {
+                self.scroll_by(-(self.height as i32));
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::select_first => {
+                self.scroll = Some(0);
+                Ok(AppStateCmdResult::Keep)
+            }
+            Internal::select_last => {
+                self.scroll = None;
+                Ok(AppStateCmdResult::Keep)
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            ),
+        }
+    }
+}
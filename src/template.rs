@@ -0,0 +1,99 @@
+//! creation of files from templates found in the `templates`
+//! directory of broot's configuration directory
+
+use std::{
+    ffi::OsStr,
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// the placeholder replaced with the name of the created file
+pub const NAME_PLACEHOLDER: &str = "{{NAME}}";
+
+/// return the directory where templates are looked for
+pub fn templates_dir() -> PathBuf {
+    crate::conf::dir().join("templates")
+}
+
+/// find the template with the given name: either a file with exactly
+/// this name, or the first one (alphabetically) having it as stem
+pub fn find_template(templates_dir: &Path, template: &str) -> Option<PathBuf> {
+    let exact = templates_dir.join(template);
+    if exact.is_file() {
+        return Some(exact);
+    }
+    let mut candidates: Vec<PathBuf> = fs::read_dir(templates_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| path.file_stem() == Some(OsStr::new(template)))
+        .collect();
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+/// replace the placeholders of the template content
+pub fn substitute(content: &str, name: &str) -> String {
+    content.replace(NAME_PLACEHOLDER, name)
+}
+
+/// create the `name` file in `dir` from the template, and
+/// return its path. Existing files aren't overwritten.
+pub fn create_from_template(
+    templates_dir: &Path,
+    template: &str,
+    dir: &Path,
+    name: &str,
+) -> io::Result<PathBuf> {
+    let template_path = find_template(templates_dir, template)
+        .ok_or_else(|| io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no template {:?} in {:?}", template, templates_dir),
+        ))?;
+    let content = fs::read_to_string(template_path)?;
+    let path = dir.join(name);
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    io::Write::write_all(&mut file, substitute(&content, name).as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod template_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        assert_eq!(
+            substitute("# {{NAME}}\nSee {{NAME}}, not {NAME}.", "notes.md"),
+            "# notes.md\nSee notes.md, not {NAME}.",
+        );
+        assert_eq!(substitute("no placeholder", "a"), "no placeholder");
+    }
+
+    #[test]
+    fn test_create_from_template() {
+        let templates = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(templates.path().join("rust.rs"), "// {{NAME}}\nfn main() {}\n").unwrap();
+        fs::write(templates.path().join("Makefile"), "all:\n").unwrap();
+        // the template is found by stem
+        let path = create_from_template(templates.path(), "rust", dir.path(), "main.rs").unwrap();
+        assert_eq!(path, dir.path().join("main.rs"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "// main.rs\nfn main() {}\n");
+        // or by exact name
+        create_from_template(templates.path(), "Makefile", dir.path(), "Makefile").unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("Makefile")).unwrap(), "all:\n");
+        // existing files aren't overwritten
+        assert!(create_from_template(templates.path(), "rust", dir.path(), "main.rs").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "// main.rs\nfn main() {}\n");
+        // unknown template
+        assert!(create_from_template(templates.path(), "python", dir.path(), "a.py").is_err());
+        assert!(!dir.path().join("a.py").exists());
+    }
+}
@@ -0,0 +1,9 @@
+//! The whole module is only available on unix now
+
+mod trash_dir;
+mod trash_state;
+
+pub use {
+    trash_dir::{TrashDir, TrashItem},
+    trash_state::TrashState,
+};
@@ -0,0 +1,339 @@
+use {
+    crate::path,
+    chrono::Local,
+    std::{
+        ffi::OsStr,
+        fs,
+        io,
+        os::unix::ffi::OsStrExt,
+        path::{Path, PathBuf},
+    },
+};
+
+const INFO_EXTENSION: &str = "trashinfo";
+
+/// a path which was moved to the trash, as described by
+/// its `.trashinfo` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashItem {
+    /// the name of the item in the `files` and `info`
+    /// directories of the trash
+    pub name: String,
+    /// where the path was before being trashed
+    pub original_path: PathBuf,
+    /// the deletion date, as written in the info file
+    /// (`YYYY-MM-DDThh:mm:ss`, in local time)
+    pub deletion_date: String,
+}
+
+/// a trash directory, as defined by the freedesktop.org specification,
+/// with a `files` directory holding the trashed paths and an `info`
+/// directory telling where they come from
+#[derive(Debug, Clone)]
+pub struct TrashDir {
+    root: PathBuf,
+}
+
+impl TrashDir {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+    /// the trash of the user: `$XDG_DATA_HOME/Trash`, which usually
+    /// is `~/.local/share/Trash`.
+    ///
+    /// There's none on macOS, whose trash isn't a freedesktop one.
+    pub fn home() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            return None;
+        }
+        std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+            .map(|data_dir| Self::new(data_dir.join("Trash")))
+    }
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    fn files_dir(&self) -> PathBuf {
+        self.root.join("files")
+    }
+    fn info_dir(&self) -> PathBuf {
+        self.root.join("info")
+    }
+    fn info_path(&self, name: &str) -> PathBuf {
+        self.info_dir().join(format!("{}.{}", name, INFO_EXTENSION))
+    }
+    /// return the path of the trashed file or directory
+    pub fn item_path(&self, item: &TrashItem) -> PathBuf {
+        self.files_dir().join(&item.name)
+    }
+    /// move a file or directory to the trash, returning the name
+    /// it was given there.
+    ///
+    /// When the path isn't on the filesystem of the trash, it's
+    /// copied then removed.
+    pub fn trash(&self, path: &Path) -> io::Result<String> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(path)
+        };
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "nothing to trash"))?
+            .to_string_lossy()
+            .to_string();
+        fs::create_dir_all(self.files_dir())?;
+        fs::create_dir_all(self.info_dir())?;
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            url_encode(path.as_os_str()),
+            Local::now().format("%Y-%m-%dT%H:%M:%S"),
+        );
+        // the info file is created first, and exclusively, so that
+        // the name is reserved even when another program trashes
+        // a path with the same name at the same time
+        let mut counter = 0;
+        let name = loop {
+            let name = if counter == 0 {
+                file_name.clone()
+            } else {
+                format!("{}.{}", file_name, counter)
+            };
+            if fs::symlink_metadata(self.files_dir().join(&name)).is_err() {
+                let created = fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(self.info_path(&name));
+                match created {
+                    Ok(_) => break name,
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            counter += 1;
+        };
+        let info_path = self.info_path(&name);
+        let moved = fs::write(&info_path, info)
+            .and_then(|_| move_path(&path, &self.files_dir().join(&name)));
+        if let Err(e) = moved {
+            let _ = fs::remove_file(&info_path);
+            return Err(e);
+        }
+        Ok(name)
+    }
+    /// list the trashed items, most recently deleted first.
+    ///
+    /// Items whose info file is invalid, or whose trashed path
+    /// doesn't exist anymore, are ignored.
+    pub fn list(&self) -> io::Result<Vec<TrashItem>> {
+        let entries = match fs::read_dir(self.info_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new()); // nothing was ever trashed
+            }
+            Err(e) => return Err(e),
+        };
+        let mut items: Vec<TrashItem> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let path = e.path();
+                if path.extension() != Some(OsStr::new(INFO_EXTENSION)) {
+                    return None;
+                }
+                let name = path.file_stem()?.to_string_lossy().to_string();
+                let info = fs::read_to_string(&path).ok()?;
+                let item = parse_info(name, &info)?;
+                if fs::symlink_metadata(self.item_path(&item)).is_err() {
+                    debug!("trashed file of {:?} not found", path);
+                    return None;
+                }
+                Some(item)
+            })
+            .collect();
+        items.sort_by(|a, b| {
+            b.deletion_date.cmp(&a.deletion_date).then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(items)
+    }
+    /// move back a trashed item to its original place, which
+    /// must be free
+    pub fn restore(&self, item: &TrashItem) -> io::Result<()> {
+        if fs::symlink_metadata(&item.original_path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{:?} already exists", item.original_path),
+            ));
+        }
+        if let Some(parent) = item.original_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        move_path(&self.item_path(item), &item.original_path)?;
+        fs::remove_file(self.info_path(&item.name))
+    }
+    /// definitively remove a trashed item
+    pub fn purge(&self, item: &TrashItem) -> io::Result<()> {
+        remove(&self.item_path(item))?;
+        fs::remove_file(self.info_path(&item.name))
+    }
+}
+
+/// remove a file or a directory with all its content
+fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// move a file or directory to a free path, which may be
+/// on another filesystem
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => copy_and_remove(from, to),
+        res => res,
+    }
+}
+
+/// move a file or directory by copying it then removing the
+/// source, which is kept when the copy fails
+fn copy_and_remove(from: &Path, to: &Path) -> io::Result<()> {
+    if let Err(e) = path::copy_recursively(from, to) {
+        if fs::symlink_metadata(to).is_ok() {
+            let _ = remove(to); // the incomplete copy
+        }
+        return Err(e);
+    }
+    remove(from)
+}
+
+/// read the content of a `.trashinfo` file
+fn parse_info(name: String, info: &str) -> Option<TrashItem> {
+    let mut lines = info.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next()? != "[Trash Info]" {
+        return None;
+    }
+    let mut original_path = None;
+    let mut deletion_date = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("Path=") {
+            original_path = Some(PathBuf::from(OsStr::from_bytes(&url_decode(value)?)));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deletion_date = Some(value.to_string());
+        } else if line.starts_with('[') {
+            break; // another group
+        }
+    }
+    Some(TrashItem {
+        name,
+        original_path: original_path?,
+        deletion_date: deletion_date.unwrap_or_default(),
+    })
+}
+
+/// escape a path the way the specification requires
+/// for the `Path` key (as in URLs, but keeping the `/`)
+fn url_encode(s: &OsStr) -> String {
+    let mut encoded = String::new();
+    for &b in s.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"/-_.!~*'()".contains(&b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    encoded
+}
+
+/// unescape the value of a `Path` key, return None when
+/// it's badly escaped
+fn url_decode(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod trash_dir_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_url_encoding() {
+        let path = OsStr::new("/home/dys/mes notes/Été 100%.md");
+        let encoded = url_encode(path);
+        assert_eq!(encoded, "/home/dys/mes%20notes/%C3%89t%C3%A9%20100%25.md");
+        assert_eq!(url_decode(&encoded).unwrap(), path.as_bytes());
+        assert_eq!(url_decode("/bad%2"), None);
+    }
+
+    #[test]
+    fn test_trash_restore_purge() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashDir::new(dir.path().join("Trash"));
+        assert!(trash.list().unwrap().is_empty());
+        let work = dir.path().join("work");
+        fs::create_dir_all(work.join("sub")).unwrap();
+        fs::write(work.join("notes.md"), "first").unwrap();
+        fs::write(work.join("sub/notes.md"), "second").unwrap();
+        assert_eq!(trash.trash(&work.join("notes.md")).unwrap(), "notes.md");
+        // another path with the same name gets another name in the trash
+        assert_eq!(trash.trash(&work.join("sub/notes.md")).unwrap(), "notes.md.1");
+        assert_eq!(trash.trash(&work.join("sub")).unwrap(), "sub");
+        assert!(!work.join("notes.md").exists());
+        assert!(!work.join("sub").exists());
+        let items = trash.list().unwrap();
+        assert_eq!(items.len(), 3);
+        let item = items.iter().find(|item| item.name == "notes.md.1").unwrap();
+        assert_eq!(item.original_path, work.join("sub/notes.md"));
+        assert_eq!(item.deletion_date.len(), 19);
+        // the original directory is recreated on restoration
+        trash.restore(item).unwrap();
+        assert_eq!(fs::read_to_string(work.join("sub/notes.md")).unwrap(), "second");
+        // the other item can't be restored over the restored one
+        let item = items.iter().find(|item| item.name == "sub").unwrap();
+        assert!(trash.restore(item).is_err());
+        trash.purge(item).unwrap();
+        let items = trash.list().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].original_path, work.join("notes.md"));
+        trash.restore(&items[0]).unwrap();
+        assert_eq!(fs::read_to_string(work.join("notes.md")).unwrap(), "first");
+        assert!(trash.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_copy_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        fs::create_dir_all(work.join("sub")).unwrap();
+        fs::write(work.join("sub/notes.md"), "notes").unwrap();
+        std::os::unix::fs::symlink("sub/notes.md", work.join("link")).unwrap();
+        let dest = dir.path().join("files/work");
+        fs::create_dir(dir.path().join("files")).unwrap();
+        copy_and_remove(&work, &dest).unwrap();
+        assert!(!work.exists());
+        assert_eq!(fs::read_to_string(dest.join("sub/notes.md")).unwrap(), "notes");
+        assert_eq!(fs::read_link(dest.join("link")).unwrap(), Path::new("sub/notes.md"));
+        // when the copy fails, the source is kept and the
+        // incomplete copy removed
+        let err = copy_and_remove(&dest, &dir.path().join("missing/work"));
+        assert!(err.is_err());
+        assert!(dest.join("sub/notes.md").exists());
+    }
+}
@@ -0,0 +1,264 @@
+use {
+    super::*,
+    crate::{
+        app::*,
+        command::{Command, TriggerType},
+        display::{CropWriter, Screen, SPACE_FILLING, W},
+        errors::ProgramError,
+        pattern::*,
+        skin::PanelSkin,
+        stage::Stage,
+        tree::TreeOptions,
+        verb::*,
+    },
+    crossterm::{
+        cursor,
+        QueueableCommand,
+    },
+    std::path::{Path, PathBuf},
+    termimad::Area,
+};
+
+/// an application state listing the items of the trash, which
+/// may be previewed, restored or purged
+pub struct TrashState {
+    trash: TrashDir,
+    items: Vec<TrashItem>,
+    item_paths: Vec<PathBuf>, // paths of the items in the trash
+    pattern: Option<Pattern>,
+    list: ListSelection,
+    tree_options: TreeOptions,
+}
+
+impl TrashState {
+    pub fn new(
+        trash: TrashDir,
+        tree_options: TreeOptions,
+    ) -> Result<TrashState, ProgramError> {
+        let mut state = TrashState {
+            trash,
+            items: Vec::new(),
+            item_paths: Vec::new(),
+            pattern: None,
+            list: ListSelection::default(),
+            tree_options,
+        };
+        state.reload()?;
+        Ok(state)
+    }
+    /// read again the trash content, keeping the current filter
+    fn reload(&mut self) -> Result<(), ProgramError> {
+        self.items = self.trash.list()?;
+        self.item_paths = self.items.iter().map(|item| self.trash.item_path(item)).collect();
+        let filtered = self.pattern.as_ref().map(|pattern| {
+            self.items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| {
+                    pattern.score_of_string(&item.original_path.to_string_lossy()).is_some()
+                })
+                .map(|(idx, _)| idx)
+                .collect()
+        });
+        self.list.set_items(self.items.len(), filtered);
+        Ok(())
+    }
+    /// restore or purge the selected item
+    fn act_on_selection(
+        &mut self,
+        internal: Internal,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let item = match self.list.selected_item_idx() {
+            Some(idx) => self.items[idx].clone(),
+            None => {
+                return Ok(AppStateCmdResult::DisplayError("the trash is empty".to_string()));
+            }
+        };
+        let done = if internal == Internal::restore {
+            self.trash.restore(&item)
+        } else {
+            self.trash.purge(&item)
+        };
+        Ok(match done {
+            Ok(()) => {
+                if internal == Internal::restore {
+                    crate::journal::record(crate::journal::Operation::Restore {
+                        path: item.original_path,
+                        name: item.name,
+                    });
+                }
+                // the refresh reloads the trash content
+                AppStateCmdResult::RefreshState { clear_cache: true }
+            }
+            Err(e) => AppStateCmdResult::DisplayError(format!(
+                "{} failed: {}",
+                internal.name(),
+                e,
+            )),
+        })
+    }
+}
+
+impl AppState for TrashState {
+
+    fn selected_path(&self) -> &Path {
+        match self.list.selected_item_idx() {
+            Some(idx) => &self.item_paths[idx],
+            None => self.trash.root(),
+        }
+    }
+
+    fn selection(&self) -> Selection<'_> {
+        let path = self.selected_path();
+        Selection {
+            path,
+            stype: if path.is_dir() { SelectionType::Directory } else { SelectionType::File },
+            is_exe: false,
+            line: 0,
+        }
+    }
+
+    fn tree_options(&self) -> TreeOptions {
+        self.tree_options.clone()
+    }
+
+    fn with_new_options(
+        &mut self,
+        _screen: Screen,
+        change_options: &dyn Fn(&mut TreeOptions),
+        _in_new_panel: bool,
+        _con: &AppContext,
+    ) -> AppStateCmdResult {
+        change_options(&mut self.tree_options);
+        AppStateCmdResult::Keep
+    }
+
+    fn refresh(&mut self, _screen: Screen, _con: &AppContext) -> Command {
+        if let Err(e) = self.reload() {
+            warn!("error while reading the trash: {}", e);
+        }
+        Command::empty()
+    }
+
+    fn on_pattern(
+        &mut self,
+        pattern: InputPattern,
+        _con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.pattern = if pattern.is_none() {
+            None
+        } else {
+            Some(pattern.pattern)
+        };
+        self.list.reset();
+        self.reload()?;
+        Ok(AppStateCmdResult::Keep)
+    }
+
+    fn match_count(&self) -> usize {
+        self.list.count()
+    }
+
+    fn display(
+        &mut self,
+        w: &mut W,
+        _screen: Screen,
+        state_area: Area,
+        panel_skin: &PanelSkin,
+        _stage: &Stage,
+        _con: &AppContext,
+    ) -> Result<(), ProgramError> {
+        if state_area.height < 2 {
+            warn!("area too small for trash");
+            return Ok(());
+        }
+        let styles = &panel_skin.styles;
+        w.queue(cursor::MoveTo(state_area.left, state_area.top))?;
+        let mut cw = CropWriter::new(w, state_area.width as usize);
+        cw.queue_g_string(
+            &styles.default,
+            format!("trash: {}", self.trash.root().to_string_lossy()),
+        )?;
+        cw.fill(&styles.default, &SPACE_FILLING)?;
+        self.list.set_page_height(state_area.height as usize - 1);
+        for y in 0..self.list.page_height() {
+            w.queue(cursor::MoveTo(state_area.left, state_area.top + 1 + y as u16))?;
+            let mut cw = CropWriter::new(w, state_area.width as usize);
+            let idx = self.list.idx_at(y);
+            let selected = idx == self.list.selection_idx();
+            let bg_style = if selected { &styles.selected_line } else { &styles.default };
+            if let Some(item_idx) = self.list.item_idx(idx) {
+                let item = &self.items[item_idx];
+                let mut date_style = styles.dates.clone();
+                let mut path_style = if self.item_paths[item_idx].is_dir() {
+                    styles.directory.clone()
+                } else {
+                    styles.file.clone()
+                };
+                if selected {
+                    if let Some(c) = styles.selected_line.get_bg() {
+                        date_style.set_bg(c);
+                        path_style.set_bg(c);
+                    }
+                }
+                // the seconds aren't displayed
+                let date = item.deletion_date.replacen('T', " ", 1);
+                cw.queue_g_string(&date_style, format!("{:16.16}", date))?;
+                cw.queue_char(bg_style, ' ')?;
+                cw.queue_str(&path_style, &item.original_path.to_string_lossy())?;
+            }
+            cw.fill(bg_style, &SPACE_FILLING)?;
+        }
+        Ok(())
+    }
+
+    fn no_verb_status(
+        &self,
+        _has_previous_state: bool,
+        _con: &AppContext,
+    ) -> Status {
+        if self.items.is_empty() {
+            Status::from_message("The trash is empty. Hit *esc* to go back")
+        } else {
+            Status::from_message(
+                "Type `:restore` to restore the selected item, or `:purge` to remove it for good",
+            )
+        }
+    }
+
+    fn on_internal(
+        &mut self,
+        w: &mut W,
+        internal_exec: &InternalExecution,
+        input_invocation: Option<&VerbInvocation>,
+        trigger_type: TriggerType,
+        cc: &CmdContext,
+        screen: Screen,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        if let Some(res) = self.list.on_internal(internal_exec, input_invocation) {
+            return Ok(res);
+        }
+        Ok(match internal_exec.internal {
+            Internal::back => {
+                if self.pattern.is_some() {
+                    self.pattern = None;
+                    self.reload()?;
+                    AppStateCmdResult::Keep
+                } else {
+                    AppStateCmdResult::PopState
+                }
+            }
+            Internal::restore | Internal::purge => {
+                self.act_on_selection(internal_exec.internal)?
+            }
+            _ => self.on_internal_generic(
+                w,
+                internal_exec,
+                input_invocation,
+                trigger_type,
+                cc,
+                screen,
+            )?,
+        })
+    }
+}
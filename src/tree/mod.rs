@@ -1,4 +1,6 @@
 
+mod modified_range;
+mod path_set;
 mod sort;
 mod special_path;
 mod tree;
@@ -7,10 +9,12 @@ mod tree_line_type;
 mod tree_options;
 
 pub use {
+    modified_range::{parse_duration, ModifiedRange},
+    path_set::PathSet,
     sort::Sort,
     special_path::*,
     tree::Tree,
     tree_line::TreeLine,
     tree_line_type::TreeLineType,
-    tree_options::TreeOptions,
+    tree_options::{adjusted_depth, TreeOptions},
 };
@@ -0,0 +1,97 @@
+use std::time::{Duration, SystemTime};
+
+/// a range of modification times, used to filter the tree
+/// on recently modified entries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModifiedRange {
+    pub from: SystemTime,
+    pub to: Option<SystemTime>, // None when there's no upper bound
+}
+
+impl ModifiedRange {
+    /// parse either a duration (e.g. `2h`, meaning modified during
+    /// the last 2 hours) or a range of ages (e.g. `7d..2d`, meaning
+    /// modified between 7 and 2 days ago)
+    pub fn parse(arg: &str, now: SystemTime) -> Result<Self, String> {
+        let arg = arg.trim();
+        let ago = |s: &str| {
+            parse_duration(s).map(|d| now.checked_sub(d).unwrap_or(SystemTime::UNIX_EPOCH))
+        };
+        if let Some((oldest, newest)) = arg.split_once("..") {
+            let (from, to) = (ago(oldest)?, ago(newest)?);
+            if to < from {
+                return Err(format!("{:?} is an empty range: the oldest age comes first", arg));
+            }
+            Ok(Self { from, to: Some(to) })
+        } else {
+            Ok(Self { from: ago(arg)?, to: None })
+        }
+    }
+    pub fn contains(&self, mtime: SystemTime) -> bool {
+        mtime >= self.from && self.to.is_none_or(|to| mtime <= to)
+    }
+}
+
+/// parse a duration made of a number and a unit among
+/// `s`, `m`, `h`, `d`, and `w` (e.g. `90m` or `3d`)
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let err = || format!("{:?} isn't a valid duration (expected e.g. 30m, 2h or 3d)", s);
+    let unit_idx = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(err)?;
+    let count: u64 = s[..unit_idx].parse().map_err(|_| err())?;
+    let unit_secs = match &s[unit_idx..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(err());
+        }
+    };
+    Ok(Duration::from_secs(count.saturating_mul(unit_secs)))
+}
+
+#[cfg(test)]
+mod modified_range_tests {
+
+    use super::*;
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * HOUR)));
+        assert_eq!(parse_duration("3d"), Ok(Duration::from_secs(3 * DAY)));
+        assert_eq!(parse_duration("1w"), Ok(Duration::from_secs(7 * DAY)));
+        assert!(parse_duration("3").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration("-3d").is_err());
+        assert!(parse_duration("2h30m").is_err());
+    }
+
+    #[test]
+    fn test_range_contains() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000 * DAY);
+        let ago = |secs: u64| now - Duration::from_secs(secs);
+        let last_hours = ModifiedRange::parse("2h", now).unwrap();
+        assert!(last_hours.contains(now));
+        assert!(last_hours.contains(ago(HOUR)));
+        assert!(last_hours.contains(ago(2 * HOUR)));
+        assert!(!last_hours.contains(ago(3 * HOUR)));
+        let range = ModifiedRange::parse("7d..2d", now).unwrap();
+        assert!(!range.contains(now));
+        assert!(!range.contains(ago(DAY)));
+        assert!(range.contains(ago(3 * DAY)));
+        assert!(range.contains(ago(7 * DAY)));
+        assert!(!range.contains(ago(8 * DAY)));
+        assert!(ModifiedRange::parse("2d..7d", now).is_err());
+        assert!(ModifiedRange::parse("2x", now).is_err());
+        // a duration longer than the epoch doesn't fail
+        assert!(ModifiedRange::parse("100000w", now).unwrap().contains(ago(999 * DAY)));
+    }
+}
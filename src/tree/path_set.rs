@@ -0,0 +1,69 @@
+use {
+    crate::path,
+    fnv::FnvHashSet,
+    std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
+};
+
+/// a set of paths, usually listed by an external command, used
+/// to filter the tree on them
+#[derive(Debug, Clone, Default)]
+pub struct PathSet {
+    paths: Arc<FnvHashSet<PathBuf>>,
+}
+
+impl PathSet {
+    /// read the output of a command listing one path per line.
+    ///
+    /// Relative paths are relative to `base`, which should be the
+    /// working directory of the command. Empty lines are ignored.
+    pub fn from_output(output: &str, base: &Path) -> Self {
+        let paths = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| path::normalize_path(base.join(line)))
+            .map(|path| match path.to_str().and_then(|s| s.strip_suffix('/')) {
+                // directories are often listed with a trailing slash
+                Some(s) if s.len() > 1 => PathBuf::from(s),
+                _ => path,
+            })
+            .collect();
+        Self {
+            paths: Arc::new(paths),
+        }
+    }
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains(path)
+    }
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod path_set_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_from_output() {
+        let base = Path::new("/home/dys/dev");
+        let output = "src/main.rs\n./src/app/\n\n  README.md  \r\n/etc/hosts\nsrc/../Cargo.toml\nsrc/main.rs\n";
+        let set = PathSet::from_output(output, base);
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(Path::new("/home/dys/dev/src/main.rs")));
+        assert!(set.contains(Path::new("/home/dys/dev/src/app")));
+        assert!(set.contains(Path::new("/home/dys/dev/README.md")));
+        assert!(set.contains(Path::new("/etc/hosts")));
+        assert!(set.contains(Path::new("/home/dys/dev/Cargo.toml")));
+        // parents of listed paths aren't in the set
+        assert!(!set.contains(Path::new("/home/dys/dev/src")));
+        assert!(PathSet::from_output("\n \n", base).is_empty());
+    }
+}
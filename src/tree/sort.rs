@@ -15,4 +15,13 @@ impl Sort {
     pub fn is_some(self) -> bool {
         !matches!(self, Sort::None)
     }
+    /// the name of the sort key, as displayed in the status
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            Sort::None => None,
+            Sort::Count => Some("count"),
+            Sort::Date => Some("date"),
+            Sort::Size => Some("size"),
+        }
+    }
 }
@@ -6,6 +6,7 @@ use {
         errors,
         file_sum::FileSum,
         git::TreeGitStatus,
+        provider::ColumnProvider,
         task_sync::ComputationResult,
         task_sync::Dam,
         tree_build::TreeBuilder,
@@ -278,6 +279,61 @@ impl Tree {
         }
         false
     }
+    /// select the first (or last) listed entry of the current directory,
+    /// which is the root when it's selected, else the selection's parent.
+    /// Return false when this directory has no listed entry.
+    pub fn try_select_in_dir(&mut self, last: bool, page_height: i32) -> bool {
+        let dir = if self.selection == 0 {
+            self.lines[0].path.as_path()
+        } else {
+            match self.lines[self.selection].path.parent() {
+                Some(dir) => dir,
+                None => return false,
+            }
+        };
+        let mut entries = self.lines.iter()
+            .enumerate()
+            .filter(|(_, line)| line.is_selectable() && line.path.parent() == Some(dir))
+            .map(|(idx, _)| idx);
+        let target = if last { entries.next_back() } else { entries.next() };
+        if let Some(idx) = target {
+            self.selection = idx;
+            self.make_selection_visible(page_height);
+            true
+        } else {
+            false
+        }
+    }
+    /// select the largest (or smallest) file among the listed direct
+    /// children of the root. Directories aren't considered, as their
+    /// size would have to be computed. Among files of the same size,
+    /// the first one by name is chosen.
+    pub fn try_select_by_size(&mut self, largest: bool, page_height: i32) -> bool {
+        let best = self.lines.iter()
+            .enumerate()
+            .filter(|(_, line)| line.depth == 1 && line.is_file())
+            .min_by(|(_, a), (_, b)| {
+                let (a_len, b_len) = (a.metadata.len(), b.metadata.len());
+                let by_size = if largest { b_len.cmp(&a_len) } else { a_len.cmp(&b_len) };
+                by_size.then_with(|| a.name.cmp(&b.name))
+            })
+            .map(|(idx, _)| idx);
+        if let Some(idx) = best {
+            self.selection = idx;
+            self.make_selection_visible(page_height);
+            true
+        } else {
+            false
+        }
+    }
+    /// return the number of lines directly matching the pattern,
+    /// 0 when there's no pattern
+    pub fn match_count(&self) -> usize {
+        if self.options.pattern.is_none() {
+            return 0;
+        }
+        self.lines.iter().filter(|line| line.direct_match).count()
+    }
     pub fn try_select_next_match(&mut self) -> bool {
         for di in 0..self.lines.len() {
             let idx = (self.selection + di + 1) % self.lines.len();
@@ -348,6 +404,31 @@ impl Tree {
         self.git_status.is_not_computed()
     }
 
+    /// tell whether the custom column is displayed with lines
+    /// not yet annotated (the root line has no column)
+    pub fn is_missing_annotations(&self) -> bool {
+        self.options.show_custom_column
+            && self.lines.iter().skip(1).any(|line| line.annotation.is_none())
+    }
+
+    /// ask the column provider the annotations of the lines, unless
+    /// the dam gets an event before they're computed
+    pub fn fetch_annotations(&mut self, provider: &ColumnProvider, dam: &Dam) {
+        let annotations = {
+            let paths: Vec<&Path> = self.lines.iter()
+                .skip(1)
+                .map(|line| line.path.as_path())
+                .collect();
+            match provider.annotations(&self.lines[0].path, &paths, dam) {
+                Some(annotations) => annotations,
+                None => return,
+            }
+        };
+        for (line, annotation) in self.lines.iter_mut().skip(1).zip(annotations) {
+            line.annotation = Some(annotation);
+        }
+    }
+
     /// fetch the file_sums of regular files (thus avoiding the
     /// long computation which is needed for directories)
     pub fn fetch_regular_file_sums(&mut self) {
@@ -447,3 +528,85 @@ impl Tree {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tree_test {
+
+    use {
+        super::*,
+        std::fs,
+    };
+
+    fn build_tree(root: &Path) -> Tree {
+        let con = AppContext::for_test(root);
+        TreeBuilder::from(root.to_path_buf(), TreeOptions::default(), 100, &con)
+            .unwrap()
+            .build(true, &Dam::unlimited())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_select_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (name, size) in &[("a.txt", 3), ("b.txt", 10), ("c.txt", 10), ("d.txt", 1), ("e.txt", 1)] {
+            fs::write(root.join(name), vec![b'x'; *size]).unwrap();
+        }
+        // directories are skipped, whatever their content
+        fs::create_dir(root.join("big")).unwrap();
+        fs::write(root.join("big/huge.txt"), vec![b'x'; 1000]).unwrap();
+        let mut tree = build_tree(root);
+        assert!(tree.try_select_by_size(true, 100));
+        assert_eq!(tree.selected_line().name, "b.txt");
+        assert!(tree.try_select_by_size(false, 100));
+        assert_eq!(tree.selected_line().name, "d.txt");
+    }
+
+    #[test]
+    fn test_select_by_size_without_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let mut tree = build_tree(dir.path());
+        assert!(!tree.try_select_by_size(true, 100));
+        assert_eq!(tree.selection, 0);
+    }
+
+    #[test]
+    fn test_select_in_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("b")).unwrap();
+        for name in &["a.txt", "b/x.txt", "b/y.txt", "b/z.txt", "c.txt"] {
+            fs::write(root.join(name), "").unwrap();
+        }
+        let mut tree = build_tree(root);
+        // from the root, the entries of the root are considered
+        assert!(tree.try_select_in_dir(true, 100));
+        assert_eq!(tree.selected_line().name, "c.txt");
+        assert!(tree.try_select_in_dir(false, 100));
+        assert_eq!(tree.selected_line().name, "a.txt");
+        // from a file of the b directory, only its siblings are considered
+        assert!(tree.try_select_path(&root.join("b/y.txt")));
+        assert!(tree.try_select_in_dir(true, 100));
+        assert_eq!(tree.selected_line().name, "z.txt");
+        assert!(tree.try_select_in_dir(false, 100));
+        assert_eq!(tree.selected_line().name, "x.txt");
+    }
+
+    #[test]
+    fn test_select_in_empty_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut tree = build_tree(dir.path());
+        assert!(!tree.try_select_in_dir(false, 100));
+        assert!(!tree.try_select_in_dir(true, 100));
+        assert_eq!(tree.selection, 0);
+    }
+
+    #[test]
+    fn test_no_match_count_without_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let tree = build_tree(dir.path());
+        assert_eq!(tree.match_count(), 0);
+    }
+}
@@ -36,6 +36,7 @@ pub struct TreeLine {
     pub sum: Option<FileSum>, // None when not measured
     pub metadata: fs::Metadata,
     pub git_status: Option<LineGitStatus>,
+    pub annotation: Option<String>, // None when not asked to the column provider
 }
 
 impl TreeLine {
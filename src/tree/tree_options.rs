@@ -1,5 +1,5 @@
 use {
-    super::Sort,
+    super::{ModifiedRange, PathSet, Sort},
     crate::{
         conf::Conf,
         display::{Cols, DEFAULT_COLS},
@@ -20,15 +20,21 @@ pub struct TreeOptions {
     pub show_dates: bool,  // whether to show the last modified date
     pub show_sizes: bool,  // whether to show sizes of files and dirs
     pub show_git_file_info: bool,
+    pub show_custom_column: bool, // whether to show the annotations of the column provider
     pub show_root_fs: bool, // show information relative to the fs of the root
     pub trim_root: bool,    // whether to cut out direct children of root
     pub show_permissions: bool, // show classic rwx unix permissions (only on unix)
+    pub octal_permissions: bool, // show the permission bits in octal (e.g. 755) instead of rwx
+    pub flat_view: bool, // list relative paths instead of drawing the tree
     pub respect_git_ignore: bool, // hide files as requested by .gitignore ?
     pub filter_by_git_status: bool, // only show files whose git status is not nul
     pub pattern: InputPattern, // an optional filtering/scoring pattern
     pub date_time_format: &'static str,
     pub sort: Sort,
     pub cols_order: Cols, // order of columns
+    pub max_depth: Option<u16>, // the deepest displayed level, None when unlimited
+    pub modified_range: Option<ModifiedRange>, // only show files modified in this range
+    pub path_set: Option<PathSet>, // only show these paths (and their parents)
 }
 
 impl TreeOptions {
@@ -42,15 +48,21 @@ impl TreeOptions {
             show_dates: self.show_dates,
             show_sizes: self.show_sizes,
             show_permissions: self.show_permissions,
+            octal_permissions: self.octal_permissions,
+            flat_view: self.flat_view,
             respect_git_ignore: self.respect_git_ignore,
             filter_by_git_status: self.filter_by_git_status,
             show_git_file_info: self.show_git_file_info,
+            show_custom_column: self.show_custom_column,
             show_root_fs: self.show_root_fs,
             trim_root: self.trim_root,
             pattern: InputPattern::none(),
             date_time_format: self.date_time_format,
             sort: self.sort,
             cols_order: self.cols_order,
+            max_depth: self.max_depth,
+            modified_range: self.modified_range,
+            path_set: self.path_set.clone(),
         }
     }
     /// counts must be computed, either for sorting or just for display
@@ -68,6 +80,29 @@ impl TreeOptions {
     pub fn needs_sum(&self) -> bool {
         self.needs_counts() || self.needs_dates() || self.needs_sizes()
     }
+    /// show or hide the size column (and the filesystem info on top,
+    /// which goes with it)
+    pub fn toggle_sizes(&mut self) {
+        self.show_sizes ^= true;
+        self.show_root_fs = self.show_sizes;
+    }
+    /// show or hide the permission columns
+    pub fn toggle_permissions(&mut self) {
+        self.show_permissions ^= true;
+    }
+    /// switch the permission bits between the rwx and octal
+    /// notations, showing the permission columns if they're hidden
+    pub fn toggle_permission_style(&mut self) {
+        if self.show_permissions {
+            self.octal_permissions ^= true;
+        } else {
+            self.show_permissions = true;
+        }
+    }
+    /// whether the lines at this depth may have children displayed
+    pub fn can_go_deeper(&self, depth: u16) -> bool {
+        self.max_depth.is_none_or(|max_depth| depth < max_depth)
+    }
     /// this method does not exist, you saw nothing
     /// (at least don't call it other than with the config, once)
     pub fn set_date_time_format(&mut self, format: String) {
@@ -84,6 +119,7 @@ impl TreeOptions {
         if let Some(b) = &config.show_selection_mark {
             self.show_selection_mark = *b;
         }
+        self.show_custom_column = config.custom_column.is_some();
         if let Some(format) = &config.date_time_format {
             self.set_date_time_format(format.clone());
         }
@@ -180,15 +216,62 @@ impl Default for TreeOptions {
             show_dates: false,
             show_sizes: false,
             show_git_file_info: false,
+            show_custom_column: false,
             show_root_fs: false,
             trim_root: false,
             show_permissions: false,
+            octal_permissions: false,
+            flat_view: false,
             respect_git_ignore: true,
             filter_by_git_status: false,
             pattern: InputPattern::none(),
             date_time_format: "%Y/%m/%d %R",
             sort: Sort::None,
             cols_order: DEFAULT_COLS,
+            max_depth: None,
+            modified_range: None,
+            path_set: None,
         }
     }
 }
+
+/// compute a new max depth from the argument of `:depth`, which
+/// is either a depth (e.g. `3`) or a change (e.g. `+1` or `-2`) of
+/// the current one. The depth can't be less than 1.
+pub fn adjusted_depth(current: u16, arg: &str) -> Result<u16, String> {
+    let arg = arg.trim();
+    let parse = |s: &str| {
+        s.parse::<u16>()
+            .map_err(|_| format!("{:?} isn't a valid depth", arg))
+    };
+    let depth = if let Some(incr) = arg.strip_prefix('+') {
+        current.saturating_add(parse(incr)?)
+    } else if let Some(decr) = arg.strip_prefix('-') {
+        current.saturating_sub(parse(decr)?)
+    } else {
+        parse(arg)?
+    };
+    Ok(depth.max(1))
+}
+
+#[cfg(test)]
+mod tree_options_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_adjusted_depth() {
+        assert_eq!(adjusted_depth(2, "4"), Ok(4));
+        assert_eq!(adjusted_depth(2, "+1"), Ok(3));
+        assert_eq!(adjusted_depth(2, "+3"), Ok(5));
+        assert_eq!(adjusted_depth(3, "-1"), Ok(2));
+        // the depth is clamped at 1
+        assert_eq!(adjusted_depth(2, "0"), Ok(1));
+        assert_eq!(adjusted_depth(1, "-1"), Ok(1));
+        assert_eq!(adjusted_depth(2, "-5"), Ok(1));
+        assert_eq!(adjusted_depth(u16::MAX, "+1"), Ok(u16::MAX));
+        assert!(adjusted_depth(2, "deep").is_err());
+        assert!(adjusted_depth(2, "+").is_err());
+        assert!(adjusted_depth(2, "--1").is_err());
+    }
+}
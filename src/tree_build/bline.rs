@@ -123,6 +123,7 @@ impl BLine {
             sum: None,
             metadata,
             git_status: None,
+            annotation: None,
         })
     }
 }
@@ -155,6 +155,21 @@ impl<'c> TreeBuilder<'c> {
                 }
             }
         }
+        if has_match {
+            if let Some(range) = &self.options.modified_range {
+                let modified = e.metadata().and_then(|m| m.modified());
+                if !modified.is_ok_and(|mtime| range.contains(mtime)) {
+                    has_match = false;
+                }
+            }
+        }
+        if has_match {
+            if let Some(path_set) = &self.options.path_set {
+                if !path_set.contains(&path) {
+                    has_match = false;
+                }
+            }
+        }
         if file_type.is_file() || file_type.is_symlink() {
             if !has_match {
                 return None;
@@ -288,7 +303,7 @@ impl<'c> TreeBuilder<'c> {
                     if child.has_match {
                         nb_lines_ok += 1;
                     }
-                    if child.can_enter() {
+                    if child.can_enter() && self.options.can_go_deeper(child.depth) {
                         next_level_dirs.push(child_id);
                     }
                     out_blines.push(child_id);
@@ -47,6 +47,24 @@ fn external(
     ).unwrap()
 }
 
+/// an external verb whose output is displayed in a new panel
+fn external_in_panel(
+    invocation_str: &str,
+    execution_str: &str,
+) -> Verb {
+    let execution = VerbExecution::External(
+        ExternalExecution::new(
+            execution_str.to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ).with_tail_in_panel(Some(true))
+    );
+    Verb::new(
+        Some(invocation_str),
+        execution,
+        VerbDescription::from_code(execution_str.to_string()),
+    ).unwrap()
+}
+
 /// declare the built_in verbs, the ones which are available
 /// in standard (they still may be overriden by configuration)
 pub fn builtin_verbs() -> Vec<Verb> {
@@ -63,6 +81,7 @@ pub fn builtin_verbs() -> Vec<Verb> {
         internal(open_leave) // calls the system open
             .with_key(ALT_ENTER)
             .with_shortcut("ol"),
+        internal(open_and_quit),
 
         #[cfg(unix)]
         external("chmod {args}", "chmod {args} {file}", StayInBroot)
@@ -73,9 +92,14 @@ pub fn builtin_verbs() -> Vec<Verb> {
         internal(open_preview),
         internal(close_preview),
         internal(toggle_preview),
+        internal(toggle_split),
+        internal(toggle_verb_bar),
+        internal(toggle_watch),
         internal(preview_image),
         internal(preview_text),
+        internal(render),
         internal(preview_binary),
+        internal(preview_as),
         internal(close_panel_ok),
         internal(close_panel_cancel)
             .with_key(BACK_TAB)
@@ -86,9 +110,20 @@ pub fn builtin_verbs() -> Vec<Verb> {
             StayInBroot,
         )
             .with_shortcut("cp"),
-        #[cfg(feature = "clipboard")]
         internal(copy_path)
             .with_alt_key('c'),
+        internal(copy_pair)
+            .needing_another_panel(),
+        internal(swap_names)
+            .needing_another_panel(),
+        internal(batch_rename),
+        internal(link_to_other)
+            .needing_another_panel(),
+        internal(zip_to_other)
+            .needing_another_panel(),
+        internal(browse_archive),
+        internal(extract),
+        internal(set_other_to_parent),
         external(
             "copy_to_panel",
             "cp -r {file} {other-panel-directory}",
@@ -105,6 +140,7 @@ pub fn builtin_verbs() -> Vec<Verb> {
             .with_control_key('f'),
         internal(help)
             .with_key(F1).with_shortcut("?"),
+        internal(commands),
         #[cfg(feature="clipboard")]
         internal(input_paste)
             .with_control_key('v'),
@@ -139,6 +175,8 @@ pub fn builtin_verbs() -> Vec<Verb> {
             .with_shortcut("os"),
         internal(open_stay_filter)
             .with_shortcut("osf"),
+        internal(open_url)
+            .with_stype(SelectionType::File),
         internal(parent).with_shortcut("p"),
         internal(page_down).with_key(PAGE_DOWN),
         internal(page_up).with_key(PAGE_UP),
@@ -155,6 +193,7 @@ pub fn builtin_verbs() -> Vec<Verb> {
         internal(print_path).with_shortcut("pp"),
         internal(print_relative_path).with_shortcut("prp"),
         internal(print_tree).with_shortcut("pt"),
+        internal(export),
         internal(quit)
             .with_control_key('c')
             .with_control_key('q')
@@ -164,16 +203,69 @@ pub fn builtin_verbs() -> Vec<Verb> {
         internal(sort_by_date).with_shortcut("sd"),
         internal(sort_by_size).with_shortcut("ss"),
         external("rm", "rm -rf {file}", StayInBroot),
+        #[cfg(unix)]
+        internal(trash),
+        #[cfg(unix)]
+        internal(open_trash),
+        #[cfg(unix)]
+        internal(restore),
+        #[cfg(unix)]
+        internal(purge),
+        internal(depth),
+        internal(filter_modified),
+        internal(duplicate),
+        internal(yank),
+        internal(cut),
+        internal(paste),
+        internal(toggle_exec),
+        internal(from_template),
+        internal(first),
+        internal(goto_newest),
+        internal(last),
+        internal(reload_config),
+        internal(save_layout),
+        internal(load_layout),
+        internal(save_session),
+        internal(load_session),
+        internal(locate),
+        internal(mark),
+        internal(mode),
+        internal(bookmark),
+        internal(bookmarks),
+        internal(jump),
+        internal(undo),
+        internal(redo),
+        internal(journal),
+        internal(pipe_listing),
+        internal(recent_next),
+        internal(recent_prev),
+        internal(repeat),
+        internal(run),
+        internal(select_largest),
+        internal(select_smallest),
+        internal(stage),
+        internal(unstage),
+        internal(toggle_stage),
+        internal(clear_stage),
+        internal(stage_content_matches),
         internal(toggle_counts).with_shortcut("counts"),
+        internal(toggle_case),
         internal(toggle_dates).with_shortcut("dates"),
         internal(toggle_files).with_shortcut("files"),
+        internal(toggle_flat).with_shortcut("flat"),
         internal(toggle_git_ignore).with_shortcut("gi"),
         internal(toggle_git_file_info).with_shortcut("gf"),
         internal(toggle_git_status).with_shortcut("gs"),
+        internal(git_status),
+        external("git_stage", "git -C {directory} add {file}", StayInBroot),
+        external("git_unstage", "git -C {directory} restore --staged {file}", StayInBroot),
+        external_in_panel("git_diff", "git -C {directory} diff {file}"),
         internal(toggle_root_fs).with_shortcut("rfs"),
         internal(toggle_hidden).with_shortcut("h"),
         #[cfg(unix)]
-        internal(toggle_perm).with_shortcut("perm"),
+        internal(toggle_perm).with_shortcut("perm").with_shortcut("toggle_perms"),
+        #[cfg(unix)]
+        internal(toggle_perm_style),
         internal(toggle_sizes).with_shortcut("sizes"),
         internal(toggle_trim_root),
         internal(total_search).with_control_key('s'),
@@ -0,0 +1,89 @@
+//! an optional log of the commands executed by verbs, for audit
+
+use {
+    crate::path,
+    chrono::{DateTime, Local},
+    std::{
+        fs::OpenOptions,
+        io::Write,
+        path::Path,
+    },
+};
+
+/// build the log line of an execution: the local time (RFC 3339), the
+/// exit code (`-` when it's unknown, for example when the command is
+/// executed after broot quits) and the tokens of the command, escaped
+/// for the shell. Those 3 fields are separated by tabs.
+fn log_line(time: DateTime<Local>, tokens: &[String], exit_code: Option<i32>) -> String {
+    let command = tokens
+        .iter()
+        .map(|token| path::escape_for_shell(Path::new(token)))
+        .collect::<Vec<String>>()
+        .join(" ");
+    format!(
+        "{}\t{}\t{}\n",
+        time.to_rfc3339(),
+        exit_code.map_or_else(|| "-".to_string(), |code| code.to_string()),
+        command,
+    )
+}
+
+/// append the execution to the log file. A failure to write
+/// the log doesn't prevent the execution.
+pub fn log_execution(log_file: Option<&Path>, tokens: &[String], exit_code: Option<i32>) {
+    let log_file = match log_file {
+        Some(log_file) => log_file,
+        None => return,
+    };
+    let line = log_line(Local::now(), tokens, exit_code);
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)
+        .and_then(|mut f| f.write_all(line.as_bytes()));
+    if let Err(e) = written {
+        warn!("failed to write verb log {:?}: {}", log_file, e);
+    }
+}
+
+#[cfg(test)]
+mod exec_log_tests {
+
+    use {
+        super::*,
+        chrono::TimeZone,
+    };
+
+    #[test]
+    fn test_log_line() {
+        let time = Local.ymd(2020, 12, 19).and_hms(14, 32, 5);
+        let tokens = vec![
+            "cp".to_string(),
+            "/home/dys/my notes.md".to_string(),
+            "/backup".to_string(),
+        ];
+        let line = log_line(time, &tokens, Some(0));
+        assert_eq!(
+            line,
+            format!("{}\t0\tcp '/home/dys/my notes.md' /backup\n", time.to_rfc3339()),
+        );
+        assert!(line.starts_with("2020-12-19T14:32:05"));
+        let line = log_line(time, &tokens[..1], None);
+        assert!(line.ends_with("\t-\tcp\n"));
+        assert_eq!(log_line(time, &tokens[..1], Some(2)).split('\t').nth(1), Some("2"));
+    }
+
+    #[test]
+    fn test_log_execution() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_file = dir.path().join("verbs.log");
+        let tokens = vec!["touch".to_string(), "a".to_string()];
+        log_execution(Some(&log_file), &tokens, Some(0));
+        log_execution(Some(&log_file), &tokens, Some(1));
+        let content = std::fs::read_to_string(&log_file).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("\t0\ttouch a"));
+        assert!(lines[1].ends_with("\t1\ttouch a"));
+    }
+}
@@ -0,0 +1,195 @@
+use {
+    super::GROUP,
+    splitty::split_unquoted_whitespace,
+    std::fmt,
+};
+
+/// a group of an execution pattern, e.g. `{file:canonical}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternGroup {
+    /// the group as written, with its braces
+    pub raw: String,
+    pub name: String,
+    pub fmt: Option<String>,
+}
+
+/// a part of an execution pattern: either some text to keep
+/// as is, or a group to replace
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternSegment {
+    Literal(String),
+    Group(PatternGroup),
+}
+
+/// cut a string in literal and group segments
+pub fn parse_segments(s: &str) -> Vec<PatternSegment> {
+    let mut segments = Vec::new();
+    let mut literal_start = 0;
+    for ec in GROUP.captures_iter(s) {
+        let whole = ec.get(0).unwrap();
+        if whole.start() > literal_start {
+            segments.push(PatternSegment::Literal(s[literal_start..whole.start()].to_string()));
+        }
+        segments.push(PatternSegment::Group(PatternGroup {
+            raw: whole.as_str().to_string(),
+            name: ec[1].to_string(),
+            fmt: ec.get(2).map(|fmt| fmt.as_str().to_string()),
+        }));
+        literal_start = whole.end();
+    }
+    if literal_start < s.len() {
+        segments.push(PatternSegment::Literal(s[literal_start..].to_string()));
+    }
+    segments
+}
+
+/// the execution pattern of a verb, parsed once, when the verb
+/// is built, so that executing the verb is just filling the
+/// groups with values
+#[derive(Debug, Clone)]
+pub struct ExecPattern {
+    raw: String,
+    /// the segments of the whole pattern
+    segments: Vec<PatternSegment>,
+    /// the segments of each token of the pattern, the pattern
+    /// being split on unquoted whitespace and unquoted
+    tokens: Vec<Vec<PatternSegment>>,
+}
+
+impl ExecPattern {
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+    pub fn segments(&self) -> &[PatternSegment] {
+        &self.segments
+    }
+    pub fn tokens(&self) -> &[Vec<PatternSegment>] {
+        &self.tokens
+    }
+    /// return all the groups of the pattern, in order
+    pub fn groups(&self) -> impl Iterator<Item = &PatternGroup> {
+        self.segments.iter().filter_map(|segment| match segment {
+            PatternSegment::Group(group) => Some(group),
+            PatternSegment::Literal(_) => None,
+        })
+    }
+    pub fn contains(&self, pat: &str) -> bool {
+        self.raw.contains(pat)
+    }
+}
+
+impl ExecPattern {
+    /// build a pattern of already complete tokens, in which no
+    /// group is looked for
+    pub fn from_literal_tokens(tokens: Vec<String>) -> Self {
+        let raw = tokens.join(" ");
+        Self {
+            segments: vec![PatternSegment::Literal(raw.clone())],
+            tokens: tokens
+                .into_iter()
+                .map(|token| vec![PatternSegment::Literal(token)])
+                .collect(),
+            raw,
+        }
+    }
+}
+
+impl From<String> for ExecPattern {
+    fn from(raw: String) -> Self {
+        let segments = parse_segments(&raw);
+        let tokens = split_unquoted_whitespace(&raw)
+            .unwrap_quotes(true)
+            .map(parse_segments)
+            .collect();
+        Self { raw, segments, tokens }
+    }
+}
+
+impl From<&str> for ExecPattern {
+    fn from(raw: &str) -> Self {
+        Self::from(raw.to_string())
+    }
+}
+
+impl fmt::Display for ExecPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.raw.fmt(f)
+    }
+}
+
+impl PartialEq<str> for ExecPattern {
+    fn eq(&self, other: &str) -> bool {
+        self.raw == other
+    }
+}
+
+#[cfg(test)]
+mod exec_pattern_tests {
+
+    use super::*;
+
+    fn literal(s: &str) -> PatternSegment {
+        PatternSegment::Literal(s.to_string())
+    }
+
+    fn group(raw: &str, name: &str, fmt: Option<&str>) -> PatternSegment {
+        PatternSegment::Group(PatternGroup {
+            raw: raw.to_string(),
+            name: name.to_string(),
+            fmt: fmt.map(|s| s.to_string()),
+        })
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        assert_eq!(parse_segments(""), vec![]);
+        assert_eq!(parse_segments("ls -l"), vec![literal("ls -l")]);
+        assert_eq!(
+            parse_segments("cp {file} {directory}/{name:fs-safe}.bak"),
+            vec![
+                literal("cp "),
+                group("{file}", "file", None),
+                literal(" "),
+                group("{directory}", "directory", None),
+                literal("/"),
+                group("{name:fs-safe}", "name", Some("fs-safe")),
+                literal(".bak"),
+            ],
+        );
+        // braces which aren't a group are literal
+        assert_eq!(
+            parse_segments("{a}{} {b:c:d}"),
+            vec![group("{a}", "a", None), literal("{} {b:c:d}")],
+        );
+        // the only group accepted as format is {line}
+        assert_eq!(
+            parse_segments("tig blame +{line} {file} # {git-author:{line}}"),
+            vec![
+                literal("tig blame +"),
+                group("{line}", "line", None),
+                literal(" "),
+                group("{file}", "file", None),
+                literal(" # "),
+                group("{git-author:{line}}", "git-author", Some("{line}")),
+            ],
+        );
+        assert_eq!(
+            parse_segments("{a:{b}}"),
+            vec![literal("{a:"), group("{b}", "b", None), literal("}")],
+        );
+    }
+
+    #[test]
+    fn test_exec_pattern_tokens() {
+        let pattern = ExecPattern::from(r#"mv "{file}" "{directory}/a b" {selection}"#);
+        assert_eq!(pattern.tokens().len(), 4);
+        assert_eq!(pattern.tokens()[1], vec![group("{file}", "file", None)]);
+        assert_eq!(
+            pattern.tokens()[2],
+            vec![group("{directory}", "directory", None), literal("/a b")],
+        );
+        let names: Vec<&str> = pattern.groups().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["file", "directory", "selection"]);
+        assert!(pattern == *r#"mv "{file}" "{directory}/a b" {selection}"#);
+    }
+}
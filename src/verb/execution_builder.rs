@@ -1,18 +1,42 @@
 use {
-    super::{InvocationParser, GROUP},
+    super::{
+        exec_pattern::{parse_segments, ExecPattern, PatternGroup, PatternSegment},
+        literal,
+        InvocationParser,
+    },
     crate::{
-        app::Selection,
-        path,
+        app::{Selection, SelectionType},
+        git,
+        path::{self, ShellEscaping},
+        pattern::SearchMode,
     },
-    fnv::FnvHashMap,
-    regex::Captures,
+    fnv::{FnvHashMap, FnvHasher},
     splitty::split_unquoted_whitespace,
-    std::path::{Path, PathBuf},
+    std::{
+        fs,
+        hash::Hasher,
+        io::{self, Read, Write},
+        path::{Path, PathBuf},
+        time::SystemTime,
+    },
 };
 
+/// the maximal size of a file whose content can be passed
+/// in base64 with `{file:base64}`
+const MAX_BASE64_FILE_SIZE: u64 = 64 * 1024;
+
+/// a function giving the value of an environment variable
+pub type EnvLookup = fn(&str) -> Option<String>;
+
+/// read the variable in the environment of broot
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
 /// a temporary structure gathering selection and invocation
 /// parameters and able to generate an executable string from
 /// a verb's execution pattern
+#[derive(Clone)]
 pub struct ExecutionStringBuilder<'b> {
     /// the current file selection
     pub sel: Selection<'b>,
@@ -22,6 +46,166 @@ pub struct ExecutionStringBuilder<'b> {
 
     /// parsed arguments
     invocation_values: Option<FnvHashMap<String, String>>,
+
+    /// number of entries matching the current search (0 when there's no search)
+    match_count: usize,
+
+    /// mode of the current search, if any
+    search_mode: Option<SearchMode>,
+
+    /// the paths `{selection}` is made of, when they're not just
+    /// the selected one (i.e. when there are staged paths)
+    selection_paths: Vec<&'b Path>,
+
+    /// the root of the tree, if any
+    root: Option<&'b Path>,
+
+    /// the 1-based index of the execution, when the verb is
+    /// executed once per batch
+    index: Option<usize>,
+
+    /// the file listing the paths of `{selection}`, substituted
+    /// to `{selection-response-file}`
+    response_file: Option<PathBuf>,
+
+    /// how paths are quoted for the shell
+    escaping: ShellEscaping,
+
+    /// the random values of `{rand}` and `{uuid}`, generated once
+    /// so that they're the same in the whole command
+    random_tokens: RandomTokens,
+
+    /// where the environment variables are read
+    env: EnvLookup,
+}
+
+/// random values available in execution patterns
+#[derive(Clone)]
+struct RandomTokens {
+    rand: String,
+    uuid: String,
+}
+
+impl RandomTokens {
+    fn new() -> Self {
+        Self {
+            rand: random_hex(),
+            uuid: random_uuid(),
+        }
+    }
+}
+
+/// the formats which may be applied to a standard path,
+/// besides the `maxdepth=` ones
+const PATH_FORMATS: &[&str] = &[
+    "shell", "raw", "base64", "env-collapse", "abs", "canonical",
+    "resolve-parents", "long-path", "unique", "native", "slash",
+    "rust-string", "python-string", "regex-escape", "name", "stem",
+    "extension", "lowercase", "uppercase",
+];
+
+/// parse the depth of a `maxdepth=` format, which must be positive
+fn parse_max_depth(depth: &str) -> Option<usize> {
+    depth.parse().ok().filter(|&depth| depth > 0)
+}
+
+/// return the error of a format which can't be applied to a standard path
+fn path_format_error(fmt: &str) -> Option<String> {
+    let valid = match fmt.strip_prefix("maxdepth=") {
+        Some(depth) => parse_max_depth(depth).is_some(),
+        None => PATH_FORMATS.contains(&fmt),
+    };
+    if valid {
+        None
+    } else {
+        Some(format!("invalid format: {:?}", fmt))
+    }
+}
+
+/// return the error of a format which can't be applied to
+/// a group which isn't a standard path
+fn group_format_error(name: &str, fmt: Option<&str>) -> Option<String> {
+    let valid = match name {
+        "path-tail" => path_tail_count(fmt).is_some(),
+        "file-stem" => matches!(fmt, None | Some("slugify") | Some("var")),
+        "rand" | "uuid" => matches!(fmt, None | Some("fresh")),
+        "selection" | "files" => match fmt.and_then(|fmt| fmt.strip_prefix("relative-to=")) {
+            Some(base) => is_relative_base(base),
+            None => true,
+        },
+        _ => true,
+    };
+    if valid {
+        None
+    } else {
+        Some(format!("invalid format: {:?}", fmt.unwrap_or_default()))
+    }
+}
+
+/// tell whether the base of a `relative-to=` format is known
+fn is_relative_base(base: &str) -> bool {
+    matches!(base, "parent" | "root" | "directory") || Path::new(base).is_absolute()
+}
+
+/// parse the number of components of a `{path-tail:n}` group
+fn path_tail_count(fmt: Option<&str>) -> Option<usize> {
+    fmt.and_then(|n| n.parse::<usize>().ok()).filter(|&n| n > 0)
+}
+
+/// check the path is a file small enough to be passed in base64
+fn check_base64_size(path: &Path) -> Result<(), String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("{} can't be read: {}", path.to_string_lossy(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("{} isn't a file", path.to_string_lossy()));
+    }
+    if metadata.len() > MAX_BASE64_FILE_SIZE {
+        return Err(format!(
+            "{} is too big to be passed in base64 (max {} bytes)",
+            path.to_string_lossy(),
+            MAX_BASE64_FILE_SIZE,
+        ));
+    }
+    Ok(())
+}
+
+/// read the file, if it's not too big, and encode its content in base64
+fn base64_content(path: &Path) -> Result<String, String> {
+    check_base64_size(path)?;
+    let mut bytes = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(MAX_BASE64_FILE_SIZE + 1).read_to_end(&mut bytes))
+        .map_err(|e| format!("{} can't be read: {}", path.to_string_lossy(), e))?;
+    if bytes.len() as u64 > MAX_BASE64_FILE_SIZE {
+        // the file grew since it was checked
+        return Err(format!("{} is too big to be passed in base64", path.to_string_lossy()));
+    }
+    Ok(base64::encode(&bytes))
+}
+
+/// make a short id of the path, stable across runs and
+/// platforms (it's the start of its 64 bits FNV-1a hash)
+fn path_id(path: &Path) -> String {
+    let mut hasher = FnvHasher::default();
+    hasher.write(path.to_string_lossy().as_bytes());
+    format!("{:016x}", hasher.finish())[..8].to_string()
+}
+
+/// make a short random hexadecimal token (8 characters)
+fn random_hex() -> String {
+    format!("{:08x}", rand::random::<u32>())
+}
+
+/// make a random (version 4) UUID, e.g. `a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d`
+fn random_uuid() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40; // version 4
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // RFC 4122 variant
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32],
+    )
 }
 
 impl<'b> ExecutionStringBuilder<'b> {
@@ -32,6 +216,15 @@ impl<'b> ExecutionStringBuilder<'b> {
             sel,
             other_file: None,
             invocation_values: None,
+            match_count: 0,
+            search_mode: None,
+            selection_paths: Vec::new(),
+            root: None,
+            index: None,
+            response_file: None,
+            escaping: ShellEscaping::default(),
+            random_tokens: RandomTokens::new(),
+            env: env_var,
         }
     }
     pub fn from_invocation(
@@ -42,13 +235,149 @@ impl<'b> ExecutionStringBuilder<'b> {
     ) -> Self {
         let invocation_values = invocation_parser
             .as_ref()
-            .zip(invocation_args.as_ref())
-            .and_then(|(parser, args)| parser.parse(args));
+            .and_then(|parser| parser.parse(invocation_args.as_deref()));
         Self {
             sel,
             other_file: other_file.as_ref(),
             invocation_values,
+            match_count: 0,
+            search_mode: None,
+            selection_paths: Vec::new(),
+            root: None,
+            index: None,
+            response_file: None,
+            escaping: ShellEscaping::default(),
+            random_tokens: RandomTokens::new(),
+            env: env_var,
+        }
+    }
+    pub fn with_match_count(mut self, match_count: usize) -> Self {
+        self.match_count = match_count;
+        self
+    }
+    pub fn with_search_mode(mut self, search_mode: Option<SearchMode>) -> Self {
+        self.search_mode = search_mode;
+        self
+    }
+    /// make `{selection}` be the given paths (when there's at least one)
+    pub fn with_staged_paths(mut self, paths: &'b [PathBuf]) -> Self {
+        self.selection_paths = paths.iter().map(|p| p.as_path()).collect();
+        self
+    }
+    /// set the root of the tree, used by `{selection:relative-to=root}`
+    pub fn with_root(mut self, root: Option<&'b Path>) -> Self {
+        self.root = root;
+        self
+    }
+    /// set the quoting rules of the shell the command is written for
+    pub fn with_env(mut self, env: EnvLookup) -> Self {
+        self.env = env;
+        self
+    }
+    pub fn with_escaping(mut self, escaping: ShellEscaping) -> Self {
+        self.escaping = escaping;
+        self
+    }
+    /// make `{selection-response-file}` refer to the given file, which
+    /// should have been written with `write_response_file`
+    pub fn with_response_file(mut self, path: PathBuf) -> Self {
+        self.response_file = Some(path);
+        self
+    }
+    /// the content of a response file for the paths of `{selection}`:
+    /// one path per line, in double quotes, with quotes and backslashes
+    /// escaped with a backslash (as accepted by gcc, clang, javac, etc.)
+    fn response_file_content(&self) -> String {
+        let mut content = String::new();
+        for path in self.get_selection_paths() {
+            content.push('"');
+            for c in path.to_string_lossy().chars() {
+                if c == '"' || c == '\\' {
+                    content.push('\\');
+                }
+                content.push(c);
+            }
+            content.push_str("\"\n");
         }
+        content
+    }
+    /// write the paths of `{selection}` in a temporary response
+    /// file, which is removed when the returned value is dropped
+    pub fn write_response_file(&self) -> io::Result<tempfile::NamedTempFile> {
+        let mut file = tempfile::Builder::new()
+            .prefix("broot-")
+            .suffix(".rsp")
+            .tempfile()?;
+        file.write_all(self.response_file_content().as_bytes())?;
+        file.flush()?;
+        Ok(file)
+    }
+    /// return the paths of `{selection}`: the staged ones if
+    /// any, else the selected one
+    pub fn get_selection_paths(&self) -> Vec<&'b Path> {
+        if self.selection_paths.is_empty() {
+            vec![self.sel.path]
+        } else {
+            self.selection_paths.clone()
+        }
+    }
+    /// return the paths of `{selection}` made relative to the base of
+    /// a `relative-to=` format, which is either `parent` (the common
+    /// parent of the paths), `root` (the root of the tree), `directory`
+    /// (the selected directory) or an absolute path.
+    ///
+    /// Return None when the format isn't a `relative-to=` one, and
+    /// the paths unchanged when there's no such base.
+    fn relative_selection_paths(&self, fmt: Option<&str>) -> Option<Result<Vec<PathBuf>, String>> {
+        let base = fmt?.strip_prefix("relative-to=")?;
+        let paths = self.get_selection_paths();
+        let base = match base {
+            "parent" => path::common_parent(&paths),
+            "root" => self.root.map(Path::to_path_buf),
+            "directory" => Some(self.get_directory()),
+            _ if Path::new(base).is_absolute() => Some(PathBuf::from(base)),
+            _ => {
+                return Some(Err(format!("invalid format: {:?}", fmt.unwrap_or_default())));
+            }
+        };
+        Some(Ok(paths
+            .iter()
+            .map(|p| match &base {
+                Some(base) => path::relative_to(p, base),
+                None => p.to_path_buf(),
+            })
+            .collect()))
+    }
+    /// split the paths of `{selection}` into batches of at most
+    /// `batch_size` paths, returning a builder per batch, with
+    /// its 1-based `{index}`.
+    ///
+    /// With batches of one path, the verb is executed per file: the
+    /// path of the batch becomes the selection, the one of `{file}`.
+    pub fn batched(&self, batch_size: usize) -> Vec<Self> {
+        let per_file = batch_size <= 1;
+        self.get_selection_paths()
+            .chunks(batch_size.max(1))
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut sel = self.sel;
+                if per_file {
+                    sel.path = chunk[0];
+                    sel.line = 0;
+                    sel.stype = if sel.path.is_dir() {
+                        SelectionType::Directory
+                    } else {
+                        SelectionType::File
+                    };
+                }
+                Self {
+                    sel,
+                    selection_paths: chunk.to_vec(),
+                    index: Some(i + 1),
+                    ..self.clone()
+                }
+            })
+            .collect()
     }
     fn get_file(&self) -> &Path {
         &self.sel.path
@@ -62,62 +391,419 @@ impl<'b> ExecutionStringBuilder<'b> {
     }
     fn path_to_string(&self, path: &Path, escape: bool) -> String {
         if escape {
-            path::escape_for_shell(path)
+            self.escaping.escape(path)
         } else {
             path.to_string_lossy().to_string()
         }
     }
-    fn get_raw_capture_replacement(&self, ec: &Captures<'_>, escape: bool) -> Option<String> {
-        let name = ec.get(1).unwrap().as_str();
+    /// return the path designated by a standard group name, if
+    /// the name is one of them and the path is available
+    fn get_standard_path(&self, name: &str) -> Option<PathBuf> {
         match name {
-            "line" => Some(self.sel.line.to_string()),
-            "file" => Some(self.path_to_string(self.get_file(), escape)),
-            "directory" => Some(self.path_to_string(&self.get_directory(), escape)),
-            "parent" => Some(self.path_to_string(self.get_parent(), escape)),
-            "other-panel-file" => self.other_file.map(|p| self.path_to_string(p, escape)),
-            "other-panel-directory" => self
-                .other_file
-                .map(|p| path::closest_dir(p))
-                .as_ref()
-                .map(|p| self.path_to_string(p, escape)),
+            "file" => Some(self.get_file().to_path_buf()),
+            "directory" => Some(self.get_directory()),
+            "parent" => Some(self.get_parent().to_path_buf()),
+            "relative-file" => Some(path::relative_to_current_dir(self.get_file())),
+            "home-relative" => path::home_dir()
+                .and_then(|home| path::home_relative(&path::absolute(self.get_file()), &home)),
+            "other-panel-file" => self.other_file.cloned(),
+            "other-panel-directory" => self.other_file.map(|p| path::closest_dir(p)),
             "other-panel-parent" => self
                 .other_file
                 .and_then(|p| p.parent())
-                .map(|p| self.path_to_string(p, escape)),
-            _ => {
-                // it's not one of the standard group names, so we'll look
-                // into the ones provided by the invocation pattern
-                self.invocation_values.as_ref()
-                    .and_then(|map| map.get(name)
-                        .map(|value| {
-                            if let Some(fmt) = ec.get(2) {
-                                match fmt.as_str() {
-                                    "path-from-directory" => path::path_str_from(self.get_directory(), value),
-                                    "path-from-parent" => path::path_str_from(self.get_parent(), value),
-                                    _ => format!("invalid format: {:?}", fmt.as_str()),
-                                }
-                            } else {
-                                value.to_string()
-                            }
-                        })
-                    )
+                .map(|p| p.to_path_buf()),
+            _ => None,
+        }
+    }
+    /// apply the optional format of a group to a standard path.
+    /// The `shell` and `raw` formats force the escaping, or its absence.
+    fn format_path(&self, path: PathBuf, fmt: Option<&str>, escape: bool) -> String {
+        if fmt == Some("base64") {
+            // base64 only uses characters which don't need escaping
+            return base64_content(&path).unwrap_or_else(|e| e);
+        }
+        if fmt == Some("env-collapse") {
+            return match path::env_collapse(&path, &path::env_collapse_vars()) {
+                Some((var_name, rest)) => {
+                    // the variable is kept out of the quotes so that
+                    // the shell can expand it
+                    let rest = if escape && rest.components().next().is_some() {
+                        self.escaping.escape(&rest)
+                    } else {
+                        rest.to_string_lossy().to_string()
+                    };
+                    format!("${{{}}}{}", var_name, rest)
+                }
+                None => self.path_to_string(&path, escape),
+            };
+        }
+        let escape = match fmt {
+            Some("shell") => true,
+            Some("raw") => false,
+            _ => escape,
+        };
+        let path = match fmt {
+            None | Some("shell") | Some("raw") => path,
+            Some("abs") => path::absolute(&path),
+            Some("canonical") => path.canonicalize().unwrap_or(path),
+            Some("resolve-parents") => path::resolve_parents(&path),
+            Some("long-path") => path::long_path(&path),
+            Some("unique") => path::unique_path(&path),
+            Some("native") => PathBuf::from(path::with_native_separators(&path.to_string_lossy())),
+            Some("slash") => PathBuf::from(path::with_slash_separators(&path.to_string_lossy())),
+            Some("rust-string") => PathBuf::from(literal::rust_string(&path.to_string_lossy())),
+            Some("python-string") => PathBuf::from(literal::python_string(&path.to_string_lossy())),
+            Some("regex-escape") => PathBuf::from(regex::escape(&path.to_string_lossy())),
+            Some("name") => path.file_name().map(PathBuf::from).unwrap_or_default(),
+            Some("stem") => path.file_stem().map(PathBuf::from).unwrap_or_default(),
+            Some("extension") => path.extension().map(PathBuf::from).unwrap_or_default(),
+            Some("lowercase") => PathBuf::from(path.to_string_lossy().to_lowercase()),
+            Some("uppercase") => PathBuf::from(path.to_string_lossy().to_uppercase()),
+            Some(fmt) if fmt.starts_with("maxdepth=") => {
+                match parse_max_depth(&fmt["maxdepth=".len()..]) {
+                    Some(max_depth) => path::trim_depth(&path, max_depth),
+                    None => {
+                        return format!("invalid format: {:?}", fmt);
+                    }
+                }
             }
+            Some(fmt) => {
+                return format!("invalid format: {:?}", fmt);
+            }
+        };
+        self.path_to_string(&path, escape)
+    }
+    /// return the value of a group, or None if the group isn't known
+    fn get_raw_replacement(&self, name: &str, fmt: Option<&str>, escape: bool) -> Option<String> {
+        if name == "line" {
+            return Some(self.sel.line.to_string());
+        }
+        if name == "index" {
+            return Some(self.index.unwrap_or(1).to_string());
+        }
+        if name == "file-name" {
+            let name = self.sel.path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            return Some(if escape {
+                self.escaping.escape(Path::new(&name))
+            } else {
+                name
+            });
+        }
+        if name == "file-extension" {
+            let extension = self.sel.path.extension()
+                .map(|ext| ext.to_string_lossy().to_string())
+                .unwrap_or_default();
+            return Some(if escape {
+                self.escaping.escape(Path::new(&extension))
+            } else {
+                extension
+            });
         }
+        if name == "file-base" {
+            let name = self.sel.path.file_name()
+                .map(|name| path::file_base(&name.to_string_lossy()).to_string())
+                .unwrap_or_default();
+            return Some(if escape {
+                self.escaping.escape(Path::new(&name))
+            } else {
+                name
+            });
+        }
+        if name == "file-id" {
+            return Some(path_id(&path::absolute(self.sel.path)));
+        }
+        if name == "editor" {
+            // the command line of the editor, which may hold arguments
+            // (e.g. `code --wait`), isn't escaped
+            return Some(preferred_editor(self.env));
+        }
+        if name == "env" {
+            if let Some(var_name) = fmt {
+                // an unset variable gives an empty string
+                let value = (self.env)(var_name).unwrap_or_default();
+                return Some(if escape {
+                    self.escaping.escape(Path::new(&value))
+                } else {
+                    value
+                });
+            }
+        }
+        if name == "rand" || name == "uuid" {
+            let fresh = match fmt {
+                None => false,
+                Some("fresh") => true,
+                Some(fmt) => {
+                    return Some(format!("invalid format: {:?}", fmt));
+                }
+            };
+            return Some(match (name, fresh) {
+                ("rand", false) => self.random_tokens.rand.clone(),
+                ("rand", true) => random_hex(),
+                (_, false) => self.random_tokens.uuid.clone(),
+                (_, true) => random_uuid(),
+            });
+        }
+        if name == "file-stem" {
+            let stem = self.sel.path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let stem = match fmt {
+                None => stem,
+                Some("slugify") => path::slugify(&stem),
+                Some("var") => path::shell_var_name(&stem),
+                Some(fmt) => {
+                    return Some(format!("invalid format: {:?}", fmt));
+                }
+            };
+            return Some(if escape {
+                self.escaping.escape(Path::new(&stem))
+            } else {
+                stem
+            });
+        }
+        if name == "path-tail" {
+            return Some(match path_tail_count(fmt) {
+                Some(n) => {
+                    let tail = path::path_tail(self.sel.path, n);
+                    if escape {
+                        self.escaping.escape(Path::new(&tail))
+                    } else {
+                        tail
+                    }
+                }
+                None => format!("invalid format: {:?}", fmt.unwrap_or_default()),
+            });
+        }
+        if name == "os" {
+            return Some(std::env::consts::OS.to_string());
+        }
+        if name == "arch" {
+            return Some(std::env::consts::ARCH.to_string());
+        }
+        if name == "match-count" {
+            return Some(self.match_count.to_string());
+        }
+        if name == "selection" || name == "files" {
+            if fmt == Some("common-parent") {
+                return Some(
+                    path::common_parent(&self.get_selection_paths())
+                        .map(|p| self.path_to_string(&p, escape))
+                        .unwrap_or_default()
+                );
+            }
+            if let Some(paths) = self.relative_selection_paths(fmt) {
+                return Some(match paths {
+                    Ok(paths) => paths
+                        .iter()
+                        .map(|p| self.path_to_string(p, escape))
+                        .collect::<Vec<String>>()
+                        .join(" "),
+                    Err(e) => e,
+                });
+            }
+            if fmt == Some("count-by-ext") {
+                let summary = path::count_by_extension(&self.get_selection_paths());
+                return Some(if escape {
+                    self.escaping.escape(Path::new(&summary))
+                } else {
+                    summary
+                });
+            }
+            return Some(
+                self.get_selection_paths()
+                    .iter()
+                    .map(|p| self.path_to_string(p, escape))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            );
+        }
+        if name == "selection-response-file" {
+            return self.response_file
+                .as_ref()
+                .map(|p| format!("@{}", self.path_to_string(p, escape)));
+        }
+        if name == "git-status" {
+            return Some(git::short_status_code(self.sel.path).to_string());
+        }
+        if name == "git-author" {
+            return Some(self.git_author(fmt, escape));
+        }
+        if name == "file-meta" {
+            let meta = path::file_meta(self.sel.path).unwrap_or_default();
+            return Some(if escape && !meta.is_empty() {
+                self.escaping.escape(Path::new(&meta))
+            } else {
+                meta
+            });
+        }
+        if name == "mtime-ago" {
+            let ago = fs::metadata(self.sel.path)
+                .and_then(|md| md.modified())
+                .map(|mtime| path::time_ago(SystemTime::now().duration_since(mtime).unwrap_or_default()))
+                .unwrap_or_default();
+            return Some(if escape && !ago.is_empty() {
+                self.escaping.escape(Path::new(&ago))
+            } else {
+                ago
+            });
+        }
+        if name == "dir-count" || name == "plain-file-count" {
+            return Some(
+                path::count_children(&self.get_directory())
+                    .map(|(dirs, files)| if name == "dir-count" { dirs } else { files })
+                    .map(|count| count.to_string())
+                    .unwrap_or_default()
+            );
+        }
+        if name == "size-delta" {
+            return Some(
+                self.other_file
+                    .and_then(|other| path::size_delta(self.sel.path, other))
+                    .unwrap_or_default()
+            );
+        }
+        if name == "nlink" {
+            return Some(path::nlink(self.sel.path).map(|n| n.to_string()).unwrap_or_default());
+        }
+        if name == "search-kind" {
+            return Some(self.search_mode.map_or("", |mode| mode.kind_name()).to_string());
+        }
+        if let Some(path) = self.get_standard_path(name) {
+            return Some(self.format_path(path, fmt, escape));
+        }
+        // it's not one of the standard group names, so we'll look
+        // into the ones provided by the invocation pattern
+        self.get_invocation_value(name, fmt)
+            .map(|value| value.unwrap_or_else(|e| e))
     }
-    fn get_capture_replacement(&self, ec: &Captures<'_>, escape: bool) -> String {
-        self.get_raw_capture_replacement(ec, escape)
-            .unwrap_or_else(|| ec[0].to_string())
+    /// return the formatted value of an invocation argument, or the
+    /// error if the value isn't compatible with the format
+    fn get_invocation_value(&self, name: &str, fmt: Option<&str>) -> Option<Result<String, String>> {
+        self.invocation_values.as_ref()
+            .and_then(|map| map.get(name))
+            .map(|value| {
+                if let Some(fmt) = fmt {
+                    match fmt {
+                        "path-from-directory" => Ok(path::path_str_from(self.get_directory(), value)),
+                        "path-from-parent" => Ok(path::path_str_from(self.get_parent(), value)),
+                        "fs-safe" => Ok(path::fs_safe_name(value)),
+                        "trim" => Ok(value.trim().to_string()),
+                        "lowercase" => Ok(value.to_lowercase()),
+                        "uppercase" => Ok(value.to_uppercase()),
+                        "int" => coerce_int(value),
+                        "float" => coerce_float(value),
+                        _ => Err(format!("invalid format: {:?}", fmt)),
+                    }
+                } else {
+                    Ok(value.to_string())
+                }
+            })
+    }
+    /// check the invocation arguments are compatible with the formats
+    /// required in the execution pattern. Return the first error found.
+    pub fn check_values(&self, exec_pattern: &str) -> Option<String> {
+        self.pattern_check_values(&ExecPattern::from(exec_pattern))
+    }
+    /// check the invocation arguments are compatible with the formats
+    /// required in the parsed execution pattern, and that the formats
+    /// of the standard groups are known
+    pub fn pattern_check_values(&self, exec_pattern: &ExecPattern) -> Option<String> {
+        exec_pattern
+            .groups()
+            .find_map(|group| match self.get_standard_path(&group.name) {
+                None if group.name == "home-relative" => Some(format!(
+                    "{} isn't in the home directory",
+                    self.get_file().to_string_lossy(),
+                )),
+                None if group.name == "git-author" && !cfg!(feature = "git-blame") => Some(
+                    "Git blame feature not enabled at compilation".to_string(),
+                ),
+                Some(path) if group.fmt.as_deref() == Some("base64") => {
+                    check_base64_size(&path).err()
+                }
+                Some(_) => group.fmt.as_deref().and_then(path_format_error),
+                None => group_format_error(&group.name, group.fmt.as_deref())
+                    .or_else(|| {
+                        self.get_invocation_value(&group.name, group.fmt.as_deref())
+                            .and_then(Result::err)
+                    }),
+            })
+    }
+    /// return the author of a line of the selected file, the line
+    /// being either the selected one (`{git-author}` or
+    /// `{git-author:{line}}`) or a given one (e.g. `{git-author:12}`).
+    /// The author is empty outside a repository or on error.
+    fn git_author(&self, fmt: Option<&str>, escape: bool) -> String {
+        let line = match fmt {
+            None | Some("{line}") => self.sel.line,
+            Some(fmt) => match fmt.parse() {
+                Ok(line) => line,
+                Err(_) => {
+                    return format!("invalid line: {:?}", fmt);
+                }
+            },
+        };
+        #[cfg(feature = "git-blame")]
+        let author = git::line_author(self.sel.path, line).unwrap_or_default();
+        #[cfg(not(feature = "git-blame"))]
+        let author = {
+            let _ = line;
+            String::new()
+        };
+        if escape && !author.is_empty() {
+            self.escaping.escape(Path::new(&author))
+        } else {
+            author
+        }
+    }
+    fn get_group_replacement(&self, group: &PatternGroup, escape: bool) -> String {
+        self.get_raw_replacement(&group.name, group.fmt.as_deref(), escape)
+            .unwrap_or_else(|| group.raw.clone())
+    }
+    /// build the string of the segments, the groups being replaced
+    /// with their values (unknown groups are kept as is)
+    fn fill(&self, segments: &[PatternSegment], escape: bool) -> String {
+        let mut s = String::new();
+        for segment in segments {
+            match segment {
+                PatternSegment::Literal(literal) => s.push_str(literal),
+                PatternSegment::Group(group) => s.push_str(&self.get_group_replacement(group, escape)),
+            }
+        }
+        s
+    }
+    /// build a path from a pattern, e.g. `{parent}/out`, without
+    /// any escaping nor splitting
+    pub fn path(
+        &self,
+        pattern: &str,
+    ) -> PathBuf {
+        PathBuf::from(self.fill(&parse_segments(pattern), false))
+    }
+    /// build the command line given as is to a shell: the values of
+    /// the groups are escaped, the rest of the pattern is kept as
+    /// written, even its quotes and spaces
+    pub fn pattern_shell_command(
+        &self,
+        exec_pattern: &ExecPattern,
+    ) -> String {
+        self.fill(exec_pattern.segments(), true)
     }
     /// build a shell compatible command, with escapings
     pub fn shell_exec_string(
         &self,
         exec_pattern: &str,
     ) -> String {
-        let replaced = GROUP
-            .replace_all(
-                exec_pattern,
-                |ec: &Captures<'_>| self.get_capture_replacement(ec, true),
-            );
+        self.pattern_shell_exec_string(&ExecPattern::from(exec_pattern))
+    }
+    /// build a shell compatible command from a parsed pattern
+    pub fn pattern_shell_exec_string(
+        &self,
+        exec_pattern: &ExecPattern,
+    ) -> String {
+        let replaced = self.fill(exec_pattern.segments(), true);
         split_unquoted_whitespace(&replaced)
             .unwrap_quotes(false)
             .map(|token| {
@@ -132,34 +818,167 @@ impl<'b> ExecutionStringBuilder<'b> {
             .collect::<Vec<String>>()
             .join(" ")
     }
+    /// build the command which `shell_exec_string` would give,
+    /// without touching the file system, so that it may be shown
+    /// before execution, even when the paths don't exist yet.
+    /// The groups whose value would be read from the file system
+    /// (e.g. `{file:base64}` or `{mtime-ago}`) are kept as is.
+    pub fn preview_string(
+        &self,
+        exec_pattern: &str,
+    ) -> String {
+        self.pattern_preview_string(&ExecPattern::from(exec_pattern))
+    }
+    /// build the preview of the command of a parsed pattern
+    pub fn pattern_preview_string(
+        &self,
+        exec_pattern: &ExecPattern,
+    ) -> String {
+        let mut replaced = String::new();
+        for segment in exec_pattern.segments() {
+            match segment {
+                PatternSegment::Literal(literal) => replaced.push_str(literal),
+                PatternSegment::Group(group) if reads_file_system(group) => {
+                    replaced.push_str(&group.raw);
+                }
+                PatternSegment::Group(group) => {
+                    replaced.push_str(&self.get_group_replacement(group, true));
+                }
+            }
+        }
+        split_unquoted_whitespace(&replaced)
+            .unwrap_quotes(false)
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
     /// build a vec of tokens which can be passed to Command to
     /// launch an executable
     pub fn exec_token(
         &self,
         exec_pattern: &str,
     ) -> Vec<String> {
-        split_unquoted_whitespace(exec_pattern)
-            .unwrap_quotes(true)
-            .map(|token| {
-                GROUP
-                    .replace_all(
-                        token,
-                        |ec: &Captures<'_>| self.get_capture_replacement(ec, false),
-                    )
-                    .to_string()
+        self.pattern_exec_token(&ExecPattern::from(exec_pattern))
+    }
+    /// build the tokens of a command from a parsed pattern
+    pub fn pattern_exec_token(
+        &self,
+        exec_pattern: &ExecPattern,
+    ) -> Vec<String> {
+        exec_pattern
+            .tokens()
+            .iter()
+            .flat_map(|token| match token.as_slice() {
+                [PatternSegment::Group(group)]
+                    if group.fmt.is_none() && (group.name == "selection" || group.name == "files") =>
+                {
+                    // one token per path (and no token when there's no path)
+                    self.get_selection_paths()
+                        .iter()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .collect()
+                }
+                [PatternSegment::Group(group)]
+                    if group.name == "selection" || group.name == "files" =>
+                {
+                    match self.relative_selection_paths(group.fmt.as_deref()) {
+                        Some(Ok(paths)) => paths
+                            .iter()
+                            .map(|p| p.to_string_lossy().to_string())
+                            .collect(),
+                        Some(Err(e)) => vec![e],
+                        None => vec![self.fill(token, false)],
+                    }
+                }
+                [PatternSegment::Group(group)] if group.fmt.is_none() && group.name == "editor" => {
+                    // the editor's arguments are separate tokens
+                    split_command(&preferred_editor(self.env))
+                }
+                _ => vec![self.fill(token, false)],
             })
             .collect()
     }
 }
 
+/// tell whether the value of the group would be read from the
+/// file system (content, metadata, or resolution of links)
+fn reads_file_system(group: &PatternGroup) -> bool {
+    matches!(
+        group.name.as_str(),
+        "git-status" | "git-author" | "file-meta" | "mtime-ago" | "size-delta" | "nlink"
+    )
+        || matches!(
+            group.fmt.as_deref(),
+            Some("base64") | Some("canonical") | Some("resolve-parents") | Some("unique")
+        )
+}
+
+/// the default editor, when neither `$VISUAL` nor `$EDITOR` is set
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+
+/// return the command line of the user's preferred editor, as
+/// given by `$VISUAL`, then `$EDITOR`, with a platform default
+fn preferred_editor(env: EnvLookup) -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var_name| env(var_name))
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// split a command line into its program and arguments
+fn split_command(command: &str) -> Vec<String> {
+    split_unquoted_whitespace(command)
+        .unwrap_quotes(true)
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// return the program and arguments of the user's preferred editor
+pub fn editor_tokens() -> Vec<String> {
+    split_command(&preferred_editor(env_var))
+}
+
+/// check the value is an integer and normalize it
+fn coerce_int(value: &str) -> Result<String, String> {
+    value.trim()
+        .parse::<i64>()
+        .map(|i| i.to_string())
+        .map_err(|_| format!("{:?} isn't an integer", value))
+}
+
+/// check the value is a finite number and normalize it
+fn coerce_float(value: &str) -> Result<String, String> {
+    value.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|f| f.is_finite())
+        .map(|f| f.to_string())
+        .ok_or_else(|| format!("{:?} isn't a number", value))
+}
+
 #[cfg(test)]
 mod execution_builder_test {
 
     use {
         super::*,
-        crate::app::SelectionType,
+        crate::{app::SelectionType, verb::GROUP},
+        std::time::Duration,
     };
 
+    /// a selection which isn't on a line nor executable
+    fn sel(path: &Path, stype: SelectionType) -> Selection<'_> {
+        Selection {
+            path,
+            line: 0,
+            stype,
+            is_exe: false,
+        }
+    }
+
     fn check_build_execution_from_sel(
         exec_pattern: &str,
         path: &str,
@@ -167,13 +986,7 @@ mod execution_builder_test {
         chk_exec_token: Vec<&str>,
     ) {
         let path = PathBuf::from(path);
-        let sel = Selection {
-            path: &path,
-            line: 0,
-            stype: SelectionType::File,
-            is_exe: false,
-        };
-        let mut builder = ExecutionStringBuilder::from_selection(sel);
+        let mut builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
         let mut map = FnvHashMap::default();
         for (k, v) in replacements {
             map.insert(k.to_owned(), v.to_owned());
@@ -203,6 +1016,1108 @@ mod execution_builder_test {
             vec![],
             vec!["xterm", "-e", "kak /path/to/file"],
         );
+        check_build_execution_from_sel(
+            "cp {file} /backup/{name:fs-safe}",
+            "/path/to/file",
+            vec![("name", "a<b>:c?.")],
+            vec!["cp", "/path/to/file", "/backup/a_b__c_"],
+        );
+    }
+
+    #[test]
+    fn test_numeric_formats() {
+        check_build_execution_from_sel(
+            "head -n {count:int} {file}",
+            "/home/dys/dev",
+            vec![("count", "007")],
+            vec!["head", "-n", "7", "/home/dys/dev"],
+        );
+        check_build_execution_from_sel(
+            "head -n {count:int}",
+            "/home/dys/dev",
+            vec![("count", " -12 ")],
+            vec!["head", "-n", "-12"],
+        );
+        check_build_execution_from_sel(
+            "sleep {duration:float}",
+            "/home/dys/dev",
+            vec![("duration", "  02.50")],
+            vec!["sleep", "2.5"],
+        );
+        assert_eq!(coerce_int("3.5"), Err(r#""3.5" isn't an integer"#.to_string()));
+        assert!(coerce_int("").is_err());
+        assert!(coerce_int("12a").is_err());
+        assert_eq!(coerce_float("3"), Ok("3".to_string()));
+        assert!(coerce_float("inf").is_err());
+        assert!(coerce_float("NaN").is_err());
+        assert!(coerce_float("two").is_err());
+    }
+
+    #[test]
+    fn test_check_values() {
+        let path = PathBuf::from("/home/dys/dev");
+        let mut builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::Directory));
+        let mut map = FnvHashMap::default();
+        map.insert("count".to_string(), "3.5".to_string());
+        map.insert("name".to_string(), "a".to_string());
+        builder.invocation_values = Some(map);
+        assert_eq!(builder.check_values("echo {name} {file}"), None);
+        assert_eq!(builder.check_values("echo {count:float}"), None);
+        assert_eq!(
+            builder.check_values("head -n {count:int} {file}"),
+            Some(r#""3.5" isn't an integer"#.to_string()),
+        );
+        assert!(builder.check_values("echo {name:wrong}").is_some());
+        // an unknown format of a standard path refuses the verb
+        assert_eq!(builder.check_values("ls {directory:abs} {file:raw}"), None);
+        assert_eq!(
+            builder.check_values("ls {file:absolute}"),
+            Some(r#"invalid format: "absolute""#.to_string()),
+        );
+    }
+
+    #[test]
+    fn test_base64_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("my note.txt");
+        fs::write(&path, "hello broot").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(builder.check_values("send {file:base64}"), None);
+        assert_eq!(
+            builder.exec_token("send {file:base64}"),
+            vec!["send", "aGVsbG8gYnJvb3Q="],
+        );
+        assert_eq!(
+            builder.shell_exec_string("send {file:base64}"),
+            "send aGVsbG8gYnJvb3Q=",
+        );
+        // a file at the cap is encoded
+        fs::write(&path, vec![0u8; MAX_BASE64_FILE_SIZE as usize]).unwrap();
+        assert_eq!(builder.check_values("send {file:base64}"), None);
+        assert_eq!(
+            builder.exec_token("send {file:base64}")[1].len() as u64,
+            MAX_BASE64_FILE_SIZE.div_ceil(3) * 4,
+        );
+        // a bigger file is an error, which prevents the execution
+        fs::write(&path, vec![0u8; MAX_BASE64_FILE_SIZE as usize + 1]).unwrap();
+        let err = builder.check_values("send {file:base64}").unwrap();
+        assert!(err.ends_with("is too big to be passed in base64 (max 65536 bytes)"));
+        // so is a directory
+        let builder = ExecutionStringBuilder::from_selection(sel(dir.path(), SelectionType::Directory));
+        assert!(builder.check_values("send {file:base64}").unwrap().ends_with("isn't a file"));
+    }
+
+    #[test]
+    fn test_forced_escaping() {
+        let path = PathBuf::from("/home/dys/my dev");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::Directory));
+        // by default, paths are escaped only in shell strings
+        assert_eq!(builder.exec_token("ls {file}"), vec!["ls", "/home/dys/my dev"]);
+        assert_eq!(builder.shell_exec_string("ls {file}"), "ls '/home/dys/my dev'");
+        // with shell, they're always escaped
+        assert_eq!(builder.exec_token("ls {file:shell}"), vec!["ls", "'/home/dys/my dev'"]);
+        assert_eq!(builder.shell_exec_string("ls {file:shell}"), "ls '/home/dys/my dev'");
+        // with raw, they're never escaped
+        assert_eq!(builder.exec_token("ls {file:raw}"), vec!["ls", "/home/dys/my dev"]);
+        assert_eq!(builder.shell_exec_string("ls {file:raw}"), "ls /home/dys/my dev");
+    }
+
+    #[test]
+    fn test_language_literals() {
+        let path = PathBuf::from(r#"/tmp/say "hi"\now.txt"#);
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(
+            builder.exec_token("gen {file:rust-string}"),
+            vec!["gen", r#""/tmp/say \"hi\"\\now.txt""#],
+        );
+        assert_eq!(
+            builder.exec_token("gen {file:python-string}"),
+            vec!["gen", r#""/tmp/say \"hi\"\\now.txt""#],
+        );
+        // in a shell string, the literal is itself escaped for the shell
+        assert_eq!(
+            builder.shell_exec_string("gen {file:rust-string}"),
+            r#"gen '"/tmp/say \"hi\"\\now.txt"'"#,
+        );
+    }
+
+    #[test]
+    fn test_time_ago() {
+        // a selection which doesn't exist gives an empty string
+        let path = PathBuf::from("/not/an/existing/file");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(builder.exec_token("echo {mtime-ago}"), vec!["echo", ""]);
+        assert_eq!(builder.shell_exec_string("echo {mtime-ago}"), "echo");
+        // in a shell command, the duration is one argument
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.txt");
+        fs::File::create(&path).unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(3 * 24 * 3600 + 60))
+            .unwrap();
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(builder.exec_token("echo {mtime-ago}"), vec!["echo", "3 days ago"]);
+        assert_eq!(builder.shell_exec_string("echo {mtime-ago}"), "echo '3 days ago'");
+    }
+
+    #[test]
+    fn test_files() {
+        let path = PathBuf::from("/home/dys/dev");
+        // without staged paths, {files} is the selection
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::Directory));
+        assert_eq!(
+            builder.exec_token("tar czf archive.tgz {files}"),
+            vec!["tar", "czf", "archive.tgz", "/home/dys/dev"],
+        );
+        let staged = vec![
+            PathBuf::from("/home/dys/my notes.md"),
+            PathBuf::from("/home/dys/todo.md"),
+            PathBuf::from("/home/dys/it's.md"),
+        ];
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::Directory))
+            .with_staged_paths(&staged);
+        // one argument per file, never split again
+        assert_eq!(
+            builder.exec_token("tar czf archive.tgz {files}"),
+            vec![
+                "tar",
+                "czf",
+                "archive.tgz",
+                "/home/dys/my notes.md",
+                "/home/dys/todo.md",
+                "/home/dys/it's.md",
+            ],
+        );
+        // each path is escaped on its own
+        assert_eq!(
+            builder.shell_exec_string("tar czf archive.tgz {files}"),
+            r"tar czf archive.tgz '/home/dys/my notes.md' /home/dys/todo.md '/home/dys/it'\''s.md'",
+        );
+        // inside a bigger token, the paths are joined
+        assert_eq!(
+            builder.exec_token("echo files={files}"),
+            vec!["echo", "files=/home/dys/my notes.md /home/dys/todo.md /home/dys/it's.md"],
+        );
+    }
+
+    #[test]
+    fn test_selection_count_by_ext() {
+        let staged: Vec<PathBuf> = [
+            "/home/dys/dev/src/main.rs",
+            "/home/dys/dev/Cargo.toml",
+            "/home/dys/dev/src/app.rs",
+            "/home/dys/dev/README.md",
+            "/home/dys/dev/src/LIB.RS",
+            "/home/dys/dev/Makefile",
+            "/home/dys/dev/CHANGELOG.md",
+            "/home/dys/dev/.gitignore",
+        ].iter().map(PathBuf::from).collect();
+        let builder = ExecutionStringBuilder::from_selection(sel(&staged[0], SelectionType::File));
+        // without staged paths, it's about the selection
+        assert_eq!(
+            builder.exec_token("echo {selection:count-by-ext}"),
+            vec!["echo", "rs:1"],
+        );
+        let builder = builder.with_staged_paths(&staged);
+        // by count, then by name
+        assert_eq!(
+            builder.exec_token("echo {selection:count-by-ext}"),
+            vec!["echo", "rs:3 (none):2 md:2 toml:1"],
+        );
+        assert_eq!(
+            builder.shell_exec_string("echo {selection:count-by-ext}"),
+            "echo 'rs:3 (none):2 md:2 toml:1'",
+        );
+    }
+
+    #[test]
+    fn test_selection_common_parent() {
+        let staged = vec![
+            PathBuf::from("/home/dys/dev/a.rs"),
+            PathBuf::from("/home/dys/dev/src/b.rs"),
+        ];
+        let builder = ExecutionStringBuilder::from_selection(sel(&staged[0], SelectionType::File));
+        assert_eq!(
+            builder.exec_token("cd {selection:common-parent}"),
+            vec!["cd", "/home/dys/dev"],
+        );
+        let builder = builder.with_staged_paths(&staged);
+        assert_eq!(
+            builder.exec_token("cd {selection:common-parent}"),
+            vec!["cd", "/home/dys/dev"],
+        );
+        // paths sharing no directory give an empty string
+        let staged = vec![
+            PathBuf::from("relative/a.rs"),
+            PathBuf::from("/home/dys/b.rs"),
+        ];
+        let builder = ExecutionStringBuilder::from_selection(sel(&staged[0], SelectionType::File))
+            .with_staged_paths(&staged);
+        assert_eq!(builder.exec_token("cd {selection:common-parent}"), vec!["cd", ""]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_selection_relative_to() {
+        let staged = vec![
+            PathBuf::from("/home/dys/dev/broot/src/a.rs"),
+            PathBuf::from("/home/dys/dev/broot/src/app/b.rs"),
+            PathBuf::from("/home/dys/dev/broot/src/my file.rs"),
+        ];
+        let root = Path::new("/home/dys/dev");
+        let builder = ExecutionStringBuilder::from_selection(sel(&staged[0], SelectionType::File))
+            .with_staged_paths(&staged)
+            .with_root(Some(root));
+        // relative to the common parent, one token per path
+        assert_eq!(
+            builder.exec_token("tar -czf out.tgz {selection:relative-to=parent}"),
+            vec!["tar", "-czf", "out.tgz", "a.rs", "app/b.rs", "my file.rs"],
+        );
+        // relative to the root of the tree
+        assert_eq!(
+            builder.exec_token("rsync -R {selection:relative-to=root} dst"),
+            vec!["rsync", "-R", "broot/src/a.rs", "broot/src/app/b.rs", "broot/src/my file.rs", "dst"],
+        );
+        // in a shell command, the paths are escaped
+        assert_eq!(
+            builder.shell_exec_string("tar -czf out.tgz {selection:relative-to=parent}"),
+            "tar -czf out.tgz a.rs app/b.rs 'my file.rs'",
+        );
+        // relative to an absolute path, going up when needed
+        assert_eq!(
+            builder.exec_token("ls {selection:relative-to=/home/dys/dev/broot/src/app}"),
+            vec!["ls", "../a.rs", "b.rs", "../my file.rs"],
+        );
+        // without tree root, the paths are kept absolute
+        let builder = ExecutionStringBuilder::from_selection(sel(&staged[0], SelectionType::File))
+            .with_staged_paths(&staged);
+        assert_eq!(
+            builder.exec_token("ls {selection:relative-to=root}")[1],
+            "/home/dys/dev/broot/src/a.rs",
+        );
+        // a single selected file is relative to its parent
+        let builder = ExecutionStringBuilder::from_selection(sel(&staged[0], SelectionType::File));
+        assert_eq!(builder.exec_token("ls {selection:relative-to=parent}"), vec!["ls", "a.rs"]);
+        assert_eq!(builder.check_values("ls {selection:relative-to=parent}"), None);
+        assert_eq!(
+            builder.check_values("ls {selection:relative-to=somewhere}"),
+            Some(r#"invalid format: "relative-to=somewhere""#.to_string()),
+        );
+    }
+
+    #[test]
+    fn test_file_base() {
+        check_build_execution_from_sel(
+            "tar xzf {file} -C {file-base}",
+            "/home/dys/dl/archive.tar.gz",
+            vec![],
+            vec!["tar", "xzf", "/home/dys/dl/archive.tar.gz", "-C", "archive"],
+        );
+    }
+
+    #[test]
+    fn test_file_stem() {
+        check_build_execution_from_sel(
+            "publish {file-stem} {file-stem:slugify}",
+            "/home/dys/blog/Un Été_à Paris.md",
+            vec![],
+            vec!["publish", "Un Été_à Paris", "un-ete-a-paris"],
+        );
+        check_build_execution_from_sel(
+            "echo {file-stem:var}={file}",
+            "/home/dys/data/2021 sales.csv",
+            vec![],
+            vec!["echo", "_2021_sales=/home/dys/data/2021 sales.csv"],
+        );
+        check_build_execution_from_sel(
+            "echo {file-stem:var}",
+            "/home/dys/My-Notes (v2).draft.md",
+            vec![],
+            vec!["echo", "my_notes__v2__draft"],
+        );
+        let path = PathBuf::from("/home/dys/notes.md");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(builder.check_values("echo {file-stem:slugify} {file-stem:var}"), None);
+        assert_eq!(
+            builder.check_values("echo {file-stem:upper}"),
+            Some(r#"invalid format: "upper""#.to_string()),
+        );
+    }
+
+    #[test]
+    fn test_file_name_components() {
+        check_build_execution_from_sel(
+            "ffmpeg -i {file} out/{file-stem}.mp4",
+            "/home/dys/videos/holidays.2020.mkv",
+            vec![],
+            vec!["ffmpeg", "-i", "/home/dys/videos/holidays.2020.mkv", "out/holidays.2020.mp4"],
+        );
+        check_build_execution_from_sel(
+            "echo {file-name} {file-stem} {file-extension}",
+            "/home/dys/dl/archive.tar.gz",
+            vec![],
+            vec!["echo", "archive.tar.gz", "archive.tar", "gz"],
+        );
+        // no extension: the extension is empty
+        check_build_execution_from_sel(
+            "echo {file-name}|{file-stem}|{file-extension}|",
+            "/home/dys/dev/Makefile",
+            vec![],
+            vec!["echo", "Makefile|Makefile||"],
+        );
+        // the leading dot of a hidden file doesn't start an extension
+        check_build_execution_from_sel(
+            "echo {file-stem}|{file-extension}",
+            "/home/dys/.bashrc",
+            vec![],
+            vec!["echo", ".bashrc|"],
+        );
+        check_build_execution_from_sel(
+            "mkdir {parent}/{file-stem}_backup",
+            "/home/dys/expérimental & 试验性.tar.gz",
+            vec![],
+            vec!["mkdir", "/home/dys/expérimental & 试验性.tar_backup"],
+        );
+        check_build_execution_from_sel(
+            "echo {file-extension}",
+            "/home/dys/试验性.文本",
+            vec![],
+            vec!["echo", "文本"],
+        );
+    }
+
+    #[test]
+    fn test_file_name_components_escaping() {
+        let path = PathBuf::from("/home/dys/expérimental & 试验性.tar.gz");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(
+            builder.shell_exec_string("mv {file-name} {file-stem}.{file-extension}"),
+            "mv 'expérimental & 试验性.tar.gz' 'expérimental & 试验性.tar'.gz",
+        );
+    }
+
+    #[test]
+    fn test_shell_escaping_modes() {
+        // the paths exist, to check the quoting isn't lost when the
+        // tokens of the shell string are checked as paths
+        let dir = tempfile::tempdir().unwrap();
+        let spaced = dir.path().join("my file.txt");
+        let quoted = dir.path().join("it's.txt");
+        fs::write(&spaced, "").unwrap();
+        fs::write(&quoted, "").unwrap();
+        let dir_str = dir.path().to_string_lossy().to_string();
+        let check = |path: &PathBuf, escaping: ShellEscaping, expected: String| {
+            let builder = ExecutionStringBuilder::from_selection(sel(path, SelectionType::File))
+                .with_escaping(escaping);
+            assert_eq!(builder.shell_exec_string("edit {file}"), expected);
+            // the tokens given to Command are never quoted
+            assert_eq!(
+                builder.exec_token("edit {file}"),
+                vec!["edit".to_string(), path.to_string_lossy().to_string()],
+            );
+        };
+        check(&spaced, ShellEscaping::Posix, format!("edit '{}/my file.txt'", dir_str));
+        check(&spaced, ShellEscaping::WindowsCmd, format!(r#"edit "{}/my file.txt""#, dir_str));
+        check(&spaced, ShellEscaping::PowerShell, format!("edit '{}/my file.txt'", dir_str));
+        check(&quoted, ShellEscaping::Posix, format!(r"edit '{}/it'\''s.txt'", dir_str));
+        check(&quoted, ShellEscaping::WindowsCmd, format!(r#"edit "{}/it's.txt""#, dir_str));
+        check(&quoted, ShellEscaping::PowerShell, format!("edit '{}/it''s.txt'", dir_str));
+    }
+
+    #[test]
+    fn test_env_vars() {
+        let path = PathBuf::from("/home/dys/notes.md");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File))
+            .with_env(|name| match name {
+                "BROOT_TEST_EDITOR" => Some("kak".to_string()),
+                "BROOT_TEST_BACKUP_DIR" => Some("/mnt/my backups".to_string()),
+                _ => None,
+            });
+        assert_eq!(
+            builder.exec_token("{env:BROOT_TEST_EDITOR} {file}"),
+            vec!["kak", "/home/dys/notes.md"],
+        );
+        assert_eq!(
+            builder.exec_token("cp {file} {env:BROOT_TEST_BACKUP_DIR}"),
+            vec!["cp", "/home/dys/notes.md", "/mnt/my backups"],
+        );
+        assert_eq!(builder.exec_token("echo [{env:BROOT_TEST_UNSET}]"), vec!["echo", "[]"]);
+        assert_eq!(
+            builder.shell_exec_string("cp {file} {env:BROOT_TEST_BACKUP_DIR} {env:BROOT_TEST_UNSET}"),
+            "cp /home/dys/notes.md '/mnt/my backups'",
+        );
+        // without variable name, env is a normal argument
+        check_build_execution_from_sel(
+            "echo {env}",
+            "/home/dys/notes.md",
+            vec![("env", "prod")],
+            vec!["echo", "prod"],
+        );
+    }
+
+    #[test]
+    fn test_editor() {
+        let path = PathBuf::from("/home/dys/notes.md");
+        let tokens = |env: EnvLookup| {
+            ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File))
+                .with_env(env)
+                .exec_token("{editor} {file}")
+        };
+        assert_eq!(
+            tokens(|name| match name {
+                "VISUAL" => Some("code --wait".to_string()),
+                "EDITOR" => Some("nano".to_string()),
+                _ => None,
+            }),
+            vec!["code", "--wait", "/home/dys/notes.md"],
+        );
+        assert_eq!(
+            tokens(|name| match name {
+                "EDITOR" => Some("nano".to_string()),
+                _ => None,
+            }),
+            vec!["nano", "/home/dys/notes.md"],
+        );
+        assert_eq!(
+            tokens(|name| match name {
+                "VISUAL" => Some("hx".to_string()),
+                _ => None,
+            }),
+            vec!["hx", "/home/dys/notes.md"],
+        );
+        // an empty variable is ignored
+        assert_eq!(
+            tokens(|name| match name {
+                "VISUAL" => Some(String::new()),
+                _ => None,
+            }),
+            vec![DEFAULT_EDITOR, "/home/dys/notes.md"],
+        );
+        assert_eq!(tokens(|_| None), vec![DEFAULT_EDITOR, "/home/dys/notes.md"]);
+        let path = PathBuf::from("/home/dys/my notes.md");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File))
+            .with_env(|name| match name {
+                "EDITOR" => Some("emacs -nw".to_string()),
+                _ => None,
+            });
+        assert_eq!(
+            builder.shell_exec_string("{editor} {file}"),
+            "emacs -nw '/home/dys/my notes.md'",
+        );
+    }
+
+    #[test]
+    fn test_file_id() {
+        let id = path_id(Path::new("/home/dys/dev/broot"));
+        assert_eq!(id.len(), 8);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+        // the id doesn't depend on the run (it's not randomly seeded)
+        assert_eq!(id, "5d477bbd");
+        assert_eq!(path_id(Path::new("/home/dys/dev/broot")), id);
+        assert_ne!(path_id(Path::new("/home/dys/dev/broot2")), id);
+        check_build_execution_from_sel(
+            "cp {file} /cache/{file-id}",
+            "/home/dys/dev/broot",
+            vec![],
+            vec!["cp", "/home/dys/dev/broot", "/cache/5d477bbd"],
+        );
+    }
+
+    #[test]
+    fn test_random_tokens() {
+        let path = PathBuf::from("/home/dys/dev");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        let tokens = builder.exec_token("mk {rand} {rand} {rand:fresh} {uuid} {uuid} {uuid:fresh}");
+        let is_hex = |s: &str| s.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase());
+        let rand = &tokens[1];
+        assert_eq!(rand.len(), 8);
+        assert!(is_hex(rand));
+        assert_eq!(&tokens[2], rand);
+        assert_eq!(tokens[3].len(), 8);
+        assert_ne!(&tokens[3], rand); // may fail once in 4 billion runs
+        let uuid = &tokens[4];
+        let groups: Vec<&str> = uuid.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|g| g.len()).collect::<Vec<usize>>(),
+            vec![8, 4, 4, 4, 12],
+        );
+        assert!(groups.iter().all(|g| is_hex(g)));
+        assert!(groups[2].starts_with('4'));
+        assert!(matches!(groups[3].chars().next(), Some('8' | '9' | 'a' | 'b')));
+        assert_eq!(&tokens[5], uuid);
+        assert_ne!(&tokens[6], uuid);
+        // the values are kept when the pattern is expanded again
+        assert_eq!(builder.exec_token("{rand}")[0], *rand);
+        assert_eq!(builder.check_values("mk {rand:fresh} {uuid}"), None);
+        assert_eq!(
+            builder.check_values("mk {rand:hex}"),
+            Some(r#"invalid format: "hex""#.to_string()),
+        );
+    }
+
+    #[test]
+    fn test_response_file() {
+        let staged = vec![
+            PathBuf::from("/home/dys/src/main.c"),
+            PathBuf::from(r#"/home/dys/my "lib"\util.c"#),
+        ];
+        let builder = ExecutionStringBuilder::from_selection(sel(&staged[0], SelectionType::File))
+            .with_staged_paths(&staged);
+        assert_eq!(
+            builder.response_file_content(),
+            "\"/home/dys/src/main.c\"\n\"/home/dys/my \\\"lib\\\"\\\\util.c\"\n",
+        );
+        // before the file is written, the group isn't replaced
+        assert_eq!(
+            builder.exec_token("gcc {selection-response-file}"),
+            vec!["gcc", "{selection-response-file}"],
+        );
+        let file = builder.write_response_file().unwrap();
+        let path = file.path().to_path_buf();
+        assert_eq!(fs::read_to_string(&path).unwrap(), builder.response_file_content());
+        let builder = builder.with_response_file(path.clone());
+        assert_eq!(
+            builder.exec_token("gcc -o app {selection-response-file}"),
+            vec!["gcc".to_string(), "-o".to_string(), "app".to_string(), format!("@{}", path.to_string_lossy())],
+        );
+        // the file is removed on drop
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_os_and_arch() {
+        let path = PathBuf::from("/home/dys/dev");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::Directory));
+        let tokens = builder.exec_token("build-{os}-{arch}.sh");
+        assert!(!std::env::consts::OS.is_empty());
+        assert!(!std::env::consts::ARCH.is_empty());
+        assert_eq!(
+            tokens,
+            vec![format!("build-{}-{}.sh", std::env::consts::OS, std::env::consts::ARCH)],
+        );
+    }
+
+    #[test]
+    fn test_path_tail() {
+        check_build_execution_from_sel(
+            "echo {path-tail:2}",
+            "/home/dys/dev/broot/src/main.rs",
+            vec![],
+            vec!["echo", "src/main.rs"],
+        );
+        let path = PathBuf::from("/home/dys/dev/broot/src/main.rs");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(builder.check_values("echo {path-tail:2}"), None);
+        assert_eq!(
+            builder.check_values("echo {path-tail:0}"),
+            Some(r#"invalid format: "0""#.to_string()),
+        );
+        assert!(builder.check_values("echo {path-tail:abc}").is_some());
+        assert!(builder.check_values("echo {path-tail}").is_some());
+    }
+
+    #[test]
+    fn test_file_meta() {
+        let dir = tempfile::tempdir().unwrap();
+        // the name of the file can't inject commands nor split
+        // the value in several arguments
+        let strange = dir.path().join("a; echo $(id) b");
+        fs::write(&strange, "").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(sel(&strange, SelectionType::File));
+        let meta = path::file_meta(&strange).unwrap();
+        assert_eq!(
+            builder.shell_exec_string("echo {file-meta}"),
+            format!("echo '{}'", meta),
+        );
+        assert_eq!(builder.exec_token("echo {file-meta}"), vec!["echo".to_string(), meta]);
+    }
+
+    #[test]
+    fn test_size_delta() {
+        let dir = tempfile::tempdir().unwrap();
+        let big = dir.path().join("big.txt");
+        let small = dir.path().join("small.txt");
+        let same = dir.path().join("same.txt");
+        fs::write(&big, "0123456789").unwrap();
+        fs::write(&small, "0123").unwrap();
+        fs::write(&same, "abcdefghij").unwrap();
+        let delta = |path: &Path, other: Option<PathBuf>| {
+            ExecutionStringBuilder::from_invocation(&None, sel(path, SelectionType::File), &other, &None)
+                .exec_token("echo {size-delta}")
+        };
+        assert_eq!(delta(&big, Some(small.clone())), vec!["echo", "+6"]);
+        assert_eq!(delta(&small, Some(big.clone())), vec!["echo", "-6"]);
+        assert_eq!(delta(&big, Some(same)), vec!["echo", "0"]);
+        // without other panel, or file, the value is empty
+        assert_eq!(delta(&big, None), vec!["echo", ""]);
+        assert_eq!(delta(&big, Some(dir.path().join("missing"))), vec!["echo", ""]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "content").unwrap();
+        let nlink = |path: &Path| {
+            ExecutionStringBuilder::from_invocation(&None, sel(path, SelectionType::File), &None, &None)
+                .exec_token("echo {nlink}")
+        };
+        assert_eq!(nlink(&file), vec!["echo", "1"]);
+        fs::hard_link(&file, dir.path().join("link.txt")).unwrap();
+        assert_eq!(nlink(&file), vec!["echo", "2"]);
+        assert_eq!(nlink(&dir.path().join("link.txt")), vec!["echo", "2"]);
+        // a symbolic link doesn't count
+        std::os::unix::fs::symlink(&file, dir.path().join("sym.txt")).unwrap();
+        assert_eq!(nlink(&file), vec!["echo", "2"]);
+        assert_eq!(nlink(&dir.path().join("missing")), vec!["echo", ""]);
+    }
+
+    #[test]
+    fn test_match_count() {
+        let path = PathBuf::from("/home/dys/dev");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::Directory));
+        assert_eq!(builder.exec_token("echo {match-count}"), vec!["echo", "0"]);
+        let builder = builder.with_match_count(42);
+        assert_eq!(builder.exec_token("echo {match-count} matches"), vec!["echo", "42", "matches"]);
+    }
+
+    #[test]
+    fn test_search_kind() {
+        let path = PathBuf::from("/home/dys/dev");
+        let kind = |search_mode| {
+            ExecutionStringBuilder::from_selection(sel(&path, SelectionType::Directory))
+                .with_search_mode(search_mode)
+                .exec_token("echo kind={search-kind}")
+        };
+        assert_eq!(kind(None), vec!["echo", "kind="]);
+        assert_eq!(kind(Some(SearchMode::NameFuzzy)), vec!["echo", "kind=fuzzy"]);
+        assert_eq!(kind(Some(SearchMode::PathFuzzy)), vec!["echo", "kind=fuzzy"]);
+        assert_eq!(kind(Some(SearchMode::NameRegex)), vec!["echo", "kind=regex"]);
+        assert_eq!(kind(Some(SearchMode::PathRegex)), vec!["echo", "kind=regex"]);
+        assert_eq!(kind(Some(SearchMode::NameExact)), vec!["echo", "kind=name"]);
+        assert_eq!(kind(Some(SearchMode::PathExact)), vec!["echo", "kind=name"]);
+        assert_eq!(kind(Some(SearchMode::ContentExact)), vec!["echo", "kind=content"]);
+        assert_eq!(kind(Some(SearchMode::ContentRegex)), vec!["echo", "kind=content"]);
+    }
+
+    #[test]
+    fn test_relative_file() {
+        let current_dir = std::env::current_dir().unwrap();
+        let file = current_dir.join("src").join("verb").join("mod.rs");
+        let file = file.to_string_lossy();
+        check_build_execution_from_sel(
+            "cat {relative-file:slash}",
+            &file,
+            vec![],
+            vec!["cat", "src/verb/mod.rs"],
+        );
+        let native = PathBuf::from("src").join("verb").join("mod.rs");
+        let native = native.to_string_lossy();
+        check_build_execution_from_sel(
+            "cat {relative-file:native}",
+            &file,
+            vec![],
+            vec!["cat", &native],
+        );
+        check_build_execution_from_sel(
+            "cat {relative-file}",
+            &current_dir.to_string_lossy(),
+            vec![],
+            vec!["cat", "."],
+        );
+    }
+
+    #[test]
+    fn test_relative_file_max_depth() {
+        let current_dir = std::env::current_dir().unwrap();
+        let deep = current_dir.join("src").join("verb").join("mod.rs");
+        check_build_execution_from_sel(
+            "echo {relative-file:maxdepth=2}",
+            &deep.to_string_lossy(),
+            vec![],
+            vec!["echo", &PathBuf::from("…").join("verb").join("mod.rs").to_string_lossy()],
+        );
+        let shallow = current_dir.join("Cargo.toml");
+        check_build_execution_from_sel(
+            "echo {relative-file:maxdepth=2}",
+            &shallow.to_string_lossy(),
+            vec![],
+            vec!["echo", "Cargo.toml"],
+        );
+        let builder = ExecutionStringBuilder::from_selection(sel(&shallow, SelectionType::File));
+        assert_eq!(builder.check_values("echo {relative-file:maxdepth=2}"), None);
+        assert_eq!(
+            builder.check_values("echo {relative-file:maxdepth=0}"),
+            Some(r#"invalid format: "maxdepth=0""#.to_string()),
+        );
+        assert!(builder.check_values("echo {file:maxdepth=two}").is_some());
+        assert!(builder.check_values("echo {file:maxdepth=}").is_some());
+    }
+
+    #[test]
+    fn test_abs_and_canonical_formats() {
+        let current_dir = std::env::current_dir().unwrap();
+        check_build_execution_from_sel(
+            "ls {file:abs}",
+            "src/main.rs",
+            vec![],
+            vec!["ls", &current_dir.join("src/main.rs").to_string_lossy()],
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        std::fs::write(&target, "content").unwrap();
+        let link = dir.path().join("link.txt");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_file(&target, &link).unwrap();
+        let link_str = link.to_string_lossy();
+        check_build_execution_from_sel(
+            "ls {file} {file:abs}",
+            &link_str,
+            vec![],
+            vec!["ls", &link_str, &link_str],
+        );
+        check_build_execution_from_sel(
+            "ls {file:canonical}",
+            &link_str,
+            vec![],
+            vec!["ls", &target.canonicalize().unwrap().to_string_lossy()],
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_long_path() {
+        let long = format!("/home/dys/{}/notes.txt", "a".repeat(300));
+        check_build_execution_from_sel(
+            "echo {file:long-path}",
+            &long,
+            vec![],
+            vec!["echo", &long],
+        );
+        check_build_execution_from_sel(
+            "echo {file:long-path}",
+            "/home/dys/notes.txt",
+            vec![],
+            vec!["echo", "/home/dys/notes.txt"],
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_parents_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let real_dir = root.join("real");
+        std::fs::create_dir(&real_dir).unwrap();
+        std::fs::write(real_dir.join("target.txt"), "content").unwrap();
+        std::os::unix::fs::symlink(real_dir.join("target.txt"), real_dir.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(&real_dir, root.join("linked-dir")).unwrap();
+        // the symlinked parent is resolved, the final link is kept
+        let link = root.join("linked-dir").join("link.txt");
+        check_build_execution_from_sel(
+            "ls {file:resolve-parents} {file:canonical}",
+            &link.to_string_lossy(),
+            vec![],
+            vec![
+                "ls",
+                &real_dir.join("link.txt").to_string_lossy(),
+                &real_dir.join("target.txt").to_string_lossy(),
+            ],
+        );
+    }
+
+    /// the replacing done before patterns were parsed, kept to
+    /// check the parsed patterns build the same strings
+    fn regex_fill(builder: &ExecutionStringBuilder<'_>, s: &str, escape: bool) -> String {
+        GROUP
+            .replace_all(s, |ec: &regex::Captures<'_>| {
+                builder
+                    .get_raw_replacement(&ec[1], ec.get(2).map(|m| m.as_str()), escape)
+                    .unwrap_or_else(|| ec[0].to_string())
+            })
+            .to_string()
+    }
+
+    #[test]
+    fn test_parsed_patterns_match_regex_replacing() {
+        let path = PathBuf::from("/home/dys/my dev/notes.tar.gz");
+        let staged = vec![PathBuf::from("/tmp/a b.rs"), PathBuf::from("/tmp/c.toml")];
+        let patterns = [
+            "vi {file}",
+            "cp {file} /backup/{name:fs-safe}",
+            "mv {file-name} {file-stem}.{file-extension}",
+            "echo {file-name}|{file-stem}|{file-extension}|",
+            "tar xzf {file} -C {file-base}",
+            r#"mv "{file}" "{directory}/a b" {parent}"#,
+            "head -n {count:int} {file}",
+            "echo {unknown} {} {a:b:c} {file:shell}",
+            "mkdir {parent}/{file-stem}_backup",
+        ];
+        let mut map = FnvHashMap::default();
+        map.insert("name".to_string(), "my: name".to_string());
+        map.insert("count".to_string(), "12".to_string());
+        for with_staged in [false, true] {
+            let mut builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+            if with_staged {
+                builder = builder.with_staged_paths(&staged);
+            }
+            builder.invocation_values = Some(map.clone());
+            for pattern in patterns {
+                let tokens: Vec<String> = split_unquoted_whitespace(pattern)
+                    .unwrap_quotes(true)
+                    .map(|token| regex_fill(&builder, token, false))
+                    .collect();
+                assert_eq!(builder.exec_token(pattern), tokens, "pattern: {:?}", pattern);
+                let parsed = ExecPattern::from(pattern);
+                assert_eq!(
+                    builder.pattern_shell_exec_string(&parsed),
+                    builder.shell_exec_string(pattern),
+                );
+                assert_eq!(
+                    builder.fill(parsed.segments(), true),
+                    regex_fill(&builder, pattern, true),
+                    "pattern: {:?}", pattern,
+                );
+                assert_eq!(builder.path(pattern), PathBuf::from(regex_fill(&builder, pattern, false)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_home_relative_group() {
+        let home = match path::home_dir() {
+            Some(home) => home,
+            None => return,
+        };
+        let inside = home.join(".config/my app/conf.toml");
+        let builder = ExecutionStringBuilder::from_selection(sel(&inside, SelectionType::File));
+        assert_eq!(builder.check_values("stow {home-relative}"), None);
+        assert_eq!(
+            builder.exec_token("stow {home-relative}"),
+            vec!["stow", &PathBuf::from(".config/my app/conf.toml").to_string_lossy()],
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            builder.shell_exec_string("stow {home-relative}"),
+            "stow '.config/my app/conf.toml'",
+        );
+        let outside = PathBuf::from("/not-a-home/notes.md");
+        if outside.starts_with(&home) {
+            return;
+        }
+        let builder = ExecutionStringBuilder::from_selection(sel(&outside, SelectionType::File));
+        assert_eq!(
+            builder.check_values("stow {home-relative}"),
+            Some("/not-a-home/notes.md isn't in the home directory".to_string()),
+        );
+        // the group isn't replaced
+        assert_eq!(
+            builder.exec_token("stow {home-relative}"),
+            vec!["stow", "{home-relative}"],
+        );
+    }
+
+    #[test]
+    fn test_preview_string() {
+        // the paths don't exist
+        let path = PathBuf::from("/not-a-dir/my photos/pic.jpg");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File))
+            .with_escaping(ShellEscaping::Posix);
+        assert_eq!(
+            builder.preview_string("cp {file}   {parent}/{file-stem}_new.{file-extension}"),
+            "cp '/not-a-dir/my photos/pic.jpg' '/not-a-dir/my photos'/pic_new.jpg",
+        );
+        assert_eq!(
+            builder.preview_string("send {file:base64} {file:canonical} {mtime-ago} {file:unique}"),
+            "send {file:base64} {file:canonical} {mtime-ago} {file:unique}",
+        );
+        // with existing paths, the preview is the executed command
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a b.txt");
+        std::fs::write(&file, "content").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(sel(&file, SelectionType::File))
+            .with_escaping(ShellEscaping::Posix);
+        for pattern in &[
+            "cat {file}",
+            "cp {file} \"{parent}/backup dir\"",
+            "mv {file-name} {file-stem}.bak",
+            "echo {file:abs}  {directory}",
+        ] {
+            assert_eq!(
+                builder.preview_string(pattern),
+                builder.shell_exec_string(pattern),
+                "pattern: {:?}", pattern,
+            );
+        }
+    }
+
+    #[test]
+    fn test_children_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/app")).unwrap();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        for name in &["README.md", ".gitignore", "Cargo.toml", "src/main.rs"] {
+            std::fs::write(root.join(name), "").unwrap();
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("src"), root.join("link-to-dir")).unwrap();
+            std::os::unix::fs::symlink(root.join("README.md"), root.join("link-to-file")).unwrap();
+        }
+        let pattern = "echo {dir-count} {plain-file-count}";
+        // on a directory, its children are counted
+        let builder = ExecutionStringBuilder::from_selection(sel(root, SelectionType::Directory));
+        assert_eq!(builder.exec_token(pattern), vec!["echo", "2", "3"]);
+        // on a file, the ones of its directory
+        let file = root.join("src/main.rs");
+        let builder = ExecutionStringBuilder::from_selection(sel(&file, SelectionType::File));
+        assert_eq!(builder.exec_token(pattern), vec!["echo", "1", "1"]);
+        let empty = root.join("src/app");
+        let builder = ExecutionStringBuilder::from_selection(sel(&empty, SelectionType::Directory));
+        assert_eq!(builder.exec_token(pattern), vec!["echo", "0", "0"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_env_collapse_format() {
+        let home = match std::env::var_os("HOME").map(PathBuf::from) {
+            Some(home) if home.is_absolute() && home != Path::new("/") => home,
+            _ => return,
+        };
+        let collapsed = path::env_collapse(&home.join("my notes.md"), &path::env_collapse_vars())
+            .map(|(name, _)| name);
+        if collapsed != Some("HOME") {
+            return; // an XDG variable is set to a directory of the home
+        }
+        let file = home.join("my notes.md");
+        let builder = ExecutionStringBuilder::from_selection(sel(&file, SelectionType::File))
+            .with_escaping(ShellEscaping::Posix);
+        assert_eq!(
+            builder.exec_token("echo {file:env-collapse}"),
+            vec!["echo", "${HOME}/my notes.md"],
+        );
+        assert_eq!(
+            builder.shell_exec_string("echo {file:env-collapse}"),
+            "echo ${HOME}'/my notes.md'",
+        );
+        let outside = PathBuf::from("/not-a-home/a b.txt");
+        let builder = ExecutionStringBuilder::from_selection(sel(&outside, SelectionType::File))
+            .with_escaping(ShellEscaping::Posix);
+        assert_eq!(
+            builder.exec_token("echo {file:env-collapse}"),
+            vec!["echo", "/not-a-home/a b.txt"],
+        );
+        assert_eq!(
+            builder.shell_exec_string("echo {file:env-collapse}"),
+            "echo '/not-a-home/a b.txt'",
+        );
+    }
+
+    #[test]
+    fn test_git_author_group() {
+        // a file outside any repository
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.md");
+        fs::write(&path, "one\ntwo\n").unwrap();
+        let sel = Selection {
+            path: &path,
+            line: 2,
+            stype: SelectionType::File,
+            is_exe: false,
+        };
+        let builder = ExecutionStringBuilder::from_selection(sel);
+        if cfg!(feature = "git-blame") {
+            assert_eq!(builder.check_values("echo {git-author:{line}}"), None);
+        } else {
+            assert_eq!(
+                builder.check_values("echo {git-author}"),
+                Some("Git blame feature not enabled at compilation".to_string()),
+            );
+        }
+        assert_eq!(
+            builder.exec_token("echo {git-author:{line}} {git-author:1} {git-author}"),
+            vec!["echo", "", "", ""],
+        );
+        assert_eq!(
+            builder.exec_token("echo {git-author:first}"),
+            vec!["echo", "invalid line: \"first\""],
+        );
+        assert_eq!(
+            builder.preview_string("echo {git-author:{line}}"),
+            "echo {git-author:{line}}",
+        );
+    }
+
+    #[test]
+    fn test_regex_escape_format() {
+        let path = PathBuf::from("/home/dys/notes (old)/[draft].v2.md");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File))
+            .with_escaping(ShellEscaping::Posix);
+        let escaped = r"/home/dys/notes \(old\)/\[draft\]\.v2\.md";
+        assert_eq!(
+            builder.exec_token("rg {file:regex-escape}"),
+            vec!["rg", escaped],
+        );
+        // the escaped value matches the path, and only it
+        let re = regex::Regex::new(&format!("^{}$", escaped)).unwrap();
+        assert!(re.is_match(&path.to_string_lossy()));
+        assert!(!re.is_match("/home/dys/notes (old)/[draft]xv2.md"));
+        assert_eq!(
+            builder.shell_exec_string("rg {parent:regex-escape}"),
+            r"rg '/home/dys/notes \(old\)'",
+        );
+    }
+
+    #[test]
+    fn test_name_formats() {
+        let path = PathBuf::from("/home/dys/Music/Live at Été.FLAC");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File))
+            .with_escaping(ShellEscaping::Posix);
+        assert_eq!(
+            builder.exec_token("ffmpeg -i {file} {file:stem}.mp3"),
+            vec!["ffmpeg", "-i", "/home/dys/Music/Live at Été.FLAC", "Live at Été.mp3"],
+        );
+        assert_eq!(
+            builder.shell_exec_string("echo {file:name} {file:extension} {parent:name}"),
+            "echo 'Live at Été.FLAC' FLAC Music",
+        );
+        assert_eq!(
+            builder.exec_token("mv {file} {file:lowercase}"),
+            vec!["mv", "/home/dys/Music/Live at Été.FLAC", "/home/dys/music/live at été.flac"],
+        );
+        // a path without extension
+        let path = PathBuf::from("/home/dys/Makefile");
+        let builder = ExecutionStringBuilder::from_selection(sel(&path, SelectionType::File));
+        assert_eq!(
+            builder.exec_token("echo {file:stem} {file:extension}"),
+            vec!["echo", "Makefile", ""],
+        );
+    }
+
+    #[test]
+    fn test_text_formats() {
+        check_build_execution_from_sel(
+            "git checkout -b {name:trim}",
+            "/home/dys/dev",
+            vec![("name", "  Fix-Panel ")],
+            vec!["git", "checkout", "-b", "Fix-Panel"],
+        );
+        check_build_execution_from_sel(
+            "git checkout -b {name:lowercase}",
+            "/home/dys/dev",
+            vec![("name", "Fix-Panel")],
+            vec!["git", "checkout", "-b", "fix-panel"],
+        );
+        check_build_execution_from_sel(
+            "echo {name:uppercase}",
+            "/home/dys/dev",
+            vec![("name", "todo")],
+            vec!["echo", "TODO"],
+        );
     }
 
 }
@@ -2,17 +2,47 @@ use {
     super::*,
     crate::{
         app::*,
+        clipboard,
         display::W,
         errors::ProgramError,
-        launchable::Launchable,
-        path,
+        journal::{self, Operation},
+        launchable::{Launchable, ProgramEnv, StdinMode},
+        path::{self, ShellEscaping},
+        stage::MtimeSnapshot,
+        tail::TailState,
+        tree::{PathSet, TreeOptions},
     },
+    fnv::FnvHashMap,
     std::{
-        fs::OpenOptions,
-        io::Write,
+        fs::{self, OpenOptions},
+        io::{self, Write},
+        path::{Path, PathBuf},
+        process::{Command, Stdio},
+        sync::atomic::{AtomicUsize, Ordering},
     },
+    tempfile::TempPath,
 };
 
+/// the number of successful executions of a toggling verb,
+/// which decides which of its patterns is the next one
+#[derive(Debug, Default)]
+struct ToggleCount(AtomicUsize);
+
+impl ToggleCount {
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+    fn incr(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Clone for ToggleCount {
+    fn clone(&self) -> Self {
+        Self(AtomicUsize::new(self.get()))
+    }
+}
+
 
 /// Definition of how the user input should be interpreted
 /// to be executed in an external command.
@@ -29,7 +59,9 @@ pub struct ExternalExecution {
     /// * {other-panel-file}
     /// * {other-panel-directory}
     /// * {other-panel-parent}
-    pub exec_pattern: String,
+    ///
+    /// It's parsed when the verb is built.
+    pub exec_pattern: ExecPattern,
 
     /// how the external process must be launched
     pub exec_mode: ExternalExecutionMode,
@@ -37,6 +69,72 @@ pub struct ExternalExecution {
     /// whether the working dir of the external process must be set
     /// to the current directory
     pub set_working_dir: bool,
+
+    /// when set, the pattern of the directory the process must be
+    /// launched in, which is created when it doesn't exist
+    pub ensure_working_dir: Option<String>,
+
+    /// whether the output of the process must be written in a
+    /// temporary file followed in a new panel
+    pub tail_in_panel: bool,
+
+    /// whether the output of the process must be captured and
+    /// copied to the clipboard
+    pub output_to_clipboard: bool,
+
+    /// whether the output of the process must be captured and
+    /// read as a list of paths on which the tree is filtered
+    pub output_to_filter: bool,
+
+    /// the changes to broot's environment for the process
+    pub env: ProgramEnv,
+
+    /// when set, the paths of `{selection}` are split in batches
+    /// of this size, and the process is launched once per batch
+    pub batch_size: Option<usize>,
+
+    /// when set, the verb alternates between `exec_pattern`
+    /// and this pattern on successive executions
+    pub toggle_exec_pattern: Option<ExecPattern>,
+
+    toggle_count: ToggleCount,
+
+    /// the patterns replacing `exec_pattern` when the selection
+    /// is of the given type (file or directory)
+    pub stype_exec_patterns: Vec<(SelectionType, ExecPattern)>,
+
+    /// when set, the file where executions are logged, instead
+    /// of the global verb log
+    pub log_file: Option<PathBuf>,
+
+    /// how paths are quoted in the command written for the shell
+    pub escaping: ShellEscaping,
+
+    /// whether a spinner is displayed while a background
+    /// process runs
+    pub show_progress: bool,
+
+    /// when set, the pattern of the path of the file produced by
+    /// the process, which is selected after a successful execution
+    pub select_output: Option<String>,
+
+    /// whether, after a successful execution, the stage must be
+    /// made of the paths of `{selection}` the process modified
+    pub restage_modified: bool,
+
+    /// whether the command is run by the user's shell, as a login
+    /// shell, so that the aliases and functions of its rc files
+    /// are available
+    pub login_shell: bool,
+
+    /// what the standard input of a process launched in the
+    /// foreground is connected to (processes in background or
+    /// with a captured output never read broot's input)
+    pub stdin: StdinMode,
+
+    /// whether a non zero exit code of a process launched without
+    /// leaving broot is an error (it's just logged otherwise)
+    pub fail_on_exit_code: bool,
 }
 
 impl ExternalExecution {
@@ -45,9 +143,100 @@ impl ExternalExecution {
         exec_mode: ExternalExecutionMode,
     ) -> Self {
         Self {
-            exec_pattern,
+            exec_pattern: ExecPattern::from(exec_pattern),
             exec_mode,
             set_working_dir: false,
+            ensure_working_dir: None,
+            tail_in_panel: false,
+            output_to_clipboard: false,
+            output_to_filter: false,
+            env: ProgramEnv::default(),
+            batch_size: None,
+            toggle_exec_pattern: None,
+            toggle_count: ToggleCount::default(),
+            stype_exec_patterns: Vec::new(),
+            log_file: None,
+            escaping: ShellEscaping::default(),
+            show_progress: false,
+            select_output: None,
+            restage_modified: false,
+            login_shell: false,
+            stdin: StdinMode::default(),
+            fail_on_exit_code: false,
+        }
+    }
+
+    pub fn with_escaping(mut self, escaping: ShellEscaping) -> Self {
+        self.escaping = escaping;
+        self
+    }
+
+    pub fn with_log_file(mut self, log_file: Option<PathBuf>) -> Self {
+        self.log_file = log_file;
+        self
+    }
+
+    pub fn with_toggle_exec_pattern(mut self, pattern: Option<String>) -> Self {
+        self.toggle_exec_pattern = pattern.map(ExecPattern::from);
+        self
+    }
+
+    /// return the pattern of the next execution: for a toggling
+    /// verb, `exec_pattern` and `toggle_exec_pattern` alternate
+    pub fn current_exec_pattern(&self) -> &ExecPattern {
+        match &self.toggle_exec_pattern {
+            Some(pattern) if self.toggle_count.get() % 2 == 1 => pattern,
+            _ => &self.exec_pattern,
+        }
+    }
+
+    /// set the pattern executed instead of `exec_pattern` when the
+    /// selection is of the given type
+    pub fn with_stype_exec_pattern(mut self, stype: SelectionType, pattern: Option<String>) -> Self {
+        if let Some(pattern) = pattern {
+            self.stype_exec_patterns.retain(|(t, _)| *t != stype);
+            self.stype_exec_patterns.push((stype, ExecPattern::from(pattern)));
+        }
+        self
+    }
+
+    /// return the pattern to execute for a selection of the given type
+    pub fn exec_pattern_for(&self, stype: SelectionType) -> &ExecPattern {
+        self.stype_exec_patterns
+            .iter()
+            .find(|(t, _)| *t == stype)
+            .map_or(&self.exec_pattern, |(_, pattern)| pattern)
+    }
+
+    /// return all the patterns the verb may execute
+    pub fn exec_patterns(&self) -> impl Iterator<Item = &ExecPattern> {
+        std::iter::once(&self.exec_pattern)
+            .chain(self.toggle_exec_pattern.iter())
+            .chain(self.stype_exec_patterns.iter().map(|(_, pattern)| pattern))
+    }
+
+    /// whether the execution pattern needs the paths of the
+    /// selection to be written in a response file
+    pub fn uses_response_file(&self) -> bool {
+        self.exec_patterns().any(|p| p.contains("{selection-response-file}"))
+    }
+
+    pub fn with_batch_size(mut self, batch_size: Option<usize>) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// return the tokens of the processes to launch: one
+    /// set of tokens per batch if there's a batch size,
+    /// else just one
+    fn batch_tokens(&self, builder: &ExecutionStringBuilder<'_>) -> Vec<Vec<String>> {
+        match self.batch_size {
+            Some(batch_size) => builder
+                .batched(batch_size)
+                .iter()
+                .map(|batch| self.exec_tokens(batch))
+                .collect(),
+            None => vec![self.exec_tokens(builder)],
         }
     }
 
@@ -58,16 +247,205 @@ impl ExternalExecution {
         self
     }
 
+    pub fn with_ensure_working_dir(mut self, pattern: Option<String>) -> Self {
+        self.ensure_working_dir = pattern;
+        self
+    }
+
+    /// return the directory the process must be launched in, if any.
+    /// The directory of `ensure_working_dir` is created when needed,
+    /// a failure preventing the execution.
+    pub fn working_dir(
+        &self,
+        builder: &ExecutionStringBuilder<'_>,
+    ) -> io::Result<Option<PathBuf>> {
+        if let Some(pattern) = &self.ensure_working_dir {
+            let dir = builder.path(pattern);
+            if !dir.is_dir() {
+                fs::create_dir_all(&dir)?;
+            }
+            Ok(Some(dir))
+        } else if self.set_working_dir {
+            Ok(Some(path::closest_dir(builder.sel.path)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn with_tail_in_panel(mut self, b: Option<bool>) -> Self {
+        if let Some(b) = b {
+            self.tail_in_panel = b;
+        }
+        self
+    }
+
+    pub fn with_show_progress(mut self, b: Option<bool>) -> Self {
+        if let Some(b) = b {
+            self.show_progress = b;
+        }
+        self
+    }
+
+    pub fn with_select_output(mut self, pattern: Option<String>) -> Self {
+        self.select_output = pattern;
+        self
+    }
+
+    /// return the path of the output of the process, when the verb
+    /// has a `select_output`. A relative path is relative to the
+    /// directory of the selection.
+    fn output_path(&self, builder: &ExecutionStringBuilder<'_>) -> Option<PathBuf> {
+        self.select_output.as_ref().map(|pattern| {
+            let output = builder.path(pattern);
+            if output.is_relative() {
+                path::closest_dir(builder.sel.path).join(output)
+            } else {
+                output
+            }
+        })
+    }
+
+    pub fn with_restage_modified(mut self, b: Option<bool>) -> Self {
+        if let Some(b) = b {
+            self.restage_modified = b;
+        }
+        self
+    }
+
+    pub fn with_stdin(mut self, stdin: StdinMode) -> Self {
+        self.stdin = stdin;
+        self
+    }
+
+    pub fn with_login_shell(mut self, b: Option<bool>) -> Self {
+        if let Some(b) = b {
+            self.login_shell = b;
+        }
+        self
+    }
+
+    /// return the tokens of the process to launch: the tokens of the
+    /// pattern, or, with `login_shell`, the user's shell running the
+    /// command line written for it
+    fn exec_tokens(&self, builder: &ExecutionStringBuilder<'_>) -> Vec<String> {
+        if self.login_shell {
+            login_shell_tokens(
+                &user_shell(),
+                builder.pattern_shell_command(&self.exec_pattern),
+            )
+        } else {
+            builder.pattern_exec_token(&self.exec_pattern)
+        }
+    }
+
+    /// return this execution with the groups of its pattern replaced,
+    /// so that it doesn't depend on the selection when it's executed.
+    /// A non zero exit code of the process is then an error.
+    pub fn resolved(&self, builder: ExecutionStringBuilder<'_>) -> Result<Self, String> {
+        let builder = builder.with_escaping(self.escaping);
+        if let Some(err) = builder.pattern_check_values(&self.exec_pattern) {
+            return Err(err);
+        }
+        Ok(Self {
+            exec_pattern: ExecPattern::from_literal_tokens(self.exec_tokens(&builder)),
+            login_shell: false,
+            fail_on_exit_code: true,
+            ..self.clone()
+        })
+    }
+
+    pub fn with_output_to_clipboard(mut self, b: Option<bool>) -> Self {
+        if let Some(b) = b {
+            self.output_to_clipboard = b;
+        }
+        self
+    }
+
+    pub fn with_output_to_filter(mut self, b: Option<bool>) -> Self {
+        if let Some(b) = b {
+            self.output_to_filter = b;
+        }
+        self
+    }
+
+    pub fn with_env(
+        mut self,
+        clean_env: Option<bool>,
+        vars: Option<&FnvHashMap<String, String>>,
+    ) -> Self {
+        if let Some(b) = clean_env {
+            self.env.clear = b;
+        }
+        if let Some(vars) = vars {
+            self.env.vars = vars
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            self.env.vars.sort();
+        }
+        self
+    }
+
     pub fn to_cmd_result(
         &self,
         w: &mut W,
         builder: ExecutionStringBuilder<'_>,
         con: &AppContext,
     ) -> Result<AppStateCmdResult, ProgramError> {
+        let builder = builder.with_escaping(self.escaping);
+        if self.toggle_exec_pattern.is_some() {
+            // we execute the current pattern as a simple verb, and we
+            // switch to the other pattern only when it succeeded
+            let execution = Self {
+                exec_pattern: self.current_exec_pattern().clone(),
+                toggle_exec_pattern: None,
+                ..self.clone()
+            };
+            let result = execution.to_cmd_result(w, builder, con)?;
+            if !matches!(result, AppStateCmdResult::DisplayError(_)) {
+                self.toggle_count.incr();
+            }
+            return Ok(result);
+        }
+        if !self.stype_exec_patterns.is_empty() {
+            // the pattern is chosen according to the type of the selection
+            let execution = Self {
+                exec_pattern: self.exec_pattern_for(builder.sel.stype).clone(),
+                stype_exec_patterns: Vec::new(),
+                ..self.clone()
+            };
+            return execution.to_cmd_result(w, builder, con);
+        }
+        if let Some(err) = builder.pattern_check_values(&self.exec_pattern) {
+            return Ok(AppStateCmdResult::DisplayError(err));
+        }
+        // the response file is removed when dropped, after the execution
+        let response_file = if self.uses_response_file() {
+            Some(builder.write_response_file()?)
+        } else {
+            None
+        };
+        let builder = match &response_file {
+            Some(file) => builder.with_response_file(file.path().to_path_buf()),
+            None => builder,
+        };
         if self.exec_mode.is_from_shell() {
             self.exec_from_shell_cmd_result(builder, con)
+        } else if self.output_to_clipboard {
+            self.clipboard_cmd_result(builder, con)
+        } else if self.output_to_filter {
+            self.filter_cmd_result(builder)
         } else {
-            self.exec_cmd_result(w, builder)
+            let log_file = self.log_file.as_deref().or(con.verb_log.as_deref());
+            if self.tail_in_panel {
+                self.tail_cmd_result(builder, log_file)
+            } else if self.exec_mode.is_capture_output() {
+                self.capture_cmd_result(builder, log_file)
+            } else if self.exec_mode.is_background() {
+                self.background_cmd_result(builder, log_file)
+            } else {
+                self.exec_cmd_result(w, builder, log_file)
+            }
         }
     }
 
@@ -81,7 +459,7 @@ impl ExternalExecution {
             // Broot was probably launched as br.
             // the whole command is exported in the passed file
             let f = OpenOptions::new().append(true).open(export_path)?;
-            writeln!(&f, "{}", builder.shell_exec_string(&self.exec_pattern))?;
+            writeln!(&f, "{}", builder.pattern_shell_exec_string(&self.exec_pattern))?;
             Ok(AppStateCmdResult::Quit)
         } else if let Some(ref export_path) = con.launch_args.file_export_path {
             // old version of the br function: only the file is exported
@@ -97,6 +475,262 @@ impl ExternalExecution {
         }
     }
 
+    /// run the process, wait for its end, and return its
+    /// standard output, trimmed
+    fn capture_output(
+        &self,
+        builder: &ExecutionStringBuilder<'_>,
+    ) -> Result<String, ProgramError> {
+        let working_dir = self.working_dir(builder)?;
+        let launchable = Launchable::program(self.exec_tokens(builder), working_dir)?;
+        let (exe, args, working_dir) = match launchable {
+            Launchable::Program { exe, args, working_dir, .. } => (exe, args, working_dir),
+            _ => unreachable!(),
+        };
+        let mut command = Command::new(&exe);
+        command
+            .args(args.iter())
+            .stdin(Stdio::null())
+            .stderr(Stdio::null());
+        if let Some(working_dir) = working_dir {
+            command.current_dir(working_dir);
+        }
+        self.env.apply_to(&mut command);
+        info!("Executing with captured output: {:?}", &command);
+        let output = command
+            .output()
+            .map_err(|source| ProgramError::LaunchError {
+                program: exe,
+                source,
+            })?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// run the process and give its captured output to the `copy` function
+    fn copy_output_with<F>(
+        &self,
+        builder: &ExecutionStringBuilder<'_>,
+        copy: F,
+    ) -> Result<AppStateCmdResult, ProgramError>
+    where
+        F: FnOnce(String) -> Result<(), String>,
+    {
+        let output = self.capture_output(builder)?;
+        Ok(match copy(output) {
+            Ok(()) => AppStateCmdResult::RefreshState { clear_cache: true },
+            Err(e) => AppStateCmdResult::DisplayError(e),
+        })
+    }
+
+    /// run the process and copy its output to the clipboard
+    fn clipboard_cmd_result(
+        &self,
+        builder: ExecutionStringBuilder<'_>,
+        con: &AppContext,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        self.copy_output_with(&builder, |output| {
+            clipboard::copy(&output, con.osc52_clipboard)
+                .map_err(|e| format!("{} while copying output", e))
+        })
+    }
+
+    /// run the process and filter the tree on the paths it listed,
+    /// one per line, relative paths being relative to the working
+    /// directory of the process
+    fn filter_cmd_result(
+        &self,
+        builder: ExecutionStringBuilder<'_>,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let base = match self.working_dir(&builder)? {
+            Some(working_dir) => working_dir,
+            None => std::env::current_dir()?,
+        };
+        let output = self.capture_output(&builder)?;
+        Ok(AppStateCmdResult::FilterPaths(PathSet::from_output(&output, &base)))
+    }
+
+    /// launch the process in background with its output written in
+    /// a temporary file, and open a panel following this file
+    fn tail_cmd_result(
+        &self,
+        builder: ExecutionStringBuilder<'_>,
+        log_file: Option<&Path>,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let tokens = self.exec_tokens(&builder);
+        let working_dir = match self.working_dir(&builder) {
+            Ok(working_dir) => working_dir,
+            Err(e) => {
+                return Ok(AppStateCmdResult::DisplayError(
+                    format!("working directory not created: {}", e),
+                ));
+            }
+        };
+        let (mut command, file, output) = self.output_file_command(&tokens, working_dir)?;
+        command
+            .stdout(file.try_clone()?)
+            .stderr(file);
+        info!("Executing in tail panel: {:?}", &command);
+        let child = command
+            .spawn()
+            .map_err(|source| ProgramError::LaunchError {
+                program: tokens[0].clone(),
+                source,
+            })?;
+        // the process is running, we don't know yet its exit code
+        exec_log::log_execution(log_file, &tokens, None);
+        Ok(AppStateCmdResult::NewPanel {
+            state: Box::new(TailState::new(tokens.join(" "), child, output, TreeOptions::default())),
+            purpose: PanelPurpose::None,
+            direction: HDir::Right,
+        })
+    }
+
+    /// build the command of a process whose output is to be written in
+    /// a temporary file, returned with the file and its path, which is
+    /// removed when dropped
+    fn output_file_command(
+        &self,
+        tokens: &[String],
+        working_dir: Option<PathBuf>,
+    ) -> Result<(Command, fs::File, TempPath), ProgramError> {
+        let launchable = Launchable::program(tokens.to_vec(), working_dir)?;
+        let (exe, args, working_dir) = match launchable {
+            Launchable::Program { exe, args, working_dir, .. } => (exe, args, working_dir),
+            _ => unreachable!(),
+        };
+        let (file, output) = tempfile::Builder::new()
+            .prefix("broot-")
+            .suffix(".log")
+            .tempfile()?
+            .into_parts();
+        let mut command = Command::new(&exe);
+        command
+            .args(args.iter())
+            .stdin(Stdio::null());
+        if let Some(working_dir) = working_dir {
+            command.current_dir(working_dir);
+        }
+        self.env.apply_to(&mut command);
+        Ok((command, file, output))
+    }
+
+    /// run the process, wait for its end, and open a panel
+    /// displaying its standard output
+    fn capture_cmd_result(
+        &self,
+        builder: ExecutionStringBuilder<'_>,
+        log_file: Option<&Path>,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let tokens = self.exec_tokens(&builder);
+        let working_dir = match self.working_dir(&builder) {
+            Ok(working_dir) => working_dir,
+            Err(e) => {
+                return Ok(AppStateCmdResult::DisplayError(
+                    format!("working directory not created: {}", e),
+                ));
+            }
+        };
+        let (mut command, file, output) = self.output_file_command(&tokens, working_dir)?;
+        command
+            .stdout(file)
+            .stderr(Stdio::piped());
+        info!("Executing with captured output: {:?}", &command);
+        let child = command
+            .spawn()
+            .map_err(|source| ProgramError::LaunchError {
+                program: tokens[0].clone(),
+                source,
+            })?;
+        let result = child.wait_with_output()?;
+        exec_log::log_execution(log_file, &tokens, result.status.code());
+        if !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Ok(AppStateCmdResult::DisplayError(
+                match stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
+                    Some(line) => format!("`{}` failed: {}", tokens.join(" "), line),
+                    None => format!("`{}` failed ({})", tokens.join(" "), result.status),
+                },
+            ));
+        }
+        let state = TailState::of_finished(
+            tokens.join(" "),
+            result.status,
+            output,
+            TreeOptions::default(),
+        )?;
+        Ok(AppStateCmdResult::NewPanel {
+            state: Box::new(state),
+            purpose: PanelPurpose::None,
+            direction: HDir::Right,
+        })
+    }
+
+    /// launch the process, detached and with its output discarded,
+    /// and let the app watch its end
+    fn background_cmd_result(
+        &self,
+        builder: ExecutionStringBuilder<'_>,
+        log_file: Option<&Path>,
+    ) -> Result<AppStateCmdResult, ProgramError> {
+        let tokens = self.exec_tokens(&builder);
+        let working_dir = match self.working_dir(&builder) {
+            Ok(working_dir) => working_dir,
+            Err(e) => {
+                return Ok(AppStateCmdResult::DisplayError(
+                    format!("working directory not created: {}", e),
+                ));
+            }
+        };
+        let launchable = Launchable::program(tokens.clone(), working_dir)?;
+        let (exe, args, working_dir) = match launchable {
+            Launchable::Program { exe, args, working_dir, .. } => (exe, args, working_dir),
+            _ => unreachable!(),
+        };
+        let mut command = Command::new(&exe);
+        command
+            .args(args.iter())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        #[cfg(unix)]
+        {
+            // in its own process group, the process doesn't receive the
+            // signals of the terminal, and survives broot
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        if let Some(working_dir) = working_dir {
+            command.current_dir(working_dir);
+        }
+        self.env.apply_to(&mut command);
+        info!("Executing in background: {:?}", &command);
+        let child = command
+            .spawn()
+            .map_err(|source| ProgramError::LaunchError {
+                program: exe,
+                source,
+            })?;
+        // the exit code will only be known by the app
+        exec_log::log_execution(log_file, &tokens, None);
+        Ok(AppStateCmdResult::LaunchedInBackground(Box::new(BackgroundJob {
+            label: tokens.join(" "),
+            child,
+            show_progress: self.show_progress,
+        })))
+    }
+
+    /// build the program launched in the foreground, either by
+    /// broot or after broot quits
+    fn launchable(
+        &self,
+        tokens: Vec<String>,
+        working_dir: Option<PathBuf>,
+    ) -> io::Result<Launchable> {
+        Ok(Launchable::program(tokens, working_dir)?
+            .with_env(self.env.clone())
+            .with_stdin(self.stdin))
+    }
+
     /// build the cmd result as an executable which will be called in a process
     /// launched by broot
     fn exec_cmd_result(
@@ -104,24 +738,68 @@ impl ExternalExecution {
         w: &mut W,
         //sel: Selection<'_>,
         builder: ExecutionStringBuilder<'_>,
+        log_file: Option<&Path>,
     ) -> Result<AppStateCmdResult, ProgramError> {
-        let launchable = Launchable::program(
-            builder.exec_token(&self.exec_pattern),
-            if self.set_working_dir {
-                Some(path::closest_dir(builder.sel.path))
-            } else {
-                None
-            },
-        )?;
+        let working_dir = match self.working_dir(&builder) {
+            Ok(working_dir) => working_dir,
+            Err(e) => {
+                return Ok(AppStateCmdResult::DisplayError(
+                    format!("working directory not created: {}", e),
+                ));
+            }
+        };
+        // the output path is computed before the execution, as it
+        // may depend on the selection
+        let output_path = self.output_path(&builder);
+        let snapshot = if self.restage_modified {
+            Some(MtimeSnapshot::of(&builder.get_selection_paths()))
+        } else {
+            None
+        };
+        if self.batch_size.is_some() {
+            for tokens in self.batch_tokens(&builder) {
+                let launchable = self.launchable(tokens.clone(), working_dir.clone())?;
+                info!("Executing batch, launchable {:?}", launchable);
+                match launchable.execute_for_exit_code(Some(w)) {
+                    Ok(exit_code) => exec_log::log_execution(log_file, &tokens, exit_code),
+                    Err(e) => {
+                        warn!("launchable failed : {:?}", e);
+                        return Ok(AppStateCmdResult::DisplayError(e.to_string()));
+                    }
+                }
+            }
+            return Ok(success_result(output_path, snapshot));
+        }
+        let tokens = self.exec_tokens(&builder);
+        // the operation must be determined before the execution
+        let operation = Operation::from_command(&tokens, working_dir.as_deref());
+        let launchable = self.launchable(tokens.clone(), working_dir)?;
         if self.exec_mode.is_leave_broot() {
+            // the command will be executed after broot quits
+            exec_log::log_execution(log_file, &tokens, None);
             Ok(AppStateCmdResult::from(launchable))
         } else {
             info!("Executing not leaving, launchable {:?}", launchable);
-            let execution = launchable.execute(Some(w));
+            let execution = launchable.execute_for_exit_code(Some(w));
             match execution {
-                Ok(()) => {
+                Ok(exit_code) => {
                     debug!("ok");
-                    Ok(AppStateCmdResult::RefreshState { clear_cache: true })
+                    exec_log::log_execution(log_file, &tokens, exit_code);
+                    match exit_code {
+                        Some(code) if code != 0 && self.fail_on_exit_code => {
+                            Ok(AppStateCmdResult::DisplayError(format!(
+                                "`{}` failed with exit code {}",
+                                tokens.join(" "),
+                                code,
+                            )))
+                        }
+                        _ => {
+                            if let Some(operation) = operation {
+                                journal::record(operation);
+                            }
+                            Ok(success_result(output_path, snapshot))
+                        }
+                    }
                 }
                 Err(e) => {
                     warn!("launchable failed : {:?}", e);
@@ -131,3 +809,427 @@ impl ExternalExecution {
         }
     }
 }
+
+/// the shell of the user, from `$SHELL`, or `sh` when it's not set
+fn user_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "sh".to_string())
+}
+
+/// return the tokens of the process running the command line
+/// in the shell, as a login shell
+fn login_shell_tokens(shell: &str, cmd: String) -> Vec<String> {
+    vec![shell.to_string(), "-lc".to_string(), cmd]
+}
+
+/// the result of a successful execution: the paths modified by the
+/// process are staged when a snapshot was taken before, otherwise
+/// the tree is just refreshed
+fn success_result(
+    output_path: Option<PathBuf>,
+    snapshot: Option<MtimeSnapshot>,
+) -> AppStateCmdResult {
+    match snapshot {
+        Some(snapshot) => AppStateCmdResult::Restage { paths: snapshot.modified_paths() },
+        None => refresh_result(output_path),
+    }
+}
+
+/// the result of a successful execution: the tree is refreshed, and
+/// the output of the process selected if it exists
+fn refresh_result(output_path: Option<PathBuf>) -> AppStateCmdResult {
+    match output_path.filter(|path| path.exists()) {
+        Some(path) => AppStateCmdResult::RefreshAndSelect { path },
+        None => AppStateCmdResult::RefreshState { clear_cache: true },
+    }
+}
+
+#[cfg(test)]
+mod external_execution_test {
+
+    use super::*;
+
+    fn selection(path: &Path) -> Selection<'_> {
+        Selection {
+            path,
+            line: 0,
+            stype: SelectionType::File,
+            is_exe: false,
+        }
+    }
+
+    #[test]
+    fn test_batches() {
+        let staged: Vec<PathBuf> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|name| PathBuf::from(format!("/tmp/{}", name)))
+            .collect();
+        let builder = ExecutionStringBuilder::from_selection(selection(&staged[0]))
+            .with_staged_paths(&staged);
+        let execution = ExternalExecution::new(
+            "echo {selection} done".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        assert_eq!(
+            execution.batch_tokens(&builder),
+            vec![vec!["echo", "/tmp/a", "/tmp/b", "/tmp/c", "/tmp/d", "/tmp/e", "done"]],
+        );
+        let execution = execution.with_batch_size(Some(2));
+        assert_eq!(
+            execution.batch_tokens(&builder),
+            vec![
+                vec!["echo", "/tmp/a", "/tmp/b", "done"],
+                vec!["echo", "/tmp/c", "/tmp/d", "done"],
+                vec!["echo", "/tmp/e", "done"],
+            ],
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_to_clipboard() {
+        let path = PathBuf::from("/tmp/some file.txt");
+        let builder = ExecutionStringBuilder::from_selection(selection(&path));
+        let execution = ExternalExecution::new(
+            r#"printf "\n  %s\n\n" {file}"#.to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ).with_output_to_clipboard(Some(true));
+        let mut copied = None;
+        let result = execution.copy_output_with(&builder, |output| {
+            copied = Some(output);
+            Ok(())
+        }).unwrap();
+        assert!(matches!(result, AppStateCmdResult::RefreshState { .. }));
+        assert_eq!(copied.as_deref(), Some("/tmp/some file.txt"));
+        // a clipboard failure is displayed
+        let result = execution.copy_output_with(&builder, |_| Err("no clipboard".to_string()))
+            .unwrap();
+        assert!(matches!(result, AppStateCmdResult::DisplayError(e) if e == "no clipboard"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_to_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "a").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(selection(&file));
+        // the process lists paths relative to its working directory
+        let execution = ExternalExecution::new(
+            r#"printf "%s\n\n%s\n" {file-name} sub/b.txt"#.to_string(),
+            ExternalExecutionMode::StayInBroot,
+        )
+            .with_set_working_dir(Some(true))
+            .with_output_to_filter(Some(true));
+        match execution.filter_cmd_result(builder).unwrap() {
+            AppStateCmdResult::FilterPaths(path_set) => {
+                assert_eq!(path_set.len(), 2);
+                assert!(path_set.contains(&file));
+                assert!(path_set.contains(&dir.path().join("sub/b.txt")));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_capture_output() {
+        let path = PathBuf::from("/tmp/some file.txt");
+        let builder = ExecutionStringBuilder::from_selection(selection(&path));
+        let execution = ExternalExecution::new(
+            r#"printf "%s\n" {file}"#.to_string(),
+            ExternalExecutionMode::CaptureOutput,
+        );
+        assert!(!execution.exec_mode.is_leave_broot());
+        let result = execution.capture_cmd_result(builder.clone(), None).unwrap();
+        assert!(matches!(result, AppStateCmdResult::NewPanel { .. }));
+        // a failure is displayed with the first line of the error output
+        let execution = ExternalExecution::new(
+            r#"sh -c "echo 'no such revision' >&2; exit 2""#.to_string(),
+            ExternalExecutionMode::CaptureOutput,
+        );
+        match execution.capture_cmd_result(builder, None).unwrap() {
+            AppStateCmdResult::DisplayError(e) => assert!(e.ends_with("failed: no such revision")),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_index_per_file() {
+        let staged: Vec<PathBuf> = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|name| PathBuf::from(format!("/tmp/{}", name)))
+            .collect();
+        let builder = ExecutionStringBuilder::from_selection(selection(&staged[0]))
+            .with_staged_paths(&staged);
+        let execution = ExternalExecution::new(
+            "mv {file} {index}_{file-name}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        // without batches, there's only one execution
+        assert_eq!(
+            execution.batch_tokens(&builder),
+            vec![vec!["mv", "/tmp/a.txt", "1_a.txt"]],
+        );
+        // with batches of one path, the verb is executed per file
+        let execution = execution.with_batch_size(Some(1));
+        assert_eq!(
+            execution.batch_tokens(&builder),
+            vec![
+                vec!["mv", "/tmp/a.txt", "1_a.txt"],
+                vec!["mv", "/tmp/b.txt", "2_b.txt"],
+                vec!["mv", "/tmp/c.txt", "3_c.txt"],
+            ],
+        );
+        // with bigger batches, the index is the one of the batch
+        let execution = ExternalExecution::new(
+            "tar cf part-{index}.tar {selection}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ).with_batch_size(Some(2));
+        assert_eq!(
+            execution.batch_tokens(&builder),
+            vec![
+                vec!["tar", "cf", "part-1.tar", "/tmp/a.txt", "/tmp/b.txt"],
+                vec!["tar", "cf", "part-2.tar", "/tmp/c.txt"],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_toggle() {
+        let execution = ExternalExecution::new(
+            "mpc play".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        // without toggle pattern, it's always the same pattern
+        execution.toggle_count.incr();
+        assert_eq!(execution.current_exec_pattern(), "mpc play");
+        let execution = ExternalExecution::new(
+            "mpc play".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ).with_toggle_exec_pattern(Some("mpc pause".to_string()));
+        assert_eq!(execution.exec_patterns().count(), 2);
+        let mut patterns = Vec::new();
+        for _ in 0..5 {
+            patterns.push(execution.current_exec_pattern().to_string());
+            execution.toggle_count.incr();
+        }
+        assert_eq!(patterns, vec!["mpc play", "mpc pause", "mpc play", "mpc pause", "mpc play"]);
+        // a new verb, e.g. after a configuration reload, starts over
+        let new_execution = ExternalExecution::new(
+            "mpc play".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ).with_toggle_exec_pattern(Some("mpc pause".to_string()));
+        assert_eq!(new_execution.current_exec_pattern(), "mpc play");
+    }
+
+    #[test]
+    fn test_stype_exec_patterns() {
+        let file = PathBuf::from("/home/dys/notes.md");
+        let dir = PathBuf::from("/home/dys/dev");
+        let file_sel = selection(&file);
+        let dir_sel = Selection {
+            stype: SelectionType::Directory,
+            ..selection(&dir)
+        };
+        let tokens = |execution: &ExternalExecution, sel: Selection<'_>| {
+            ExecutionStringBuilder::from_selection(sel)
+                .pattern_exec_token(execution.exec_pattern_for(sel.stype))
+        };
+        // with only one pattern, it applies to all types
+        let execution = ExternalExecution::new(
+            "ls -l {file}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        assert_eq!(tokens(&execution, file_sel), vec!["ls", "-l", "/home/dys/notes.md"]);
+        assert_eq!(tokens(&execution, dir_sel), vec!["ls", "-l", "/home/dys/dev"]);
+        // a pattern for directories
+        let execution = ExternalExecution::new(
+            "nvim {file}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ).with_stype_exec_pattern(SelectionType::Directory, Some("tmux new-window -c {file}".to_string()));
+        assert_eq!(execution.exec_patterns().count(), 2);
+        assert_eq!(tokens(&execution, file_sel), vec!["nvim", "/home/dys/notes.md"]);
+        assert_eq!(tokens(&execution, dir_sel), vec!["tmux", "new-window", "-c", "/home/dys/dev"]);
+        // a pattern for files too
+        let execution = execution
+            .with_stype_exec_pattern(SelectionType::File, Some("bat {file}".to_string()));
+        assert_eq!(tokens(&execution, file_sel), vec!["bat", "/home/dys/notes.md"]);
+        assert_eq!(tokens(&execution, dir_sel), vec!["tmux", "new-window", "-c", "/home/dys/dev"]);
+    }
+
+    #[test]
+    fn test_batches_without_staged_paths() {
+        // without staged paths, the selection is the selected path
+        let path = PathBuf::from("/tmp/a");
+        let builder = ExecutionStringBuilder::from_selection(selection(&path));
+        let execution = ExternalExecution::new(
+            "echo {selection}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ).with_batch_size(Some(2));
+        assert_eq!(execution.batch_tokens(&builder), vec![vec!["echo", "/tmp/a"]]);
+    }
+
+    #[test]
+    fn test_ensure_working_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("report.md");
+        fs::write(&file, "").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(selection(&file));
+        let out = dir.path().join("out");
+        // without option, there's no working directory
+        let execution = ExternalExecution::new(
+            "pandoc {file}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        assert_eq!(execution.working_dir(&builder).unwrap(), None);
+        let execution = execution.with_set_working_dir(Some(true));
+        assert_eq!(execution.working_dir(&builder).unwrap(), Some(dir.path().to_path_buf()));
+        // the directory is created when missing, then reused
+        let execution = execution.with_ensure_working_dir(Some("{parent}/out".to_string()));
+        assert!(!out.exists());
+        assert_eq!(execution.working_dir(&builder).unwrap(), Some(out.clone()));
+        assert!(out.is_dir());
+        fs::write(out.join("kept.txt"), "").unwrap();
+        assert_eq!(execution.working_dir(&builder).unwrap(), Some(out.clone()));
+        assert!(out.join("kept.txt").exists());
+        // nested directories are created too
+        let execution = execution.with_ensure_working_dir(Some("{parent}/a/b".to_string()));
+        assert_eq!(execution.working_dir(&builder).unwrap(), Some(dir.path().join("a/b")));
+        // the creation fails when a file has the path
+        let execution = execution.with_ensure_working_dir(Some("{file}/out".to_string()));
+        assert!(execution.working_dir(&builder).is_err());
+    }
+
+    #[test]
+    fn test_select_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("photo.jpg");
+        fs::write(&file, "").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(selection(&file));
+        let execution = ExternalExecution::new(
+            "mogrify -format png {file}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        assert_eq!(execution.output_path(&builder), None);
+        let execution = execution.with_select_output(Some("{file-stem}.png".to_string()));
+        let output = dir.path().join("photo.png");
+        assert_eq!(execution.output_path(&builder), Some(output.clone()));
+        let execution = execution.with_select_output(Some("{parent}/png/{file-stem}.png".to_string()));
+        assert_eq!(execution.output_path(&builder), Some(dir.path().join("png/photo.png")));
+        // the output is selected only when it exists
+        assert!(matches!(
+            refresh_result(Some(output.clone())),
+            AppStateCmdResult::RefreshState { clear_cache: true },
+        ));
+        fs::write(&output, "").unwrap();
+        assert!(matches!(
+            refresh_result(Some(output.clone())),
+            AppStateCmdResult::RefreshAndSelect { path } if path == output,
+        ));
+        assert!(matches!(
+            refresh_result(None),
+            AppStateCmdResult::RefreshState { clear_cache: true },
+        ));
+    }
+
+    #[test]
+    fn test_restage_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        let c = dir.path().join("c.txt");
+        for path in &[&a, &b] {
+            fs::write(path, "").unwrap();
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(std::time::SystemTime::UNIX_EPOCH)
+                .unwrap();
+        }
+        let staged = vec![a.clone(), b.clone(), c.clone()];
+        let builder = ExecutionStringBuilder::from_selection(selection(&a))
+            .with_staged_paths(&staged);
+        // the snapshot is made of the paths of {selection}
+        let snapshot = MtimeSnapshot::of(&builder.get_selection_paths());
+        fs::write(&b, "changed").unwrap();
+        fs::write(&c, "created").unwrap();
+        assert!(matches!(
+            success_result(None, Some(snapshot)),
+            AppStateCmdResult::Restage { paths } if paths == vec![b.clone(), c.clone()],
+        ));
+        assert!(matches!(
+            success_result(None, None),
+            AppStateCmdResult::RefreshState { clear_cache: true },
+        ));
+    }
+
+    #[test]
+    fn test_login_shell_tokens() {
+        let path = PathBuf::from("/tmp/it's a \"file\".txt");
+        let builder = ExecutionStringBuilder::from_selection(selection(&path));
+        let execution = ExternalExecution::new(
+            "wc -l {file} > counts".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        assert_eq!(
+            execution.exec_tokens(&builder),
+            vec!["wc", "-l", "/tmp/it's a \"file\".txt", ">", "counts"],
+        );
+        // with a login shell, the whole command is given to the shell,
+        // in one argument, the paths being quoted for the shell
+        let cmd = builder.pattern_shell_command(&execution.exec_pattern);
+        assert_eq!(
+            login_shell_tokens("/bin/zsh", cmd.clone()),
+            vec!["/bin/zsh".to_string(), "-lc".to_string(), cmd],
+        );
+        let execution = execution.with_login_shell(Some(true));
+        let tokens = execution.exec_tokens(&builder);
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0], user_shell());
+        assert_eq!(tokens[1], "-lc");
+        assert!(tokens[2].starts_with("wc -l "));
+        assert!(tokens[2].ends_with(" > counts"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_login_shell_quoting() {
+        // the shell receives the path as one argument, unchanged, and
+        // the quotes and spaces of the pattern as written
+        let path = PathBuf::from("/tmp/it's a \"file\" $HOME.txt");
+        let builder = ExecutionStringBuilder::from_selection(selection(&path));
+        let pattern = ExecPattern::from("printf '%s|%s'  {file}  \"a  b\"");
+        let tokens = login_shell_tokens("sh", builder.pattern_shell_command(&pattern));
+        let output = Command::new(&tokens[0])
+            .args(&tokens[1..])
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{}|a  b", path.to_string_lossy()),
+        );
+    }
+
+    #[test]
+    fn test_stdin() {
+        let execution = ExternalExecution::new(
+            "cat {file}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        );
+        let tokens = vec!["cat".to_string(), "notes.md".to_string()];
+        let launchable = execution.launchable(tokens.clone(), None).unwrap();
+        assert!(matches!(launchable, Launchable::Program { stdin: StdinMode::Inherit, .. }));
+        let execution = execution.with_stdin(StdinMode::Null);
+        let launchable = execution.launchable(tokens, None).unwrap();
+        assert!(matches!(
+            launchable,
+            Launchable::Program { stdin: StdinMode::Null, ref args, .. } if args == &["notes.md"],
+        ));
+    }
+}
@@ -8,6 +8,14 @@ pub enum ExternalExecutionMode {
 
     /// executed in a sub process without quitting broot
     StayInBroot,
+
+    /// executed in a detached sub process, broot going on without
+    /// waiting for its end
+    Background,
+
+    /// executed in a sub process without quitting broot, its standard
+    /// output being displayed in a new panel at its end
+    CaptureOutput,
 }
 
 impl ExternalExecutionMode {
@@ -15,14 +23,26 @@ impl ExternalExecutionMode {
         matches!(self, Self::FromParentShell)
     }
     pub fn is_leave_broot(self) -> bool {
-        !matches!(self, Self::StayInBroot)
+        !matches!(self, Self::StayInBroot | Self::Background | Self::CaptureOutput)
+    }
+    pub fn is_background(self) -> bool {
+        matches!(self, Self::Background)
+    }
+    pub fn is_capture_output(self) -> bool {
+        matches!(self, Self::CaptureOutput)
     }
 
     pub fn from_conf(
-        from_shell: Option<bool>,  // default is false
-        leave_broot: Option<bool>, // default is true
+        from_shell: Option<bool>,     // default is false
+        leave_broot: Option<bool>,    // default is true
+        background: Option<bool>,     // default is false
+        capture_output: Option<bool>, // default is false
     ) -> Self {
-        if from_shell.unwrap_or(false) {
+        if background.unwrap_or(false) {
+            Self::Background
+        } else if capture_output.unwrap_or(false) {
+            Self::CaptureOutput
+        } else if from_shell.unwrap_or(false) {
             Self::FromParentShell
         } else if leave_broot.unwrap_or(true) {
             Self::LeaveBroot
@@ -46,11 +46,23 @@ macro_rules! Internals {
 
 Internals! {
     back: "revert to the previous state (mapped to *esc*)",
+    bookmark: "save the directory of the selection as a bookmark of the given name",
+    bookmarks: "list the bookmarks",
     close_panel_ok: "close the panel, validating the selected path",
     close_panel_cancel: "close the panel, not using the selected path",
+    commands: "open a palette listing all verbs",
     copy_path: "copy path to system clipboard",
+    copy_pair: "copy the selections of both panels as LEFT and RIGHT shell variables",
+    cut: "put the selection in the yank buffer, to be moved on paste",
+    depth: "set or change the maximal displayed depth of the tree",
+    duplicate: "copy the selection in place, with a new name",
+    export: "print the tree as JSON or NUL separated paths and leaves broot",
     filesystems: "list mounted filesystems",
+    filter_modified: "only show the files modified in the given time range",
+    first: "select the first entry of the current directory",
     focus: "display the directory (mapped to *enter*)",
+    goto_newest: "select the most recently modified file of the tree, focusing its directory",
+    from_template: "create a file in the current directory from a template",
     help: "display broot's help",
     input_del_char_left: "delete the char left of the cursor",
     input_del_char_below: "delete the char left at the cursor's position",
@@ -63,69 +75,178 @@ Internals! {
     input_go_word_left: "move the cursor one word to the left",
     input_go_word_right: "move the cursor one word to the right",
     input_paste: "paste the clipboard content into the input",
+    journal: "list the file operations which can be undone or redone",
+    jump: "focus the directory of a bookmark",
+    last: "select the last entry of the current directory",
+    load_layout: "restore a panel layout saved with :save_layout",
+    load_session: "restore the panels, with their patterns and options, saved with :save_session",
+    locate: "open the parent of the selection in a new panel, the selection being selected",
     line_down: "move one line down",
     line_up: "move one line up",
+    mode: "switch to the given key mode",
+    mark: "add or remove the selection in a named mark set, or run a verb on a mark set",
     open_stay: "open file or directory according to OS (stay in broot)",
     open_stay_filter: "display the directory, keeping the current pattern",
     open_leave: "open file or directory according to OS (quit broot)",
+    open_and_quit: "open file or directory according to OS, then quit broot if it succeeded",
+    open_url: "open the URL of a .url or .desktop file, or of a file containing a URL, in the browser",
     next_match: "select the next match",
     next_same_depth: "select the next file at the same depth",
     no_sort: "don't sort",
     page_down: "scroll one page down",
     page_up: "scroll one page up",
     parent: "move to the parent directory",
+    paste: "copy or move the yanked paths into the current directory",
     panel_left: "focus panel on left",
     panel_right: "focus panel on right",
+    pipe_listing: "feed the names of the entries of the current directory to a command",
     previous_match: "select the previous match",
     previous_same_depth: "select the previous file at the same depth",
+    redo: "do again the last undone file operation",
+    recent_next: "select the next, more recent, of the recently selected files",
+    recent_prev: "select the previous, older, of the recently selected files",
     open_preview: "open the preview panel",
     close_preview: "close the preview panel",
     toggle_preview: "open/close the preview panel",
+    toggle_split: "split the panel in two panels showing the same tree, or close the split",
+    toggle_verb_bar: "show/hide a bar listing the verbs applicable to the selection",
+    toggle_watch: "toggle the automatic refresh of the trees when files change",
     preview_image: "preview the selection as image",
     preview_text: "preview the selection as text",
+    render: "preview the selection rendered: formatted markdown, or csv as a table",
     preview_binary: "preview the selection as binary",
+    preview_as: "preview the selection as text highlighted with the given syntax",
     print_path: "print path and leaves broot",
     print_relative_path: "print relative path and leaves broot",
     print_tree: "print tree and leaves broot",
+    link_to_other: "create in the other panel's directory a link to the selection",
+    zip_to_other: "create in the other panel's directory a zip archive of the selection",
+    browse_archive: "browse the entries of an archive",
+    extract: "extract the selected entry of the browsed archive next to it",
+    trash: "move the selection to the trash",
+    open_trash: "list the items of the trash",
+    restore: "restore the selected item of the trash",
+    purge: "remove for good the selected item of the trash",
+    set_other_to_parent: "set the root of the other panel to the parent of the selection",
+    swap_names: "swap the names of the selections of both panels",
+    batch_rename: "rename the staged paths, or the ones of the tree, in your editor",
+    stage: "add the selection to the stage",
+    unstage: "remove the selection from the stage",
+    toggle_stage: "add the selection to the stage, or remove it if it's already staged",
+    clear_stage: "remove all paths from the stage",
+    stage_content_matches: "stage all files of the tree containing the given pattern",
     start_end_panel: "either open or close an additional panel",
     quit: "quit Broot",
     refresh: "refresh tree and clear size cache",
+    reload_config: "read again the verbs of the configuration files",
+    repeat: "execute again the last navigation internal, the given number of times",
+    run: "run a shell command in the directory of the selection, following its output in a panel",
     //restore_pattern: "restore a pattern which was just removed",
     select_first: "select the first file",
     select_last: "select the last file",
+    select_largest: "select the largest file of the current directory",
+    select_smallest: "select the smallest file of the current directory",
+    save_layout: "save the panel layout under a name",
+    save_session: "save the panels, with their patterns and options, under a name",
     sort_by_count: "sort by count",
     sort_by_date: "sort by date",
     sort_by_size: "sort by size",
+    toggle_case: "toggle the case sensitivity of the current search",
     toggle_counts: "toggle showing number of files in directories",
     toggle_dates: "toggle showing last modified dates",
+    toggle_exec: "toggle the permission of the owner to execute the selection",
     toggle_files: "toggle showing files (or just folders)",
     toggle_git_ignore: "toggle use of .gitignore",
     toggle_git_file_info: "toggle display of git file information",
     toggle_git_status: "toggle showing only files relevant for git status",
+    git_status: "only show files relevant for git status",
     toggle_root_fs: "toggle showing filesystem info on top",
     toggle_hidden: "toggle showing hidden files",
+    toggle_flat: "toggle listing the tree as flat relative paths",
     toggle_perm: "toggle showing file permissions",
+    toggle_perm_style: "switch the file permissions between the rwx and octal notations",
     toggle_sizes: "toggle showing sizes",
     toggle_trim_root: "toggle removing nodes at first level too",
     total_search: "search again but on all children",
+    undo: "revert the last file operation (move, copy, directory creation, rename or trashing)",
     up_tree: "focus the parent of the current root",
+    yank: "put the selection in the yank buffer, to be copied on paste",
 }
 
 impl Internal {
     pub fn invocation_pattern(self) -> &'static str {
         match self {
+            Internal::bookmark => r"bookmark (?P<name>\S+)",
+            Internal::depth => r"depth (?P<depth>\S+)?",
+            Internal::export => r"export (?P<format>\S+)?",
+            Internal::filter_modified => r"filter_modified (?P<since>\S+)?",
             Internal::focus => r"focus (?P<path>.*)?",
+            Internal::jump => r"jump (?P<name>\S+)",
             Internal::line_down => r"line_down (?P<count>\d*)?",
             Internal::line_up => r"line_up (?P<count>\d*)?",
+            Internal::load_layout => r"load_layout (?P<name>\S+)",
+            Internal::load_session => r"load_session (?P<name>\S+)",
+            Internal::mark => r"mark (?P<args>.+)",
+            Internal::mode => r"mode (?P<name>\S+)",
+            Internal::paste => r"paste (?P<on_conflict>\S+)?",
+            Internal::link_to_other => r"link_to_other (?P<name>.*)?",
+            Internal::zip_to_other => r"zip_to_other (?P<name>.*)?",
+            Internal::from_template => r"from_template (?P<template>\S+) (?P<name>.+)",
+            Internal::pipe_listing => r"pipe_listing (?P<cmd>.+)",
+            Internal::preview_as => r"preview_as (?P<syntax>.+)",
+            Internal::repeat => r"repeat (?P<count>\d*)?",
+            Internal::run => r"run (?P<cmd>.+)",
+            Internal::save_layout => r"save_layout (?P<name>\S+)",
+            Internal::save_session => r"save_session (?P<name>\S+)",
+            Internal::stage_content_matches => r"stage_content_matches (?P<pattern>.+)",
             _ => self.name(),
         }
     }
     pub fn exec_pattern(self) -> &'static str {
         match self {
+            Internal::bookmark => r"bookmark {name}",
+            Internal::depth => r"depth {depth}",
+            Internal::export => r"export {format}",
+            Internal::filter_modified => r"filter_modified {since}",
             Internal::focus => r"focus {path}",
+            Internal::jump => r"jump {name}",
             Internal::line_down => r"line_down {count}",
             Internal::line_up => r"line_up {count}",
+            Internal::load_layout => r"load_layout {name}",
+            Internal::load_session => r"load_session {name}",
+            Internal::mark => r"mark {args}",
+            Internal::mode => r"mode {name}",
+            Internal::paste => r"paste {on_conflict}",
+            Internal::link_to_other => r"link_to_other {name}",
+            Internal::zip_to_other => r"zip_to_other {name}",
+            Internal::from_template => r"from_template {template} {name}",
+            Internal::pipe_listing => r"pipe_listing {cmd}",
+            Internal::preview_as => r"preview_as {syntax}",
+            Internal::repeat => r"repeat {count}",
+            Internal::run => r"run {cmd}",
+            Internal::save_layout => r"save_layout {name}",
+            Internal::save_session => r"save_session {name}",
+            Internal::stage_content_matches => r"stage_content_matches {pattern}",
             _ => self.name(),
         }
     }
+    /// tell whether the internal is a move of the selection,
+    /// which `:repeat` can execute again
+    pub fn is_repeatable(self) -> bool {
+        matches!(
+            self,
+            Internal::line_down
+                | Internal::line_up
+                | Internal::page_down
+                | Internal::page_up
+                | Internal::next_match
+                | Internal::previous_match
+                | Internal::next_same_depth
+                | Internal::previous_same_depth
+                | Internal::recent_next
+                | Internal::recent_prev
+                | Internal::parent
+                | Internal::up_tree
+        )
+    }
 }
@@ -0,0 +1,291 @@
+//! utility functions to help handle the `:batch_rename` internal
+
+use {
+    super::execution_builder::editor_tokens,
+    crate::{
+        app::*,
+        display::W,
+        errors::ProgramError,
+        journal,
+        launchable::Launchable,
+        path,
+    },
+    std::{
+        fs,
+        io::{self, Write},
+        path::{Path, PathBuf},
+    },
+};
+
+/// the comment written at the start of the file given to the editor
+const HEADER: &str = "\
+# Edit the paths to rename or move the files, then save and quit.
+# Lines must be neither added nor removed. Relative paths are
+# relative to the root of the tree.
+";
+
+/// a change of path to apply
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// write the paths, one per line, relative to the base
+/// when they're inside it
+pub fn rename_file_content(paths: &[PathBuf], base: &Path) -> String {
+    let mut content = HEADER.to_string();
+    for path in paths {
+        let line = path.strip_prefix(base).unwrap_or(path);
+        content.push_str(&line.to_string_lossy());
+        content.push('\n');
+    }
+    content
+}
+
+/// compare the lines written by the user to the initial paths and
+/// return the renames to do, or an error when they can't all be done
+pub fn rename_plan(
+    paths: &[PathBuf],
+    base: &Path,
+    edited: &str,
+) -> Result<Vec<Rename>, String> {
+    // only the header written by broot is removed, as
+    // file names may start with a `#`
+    let mut header_lines = HEADER.lines().peekable();
+    let lines: Vec<&str> = edited
+        .lines()
+        .skip_while(|line| header_lines.next_if(|h| *h == line.trim_end()).is_some())
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if lines.len() != paths.len() {
+        return Err(format!(
+            "{} paths were given but {} lines were found: lines can't be added or removed",
+            paths.len(),
+            lines.len(),
+        ));
+    }
+    let mut renames = Vec::new();
+    let mut final_paths = Vec::new();
+    for (path, line) in paths.iter().zip(lines) {
+        let target = path::normalize_path(base.join(line.trim_end_matches('/')));
+        if target.file_name().is_none() {
+            return Err(format!("invalid path: {:?}", line));
+        }
+        if final_paths.contains(&target) {
+            return Err(format!("{:?} is the destination of several paths", target));
+        }
+        final_paths.push(target.clone());
+        if &target != path {
+            renames.push(Rename { from: path.clone(), to: target });
+        }
+    }
+    for rename in &renames {
+        let replaced = renames.iter().any(|r| r.from == rename.to);
+        if !replaced && fs::symlink_metadata(&rename.to).is_ok() {
+            return Err(format!("{:?} already exists", rename.to));
+        }
+        if rename.to.starts_with(&rename.from) {
+            return Err(format!("{:?} can't be moved inside itself", rename.from));
+        }
+        if renames.iter().any(|r| r.from != rename.from && rename.from.starts_with(&r.from)) {
+            return Err(format!(
+                "{:?} can't be changed as one of its parents is renamed too",
+                rename.from,
+            ));
+        }
+    }
+    Ok(renames)
+}
+
+/// do the renames, which must have been checked by `rename_plan`.
+///
+/// The paths are first all moved to temporary names, so that names
+/// may be swapped. When a rename fails, the previous ones are
+/// reverted and the created directories removed.
+pub fn apply_renames(renames: &[Rename]) -> io::Result<()> {
+    apply_renames_with(renames, |from, to| fs::rename(from, to))
+}
+
+fn apply_renames_with<R>(renames: &[Rename], rename: R) -> io::Result<()>
+where
+    R: Fn(&Path, &Path) -> io::Result<()>,
+{
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut created_dirs: Vec<PathBuf> = Vec::new();
+    let res = do_renames(renames, &rename, &mut done, &mut created_dirs);
+    if res.is_err() {
+        for (from, to) in done.iter().rev() {
+            if let Err(e) = rename(to, from) {
+                warn!("rename of {:?} to {:?} not reverted: {}", to, from, e);
+            }
+        }
+        for dir in created_dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+    res
+}
+
+/// do the renames, keeping track of what was done so that
+/// it can be reverted
+fn do_renames<R>(
+    renames: &[Rename],
+    rename: &R,
+    done: &mut Vec<(PathBuf, PathBuf)>,
+    created_dirs: &mut Vec<PathBuf>,
+) -> io::Result<()>
+where
+    R: Fn(&Path, &Path) -> io::Result<()>,
+{
+    let mut moved = Vec::new();
+    for (idx, r) in renames.iter().enumerate() {
+        let tmp = r.from.with_file_name(format!(
+            ".broot-rename-{}-{}",
+            std::process::id(),
+            idx,
+        ));
+        rename(&r.from, &tmp)?;
+        done.push((r.from.clone(), tmp.clone()));
+        moved.push((tmp, &r.to));
+    }
+    for (tmp, to) in moved {
+        if let Some(parent) = to.parent() {
+            let mut missing: Vec<&Path> = parent
+                .ancestors()
+                .take_while(|dir| fs::symlink_metadata(dir).is_err())
+                .collect();
+            missing.reverse();
+            for dir in missing {
+                fs::create_dir(dir)?;
+                created_dirs.push(dir.to_path_buf());
+            }
+        }
+        rename(&tmp, to)?;
+        done.push((tmp, to.clone()));
+    }
+    Ok(())
+}
+
+/// let the user edit the paths in their editor, then
+/// rename the files accordingly
+pub fn batch_rename(
+    w: &mut W,
+    paths: &[PathBuf],
+    base: &Path,
+) -> Result<AppStateCmdResult, ProgramError> {
+    if paths.is_empty() {
+        return Ok(AppStateCmdResult::DisplayError("no path to rename".to_string()));
+    }
+    let mut file = tempfile::Builder::new()
+        .prefix("broot-rename-")
+        .suffix(".txt")
+        .tempfile()?;
+    file.write_all(rename_file_content(paths, base).as_bytes())?;
+    file.flush()?;
+    let mut tokens = editor_tokens();
+    tokens.push(file.path().to_string_lossy().to_string());
+    Launchable::program(tokens, None)?.execute(Some(w))?;
+    let edited = fs::read_to_string(file.path())?;
+    let renames = match rename_plan(paths, base, &edited) {
+        Ok(renames) => renames,
+        Err(e) => {
+            return Ok(AppStateCmdResult::DisplayError(format!("nothing renamed: {}", e)));
+        }
+    };
+    if renames.is_empty() {
+        return Ok(AppStateCmdResult::Keep);
+    }
+    info!("batch renaming {} paths", renames.len());
+    Ok(match apply_renames(&renames) {
+        Ok(()) => {
+            journal::record(journal::Operation::Renames { renames });
+            AppStateCmdResult::RefreshState { clear_cache: true }
+        }
+        Err(e) => AppStateCmdResult::DisplayError(format!("renaming failed: {}", e)),
+    })
+}
+
+#[cfg(test)]
+mod internal_batch_rename_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rename_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        for name in &["a.txt", "b.txt", "c.txt", "other.txt"] {
+            fs::write(base.join(name), name).unwrap();
+        }
+        let paths: Vec<PathBuf> = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|name| base.join(name))
+            .collect();
+        let content = rename_file_content(&paths, base);
+        assert_eq!(content, format!("{}a.txt\nb.txt\nc.txt\n", HEADER));
+        // nothing changed
+        assert_eq!(rename_plan(&paths, base, &content), Ok(vec![]));
+        // a and b swapped, c moved in a new directory
+        let renames = rename_plan(&paths, base, "b.txt\na.txt\n\nnew/c.txt\n").unwrap();
+        assert_eq!(renames.len(), 3);
+        assert_eq!(renames[2], Rename { from: base.join("c.txt"), to: base.join("new/c.txt") });
+        // invalid plans
+        assert!(rename_plan(&paths, base, "a.txt\nb.txt\n").is_err()); // a line removed
+        assert!(rename_plan(&paths, base, "a.txt\na.txt\nc.txt").is_err()); // twice the same path
+        assert!(rename_plan(&paths, base, "a.txt\nother.txt\nc.txt").is_err()); // existing file
+        apply_renames(&renames).unwrap();
+        assert_eq!(fs::read_to_string(base.join("a.txt")).unwrap(), "b.txt");
+        assert_eq!(fs::read_to_string(base.join("b.txt")).unwrap(), "a.txt");
+        assert_eq!(fs::read_to_string(base.join("new/c.txt")).unwrap(), "c.txt");
+        assert!(!base.join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_header_and_hash_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        let paths = vec![base.join("#1.md"), base.join("notes.md")];
+        let content = rename_file_content(&paths, base);
+        assert_eq!(rename_plan(&paths, base, &content), Ok(vec![]));
+        let edited = content.replace("notes.md", "#2.md");
+        assert_eq!(
+            rename_plan(&paths, base, &edited),
+            Ok(vec![Rename { from: base.join("notes.md"), to: base.join("#2.md") }]),
+        );
+        // the header may have been removed by the user
+        assert_eq!(rename_plan(&paths, base, "#1.md\n#2.md\n").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        for name in &["a.txt", "b.txt", "c.txt"] {
+            fs::write(base.join(name), name).unwrap();
+        }
+        let renames = vec![
+            Rename { from: base.join("a.txt"), to: base.join("b.txt") },
+            Rename { from: base.join("b.txt"), to: base.join("new/dir/a.txt") },
+            Rename { from: base.join("c.txt"), to: base.join("d.txt") },
+        ];
+        // the last rename fails
+        let err = apply_renames_with(&renames, |from, to| {
+            if to.ends_with("d.txt") {
+                Err(io::Error::other("failing"))
+            } else {
+                fs::rename(from, to)
+            }
+        });
+        assert!(err.is_err());
+        let mut names: Vec<String> = fs::read_dir(base)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "b.txt", "c.txt"]);
+        for name in &["a.txt", "b.txt", "c.txt"] {
+            assert_eq!(fs::read_to_string(base.join(name)).unwrap(), *name);
+        }
+    }
+}
@@ -18,6 +18,11 @@ pub struct InternalExecution {
     /// arguments
     /// (for example `"~"` when a verb execution is `:!focus ~`)
     pub arg: Option<String>,
+
+    /// the internals to execute after this one, in order, each
+    /// one only when the previous one didn't fail
+    /// (for example `:toggle_hidden` in `:focus ~; :toggle_hidden`)
+    pub then: Vec<InternalExecution>,
 }
 
 impl InternalExecution {
@@ -26,6 +31,7 @@ impl InternalExecution {
             internal,
             bang: false,
             arg: None,
+            then: Vec::new(),
         }
     }
     pub fn from_internal_bang(internal: Internal, bang: bool) -> Self {
@@ -33,17 +39,46 @@ impl InternalExecution {
             internal,
             bang,
             arg: None,
+            then: Vec::new(),
         }
     }
+    /// parse an internal with its bang and argument, or a chain of
+    /// such internals separated with `;` (e.g. `focus ~; :toggle_hidden`)
     pub fn try_from(invocation_str: &str) -> Result<Self, ConfError> {
+        let mut steps = invocation_str
+            .split(';')
+            .map(|step| {
+                let step = step.trim();
+                step.strip_prefix(':').unwrap_or(step)
+            });
+        let mut execution = Self::try_from_single(steps.next().unwrap_or_default())?;
+        for step in steps {
+            execution.then.push(Self::try_from_single(step)?);
+        }
+        Ok(execution)
+    }
+    fn try_from_single(invocation_str: &str) -> Result<Self, ConfError> {
         let invocation = VerbInvocation::from(invocation_str);
         let internal = Internal::try_from(&invocation.name)?;
         Ok(Self {
             internal,
             bang: invocation.bang,
             arg: invocation.args,
+            then: Vec::new(),
         })
     }
+    pub fn is_chain(&self) -> bool {
+        !self.then.is_empty()
+    }
+    /// return all the internals of the chain, this one first
+    pub fn steps(&self) -> impl Iterator<Item = &InternalExecution> {
+        std::iter::once(self).chain(self.then.iter())
+    }
+    /// return the invocation executing this internal (but not
+    /// the ones chained after it)
+    pub fn to_invocation(&self) -> VerbInvocation {
+        VerbInvocation::new(self.internal.name().to_string(), self.arg.clone(), self.bang)
+    }
 }
 impl fmt::Display for InternalExecution {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -54,6 +89,54 @@ impl fmt::Display for InternalExecution {
         if let Some(arg) = &self.arg {
             write!(f, " {}", arg)?;
         }
+        for step in &self.then {
+            write!(f, "; {}", step)?;
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod internal_execution_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_single_internal() {
+        let execution = InternalExecution::try_from("focus ~").unwrap();
+        assert_eq!(execution.internal, Internal::focus);
+        assert_eq!(execution.arg.as_deref(), Some("~"));
+        assert!(!execution.bang);
+        assert!(!execution.is_chain());
+        assert_eq!(execution.to_string(), ":focus ~");
+        let execution = InternalExecution::try_from("toggle_hidden").unwrap();
+        assert_eq!(execution.arg, None);
+        assert_eq!(execution.to_string(), ":toggle_hidden");
+    }
+
+    #[test]
+    fn test_chain() {
+        let execution = InternalExecution::try_from("focus ~ ; :toggle_hidden").unwrap();
+        let steps: Vec<(Internal, Option<&str>, bool)> = execution
+            .steps()
+            .map(|step| (step.internal, step.arg.as_deref(), step.bang))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![(Internal::focus, Some("~"), false), (Internal::toggle_hidden, None, false)],
+        );
+        assert_eq!(execution.to_string(), ":focus ~; :toggle_hidden");
+        // the description code can be parsed back
+        let code = execution.to_string();
+        let reparsed = InternalExecution::try_from(&code[1..]).unwrap();
+        assert_eq!(reparsed.to_string(), code);
+        // each step has its own bang, and whitespace is tolerated
+        let execution = InternalExecution::try_from("focus! /tmp;toggle_hidden;  :help!  ").unwrap();
+        let bangs: Vec<bool> = execution.steps().map(|step| step.bang).collect();
+        assert_eq!(bangs, vec![true, false, true]);
+        assert_eq!(execution.to_string(), ":focus! /tmp; :toggle_hidden; :help!");
+        assert_eq!(execution.then[1].arg, None);
+        // an unknown internal in the chain is an error
+        assert!(InternalExecution::try_from("focus ~; :not_an_internal").is_err());
+    }
+}
@@ -7,6 +7,7 @@ use {
         browser::BrowserState,
         command::TriggerType,
         display::Screen,
+        errors::TreeBuildError,
         path::{self, PathAnchor},
         preview::PreviewState,
         task_sync::Dam,
@@ -133,3 +134,93 @@ pub fn on_internal(
         .unwrap_or(internal_exec.bang);
     on_path(selected_path.to_path_buf(), screen, tree_options, bang, con)
 }
+
+/// build the state of a tree whose root is the parent of the path,
+/// with the path selected. Hidden and git-ignored files are shown
+/// when it's needed to select the path.
+pub fn locate_state(
+    path: &Path,
+    screen: Screen,
+    mut tree_options: TreeOptions,
+    con: &AppContext,
+) -> Result<Option<BrowserState>, TreeBuildError> {
+    let parent = path.parent().unwrap_or(path).to_path_buf();
+    let mut state = BrowserState::new(parent.clone(), tree_options.clone(), screen, con, &Dam::unlimited())?;
+    if let Some(bs) = &mut state {
+        if !bs.tree.try_select_path(path) && (!tree_options.show_hidden || tree_options.respect_git_ignore) {
+            tree_options.show_hidden = true;
+            tree_options.respect_git_ignore = false;
+            state = BrowserState::new(parent, tree_options, screen, con, &Dam::unlimited())?;
+            if let Some(bs) = &mut state {
+                bs.tree.try_select_path(path);
+            }
+        }
+    }
+    if let Some(bs) = &mut state {
+        bs.tree.make_selection_visible(BrowserState::page_height(screen));
+    }
+    Ok(state)
+}
+
+/// open the parent of the path in a new panel, with the path selected
+pub fn locate(
+    path: &Path,
+    screen: Screen,
+    tree_options: TreeOptions,
+    con: &AppContext,
+) -> AppStateCmdResult {
+    match locate_state(path, screen, tree_options, con) {
+        Ok(Some(bs)) => AppStateCmdResult::NewPanel {
+            state: Box::new(bs),
+            purpose: PanelPurpose::None,
+            direction: HDir::Right,
+        },
+        Ok(None) => AppStateCmdResult::Keep, // this isn't supposed to happen
+        Err(e) => AppStateCmdResult::DisplayError(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod internal_focus_tests {
+
+    use {
+        super::*,
+        std::fs,
+    };
+
+    fn context(root: &Path) -> AppContext {
+        AppContext::for_test(root)
+    }
+
+    #[test]
+    fn test_locate() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/app")).unwrap();
+        for name in &["README.md", "src/main.rs", "src/app/app.rs", "src/app/mod.rs", "src/.hidden"] {
+            fs::write(root.join(name), "").unwrap();
+        }
+        let con = context(root);
+        let screen = Screen { width: 80, height: 40 };
+        let tree_options = TreeOptions {
+            flat_view: true,
+            ..TreeOptions::default()
+        };
+        let path = root.join("src/app/mod.rs");
+        let bs = locate_state(&path, screen, tree_options.clone(), &con).unwrap().unwrap();
+        assert_eq!(bs.root(), root.join("src/app"));
+        assert_eq!(bs.tree.selected_line().path, path);
+        // the options of the tree are kept
+        assert!(bs.tree.options.flat_view);
+        // a hidden file is located too
+        let path = root.join("src/.hidden");
+        let bs = locate_state(&path, screen, tree_options, &con).unwrap().unwrap();
+        assert_eq!(bs.root(), root.join("src"));
+        assert_eq!(bs.tree.selected_line().path, path);
+        assert!(bs.tree.options.show_hidden);
+        let bs = locate_state(&root.join("src"), screen, TreeOptions::default(), &con).unwrap().unwrap();
+        assert_eq!(bs.root(), root);
+        assert_eq!(bs.tree.selected_line().path, root.join("src"));
+        assert!(!bs.tree.options.show_hidden);
+    }
+}
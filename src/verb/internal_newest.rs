@@ -0,0 +1,175 @@
+//! utility functions to help handle the `:goto_newest` internal
+
+use {
+    super::*,
+    crate::{
+        app::*,
+        display::Screen,
+        git::GitIgnorer,
+        tree::TreeOptions,
+    },
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        time::SystemTime,
+    },
+};
+
+/// the number of files after which the scan stops
+const MAX_SCANNED_FILES: usize = 100_000;
+
+/// the most recently modified file found by a scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewestFile {
+    pub path: PathBuf,
+    pub mtime: SystemTime,
+    /// false when the scan was stopped before the end of the subtree
+    pub complete: bool,
+}
+
+/// find the most recently modified file of the subtree, hidden and
+/// git ignored files being filtered according to the tree options.
+///
+/// Symbolic links aren't followed. When several files have the same
+/// modification time, the first one in tree order is returned.
+pub fn newest_file(
+    root: &Path,
+    options: &TreeOptions,
+    max_scanned_files: usize,
+) -> Option<NewestFile> {
+    let mut git_ignorer = GitIgnorer::default();
+    let root_chain = git_ignorer.root_chain(root);
+    let mut newest: Option<(PathBuf, SystemTime)> = None;
+    let mut scanned_files = 0;
+    let mut complete = true;
+    let mut dirs = vec![(root.to_path_buf(), root_chain)];
+    'scan: while let Some((dir, chain)) = dirs.pop() {
+        let mut entries: Vec<fs::DirEntry> = match fs::read_dir(&dir) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).collect(),
+            Err(e) => {
+                debug!("can't read {:?} : {:?}", &dir, e);
+                continue;
+            }
+        };
+        entries.sort_by_key(|e| e.file_name().to_string_lossy().to_lowercase());
+        let mut sub_dirs = Vec::new();
+        for e in entries {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            if !options.show_hidden && name.starts_with('.') {
+                continue;
+            }
+            let file_type = match e.file_type() {
+                Ok(ft) => ft,
+                Err(_) => continue,
+            };
+            let path = e.path();
+            if options.respect_git_ignore
+                && !git_ignorer.accepts(&chain, &path, &name, file_type.is_dir())
+            {
+                continue;
+            }
+            if file_type.is_dir() {
+                let sub_chain = git_ignorer.deeper_chain(&chain, &path);
+                sub_dirs.push((path, sub_chain));
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            if scanned_files >= max_scanned_files {
+                complete = false;
+                break 'scan;
+            }
+            scanned_files += 1;
+            if let Ok(mtime) = e.metadata().and_then(|md| md.modified()) {
+                if newest.as_ref().is_none_or(|(_, newest_mtime)| mtime > *newest_mtime) {
+                    newest = Some((path, mtime));
+                }
+            }
+        }
+        dirs.extend(sub_dirs.into_iter().rev());
+    }
+    newest.map(|(path, mtime)| NewestFile { path, mtime, complete })
+}
+
+/// focus the parent of the most recently modified file of the
+/// subtree, with this file selected
+pub fn goto_newest(
+    root: &Path,
+    screen: Screen,
+    tree_options: TreeOptions,
+    in_new_panel: bool,
+    con: &AppContext,
+) -> AppStateCmdResult {
+    match newest_file(root, &tree_options, MAX_SCANNED_FILES) {
+        Some(newest) => {
+            if !newest.complete {
+                info!("goto_newest: scan stopped after {} files", MAX_SCANNED_FILES);
+            }
+            let state = internal_focus::locate_state(&newest.path, screen, tree_options, con);
+            AppStateCmdResult::from_optional_state(state, in_new_panel)
+        }
+        None => AppStateCmdResult::DisplayError("no file in this tree".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod internal_newest_tests {
+
+    use {
+        super::*,
+        std::time::Duration,
+    };
+
+    fn touch(path: &Path, secs_ago: u64) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, "content").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(secs_ago))
+            .unwrap();
+    }
+
+    fn newest_name(root: &Path, options: &TreeOptions, max: usize) -> Option<String> {
+        newest_file(root, options, max).map(|newest| {
+            newest.path.strip_prefix(root).unwrap().to_string_lossy().to_string()
+        })
+    }
+
+    #[test]
+    fn test_newest_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(&root.join("a.txt"), 500);
+        touch(&root.join("src/main.rs"), 300);
+        touch(&root.join("src/deep/mod.rs"), 100);
+        touch(&root.join("z.txt"), 200);
+        let options = TreeOptions::default();
+        assert_eq!(newest_name(root, &options, 1000).as_deref(), Some("src/deep/mod.rs"));
+        // a directory's mtime doesn't count, only files
+        fs::create_dir(root.join("empty")).unwrap();
+        assert_eq!(newest_name(root, &options, 1000).as_deref(), Some("src/deep/mod.rs"));
+        // hidden files are skipped unless the tree shows them
+        touch(&root.join(".hidden"), 10);
+        assert_eq!(newest_name(root, &options, 1000).as_deref(), Some("src/deep/mod.rs"));
+        let with_hidden = TreeOptions {
+            show_hidden: true,
+            ..TreeOptions::default()
+        };
+        assert_eq!(newest_name(root, &with_hidden, 1000).as_deref(), Some(".hidden"));
+        // the scan is bounded: `a.txt`, then `z.txt` which was modified
+        // more recently, are scanned before the content of `src`
+        let newest = newest_file(root, &options, 2).unwrap();
+        assert_eq!(newest.path, root.join("z.txt"));
+        assert!(!newest.complete);
+        assert!(newest_file(root, &options, 1000).unwrap().complete);
+        // no file
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(newest_file(empty.path(), &options, 1000), None);
+    }
+}
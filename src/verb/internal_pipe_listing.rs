@@ -0,0 +1,167 @@
+//! utility functions to help handle the `:pipe_listing` internal
+
+use {
+    crate::{
+        app::*,
+        errors::ProgramError,
+        tail::TailState,
+        tree::TreeOptions,
+    },
+    splitty::split_unquoted_whitespace,
+    std::{
+        fs,
+        io::{self, Write},
+        path::Path,
+        process::{Child, Command, Stdio},
+        thread,
+    },
+};
+
+/// what the lines of the listing are made of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListingKind {
+    /// the names of the entries
+    Names,
+    /// the complete paths of the entries
+    Paths,
+}
+
+/// list the entries of the directory, sorted, one per line. Hidden
+/// entries are only listed when `show_hidden` is true.
+pub fn listing(dir: &Path, kind: ListingKind, show_hidden: bool) -> io::Result<String> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !show_hidden && name.starts_with('.') {
+            continue;
+        }
+        entries.push(match kind {
+            ListingKind::Names => name,
+            ListingKind::Paths => entry.path().to_string_lossy().to_string(),
+        });
+    }
+    entries.sort();
+    let mut listing = String::new();
+    for entry in entries {
+        listing.push_str(&entry);
+        listing.push('\n');
+    }
+    Ok(listing)
+}
+
+/// spawn the command, its standard input being fed with the given
+/// input. The input is written from another thread so that a command
+/// which doesn't read it all, or writes a lot, doesn't block broot.
+pub fn spawn_with_input(command: &mut Command, input: String) -> io::Result<Child> {
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || {
+            // an error here is just the command not reading its input
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+    Ok(child)
+}
+
+/// launch the command with the listing of the directory as input,
+/// and open a panel following its output
+pub fn on_dir(
+    dir: &Path,
+    cmd: &str,
+    kind: ListingKind,
+    tree_options: &TreeOptions,
+) -> Result<AppStateCmdResult, ProgramError> {
+    let tokens: Vec<String> = split_unquoted_whitespace(cmd)
+        .unwrap_quotes(true)
+        .map(|t| t.to_string())
+        .collect();
+    let exe = match tokens.first() {
+        Some(exe) => exe.clone(),
+        None => {
+            return Ok(AppStateCmdResult::DisplayError(
+                "a command is needed, e.g. `:pipe_listing wc -l`".to_string(),
+            ));
+        }
+    };
+    let input = listing(dir, kind, tree_options.show_hidden)?;
+    let (file, output) = tempfile::Builder::new()
+        .prefix("broot-")
+        .suffix(".log")
+        .tempfile()?
+        .into_parts();
+    let mut command = Command::new(&exe);
+    command
+        .args(&tokens[1..])
+        .current_dir(dir)
+        .stdout(file.try_clone()?)
+        .stderr(file);
+    info!("Executing with the listing of {:?} as input: {:?}", dir, &command);
+    let child = spawn_with_input(&mut command, input)
+        .map_err(|source| ProgramError::LaunchError {
+            program: exe,
+            source,
+        })?;
+    Ok(AppStateCmdResult::NewPanel {
+        state: Box::new(TailState::new(tokens.join(" "), child, output, TreeOptions::default())),
+        purpose: PanelPurpose::None,
+        direction: HDir::Right,
+    })
+}
+
+#[cfg(test)]
+mod internal_pipe_listing_tests {
+
+    use super::*;
+
+    fn dir_with_entries() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.txt"), "").unwrap();
+        fs::write(dir.path().join("a file.md"), "").unwrap();
+        fs::write(dir.path().join(".hidden"), "").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_listing() {
+        let dir = dir_with_entries();
+        assert_eq!(
+            listing(dir.path(), ListingKind::Names, false).unwrap(),
+            "a file.md\nb.txt\nsrc\n",
+        );
+        assert_eq!(
+            listing(dir.path(), ListingKind::Names, true).unwrap(),
+            ".hidden\na file.md\nb.txt\nsrc\n",
+        );
+        let paths = listing(dir.path(), ListingKind::Paths, false).unwrap();
+        let expected: Vec<String> = ["a file.md", "b.txt", "src"]
+            .iter()
+            .map(|name| dir.path().join(name).to_string_lossy().to_string())
+            .collect();
+        assert_eq!(paths.lines().collect::<Vec<&str>>(), expected);
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(listing(empty.path(), ListingKind::Names, true).unwrap(), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_listing_fed_to_stdin() {
+        let dir = dir_with_entries();
+        let input = listing(dir.path(), ListingKind::Names, false).unwrap();
+        let child = spawn_with_input(
+            Command::new("sort").arg("-r").stdout(Stdio::piped()),
+            input,
+        ).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "src\nb.txt\na file.md\n");
+        let input = listing(dir.path(), ListingKind::Names, true).unwrap();
+        let child = spawn_with_input(
+            Command::new("wc").arg("-l").stdout(Stdio::piped()),
+            input,
+        ).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "4");
+    }
+}
@@ -0,0 +1,93 @@
+//! utility functions to help handle the `:run` internal
+
+use {
+    super::*,
+};
+
+/// the shell, and its argument, executing a command line
+#[cfg(not(windows))]
+const SHELL: &[&str] = &["sh", "-c"];
+#[cfg(windows)]
+const SHELL: &[&str] = &["cmd", "/C"];
+
+/// return the tokens of the process running the command line in
+/// the shell. The groups of the command line (e.g. `{file}`) are
+/// replaced, with the escaping the shell needs.
+pub fn shell_tokens(cmd: &str, builder: &ExecutionStringBuilder<'_>) -> Vec<String> {
+    let mut tokens: Vec<String> = SHELL.iter().map(|t| t.to_string()).collect();
+    tokens.push(builder.shell_exec_string(cmd));
+    tokens
+}
+
+/// build the execution of a command line typed by the user, run in
+/// the directory of the selection, with its output followed in a panel
+pub fn execution(cmd: &str, builder: &ExecutionStringBuilder<'_>) -> ExternalExecution {
+    let mut execution = ExternalExecution::new(String::new(), ExternalExecutionMode::StayInBroot)
+        .with_set_working_dir(Some(true))
+        .with_tail_in_panel(Some(true));
+    execution.exec_pattern = ExecPattern::from_literal_tokens(shell_tokens(cmd, builder));
+    execution
+}
+
+#[cfg(all(test, unix))]
+mod internal_run_tests {
+
+    use {
+        super::*,
+        crate::app::{Selection, SelectionType},
+        std::path::{Path, PathBuf},
+    };
+
+    fn selection(path: &Path, stype: SelectionType) -> Selection<'_> {
+        Selection {
+            path,
+            line: 0,
+            stype,
+            is_exe: false,
+        }
+    }
+
+    #[test]
+    fn test_run_execution() {
+        let file = PathBuf::from("/home/dys/dev/my notes.md");
+        let builder = ExecutionStringBuilder::from_selection(selection(&file, SelectionType::File));
+        let run = execution("wc -l {file} > {file-stem}.count", &builder);
+        assert_eq!(
+            builder.pattern_exec_token(&run.exec_pattern),
+            vec!["sh", "-c", "wc -l '/home/dys/dev/my notes.md' > 'my notes'.count"],
+        );
+        assert!(run.tail_in_panel);
+    }
+
+    #[test]
+    fn test_run_working_dir() {
+        // the process is launched in the directory of the selection
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.md");
+        std::fs::write(&file, "").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(selection(&file, SelectionType::File));
+        let run = execution("ls -l", &builder);
+        assert_eq!(
+            builder.pattern_exec_token(&run.exec_pattern),
+            vec!["sh", "-c", "ls -l"],
+        );
+        assert_eq!(run.working_dir(&builder).unwrap(), Some(dir.path().to_path_buf()));
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+        let builder = ExecutionStringBuilder::from_selection(selection(&sub, SelectionType::Directory));
+        let run = execution("ls -l", &builder);
+        assert_eq!(run.working_dir(&builder).unwrap(), Some(sub.clone()));
+    }
+
+    #[test]
+    fn test_run_literal_braces() {
+        // braces coming from the replaced values aren't groups
+        let file = PathBuf::from("/tmp/{file}");
+        let builder = ExecutionStringBuilder::from_selection(selection(&file, SelectionType::File));
+        let run = execution("cat {file}", &builder);
+        assert_eq!(
+            builder.pattern_exec_token(&run.exec_pattern),
+            vec!["sh", "-c", "cat '/tmp/{file}'"],
+        );
+    }
+}
@@ -35,6 +35,10 @@ pub struct InvocationParser {
     /// to select the argument in another panel)
     pub arg_selection_type: Option<SelectionType>,
 
+    /// the values of the arguments declared with a default
+    /// value (like `{name=default}`), used when they're not given
+    defaults: FnvHashMap<String, String>,
+
 }
 
 impl InvocationParser {
@@ -46,9 +50,9 @@ impl InvocationParser {
         let mut args_parser = None;
         let mut arg_selection_type = None;
         let mut arg_anchor = PathAnchor::Unspecified;
+        let mut defaults = FnvHashMap::default();
         if let Some(args) = &invocation_pattern.args {
-            let spec = GROUP.replace_all(args, r"(?P<$1>.+)");
-            let spec = format!("^{}$", spec);
+            let spec = args_spec(args, &mut defaults);
             args_parser = match Regex::new(&spec) {
                 Ok(regex) => Some(regex),
                 Err(_) => {
@@ -73,6 +77,7 @@ impl InvocationParser {
             args_parser,
             arg_selection_type,
             arg_anchor,
+            defaults,
         })
     }
 
@@ -108,19 +113,129 @@ impl InvocationParser {
         }
     }
 
-    pub fn parse(&self, args: &str) -> Option<FnvHashMap<String, String>> {
+    /// read the arguments typed by the user, completed with the
+    /// default values of the omitted ones
+    pub fn parse(&self, args: Option<&str>) -> Option<FnvHashMap<String, String>> {
         self.args_parser.as_ref()
             .map(|r| {
                 let mut map = FnvHashMap::default();
-                if let Some(input_cap) = r.captures(&args) {
+                if let Some(input_cap) = args.and_then(|args| r.captures(args)) {
                     for name in r.capture_names().flatten() {
                         if let Some(c) = input_cap.name(name) {
                             map.insert(name.to_string(), c.as_str().to_string());
                         }
                     }
                 }
+                for (name, value) in &self.defaults {
+                    map.entry(name.to_string()).or_insert_with(|| value.to_string());
+                }
                 map
             })
     }
 
 }
+
+/// build the regex reading the arguments of an invocation pattern, and
+/// gather the default values of the arguments declared like `{name=default}`.
+///
+/// An argument with a default value may be omitted, with the space before
+/// it. As the arguments can't be all greedy in such a case, they're all
+/// lazy when there's a default value.
+fn args_spec(args: &str, defaults: &mut FnvHashMap<String, String>) -> String {
+    let lazy = if GROUP.captures_iter(args).any(|c| c[1].contains('=')) {
+        "?"
+    } else {
+        ""
+    };
+    let mut spec = String::from("^");
+    let mut end = 0;
+    for c in GROUP.captures_iter(args) {
+        let group = c.get(0).unwrap();
+        let mut literal = &args[end..group.start()];
+        end = group.end();
+        if let Some((name, default)) = c[1].split_once('=') {
+            defaults.insert(name.to_string(), default.to_string());
+            let separator = if let Some(stripped) = literal.strip_suffix(' ') {
+                literal = stripped;
+                " "
+            } else {
+                ""
+            };
+            spec.push_str(literal);
+            spec.push_str(&format!("(?:{}(?P<{}>.*{}))?", separator, name, lazy));
+        } else {
+            spec.push_str(literal);
+            spec.push_str(&format!("(?P<{}>.+{})", &c[1], lazy));
+        }
+    }
+    spec.push_str(&args[end..]);
+    spec.push('$');
+    spec
+}
+
+#[cfg(test)]
+mod invocation_parser_tests {
+
+    use super::*;
+
+    fn parse(pattern: &str, args: Option<&str>) -> Option<FnvHashMap<String, String>> {
+        InvocationParser::new(pattern).unwrap().parse(args)
+    }
+
+    fn map(entries: &[(&str, &str)]) -> FnvHashMap<String, String> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_without_default() {
+        assert_eq!(parse("mv {new_name}", Some("a.txt")), Some(map(&[("new_name", "a.txt")])));
+        // the argument is required
+        assert_eq!(parse("mv {new_name}", None), Some(map(&[])));
+        assert_eq!(parse("mv {new_name}", Some("")), Some(map(&[])));
+        // the arguments are greedy, as before
+        assert_eq!(
+            parse("cp {a} {b}", Some("x y z")),
+            Some(map(&[("a", "x y"), ("b", "z")])),
+        );
+        assert_eq!(parse("refresh", None), None);
+    }
+
+    #[test]
+    fn test_default_values() {
+        assert_eq!(parse("grep {pattern=TODO}", None), Some(map(&[("pattern", "TODO")])));
+        assert_eq!(parse("grep {pattern=TODO}", Some("FIXME")), Some(map(&[("pattern", "FIXME")])));
+        // an explicitly empty value overrides the default one
+        assert_eq!(parse("grep {pattern=TODO}", Some("")), Some(map(&[("pattern", "")])));
+        // a default value may contain spaces
+        assert_eq!(
+            parse("commit {message=work in progress}", None),
+            Some(map(&[("message", "work in progress")])),
+        );
+        assert_eq!(
+            parse("commit {message=work in progress}", Some("fix the tests")),
+            Some(map(&[("message", "fix the tests")])),
+        );
+    }
+
+    #[test]
+    fn test_required_and_default() {
+        let pattern = "head {file} {count=10:int}";
+        assert_eq!(
+            parse(pattern, Some("main.rs 20")),
+            Some(map(&[("file", "main.rs"), ("count", "20")])),
+        );
+        assert_eq!(
+            parse(pattern, Some("main.rs")),
+            Some(map(&[("file", "main.rs"), ("count", "10")])),
+        );
+        // the required argument is still required
+        let parser = InvocationParser::new(pattern).unwrap();
+        let invocation = VerbInvocation::from("head");
+        assert!(parser.check_args(&invocation, &None).is_some());
+        let invocation = VerbInvocation::from("head main.rs");
+        assert!(parser.check_args(&invocation, &None).is_none());
+        // when all arguments have defaults, none is required
+        let parser = InvocationParser::new("grep {pattern=TODO}").unwrap();
+        assert!(parser.check_args(&VerbInvocation::from("grep"), &None).is_none());
+    }
+}
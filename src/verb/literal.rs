@@ -0,0 +1,74 @@
+//! escapers building string literals of a few programming
+//! languages, for verbs generating code
+
+use std::fmt::Write;
+
+/// build a Rust string literal (with its double quotes)
+pub fn rust_string(s: &str) -> String {
+    let mut lit = String::with_capacity(s.len() + 2);
+    lit.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => lit.push_str("\\\""),
+            '\\' => lit.push_str("\\\\"),
+            '\n' => lit.push_str("\\n"),
+            '\r' => lit.push_str("\\r"),
+            '\t' => lit.push_str("\\t"),
+            '\0' => lit.push_str("\\0"),
+            c if c.is_control() => {
+                let _ = write!(lit, "\\u{{{:x}}}", c as u32);
+            }
+            c => lit.push(c),
+        }
+    }
+    lit.push('"');
+    lit
+}
+
+/// build a Python (3) string literal (with its double quotes)
+pub fn python_string(s: &str) -> String {
+    let mut lit = String::with_capacity(s.len() + 2);
+    lit.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => lit.push_str("\\\""),
+            '\\' => lit.push_str("\\\\"),
+            '\n' => lit.push_str("\\n"),
+            '\r' => lit.push_str("\\r"),
+            '\t' => lit.push_str("\\t"),
+            c if c.is_control() && (c as u32) < 0x100 => {
+                let _ = write!(lit, "\\x{:02x}", c as u32);
+            }
+            c if c.is_control() => {
+                let _ = write!(lit, "\\u{:04x}", c as u32);
+            }
+            c => lit.push(c),
+        }
+    }
+    lit.push('"');
+    lit
+}
+
+#[cfg(test)]
+mod literal_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rust_string() {
+        assert_eq!(rust_string("/home/dys/dev"), r#""/home/dys/dev""#);
+        assert_eq!(rust_string(r#"/tmp/a "b".txt"#), r#""/tmp/a \"b\".txt""#);
+        assert_eq!(rust_string(r"C:\Users\dys"), r#""C:\\Users\\dys""#);
+        assert_eq!(rust_string("a\tb\nc\u{1b}"), r#""a\tb\nc\u{1b}""#);
+        assert_eq!(rust_string("été"), r#""été""#);
+    }
+
+    #[test]
+    fn test_python_string() {
+        assert_eq!(python_string("/home/dys/dev"), r#""/home/dys/dev""#);
+        assert_eq!(python_string(r#"/tmp/a "b".txt"#), r#""/tmp/a \"b\".txt""#);
+        assert_eq!(python_string(r"C:\Users\dys"), r#""C:\\Users\\dys""#);
+        assert_eq!(python_string("a\tb\nc\u{1b}"), r#""a\tb\nc\x1b""#);
+        assert_eq!(python_string("été"), r#""été""#);
+    }
+}
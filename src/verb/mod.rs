@@ -1,12 +1,20 @@
 mod builtin;
+mod exec_log;
+mod exec_pattern;
 mod execution_builder;
 mod external_execution;
 mod external_execution_mode;
 mod internal;
+pub mod internal_batch_rename;
 mod internal_execution;
 pub mod internal_focus;
+pub mod internal_newest;
+pub mod internal_pipe_listing;
+pub mod internal_run;
 mod invocation_parser;
+mod literal;
 mod sequence_execution;
+mod steps_execution;
 mod verb;
 mod verb_description;
 mod verb_execution;
@@ -14,6 +22,7 @@ mod verb_invocation;
 mod verb_store;
 
 pub use {
+    exec_pattern::{ExecPattern, PatternGroup, PatternSegment},
     execution_builder::ExecutionStringBuilder,
     external_execution::ExternalExecution,
     external_execution_mode::ExternalExecutionMode,
@@ -21,6 +30,7 @@ pub use {
     internal_execution::InternalExecution,
     invocation_parser::InvocationParser,
     sequence_execution::SequenceExecution,
+    steps_execution::StepsExecution,
     verb::Verb,
     verb_description::VerbDescription,
     verb_execution::VerbExecution,
@@ -32,5 +42,5 @@ pub use {
 // the group you find in invocation patterns and execution patterns
 lazy_static! {
     pub static ref GROUP: regex::Regex =
-        regex::Regex::new(r"\{([^{}:]+)(?::([^{}:]+))?\}").unwrap();
+        regex::Regex::new(r"\{([^{}:]+)(?::([^{}:]+|\{line\}))?\}").unwrap();
 }
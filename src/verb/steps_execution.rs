@@ -0,0 +1,124 @@
+use {
+    super::*,
+    std::fmt,
+};
+
+/// A verb execution made of several executions, internals or
+/// externals, done in order
+/// (for example `[":focus {parent}", "git add {file}", ":refresh"]`)
+#[derive(Debug, Clone)]
+pub struct StepsExecution {
+
+    /// the internal and external executions, in order
+    pub steps: Vec<VerbExecution>,
+
+    /// whether the steps following a failed one must be skipped
+    pub stop_on_error: bool,
+
+}
+
+impl StepsExecution {
+    /// return the steps with their groups replaced, so that they
+    /// act on the selection of the verb's trigger even when a
+    /// previous step changed it, or the first invalid value
+    pub fn resolve(
+        &self,
+        builder: &ExecutionStringBuilder<'_>,
+    ) -> Result<Vec<VerbExecution>, String> {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                VerbExecution::Internal(internal_exec) => {
+                    let arg = match &internal_exec.arg {
+                        Some(arg) => {
+                            if let Some(err) = builder.check_values(arg) {
+                                return Err(err);
+                            }
+                            Some(builder.path(arg).to_string_lossy().to_string())
+                        }
+                        None => None,
+                    };
+                    Ok(VerbExecution::Internal(InternalExecution {
+                        arg,
+                        ..internal_exec.clone()
+                    }))
+                }
+                VerbExecution::External(external_exec) => external_exec
+                    .resolved(builder.clone())
+                    .map(VerbExecution::External),
+                _ => Ok(step.clone()),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for StepsExecution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, step) in self.steps.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "; ")?;
+            }
+            step.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod steps_execution_tests {
+
+    use {
+        super::*,
+        crate::app::{Selection, SelectionType},
+        std::path::PathBuf,
+    };
+
+    #[test]
+    fn test_resolve() {
+        let path = PathBuf::from("/home/dys/my notes/todo.md");
+        let sel = Selection {
+            path: &path,
+            line: 0,
+            stype: SelectionType::File,
+            is_exe: false,
+        };
+        let builder = ExecutionStringBuilder::from_selection(sel);
+        let steps_ex = StepsExecution {
+            steps: vec![
+                VerbExecution::Internal(InternalExecution::try_from("focus {parent}").unwrap()),
+                VerbExecution::External(ExternalExecution::new(
+                    "git add {file}".to_string(),
+                    ExternalExecutionMode::StayInBroot,
+                )),
+                VerbExecution::Internal(InternalExecution::try_from("refresh").unwrap()),
+            ],
+            stop_on_error: true,
+        };
+        let steps = steps_ex.resolve(&builder).unwrap();
+        assert_eq!(
+            steps.iter().map(|step| step.to_string()).collect::<Vec<String>>(),
+            vec![
+                ":focus /home/dys/my notes",
+                "git add /home/dys/my notes/todo.md",
+                ":refresh",
+            ],
+        );
+        // the values are now literal: the path is one token, and the
+        // external doesn't depend on the selection anymore
+        match &steps[1] {
+            VerbExecution::External(external_exec) => {
+                assert!(external_exec.fail_on_exit_code);
+                let other_path = PathBuf::from("/tmp/other");
+                let other_builder = ExecutionStringBuilder::from_selection(Selection {
+                    path: &other_path,
+                    ..sel
+                });
+                assert_eq!(
+                    other_builder.pattern_exec_token(&external_exec.exec_pattern),
+                    vec!["git", "add", "/home/dys/my notes/todo.md"],
+                );
+            }
+            _ => panic!("not an external"),
+        }
+    }
+}
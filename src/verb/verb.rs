@@ -7,7 +7,10 @@ use {
         path::{self, PathAnchor},
     },
     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
-    std::path::PathBuf,
+    std::{
+        path::{Path, PathBuf},
+        time::Duration,
+    },
 };
 
 /// what makes a verb.
@@ -51,6 +54,38 @@ pub struct Verb {
     /// whether we need to have a secondary panel for execution
     /// (which is the case when the execution pattern has {other-panel-file})
     pub need_another_panel: bool,
+
+    /// whether the verb must not be executed when the selection
+    /// is on a network file system (only checked on unix)
+    pub skip_on_network_fs: bool,
+
+    /// whether the verb must not be executed when broot isn't
+    /// the foreground process of its terminal
+    pub require_foreground: bool,
+
+    /// whether the name of the selection must be typed as argument
+    /// for the verb to be executed (for the most dangerous verbs)
+    pub confirm_typing: bool,
+
+    /// whether the execution must be aborted when the selection
+    /// changed since the verb was started in the input
+    pub stable_selection: bool,
+
+    /// the minimal size, in bytes, of the selection for the
+    /// verb to be executed
+    pub min_size: Option<u64>,
+
+    /// the maximal size, in bytes, of the selection for the
+    /// verb to be executed
+    pub max_size: Option<u64>,
+
+    /// when set, triggers of the verb closer than this duration
+    /// are coalesced into one execution, after the last one
+    pub debounce: Option<Duration>,
+
+    /// the key mode in which the keys of the verb trigger it,
+    /// None if they trigger it in all modes
+    pub mode: Option<String>,
 }
 
 impl Verb {
@@ -67,8 +102,8 @@ impl Verb {
         }
         let mut need_another_panel = false;
         if let VerbExecution::External(ref external) = execution {
-            for group in GROUP.find_iter(&external.exec_pattern) {
-                if group.as_str().starts_with("{other-panel-") {
+            for exec_pattern in external.exec_patterns() {
+                if exec_pattern.groups().any(|group| group.name.starts_with("other-panel-")) {
                     need_another_panel = true;
                 }
             }
@@ -82,6 +117,14 @@ impl Verb {
             description,
             selection_condition: SelectionType::Any,
             need_another_panel,
+            skip_on_network_fs: false,
+            require_foreground: false,
+            confirm_typing: false,
+            stable_selection: false,
+            min_size: None,
+            max_size: None,
+            debounce: None,
+            mode: None,
         })
     }
 
@@ -134,6 +177,8 @@ impl Verb {
     ) -> Option<String> {
         if self.need_another_panel && other_path.is_none() {
             Some("This verb needs exactly two panels".to_string())
+        } else if self.confirm_typing {
+            None // arguments are checked with check_typed_confirmation
         } else if let Some(ref parser) = self.invocation_parser {
             parser.check_args(invocation, other_path)
         } else if invocation.args.is_some() {
@@ -143,6 +188,68 @@ impl Verb {
         }
     }
 
+    /// When the verb requires a typed confirmation, check the arguments
+    /// are exactly the name of the selection. Return the message to
+    /// display when the verb can't be executed.
+    pub fn check_typed_confirmation(
+        &self,
+        sel: Selection<'_>,
+        args: Option<&str>,
+    ) -> Option<String> {
+        if !self.confirm_typing {
+            return None;
+        }
+        let name = sel.path.file_name()
+            .unwrap_or(sel.path.as_os_str())
+            .to_string_lossy();
+        if args == Some(name.as_ref()) {
+            None
+        } else {
+            Some(format!("Type the name of the selection (`{}`) after the verb to confirm", name))
+        }
+    }
+
+    /// When the verb requires a stable selection, check the selection
+    /// is still the one which was selected when the verb was started.
+    /// Return the message to display when the verb can't be executed.
+    pub fn check_stable_selection(
+        &self,
+        initial: Option<&Path>,
+        current: &Path,
+    ) -> Option<String> {
+        match initial {
+            Some(initial) if self.stable_selection && initial != current => Some(format!(
+                "Selection changed since the verb was started (was `{}`): aborted",
+                initial.to_string_lossy(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// whether the verb's execution depends on the size of the selection
+    pub fn has_size_condition(&self) -> bool {
+        self.min_size.is_some() || self.max_size.is_some()
+    }
+
+    /// When the verb has size conditions, check the size of the
+    /// selection is in range. Return the message to display when
+    /// the verb can't be executed.
+    pub fn check_size(&self, size: u64) -> Option<String> {
+        match (self.min_size, self.max_size) {
+            (Some(min_size), _) if size < min_size => Some(format!(
+                "verb skipped: the selection's size ({}) is below {}",
+                file_size::fit_4(size),
+                file_size::fit_4(min_size),
+            )),
+            (_, Some(max_size)) if size > max_size => Some(format!(
+                "verb skipped: the selection's size ({}) is above {}",
+                file_size::fit_4(size),
+                file_size::fit_4(max_size),
+            )),
+            _ => None,
+        }
+    }
+
     fn get_status_markdown(
         &self,
         sel: Selection<'_>,
@@ -179,10 +286,19 @@ impl Verb {
             )
         };
         if let VerbExecution::Sequence(seq_ex) = &self.execution {
-            let exec_desc = builder().shell_exec_string(&seq_ex.sequence.raw);
+            let builder = builder();
+            if let Some(err) = builder.check_values(&seq_ex.sequence.raw) {
+                return format!("Invalid argument: {}", err);
+            }
+            let exec_desc = builder.shell_exec_string(&seq_ex.sequence.raw);
             format!("Hit *enter* to **{}**: `{}`", name, &exec_desc)
         } else if let VerbExecution::External(external_exec) = &self.execution {
-            let exec_desc = builder().shell_exec_string(&external_exec.exec_pattern);
+            let builder = builder().with_escaping(external_exec.escaping);
+            let exec_pattern = external_exec.current_exec_pattern();
+            if let Some(err) = builder.pattern_check_values(exec_pattern) {
+                return format!("Invalid argument: {}", err);
+            }
+            let exec_desc = builder.pattern_shell_exec_string(exec_pattern);
             format!("Hit *enter* to **{}**: `{}`", name, &exec_desc)
         } else if self.description.code {
             format!("Hit *enter* to **{}**: `{}`", name, &self.description.content)
@@ -199,6 +315,8 @@ impl Verb {
     ) -> Status {
         if let Some(err) = self.check_args(invocation, other_path) {
             Status::new(err, true)
+        } else if let Some(msg) = self.check_typed_confirmation(sel, invocation.args.as_deref()) {
+            Status::new(msg, false)
         } else {
             Status::new(
                 self.get_status_markdown(
@@ -219,6 +337,31 @@ impl Verb {
             .and_then(|parser| parser.arg_selection_type)
     }
 
+    /// tell whether the verb takes a unique argument which is a path
+    /// (declared with a `path-from-parent` or `path-from-directory` format)
+    pub fn has_path_arg(&self) -> bool {
+        !matches!(self.get_arg_anchor(), PathAnchor::Unspecified)
+    }
+
+    /// tell whether the verb needs arguments which aren't in the given
+    /// invocation, in which case, when it's triggered by a key, they're
+    /// asked in the input instead of the verb failing
+    pub fn needs_args_prompt(&self, invocation: Option<&VerbInvocation>) -> bool {
+        if self.confirm_typing || invocation.is_some_and(|inv| inv.args.is_some()) {
+            return false;
+        }
+        let (name, parser) = match (self.names.first(), &self.invocation_parser) {
+            (Some(name), Some(parser)) => (name, parser),
+            _ => return false,
+        };
+        let invocation = VerbInvocation {
+            name: name.to_string(),
+            args: None,
+            bang: false,
+        };
+        parser.check_args(&invocation, &None).is_some()
+    }
+
     pub fn get_arg_anchor(&self) -> PathAnchor {
         self.invocation_parser
             .as_ref()
@@ -233,3 +376,105 @@ impl Verb {
     }
 
 }
+
+#[cfg(test)]
+mod verb_test {
+
+    use super::*;
+
+    fn rm_verb(confirm_typing: bool) -> Verb {
+        let execution = VerbExecution::External(ExternalExecution::new(
+            "rm -rf {file}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ));
+        let mut verb = Verb::new(
+            Some("rm"),
+            execution,
+            VerbDescription::from_text("remove".to_string()),
+        ).unwrap();
+        verb.confirm_typing = confirm_typing;
+        verb
+    }
+
+    #[test]
+    fn test_typed_confirmation() {
+        let path = PathBuf::from("/home/dys/precious.db");
+        let sel = Selection {
+            path: &path,
+            line: 0,
+            stype: SelectionType::File,
+            is_exe: false,
+        };
+        let verb = rm_verb(true);
+        assert!(verb.check_typed_confirmation(sel, Some("precious.db")).is_none());
+        assert!(verb.check_typed_confirmation(sel, None).is_some());
+        assert!(verb.check_typed_confirmation(sel, Some("")).is_some());
+        assert!(verb.check_typed_confirmation(sel, Some("precious")).is_some());
+        assert!(verb.check_typed_confirmation(sel, Some("Precious.db")).is_some());
+        assert!(verb.check_typed_confirmation(sel, Some("/home/dys/precious.db")).is_some());
+        // the name given as argument isn't refused as an unexpected argument
+        let invocation = VerbInvocation::from("rm precious.db");
+        assert!(verb.check_args(&invocation, &None).is_none());
+        // verbs without confirmation are executed directly
+        let verb = rm_verb(false);
+        assert!(verb.check_typed_confirmation(sel, None).is_none());
+    }
+
+    #[test]
+    fn test_stable_selection() {
+        let initial = PathBuf::from("/home/dys/precious.db");
+        let other = PathBuf::from("/home/dys/trash.db");
+        let mut verb = rm_verb(false);
+        verb.stable_selection = true;
+        // the selection didn't change between trigger and execution
+        assert!(verb.check_stable_selection(Some(&initial), &initial).is_none());
+        // the selection moved while the verb was being typed
+        let msg = verb.check_stable_selection(Some(&initial), &other);
+        assert!(msg.unwrap().contains("precious.db"));
+        // the verb was triggered and executed at once (e.g. with a key)
+        assert!(verb.check_stable_selection(None, &other).is_none());
+        // verbs without the option don't care
+        verb.stable_selection = false;
+        assert!(verb.check_stable_selection(Some(&initial), &other).is_none());
+    }
+
+    #[test]
+    fn test_size_condition() {
+        let mut verb = rm_verb(false);
+        assert!(!verb.has_size_condition());
+        assert!(verb.check_size(0).is_none());
+        verb.min_size = Some(500_000);
+        verb.max_size = Some(1_000_000);
+        assert!(verb.has_size_condition());
+        // below
+        assert!(verb.check_size(499_999).unwrap().contains("below"));
+        // within
+        assert!(verb.check_size(500_000).is_none());
+        assert!(verb.check_size(750_000).is_none());
+        assert!(verb.check_size(1_000_000).is_none());
+        // above
+        assert!(verb.check_size(1_000_001).unwrap().contains("above"));
+        // only one bound
+        verb.min_size = None;
+        assert!(verb.check_size(0).is_none());
+    }
+
+    #[test]
+    fn test_args_prompt() {
+        let execution = VerbExecution::External(ExternalExecution::new(
+            "mkdir -p {subpath:path-from-directory}".to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ));
+        let verb = Verb::new(
+            Some("mkdir {subpath}"),
+            execution,
+            VerbDescription::from_text("create a directory".to_string()),
+        ).unwrap();
+        assert!(verb.needs_args_prompt(None));
+        assert!(verb.needs_args_prompt(Some(&VerbInvocation::from("other"))));
+        assert!(!verb.needs_args_prompt(Some(&VerbInvocation::from("mkdir a/b"))));
+        // verbs without argument, or with a typed confirmation, are executed
+        assert!(!rm_verb(false).needs_args_prompt(None));
+        assert!(!rm_verb(true).needs_args_prompt(None));
+    }
+}
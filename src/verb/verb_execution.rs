@@ -17,6 +17,10 @@ pub enum VerbExecution {
     /// the execution is a sequence similar to what can be given
     /// to broot with --cmd
     Sequence(SequenceExecution),
+
+    /// the execution is a list of internals and externals, as given
+    /// in the `execution` of a verb in conf
+    Steps(StepsExecution),
 }
 
 impl fmt::Display for VerbExecution {
@@ -25,6 +29,7 @@ impl fmt::Display for VerbExecution {
             Self::Internal(ie) => ie.fmt(f),
             Self::External(ee) => ee.exec_pattern.fmt(f),
             Self::Sequence(se) => se.sequence.raw.fmt(f),
+            Self::Steps(se) => se.fmt(f),
         }
     }
 }
@@ -2,6 +2,20 @@ use {
     std::fmt,
 };
 
+/// the separator between the namespace of a verb and its local
+/// name, e.g. in `git/commit`
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// split a verb name in its namespace, if any, and its local name,
+/// e.g. `git/commit` in `git` and `commit`. Namespaces may be nested,
+/// the local name being after the last separator.
+pub fn split_namespace(name: &str) -> (Option<&str>, &str) {
+    match name.rfind(NAMESPACE_SEPARATOR) {
+        Some(idx) => (Some(&name[..idx]), &name[idx + 1..]),
+        None => (None, name),
+    }
+}
+
 /// the verb and its arguments, making the invocation.
 /// When coming from parsing, the args is Some as soon
 /// as there's a separator (i.e. it's "" in "cp ")
@@ -37,6 +51,10 @@ impl VerbInvocation {
     pub fn is_empty(&self) -> bool {
         self.name.is_empty()
     }
+    /// return the namespace of the verb, e.g. `git` for `git/commit`
+    pub fn namespace(&self) -> Option<&str> {
+        split_namespace(&self.name).0
+    }
     /// build a new String
     pub fn complete_name(&self) -> String {
         if self.bang {
@@ -110,4 +128,15 @@ mod verb_invocation_tests {
             VerbInvocation::new("cp", Some("../"), false),
         );
     }
+    #[test]
+    fn check_namespaced_invocation_parsing() {
+        let invocation = VerbInvocation::from("git/commit -m wip");
+        assert_eq!(invocation, VerbInvocation::new("git/commit", Some("-m wip"), false));
+        assert_eq!(invocation.namespace(), Some("git"));
+        assert_eq!(VerbInvocation::from("git/").namespace(), Some("git"));
+        assert_eq!(VerbInvocation::from("cp ../a/b").namespace(), None);
+        assert_eq!(split_namespace("git/remote/add"), (Some("git/remote"), "add"));
+        assert_eq!(split_namespace("git/"), (Some("git"), ""));
+        assert_eq!(split_namespace("commit"), (None, "commit"));
+    }
 }
@@ -1,19 +1,53 @@
 use {
     super::{
         builtin::builtin_verbs,
+        verb_invocation::split_namespace,
         Internal,
         Verb,
+        VerbExecution,
     },
     crate::{
-        app::SelectionType,
+        app::{Selection, SelectionType},
         conf::Conf,
         errors::ConfError,
         keys,
+        provider::provided_verbs,
     },
     crossterm::event::KeyEvent,
     std::convert::TryFrom,
 };
 
+/// score the match of the typed characters in a verb name, when
+/// they're all found in order (case being ignored).
+/// Characters found at the start of the name or of a word of the
+/// name, and consecutive characters, improve the score, while the
+/// skipped characters lower it.
+fn fuzzy_score(name: &str, typed: &str) -> Option<i32> {
+    let name: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    let typed: Vec<char> = typed.chars().flat_map(char::to_lowercase).collect();
+    if typed.is_empty() {
+        return None;
+    }
+    let mut score = 0;
+    let mut ni = 0;
+    let mut previous_match: Option<usize> = None;
+    for &tc in &typed {
+        let idx = (ni..name.len()).find(|&i| name[i] == tc)?;
+        score -= (idx - ni) as i32; // skipped characters
+        if idx == 0 {
+            score += 20;
+        } else if !name[idx - 1].is_alphanumeric() {
+            score += 10; // start of a word
+        }
+        if previous_match.map_or(idx == 0, |p| p + 1 == idx) {
+            score += 5; // consecutive characters
+        }
+        previous_match = Some(idx);
+        ni = idx + 1;
+    }
+    Some(score)
+}
+
 /// Provide access to the verbs:
 /// - the built-in ones
 /// - the user defined ones
@@ -21,9 +55,12 @@ use {
 /// When the user types some keys, we select a verb
 /// - if the input exactly matches a shortcut or the name
 /// - if only one verb name starts with the input
+/// - when fuzzy search is enabled and no verb name starts with the
+///   input, if only one verb name contains its characters in order
 #[derive(Default)]
 pub struct VerbStore {
     pub verbs: Vec<Verb>,
+    pub fuzzy_search: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -40,7 +77,23 @@ impl VerbStore {
         for vc in &conf.verbs {
             self.verbs.push(Verb::try_from(vc)?);
         }
+        // then the verbs generated by the providers, a failing
+        // provider or an invalid provided verb being only logged
+        for command in &conf.verb_providers {
+            match provided_verbs(command) {
+                Ok(vcs) => {
+                    for vc in &vcs {
+                        match Verb::try_from(vc) {
+                            Ok(verb) => self.verbs.push(verb),
+                            Err(e) => warn!("invalid verb provided by {:?}: {}", command, e),
+                        }
+                    }
+                }
+                Err(e) => warn!("{}", e),
+            }
+        }
         self.verbs.extend(builtin_verbs());
+        self.fuzzy_search = conf.fuzzy_verb_search.unwrap_or(false);
         Ok(())
     }
 
@@ -71,12 +124,86 @@ impl VerbStore {
             }
         }
         match nb_found {
+            0 if self.fuzzy_search => self.fuzzy_search(prefix, stype),
             0 => PrefixSearchResult::NoMatch,
             1 => PrefixSearchResult::Match(completions[0], &self.verbs[found_index]),
             _ => PrefixSearchResult::Matches(completions),
         }
     }
 
+    /// search the verbs whose names contain the typed characters
+    /// in order (ignoring case), the best ranked first.
+    ///
+    /// When a namespace is typed (e.g. `git/cmt`), only the verbs of
+    /// this namespace are searched, on their local names.
+    fn fuzzy_search<'v>(
+        &'v self,
+        typed: &str,
+        stype: Option<SelectionType>,
+    ) -> PrefixSearchResult<'v, &'v Verb> {
+        let (typed_namespace, typed_local) = split_namespace(typed);
+        let mut candidates: Vec<(i32, &'v str, &'v Verb)> = Vec::new();
+        for verb in &self.verbs {
+            if let Some(stype) = stype {
+                if !stype.respects(verb.selection_condition) {
+                    continue;
+                }
+            }
+            for name in &verb.names {
+                if candidates.iter().any(|&(_, n, _)| n == name) {
+                    continue; // a previous verb has this name
+                }
+                let score = match typed_namespace {
+                    Some(namespace) => match split_namespace(name) {
+                        (Some(name_namespace), local_name) if name_namespace == namespace => {
+                            fuzzy_score(local_name, typed_local)
+                        }
+                        _ => None,
+                    },
+                    None => fuzzy_score(name, typed),
+                };
+                if let Some(score) = score {
+                    candidates.push((score, name, verb));
+                }
+            }
+        }
+        // the best scores first, the names in alphabetical order on ties
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        match candidates.len() {
+            0 => PrefixSearchResult::NoMatch,
+            1 => PrefixSearchResult::Match(candidates[0].1, candidates[0].2),
+            _ => PrefixSearchResult::Matches(candidates.iter().map(|c| c.1).collect()),
+        }
+    }
+
+    /// return the verbs running a command or a sequence which may be
+    /// executed on the selection: the ones applying to its type, not
+    /// needing another panel when there's none, and, for a file, whose
+    /// size conditions are met (the size of a directory would be too
+    /// long to compute). Verbs without name aren't returned.
+    pub fn applicable_verbs(
+        &self,
+        sel: Selection<'_>,
+        has_other_panel: bool,
+    ) -> Vec<&Verb> {
+        self.verbs
+            .iter()
+            .filter(|verb| !verb.names.is_empty())
+            .filter(|verb| !matches!(verb.execution, VerbExecution::Internal(_)))
+            .filter(|verb| sel.stype.respects(verb.selection_condition))
+            .filter(|verb| has_other_panel || !verb.need_another_panel)
+            .filter(|verb| {
+                if !verb.has_size_condition() || sel.stype != SelectionType::File {
+                    return true;
+                }
+                match sel.path.metadata() {
+                    Ok(md) => verb.check_size(md.len()).is_none(),
+                    Err(_) => false,
+                }
+            })
+            .collect()
+    }
+
     /// return the index of the verb which is triggered by the given keyboard key, if any
     pub fn index_of_key(&self, key: KeyEvent) -> Option<usize> {
         for i in 0..self.verbs.len() {
@@ -115,3 +242,238 @@ impl VerbStore {
     }
 
 }
+
+#[cfg(test)]
+mod verb_store_tests {
+
+    use {
+        super::*,
+        crate::verb::{
+            ExecutionStringBuilder, ExternalExecution, ExternalExecutionMode,
+            InternalExecution, VerbDescription, VerbExecution,
+        },
+        std::{fs, path::Path},
+    };
+
+    fn external_verb(invocation: Option<&str>, exec_pattern: &str) -> Verb {
+        let execution = VerbExecution::External(ExternalExecution::new(
+            exec_pattern.to_string(),
+            ExternalExecutionMode::StayInBroot,
+        ));
+        Verb::new(invocation, execution, VerbDescription::from_text(String::new())).unwrap()
+    }
+
+    fn make_store(names: &[&str]) -> VerbStore {
+        let verbs = names
+            .iter()
+            .map(|name| external_verb(Some(name), &format!("echo {}", name)))
+            .collect();
+        VerbStore {
+            verbs,
+            fuzzy_search: true,
+        }
+    }
+
+    fn names(result: PrefixSearchResult<'_, &Verb>) -> Vec<String> {
+        match result {
+            PrefixSearchResult::NoMatch => vec![],
+            PrefixSearchResult::Match(name, _) => vec![name.to_string()],
+            PrefixSearchResult::Matches(names) => names.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("move_to_first", "mvf").is_some());
+        assert!(fuzzy_score("move_to_first", "MVF").is_some());
+        assert!(fuzzy_score("move_to_first", "fmv").is_none());
+        assert!(fuzzy_score("move_to_first", "").is_none());
+        // consecutive characters and word starts are better than
+        // characters found inside words
+        assert!(fuzzy_score("move_to_first", "mov") > fuzzy_score("move_to_first", "mve"));
+        assert!(fuzzy_score("move_to_first", "mtf") > fuzzy_score("move_to_first", "ovi"));
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let store = make_store(&["copy_to", "move_to_first", "mkdir", "toggle_files", "tf"]);
+        // prefix search comes first
+        assert_eq!(names(store.search("mk", None)), vec!["mkdir"]);
+        assert_eq!(names(store.search("tf", None)), vec!["tf"]);
+        assert_eq!(names(store.search("mov", None)), vec!["move_to_first"]);
+        // then fuzzy search
+        assert_eq!(names(store.search("mvf", None)), vec!["move_to_first"]);
+        assert_eq!(names(store.search("MVF", None)), vec!["move_to_first"]);
+        assert_eq!(names(store.search("xyz", None)), Vec::<String>::new());
+        assert_eq!(
+            names(store.search("ot", None)),
+            vec!["copy_to", "move_to_first"],
+        );
+        // ties are broken by name
+        let store = make_store(&["tb", "ta_b", "tc_b", "ta_c"]);
+        assert_eq!(names(store.search("t_b", None)), vec!["ta_b", "tc_b"]);
+        // without fuzzy search, there's only prefix search
+        let mut store = make_store(&["move_to_first"]);
+        store.fuzzy_search = false;
+        assert_eq!(names(store.search("mvf", None)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_namespaced_search() {
+        let store = make_store(&[
+            "commit", "git/commit", "git/checkout", "git/push", "git/remote/add", "go/clean",
+        ]);
+        // typing the namespace narrows the search to its verbs
+        assert_eq!(
+            names(store.search("git/", None)),
+            vec!["git/commit", "git/checkout", "git/push", "git/remote/add"],
+        );
+        assert_eq!(names(store.search("git/c", None)), vec!["git/commit", "git/checkout"]);
+        assert_eq!(names(store.search("git/p", None)), vec!["git/push"]);
+        assert_eq!(names(store.search("go/", None)), vec!["go/clean"]);
+        assert_eq!(names(store.search("git/remote/", None)), vec!["git/remote/add"]);
+        // a name without namespace isn't found in the namespace
+        assert_eq!(names(store.search("commit", None)), vec!["commit"]);
+        assert_eq!(names(store.search("git/commit", None)), vec!["git/commit"]);
+        // fuzzy search stays in the typed namespace, on local names
+        assert_eq!(names(store.search("git/ckt", None)), vec!["git/checkout"]);
+        assert_eq!(names(store.search("git/cmt", None)), vec!["git/commit"]);
+        assert_eq!(names(store.search("go/ckt", None)), Vec::<String>::new());
+        assert_eq!(names(store.search("g/ckt", None)), Vec::<String>::new());
+        // without namespace, fuzzy search looks at whole names
+        assert_eq!(names(store.search("gckt", None)), vec!["git/checkout"]);
+    }
+
+    #[test]
+    fn test_applicable_verbs() {
+        let mut small = external_verb(Some("small"), "gzip {file}");
+        small.max_size = Some(10);
+        let mut big = external_verb(Some("big"), "split {file}");
+        big.min_size = Some(10);
+        let verbs = vec![
+            external_verb(Some("edit"), "vi {file}"),
+            external_verb(Some("cd"), "cd {directory}").with_stype(SelectionType::Directory),
+            external_verb(Some("view"), "less {file}").with_stype(SelectionType::File),
+            external_verb(Some("copy_to_panel"), "cp {file} {other-panel-directory}"),
+            external_verb(None, "echo nameless"),
+            Verb::new(
+                Some("top"),
+                VerbExecution::Internal(InternalExecution::from_internal(Internal::select_first)),
+                VerbDescription::from_text(String::new()),
+            ).unwrap(),
+            small,
+            big,
+        ];
+        let store = VerbStore {
+            verbs,
+            fuzzy_search: true,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "some twenty bytes...").unwrap();
+        let applicable = |path: &Path, stype: SelectionType, has_other_panel: bool| -> Vec<String> {
+            let sel = Selection {
+                path,
+                line: 0,
+                stype,
+                is_exe: false,
+            };
+            store
+                .applicable_verbs(sel, has_other_panel)
+                .iter()
+                .map(|verb| verb.names[0].clone())
+                .collect()
+        };
+        assert_eq!(applicable(&file, SelectionType::File, false), vec!["edit", "view", "big"]);
+        assert_eq!(
+            applicable(&file, SelectionType::File, true),
+            vec!["edit", "view", "copy_to_panel", "big"],
+        );
+        // the size of a directory isn't computed
+        assert_eq!(
+            applicable(dir.path(), SelectionType::Directory, false),
+            vec!["edit", "cd", "small", "big"],
+        );
+        fs::write(&file, "tiny").unwrap();
+        assert_eq!(applicable(&file, SelectionType::File, false), vec!["edit", "view", "small"]);
+        // a file whose size can't be read doesn't meet size conditions
+        let missing = dir.path().join("missing.txt");
+        assert_eq!(applicable(&missing, SelectionType::File, false), vec!["edit", "view"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_verb_provider() {
+        let mut conf = Conf {
+            verb_providers: vec![
+                "false".to_string(),
+                r#"printf "[[verbs]]\ninvocation = 'build'\nexternal = 'make'\n""#.to_string(),
+            ],
+            ..Conf::default()
+        };
+        let mut store = VerbStore::default();
+        store.init(&mut conf).unwrap();
+        assert!(matches!(store.search("build", None), PrefixSearchResult::Match(..)));
+    }
+
+    #[test]
+    fn test_git_builtins() {
+        let store = VerbStore {
+            verbs: builtin_verbs(),
+            fuzzy_search: true,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "fn main() {}").unwrap();
+        let builder = ExecutionStringBuilder::from_selection(Selection {
+            path: &path,
+            line: 0,
+            stype: SelectionType::File,
+            is_exe: false,
+        });
+        let tokens = |command: &str| -> Vec<String> {
+            command
+                .split(' ')
+                .map(|t| match t {
+                    "{directory}" => dir.path().to_string_lossy().to_string(),
+                    "{file}" => path.to_string_lossy().to_string(),
+                    _ => t.to_string(),
+                })
+                .collect()
+        };
+        // return the tokens of the command of the verb and whether
+        // its output is shown in a panel
+        let command = |name: &str| -> (Vec<String>, bool) {
+            let verb = match store.search(name, Some(SelectionType::File)) {
+                PrefixSearchResult::Match(_, verb) => verb,
+                _ => panic!("no verb {:?}", name),
+            };
+            match &verb.execution {
+                VerbExecution::External(external) => {
+                    assert!(matches!(external.exec_mode, ExternalExecutionMode::StayInBroot));
+                    (
+                        builder.pattern_exec_token(&external.exec_pattern),
+                        external.tail_in_panel,
+                    )
+                }
+                _ => panic!("{:?} isn't an external verb", name),
+            }
+        };
+        assert_eq!(
+            command("git_stage"),
+            (tokens("git -C {directory} add {file}"), false),
+        );
+        assert_eq!(
+            command("git_unstage"),
+            (tokens("git -C {directory} restore --staged {file}"), false),
+        );
+        assert_eq!(
+            command("git_diff"),
+            (tokens("git -C {directory} diff {file}"), true),
+        );
+        match store.search("git_status", None) {
+            PrefixSearchResult::Match(_, verb) => assert!(verb.get_internal() == Some(Internal::git_status)),
+            _ => panic!("no git_status verb"),
+        }
+    }
+}
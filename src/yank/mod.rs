@@ -0,0 +1,458 @@
+use {
+    crate::{
+        journal::Operation,
+        path,
+    },
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+};
+
+/// what must be done with the yanked paths on paste
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YankMode {
+    Copy, // set by :yank
+    Move, // set by :cut
+}
+
+/// what's done when the name of a pasted path is already
+/// used in the destination directory
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConflictPolicy {
+    Ask,       // stop the paste and let the user choose
+    Skip,      // don't paste this path
+    Overwrite, // remove the existing path, then paste
+    #[default]
+    Rename,    // paste with a number inserted before the extension
+}
+
+impl ConflictPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ask" => Some(Self::Ask),
+            "skip" => Some(Self::Skip),
+            "overwrite" => Some(Self::Overwrite),
+            "rename" => Some(Self::Rename),
+            _ => None,
+        }
+    }
+}
+
+/// what was done by a paste
+#[derive(Debug, Default, PartialEq)]
+pub struct PasteReport {
+    /// the created (or overwritten) paths
+    pub created: Vec<PathBuf>,
+    /// the yanked or cut paths of the created ones, in the same order
+    pub sources: Vec<PathBuf>,
+    /// the number of existing paths removed to be overwritten
+    pub overwritten: usize,
+    /// the number of paths not pasted because of a conflict
+    pub skipped: usize,
+    /// with the `ask` policy, the existing path which stopped the paste
+    pub conflict: Option<PathBuf>,
+}
+
+impl PasteReport {
+    /// the operation reverting the paste in the journal, or None when
+    /// nothing was pasted. When existing paths were overwritten, the
+    /// paste can't be reverted and the operation says so.
+    pub fn operation(&self, mode: YankMode, dir: &Path) -> Option<Operation> {
+        if self.overwritten > 0 {
+            return Some(Operation::Irreversible {
+                path: dir.to_path_buf(),
+                description: format!(
+                    "paste overwriting {} paths in {:?}",
+                    self.overwritten,
+                    dir,
+                ),
+            });
+        }
+        let mut operations: Vec<Operation> = self.sources
+            .iter()
+            .zip(self.created.iter())
+            .map(|(from, to)| {
+                let (from, to) = (from.clone(), to.clone());
+                match mode {
+                    YankMode::Copy => Operation::Copy { from, to },
+                    YankMode::Move => Operation::Move { from, to },
+                }
+            })
+            .collect();
+        match operations.len() {
+            0 => None,
+            1 => operations.pop(),
+            _ => Some(Operation::Several { operations }),
+        }
+    }
+}
+
+/// what was done with one path
+enum Pasted {
+    /// the path was pasted, maybe after the removal of an existing one
+    Created { dst: PathBuf, overwritten: bool },
+    Skipped,
+    Conflict(PathBuf),
+}
+
+/// the paths the user yanked or cut, waiting to be pasted
+/// in another directory.
+///
+/// Like the stage, the yank buffer belongs to the app and is
+/// shared by all panels.
+#[derive(Debug, Clone)]
+pub struct YankBuffer {
+    paths: Vec<PathBuf>,
+    mode: YankMode,
+    /// the index of the path which stopped the last paste on
+    /// a conflict, from which the next paste goes on
+    pending: Option<usize>,
+}
+
+impl Default for YankBuffer {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            mode: YankMode::Copy,
+            pending: None,
+        }
+    }
+}
+
+impl YankBuffer {
+    /// replace the content of the buffer with paths to copy on paste
+    pub fn yank(&mut self, paths: Vec<PathBuf>) {
+        self.paths = paths;
+        self.mode = YankMode::Copy;
+        self.pending = None;
+    }
+    /// replace the content of the buffer with paths to move on paste
+    pub fn cut(&mut self, paths: Vec<PathBuf>) {
+        self.paths = paths;
+        self.mode = YankMode::Move;
+        self.pending = None;
+    }
+    pub fn mode(&self) -> YankMode {
+        self.mode
+    }
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+    /// copy or move the paths of the buffer into the given directory.
+    ///
+    /// When a name is already used in the directory, the `policy`
+    /// applies. With `ConflictPolicy::Ask`, the paste stops on the
+    /// conflicting path and the next paste starts from it, applying
+    /// `answer` to this path only, then asking again on the following
+    /// conflicts. When the last paste wasn't stopped, `answer` replaces
+    /// the policy for all paths.
+    ///
+    /// Moved paths are removed from the buffer, as they can't be moved
+    /// again, while copied ones stay available for another paste.
+    pub fn paste(
+        &mut self,
+        dir: &Path,
+        policy: ConflictPolicy,
+        answer: Option<ConflictPolicy>,
+    ) -> io::Result<PasteReport> {
+        let (start, first_policy, policy) = match self.pending.take() {
+            Some(idx) => (idx, answer.unwrap_or(policy), ConflictPolicy::Ask),
+            None => {
+                let policy = answer.unwrap_or(policy);
+                (0, policy, policy)
+            }
+        };
+        let mut report = PasteReport::default();
+        let mut result = Ok(());
+        let mut end = start;
+        for (idx, src) in self.paths.iter().enumerate().skip(start) {
+            let policy = if idx == start { first_policy } else { policy };
+            match paste_one(src, dir, self.mode, policy) {
+                Ok(Pasted::Created { dst, overwritten }) => {
+                    report.created.push(dst);
+                    report.sources.push(src.clone());
+                    if overwritten {
+                        report.overwritten += 1;
+                    }
+                }
+                Ok(Pasted::Skipped) => report.skipped += 1,
+                Ok(Pasted::Conflict(dst)) => {
+                    report.conflict = Some(dst);
+                    break;
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+            end = idx + 1;
+        }
+        if self.mode == YankMode::Move {
+            self.paths.drain(start..end);
+            end = start;
+        }
+        if report.conflict.is_some() {
+            self.pending = Some(end);
+        }
+        result.map(|_| report)
+    }
+}
+
+/// copy or move a path into the directory, handling a name
+/// conflict according to the policy
+fn paste_one(
+    src: &Path,
+    dir: &Path,
+    mode: YankMode,
+    policy: ConflictPolicy,
+) -> io::Result<Pasted> {
+    let name = src.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "no file name to paste")
+    })?;
+    if src.is_dir() && dir.starts_with(src) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("can't paste {:?} into itself", name),
+        ));
+    }
+    let mut dst = dir.join(name);
+    let mut overwritten = false;
+    if let Ok(metadata) = fs::symlink_metadata(&dst) {
+        match policy {
+            ConflictPolicy::Ask => {
+                return Ok(Pasted::Conflict(dst));
+            }
+            ConflictPolicy::Skip => {
+                return Ok(Pasted::Skipped);
+            }
+            // a path pasted in its own directory can't replace itself
+            ConflictPolicy::Overwrite if !is_same_path(src, &dst) => {
+                if metadata.is_dir() {
+                    fs::remove_dir_all(&dst)?;
+                } else {
+                    fs::remove_file(&dst)?;
+                }
+                overwritten = true;
+            }
+            _ => {
+                dst = path::unique_path(&dst);
+            }
+        }
+    }
+    match mode {
+        YankMode::Copy => {
+            path::copy_recursively(src, &dst)?;
+        }
+        YankMode::Move => {
+            if fs::rename(src, &dst).is_err() {
+                // probably another device: let's copy then remove
+                path::copy_recursively(src, &dst)?;
+                if src.is_dir() {
+                    fs::remove_dir_all(src)?;
+                } else {
+                    fs::remove_file(src)?;
+                }
+            }
+        }
+    }
+    Ok(Pasted::Created { dst, overwritten })
+}
+
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod yank_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_buffer_transitions() {
+        let mut buffer = YankBuffer::default();
+        assert!(buffer.is_empty());
+        buffer.yank(vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+        assert_eq!(buffer.mode(), YankMode::Copy);
+        assert_eq!(buffer.len(), 2);
+        // a cut replaces the previous yank
+        buffer.cut(vec![PathBuf::from("/c")]);
+        assert_eq!(buffer.mode(), YankMode::Move);
+        assert_eq!(buffer.paths(), &[PathBuf::from("/c")]);
+        buffer.yank(vec![PathBuf::from("/d")]);
+        assert_eq!(buffer.mode(), YankMode::Copy);
+        assert_eq!(buffer.paths(), &[PathBuf::from("/d")]);
+    }
+
+    #[test]
+    fn test_paste_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let dst = dir.path().join("dst");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::create_dir(&dst).unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        fs::write(src.join("sub/b.txt"), "b").unwrap();
+        let mut buffer = YankBuffer::default();
+        buffer.yank(vec![src.join("a.txt"), src.join("sub")]);
+        let created = buffer.paste(&dst, ConflictPolicy::Rename, None).unwrap().created;
+        assert_eq!(created, vec![dst.join("a.txt"), dst.join("sub")]);
+        assert_eq!(fs::read_to_string(dst.join("sub/b.txt")).unwrap(), "b");
+        assert!(src.join("a.txt").exists());
+        // the buffer is kept, in the same order, and a second paste
+        // doesn't overwrite the first copies
+        assert_eq!(buffer.paths(), &[src.join("a.txt"), src.join("sub")]);
+        let created = buffer.paste(&dst, ConflictPolicy::Rename, None).unwrap().created;
+        assert_eq!(created, vec![dst.join("a (1).txt"), dst.join("sub (1)")]);
+        // a directory can't be pasted into itself
+        buffer.yank(vec![src.clone()]);
+        assert!(buffer.paste(&src.join("sub"), ConflictPolicy::Rename, None).is_err());
+    }
+
+    #[test]
+    fn test_paste_moves() {
+        let dir = tempfile::tempdir().unwrap();
+        let dst = dir.path().join("dst");
+        fs::create_dir(&dst).unwrap();
+        fs::write(dir.path().join("a.txt"), "new").unwrap();
+        fs::write(dst.join("a.txt"), "old").unwrap();
+        let mut buffer = YankBuffer::default();
+        buffer.cut(vec![dir.path().join("a.txt")]);
+        let created = buffer.paste(&dst, ConflictPolicy::Rename, None).unwrap().created;
+        assert_eq!(created, vec![dst.join("a (1).txt")]);
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(dst.join("a (1).txt")).unwrap(), "new");
+        assert!(!dir.path().join("a.txt").exists());
+        // moved paths can't be pasted again
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_paste_operation() {
+        let (_dir, src, dst) = conflict_dirs();
+        let report = yanked(&src).paste(&dst, ConflictPolicy::Skip, None).unwrap();
+        assert_eq!(
+            report.operation(YankMode::Copy, &dst),
+            Some(Operation::Copy { from: src.join("b.txt"), to: dst.join("b.txt") }),
+        );
+        let mut buffer = YankBuffer::default();
+        buffer.cut(vec![src.join("a.txt"), src.join("c.txt")]);
+        let report = buffer.paste(&dst, ConflictPolicy::Rename, None).unwrap();
+        let mut operation = report.operation(YankMode::Move, &dst).unwrap();
+        operation.undo().unwrap();
+        assert!(src.join("a.txt").exists());
+        assert!(src.join("c.txt").exists());
+        assert!(!dst.join("a (1).txt").exists());
+        // overwritten paths can't be brought back
+        let report = yanked(&src).paste(&dst, ConflictPolicy::Overwrite, None).unwrap();
+        assert_eq!(report.overwritten, 3);
+        assert!(report.operation(YankMode::Copy, &dst).unwrap().undo().is_err());
+        let report = yanked(&src).paste(&dst, ConflictPolicy::Skip, None).unwrap();
+        assert_eq!(report.operation(YankMode::Copy, &dst), None);
+    }
+
+    /// prepare a directory with a `src` directory whose `a.txt`,
+    /// `b.txt` and `c.txt` files conflict with the ones of `dst`
+    /// except `b.txt`
+    fn conflict_dirs() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let dst = dir.path().join("dst");
+        fs::create_dir(&src).unwrap();
+        fs::create_dir(&dst).unwrap();
+        for name in &["a.txt", "b.txt", "c.txt"] {
+            fs::write(src.join(name), "new").unwrap();
+        }
+        fs::write(dst.join("a.txt"), "old").unwrap();
+        fs::write(dst.join("c.txt"), "old").unwrap();
+        (dir, src, dst)
+    }
+
+    fn yanked(src: &Path) -> YankBuffer {
+        let mut buffer = YankBuffer::default();
+        buffer.yank(["a.txt", "b.txt", "c.txt"].iter().map(|name| src.join(name)).collect());
+        buffer
+    }
+
+    #[test]
+    fn test_paste_conflict_skip() {
+        let (_dir, src, dst) = conflict_dirs();
+        let report = yanked(&src).paste(&dst, ConflictPolicy::Skip, None).unwrap();
+        assert_eq!(report.created, vec![dst.join("b.txt")]);
+        assert_eq!(report.skipped, 2);
+        assert_eq!(report.conflict, None);
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(dst.join("c.txt")).unwrap(), "old");
+        assert!(!dst.join("a (1).txt").exists());
+    }
+
+    #[test]
+    fn test_paste_conflict_overwrite() {
+        let (_dir, src, dst) = conflict_dirs();
+        let report = yanked(&src).paste(&dst, ConflictPolicy::Overwrite, None).unwrap();
+        assert_eq!(report.created, vec![dst.join("a.txt"), dst.join("b.txt"), dst.join("c.txt")]);
+        assert_eq!(report.skipped, 0);
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(dst.join("c.txt")).unwrap(), "new");
+        // a directory is replaced, not merged
+        fs::create_dir_all(src.join("d/new")).unwrap();
+        fs::create_dir_all(dst.join("d/old")).unwrap();
+        let mut buffer = YankBuffer::default();
+        buffer.cut(vec![src.join("d")]);
+        buffer.paste(&dst, ConflictPolicy::Overwrite, None).unwrap();
+        assert!(dst.join("d/new").exists());
+        assert!(!dst.join("d/old").exists());
+        assert!(!src.join("d").exists());
+        // pasting a file in its own directory can't overwrite it
+        buffer.yank(vec![dst.join("a.txt")]);
+        let report = buffer.paste(&dst, ConflictPolicy::Overwrite, None).unwrap();
+        assert_eq!(report.created, vec![dst.join("a (1).txt")]);
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "new");
+    }
+
+    #[test]
+    fn test_paste_conflict_rename() {
+        let (_dir, src, dst) = conflict_dirs();
+        let report = yanked(&src).paste(&dst, ConflictPolicy::Rename, None).unwrap();
+        assert_eq!(
+            report.created,
+            vec![dst.join("a (1).txt"), dst.join("b.txt"), dst.join("c (1).txt")],
+        );
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(dst.join("a (1).txt")).unwrap(), "new");
+    }
+
+    #[test]
+    fn test_paste_conflict_ask() {
+        let (_dir, src, dst) = conflict_dirs();
+        let mut buffer = YankBuffer::default();
+        buffer.cut(["a.txt", "b.txt", "c.txt"].iter().map(|name| src.join(name)).collect());
+        // the paste stops on the first conflict
+        let report = buffer.paste(&dst, ConflictPolicy::Ask, None).unwrap();
+        assert!(report.created.is_empty());
+        assert_eq!(report.conflict, Some(dst.join("a.txt")));
+        assert_eq!(buffer.len(), 3);
+        // the answer applies to the conflicting path only
+        let report = buffer.paste(&dst, ConflictPolicy::Rename, Some(ConflictPolicy::Overwrite)).unwrap();
+        assert_eq!(report.created, vec![dst.join("a.txt"), dst.join("b.txt")]);
+        assert_eq!(report.conflict, Some(dst.join("c.txt")));
+        assert_eq!(buffer.paths(), &[src.join("c.txt")]);
+        // the answer may come from a verb with another policy
+        let report = buffer.paste(&dst, ConflictPolicy::Rename, Some(ConflictPolicy::Skip)).unwrap();
+        assert_eq!(report, PasteReport { skipped: 1, ..PasteReport::default() });
+        assert!(buffer.is_empty());
+        assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(dst.join("c.txt")).unwrap(), "old");
+        assert!(src.join("c.txt").exists());
+    }
+}